@@ -0,0 +1,457 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    database::{from_sql_id, to_sql_id},
+    quiz::saved::SavedQuiz,
+};
+use anyhow::Context;
+use indexmap::IndexMap;
+use indoc::indoc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serenity::model::id::{GuildId, UserId};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A quiz that has ended in a guild, kept for `/archive`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchivedGame {
+    /// Number of the game in the archive, `None` until it is recorded
+    pub id: Option<i64>,
+    pub ended_at: SystemTime,
+    /// Estimated difficulty tier of the answer
+    pub difficulty: String,
+    pub answer: String,
+    /// Player who guessed the answer, or `None` if the quiz was given up or timed out
+    pub solver: Option<UserId>,
+    /// Number of distinct words queried
+    pub queries: u32,
+    /// Players who joined, queried or solved the quiz
+    pub players: Vec<UserId>,
+    /// The quiz as it ended, to replay its moves; read back only by [Archive::game]
+    pub transcript: Option<SavedQuiz>,
+}
+
+/// Statistics of a player over the games archived in a guild, for `/stats`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerStats {
+    pub played: usize,
+    pub solved: usize,
+    /// Queries of the games the player solved, in total
+    pub solve_queries: u64,
+    /// Difficulty the player has played the most, the latest of them on ties
+    pub favorite_difficulty: Option<String>,
+    /// Number of the latest games of the player that the player solved in a row
+    pub streak: usize,
+}
+
+impl PlayerStats {
+    pub fn solve_rate(&self) -> Option<f64> {
+        (self.played > 0).then(|| self.solved as f64 / self.played as f64)
+    }
+
+    pub fn queries_per_solve(&self) -> Option<f64> {
+        (self.solved > 0).then(|| self.solve_queries as f64 / self.solved as f64)
+    }
+}
+
+/// Games that have ended in each guild, stored in an SQLite database.
+pub struct Archive<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> Archive<'a> {
+    /// Create the tables of the archive in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS archived_games (
+                    id INTEGER PRIMARY KEY,
+                    guild_id INTEGER NOT NULL,
+                    ended_at INTEGER NOT NULL,
+                    difficulty TEXT NOT NULL,
+                    answer TEXT NOT NULL,
+                    solver_id INTEGER,
+                    queries INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS archived_games_by_guild
+                    ON archived_games (guild_id, ended_at);
+                CREATE TABLE IF NOT EXISTS archived_players (
+                    game_id INTEGER NOT NULL REFERENCES archived_games (id),
+                    user_id INTEGER NOT NULL,
+                    PRIMARY KEY (game_id, user_id)
+                );
+                CREATE INDEX IF NOT EXISTS archived_players_by_user
+                    ON archived_players (user_id);
+                CREATE TABLE IF NOT EXISTS archived_transcripts (
+                    game_id INTEGER PRIMARY KEY REFERENCES archived_games (id),
+                    transcript TEXT NOT NULL
+                );
+            "})
+            .context("failed to initialize the archive")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        Archive { connection }
+    }
+
+    /// Keep `game`, which has ended in `guild`.
+    pub fn record(&self, guild: GuildId, game: &ArchivedGame) -> anyhow::Result<()> {
+        let ended_at = game
+            .ended_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO archived_games
+                        (guild_id, ended_at, difficulty, answer, solver_id, queries)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "},
+                params![
+                    to_sql_id(guild.0),
+                    ended_at,
+                    game.difficulty,
+                    game.answer,
+                    game.solver.map(|solver| to_sql_id(solver.0)),
+                    game.queries
+                ],
+            )
+            .context("failed to archive a game")?;
+
+        let id = self.connection.last_insert_rowid();
+        for player in &game.players {
+            self.connection
+                .execute(
+                    "INSERT OR IGNORE INTO archived_players (game_id, user_id) VALUES (?1, ?2)",
+                    params![id, to_sql_id(player.0)],
+                )
+                .context("failed to archive a player of a game")?;
+        }
+        if let Some(transcript) = &game.transcript {
+            self.connection
+                .execute(
+                    "INSERT INTO archived_transcripts (game_id, transcript) VALUES (?1, ?2)",
+                    params![id, transcript.to_json()],
+                )
+                .context("failed to archive the transcript of a game")?;
+        }
+        Ok(())
+    }
+
+    /// Number of games that have ended in `guild`.
+    pub fn count(&self, guild: GuildId) -> anyhow::Result<usize> {
+        self.connection
+            .query_row(
+                "SELECT COUNT(*) FROM archived_games WHERE guild_id = ?1",
+                params![to_sql_id(guild.0)],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count as usize)
+            .context("failed to count archived games")
+    }
+
+    /// At most `limit` games of `guild`, the latest first, skipping the `offset` latest.
+    pub fn games(
+        &self,
+        guild: GuildId,
+        offset: usize,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ArchivedGame>> {
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT ended_at, difficulty, answer, solver_id, queries, id
+            FROM archived_games
+            WHERE guild_id = ?1
+            ORDER BY ended_at DESC, id DESC
+            LIMIT ?2 OFFSET ?3
+        "})?;
+        let games = statement
+            .query_map(
+                params![to_sql_id(guild.0), limit as i64, offset as i64],
+                |row| {
+                    Ok(ArchivedGame {
+                        ended_at: UNIX_EPOCH + Duration::from_secs(row.get::<_, i64>(0)? as u64),
+                        difficulty: row.get(1)?,
+                        answer: row.get(2)?,
+                        solver: row
+                            .get::<_, Option<i64>>(3)?
+                            .map(|solver| UserId(from_sql_id(solver))),
+                        queries: row.get(4)?,
+                        players: self.players(row.get(5)?)?,
+                        id: Some(row.get(5)?),
+                        transcript: None,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the archive")?;
+        Ok(games)
+    }
+
+    /// The game `id` of `guild` with its transcript, if there is such a game.
+    /// Games archived before transcripts were kept have none.
+    pub fn game(&self, guild: GuildId, id: i64) -> anyhow::Result<Option<ArchivedGame>> {
+        let game = self
+            .connection
+            .query_row(
+                indoc! {"
+                    SELECT ended_at, difficulty, answer, solver_id, queries, transcript
+                    FROM archived_games
+                    LEFT JOIN archived_transcripts ON archived_transcripts.game_id = archived_games.id
+                    WHERE guild_id = ?1 AND id = ?2
+                "},
+                params![to_sql_id(guild.0), id],
+                |row| {
+                    Ok((
+                        ArchivedGame {
+                            ended_at: UNIX_EPOCH
+                                + Duration::from_secs(row.get::<_, i64>(0)? as u64),
+                            difficulty: row.get(1)?,
+                            answer: row.get(2)?,
+                            solver: row
+                                .get::<_, Option<i64>>(3)?
+                                .map(|solver| UserId(from_sql_id(solver))),
+                            queries: row.get(4)?,
+                            players: self.players(id)?,
+                            id: Some(id),
+                            transcript: None,
+                        },
+                        row.get::<_, Option<String>>(5)?,
+                    ))
+                },
+            )
+            .optional()
+            .context("failed to query the archive")?;
+        game.map(|(game, transcript)| {
+            Ok(ArchivedGame {
+                transcript: transcript
+                    .map(|transcript| SavedQuiz::from_json(&transcript))
+                    .transpose()
+                    .with_context(|| format!("the transcript of game #{} is broken", id))?,
+                ..game
+            })
+        })
+        .transpose()
+    }
+
+    fn players(&self, game: i64) -> rusqlite::Result<Vec<UserId>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT user_id FROM archived_players WHERE game_id = ?1 ORDER BY rowid")?;
+        let players = statement
+            .query_map(params![game], |row| {
+                row.get::<_, i64>(0).map(|user| UserId(from_sql_id(user)))
+            })?
+            .collect();
+        players
+    }
+
+    /// Statistics of `user` over the games archived in `guild`.
+    pub fn stats(&self, guild: GuildId, user: UserId) -> anyhow::Result<PlayerStats> {
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT archived_games.difficulty, archived_games.solver_id, archived_games.queries
+            FROM archived_games
+            JOIN archived_players ON archived_players.game_id = archived_games.id
+            WHERE archived_games.guild_id = ?1 AND archived_players.user_id = ?2
+            ORDER BY archived_games.ended_at DESC, archived_games.id DESC
+        "})?;
+        let games = statement
+            .query_map(params![to_sql_id(guild.0), to_sql_id(user.0)], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)? == Some(to_sql_id(user.0)),
+                    row.get::<_, u32>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the archive")?;
+
+        let mut stats = PlayerStats {
+            played: games.len(),
+            streak: games.iter().take_while(|(_, solved, _)| *solved).count(),
+            ..PlayerStats::default()
+        };
+        // in the order of the latest game of each difficulty
+        let mut difficulties = IndexMap::<&str, usize>::new();
+        for (difficulty, solved, queries) in &games {
+            *difficulties.entry(difficulty.as_str()).or_default() += 1;
+            if *solved {
+                stats.solved += 1;
+                stats.solve_queries += u64::from(*queries);
+            }
+        }
+        stats.favorite_difficulty = difficulties
+            .into_iter()
+            .rev()
+            .max_by_key(|&(_, count)| count)
+            .map(|(difficulty, _)| difficulty.to_string());
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArchivedGame, PlayerStats};
+    use crate::{
+        bot::QuizOptions,
+        database::Database,
+        quiz::saved::{Move, Origin, SavedQuiz},
+    };
+    use serenity::model::id::{GuildId, UserId};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn archive_pages_through_games_of_a_guild() {
+        let database = Database::open_in_memory().unwrap();
+        let archive = database.archive();
+        let game = |minutes: u64, solver: Option<u64>| ArchivedGame {
+            ended_at: UNIX_EPOCH + Duration::from_secs(minutes * 60),
+            difficulty: "normal".to_string(),
+            answer: format!("a{minutes}"),
+            solver: solver.map(UserId),
+            queries: minutes as u32,
+            players: solver.into_iter().chain([30]).map(UserId).collect(),
+            id: None,
+            transcript: None,
+        };
+        let numbered = |id: i64, game: ArchivedGame| ArchivedGame {
+            id: Some(id),
+            ..game
+        };
+        for minutes in 1..=5 {
+            archive
+                .record(GuildId(1), &game(minutes, Some(10)))
+                .unwrap();
+        }
+        archive.record(GuildId(1), &game(6, None)).unwrap();
+        archive.record(GuildId(2), &game(7, Some(20))).unwrap();
+
+        assert_eq!(archive.count(GuildId(1)).unwrap(), 6);
+        assert_eq!(
+            archive.games(GuildId(1), 0, 2).unwrap(),
+            vec![numbered(6, game(6, None)), numbered(5, game(5, Some(10)))]
+        );
+        assert_eq!(
+            archive.games(GuildId(1), 4, 4).unwrap(),
+            vec![
+                numbered(2, game(2, Some(10))),
+                numbered(1, game(1, Some(10)))
+            ]
+        );
+        assert!(archive.games(GuildId(3), 0, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn transcripts_are_read_back_by_game() {
+        let database = Database::open_in_memory().unwrap();
+        let archive = database.archive();
+        let transcript = SavedQuiz {
+            origin: Origin::Generated(QuizOptions {
+                seed: Some(3),
+                ..QuizOptions::default()
+            }),
+            started_at: UNIX_EPOCH,
+            moves: vec![Move::Join(UserId(10))],
+        };
+        let game = ArchivedGame {
+            id: None,
+            ended_at: UNIX_EPOCH + Duration::from_secs(60),
+            difficulty: "easy".to_string(),
+            answer: "a*".to_string(),
+            solver: None,
+            queries: 0,
+            players: vec![UserId(10)],
+            transcript: Some(transcript.clone()),
+        };
+        archive.record(GuildId(1), &game).unwrap();
+        archive
+            .record(
+                GuildId(1),
+                &ArchivedGame {
+                    transcript: None,
+                    ..game.clone()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            archive.game(GuildId(1), 1).unwrap(),
+            Some(ArchivedGame {
+                id: Some(1),
+                ..game.clone()
+            })
+        );
+        // the listing leaves transcripts out
+        assert_eq!(archive.games(GuildId(1), 1, 1).unwrap()[0].transcript, None);
+        assert_eq!(
+            archive.game(GuildId(1), 2).unwrap().unwrap().transcript,
+            None
+        );
+        assert_eq!(archive.game(GuildId(2), 1).unwrap(), None);
+        assert_eq!(archive.game(GuildId(1), 3).unwrap(), None);
+    }
+    #[test]
+    fn stats_cover_the_games_a_player_took_part_in() {
+        let database = Database::open_in_memory().unwrap();
+        let archive = database.archive();
+        let game =
+            |minutes: u64, difficulty: &str, solver: Option<u64>, players: &[u64]| ArchivedGame {
+                ended_at: UNIX_EPOCH + Duration::from_secs(minutes * 60),
+                difficulty: difficulty.to_string(),
+                answer: "a".to_string(),
+                solver: solver.map(UserId),
+                queries: minutes as u32,
+                players: players.iter().copied().map(UserId).collect(),
+                id: None,
+                transcript: None,
+            };
+        let games = [
+            game(1, "hard", Some(10), &[10, 20]),
+            game(2, "easy", Some(20), &[10, 20]),
+            game(3, "easy", Some(10), &[10]),
+            game(4, "hard", None, &[20]),
+            game(5, "hard", Some(10), &[10, 20]),
+        ];
+        for game in &games {
+            archive.record(GuildId(1), game).unwrap();
+        }
+        archive
+            .record(GuildId(2), &game(6, "easy", Some(10), &[10]))
+            .unwrap();
+
+        let stats = archive.stats(GuildId(1), UserId(10)).unwrap();
+        assert_eq!(
+            stats,
+            PlayerStats {
+                played: 4,
+                solved: 3,
+                solve_queries: 9,
+                favorite_difficulty: Some("hard".to_string()),
+                streak: 2,
+            }
+        );
+        assert_eq!(stats.solve_rate(), Some(0.75));
+        assert_eq!(stats.queries_per_solve(), Some(3.0));
+        assert_eq!(archive.stats(GuildId(1), UserId(20)).unwrap().streak, 0);
+        assert_eq!(
+            archive.stats(GuildId(1), UserId(30)).unwrap(),
+            PlayerStats::default()
+        );
+    }
+}
@@ -17,30 +17,79 @@
  *
  */
 
-use crate::regex::{randomly_generate, Alphabet, Difficulty, RegexAst};
-use anyhow::anyhow;
+use crate::regex::{
+    estimate_difficulty, generate_with_preset, generator::generate_dfa, randomly_generate,
+    svg_to_png, Alphabet, Dfa, DiffExample, Difficulty, DifficultyEstimate, DifficultyPreset,
+    RegexAst,
+};
+use anyhow::{anyhow, Context};
 
-use crate::parser::CustomId;
+use crate::{
+    archive::ArchivedGame,
+    parser::CustomId,
+    puzzle_bank::PlayOutcome,
+    quiz::{
+        analysis::{Analysis, PlayerRecord},
+        classroom::{dashboard, Progress},
+        golf::GolfRound,
+        guess_limits::GuessLimits,
+        handicap::Handicaps,
+        hidden::{FinalGuess, Guess, HiddenLanguage, QuizVariant},
+        hints::{Hint, HintTier, Hints},
+        history::{history_table, QueryRecord, HISTORY_LIMIT},
+        multi_round::Match,
+        race::Race,
+        saved::{Move, Origin, SavedQuiz},
+        scoring::{score, Score, ScoringRules, SolveRecord},
+        seed_code::SeedCode,
+        spoilers::{SpoilerKind, Spoilers},
+        submission::Submission,
+        team::{Team, TeamBudget, Teams},
+        tournament::{MatchId, Tournament},
+        vote::{Vote, VoteStatus},
+    },
+};
 use indexmap::{indexmap, indexset, IndexMap, IndexSet};
 use indoc::indoc;
 use itertools::{Either, Itertools};
+use rand::{rngs::StdRng, SeedableRng};
 use serenity::{
     builder::{CreateButton, CreateEmbed},
     model::{
-        id::{ChannelId, UserId},
+        id::{ChannelId, GuildId, MessageId, UserId},
         interactions::message_component::ButtonStyle,
         user::User,
     },
     utils::Colour,
 };
 use std::{
+    collections::HashSet,
     convert::TryInto,
     num::NonZeroU8,
     sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
 use strum::IntoEnumIterator;
 use tokio::sync::mpsc::{Receiver, Sender};
 
+/// Upper bound of AST size searched when revealing the answer in its smallest form
+const REVEAL_SEARCH_SIZE: usize = 6;
+/// Number of accepted words sampled to be shown when a quiz starts
+const OPENING_EXAMPLE_COUNT: usize = 2;
+/// Maximum number of words listed when revealing an answer matching finitely many words
+const REVEAL_WORD_LIST_SIZE: usize = 10;
+/// Maximum number of DFA states built when checking a guess against the answer
+const EQUIVALENCE_CHECK_BUDGET: usize = 5000;
+/// Number of states of the minimal DFA hidden in a quiz of the DFA variant
+const AUTOMATON_STATE_COUNT: usize = 4;
+/// Number of random automata drawn when looking for one with a given number of states
+const AUTOMATON_GENERATION_ATTEMPTS: usize = 1000;
+
+/// Maximum number of quizzes going on at once in a channel
+const MAX_QUIZZES_PER_CHANNEL: usize = 5;
+/// Maximum number of practice quizzes a player may play at once
+const MAX_PRACTICE_QUIZZES_PER_PLAYER: usize = 2;
+
 /// Struct that holds sender and receiver
 pub struct Tsx<T> {
     pub sender: Arc<Sender<T>>,
@@ -64,23 +113,204 @@ pub enum Msg {
     Err(anyhow::Error),
 }
 
+/// Options chosen when starting a quiz.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuizOptions {
+    pub variant: QuizVariant,
+    /// Number of letters in the domain, which the answer and queries may use,
+    /// defaulting to the alphabet size of `preset`
+    pub letters: Option<NonZeroU8>,
+    pub preset: Option<DifficultyPreset>,
+    /// Seed to replay a quiz from
+    pub seed: Option<u64>,
+    /// Number of distinct words the channel may query in total, after which only guesses are allowed
+    pub max_queries: Option<usize>,
+    /// Day of the daily puzzle this quiz is (see [crate::daily])
+    pub daily: Option<u64>,
+    /// Match of the tournament of the guild this quiz is played for
+    pub tournament_match: Option<MatchId>,
+    /// Race this quiz is one player's lane of, keyed as in [`Container::races`]
+    pub race: Option<u64>,
+    /// Time after which the answer is revealed and the quiz closed
+    pub time_limit: Option<Duration>,
+    /// Player who started the quiz, who may give it up without a vote
+    pub owner: Option<UserId>,
+    /// Budget each team shares, if the quiz is played in teams
+    pub team_budget: Option<TeamBudget>,
+    pub guess_limits: GuessLimits,
+    pub handicaps: Handicaps,
+    /// Points each unit of hint cost deducts from the score of the solver,
+    /// defaulting to [`ScoringRules::per_hint_point`]
+    pub hint_penalty: Option<u32>,
+    /// Whether the owner plays alone in a direct message, where no record is kept
+    pub practice: bool,
+}
+
+impl QuizOptions {
+    /// Number of letters in the domain.
+    pub fn letters(&self) -> NonZeroU8 {
+        self.letters.unwrap_or_else(|| match self.preset {
+            Some(preset) => preset.alphabet_size(),
+            None => 3u8.try_into().unwrap(),
+        })
+    }
+}
+
+impl From<SeedCode> for QuizOptions {
+    fn from(code: SeedCode) -> Self {
+        QuizOptions {
+            variant: code.variant,
+            letters: Some(code.letters),
+            preset: code.preset,
+            seed: Some(code.seed),
+            ..QuizOptions::default()
+        }
+    }
+}
+
+impl Default for QuizOptions {
+    fn default() -> Self {
+        QuizOptions {
+            variant: QuizVariant::Regex,
+            letters: None,
+            preset: None,
+            seed: None,
+            max_queries: None,
+            daily: None,
+            tournament_match: None,
+            race: None,
+            time_limit: None,
+            owner: None,
+            team_budget: None,
+            guess_limits: GuessLimits::default(),
+            handicaps: Handicaps::default(),
+            hint_penalty: None,
+            practice: false,
+        }
+    }
+}
+
+/// Identifier of a quiz, unique among the quizzes started since the bot started
+/// and the quizzes restored from before it restarted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QuizId(pub u64);
+
+impl std::fmt::Display for QuizId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A query or guess kept so that a moderator can retract it with `/undo`
+enum Action {
+    /// The last entry of the query log
+    Query,
+    /// A guess by `user`, whose checked guess had been `previous` before it
+    Guess {
+        user: UserId,
+        previous: Option<FinalGuess>,
+    },
+}
+
+struct Retractable {
+    id: u64,
+    action: Action,
+    /// The message the bot responded with, once it is known
+    response: Option<MessageId>,
+}
+
+/// A query or guess retracted by `/undo`
+pub struct Retracted {
+    pub description: String,
+    /// The message the bot responded with, to be deleted
+    pub response: Option<MessageId>,
+}
+
 pub struct Quiz {
+    /// Identifier given when the quiz is started in a channel
+    id: Option<QuizId>,
     size: u8,
+    /// Seed of the random number generator the answer was generated from
+    seed: u64,
+    hidden: HiddenLanguage,
+    /// An expression denoting the hidden language
     regex: RegexAst,
+    /// Minimal DFA of the hidden language over the domain, on which queries and guesses are checked
+    answer_dfa: Dfa,
+    difficulty: DifficultyEstimate,
     history: IndexMap<String, String>,
+    /// Every query in the order asked, with its author and time
+    query_log: Vec<QueryRecord>,
+    /// Queries and guesses in the order made, the latest of which `/undo` retracts
+    retractable: Vec<Retractable>,
+    next_action: u64,
     participants: IndexSet<UserId>,
+    /// Number of queries each player has made, including players who have given up
+    query_counts: IndexMap<UserId, usize>,
+    /// The last guess of each player that was checked against the answer
+    final_guesses: IndexMap<UserId, FinalGuess>,
+    /// Number of guesses each player has made
+    guess_counts: IndexMap<UserId, usize>,
+    hints: Hints,
+    started_at: Instant,
+    /// Number of distinct words that may be queried, shared by all players
+    max_queries: Option<usize>,
+    daily: Option<u64>,
+    tournament_match: Option<MatchId>,
+    race: Option<u64>,
+    time_limit: Option<Duration>,
+    owner: Option<UserId>,
+    /// Player who submitted the answer, who may not take part
+    author: Option<UserId>,
+    /// Whether the author is a teacher following the students on a dashboard
+    classroom: bool,
+    /// Teams sharing their budgets, if the quiz is played in teams
+    teams: Option<Teams>,
+    guess_limits: GuessLimits,
+    handicaps: Handicaps,
+    /// Whether the quiz is a practice quiz of its owner (see [`QuizOptions::practice`])
+    practice: bool,
+    /// Settings to start a fresh quiz like this one with, when it is played again
+    settings: QuizOptions,
+    /// How the quiz was started, to start it again when it is restored
+    origin: Origin,
+    /// Changes players have made, in order, to replay when the quiz is restored
+    moves: Vec<Move>,
+    /// Vote of the participants to give up the quiz together
+    give_up_vote: Vote,
+    scoring: ScoringRules,
 }
 
 pub enum InspectionAcceptance {
     Accepted(String),
-    WrongAnswer(String),
+    /// The guess along with a shortest word on which it disagrees with the answer
+    /// (the answer being the left-hand side)
+    WrongAnswer(String, DiffExample),
 }
 
-impl std::fmt::Display for InspectionAcceptance {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl InspectionAcceptance {
+    /// The verdict on the guess, hiding the counterexample as `spoilers` configure.
+    pub fn describe(&self, spoilers: &Spoilers) -> String {
         match self {
-            InspectionAcceptance::Accepted(input) => write!(f, "{input} => AC"),
-            InspectionAcceptance::WrongAnswer(input) => write!(f, "{input} => WA"),
+            InspectionAcceptance::Accepted(input) => format!("{input} => AC"),
+            InspectionAcceptance::WrongAnswer(input, counterexample) => {
+                let word = match counterexample.word() {
+                    [] => "`ε`".to_string(),
+                    w => format!("`{}`", Alphabet::slice_to_plain_string(w)),
+                };
+                let explanation = match counterexample {
+                    DiffExample::OnlyLeft(_) => "is accepted by the answer but not by your guess",
+                    DiffExample::OnlyRight(_) => "is accepted by your guess but not by the answer",
+                };
+                // the word is a big hint to the other players
+                format!(
+                    "{input} => WA\n{}",
+                    spoilers.wrap(
+                        SpoilerKind::Counterexamples,
+                        format!("{word} {explanation}.")
+                    )
+                )
+            }
         }
     }
 }
@@ -101,38 +331,481 @@ impl std::fmt::Display for QueryMatch {
 
 impl Quiz {
     pub fn new() -> Self {
-        let regex = randomly_generate(&Difficulty(3u8.try_into().unwrap()));
-        println!("{}", regex);
+        Self::new_with_difficulty(3u8.try_into().unwrap())
+    }
+
+    pub fn new_with_difficulty(difficulty: NonZeroU8) -> Self {
+        Self::new_with_seed(difficulty, rand::random())
+    }
+
+    /// Generate a quiz reproducibly: the same difficulty and seed always yield the same answer.
+    pub fn new_with_seed(difficulty: NonZeroU8, seed: u64) -> Self {
+        let regex = randomly_generate(&Difficulty(difficulty), &mut StdRng::seed_from_u64(seed));
+        Self::with_hidden(difficulty, seed, HiddenLanguage::Regex(regex))
+    }
+
+    /// Generate a quiz of the DFA variant reproducibly,
+    /// hiding a random minimal DFA over the domain of `difficulty` alphabets.
+    pub fn new_automaton_with_seed(difficulty: NonZeroU8, seed: u64) -> Self {
+        Self::automaton_with_state_count(difficulty, seed, AUTOMATON_STATE_COUNT)
+    }
+
+    fn automaton_with_state_count(difficulty: NonZeroU8, seed: u64, max_states: usize) -> Self {
+        let domain = Alphabet::iter().take(difficulty.get().into()).collect_vec();
+        let mut rng = StdRng::seed_from_u64(seed);
+        // over a single alphabet, large minimal automata may be too rare to sample,
+        // so fall back to fewer states
+        let dfa = (2..=max_states)
+            .rev()
+            .find_map(|state_count| {
+                generate_dfa(
+                    &domain,
+                    state_count,
+                    AUTOMATON_GENERATION_ATTEMPTS,
+                    &mut rng,
+                )
+            })
+            .expect("an automaton with two states is found");
+        Self::with_hidden(difficulty, seed, HiddenLanguage::Automaton(dfa))
+    }
+
+    /// Generate a quiz as chosen by `options`, drawing a seed unless one is given.
+    /// Drawn seeds fit in 32 bits so that seed codes stay short.
+    pub fn new_with_options(options: &QuizOptions) -> Self {
+        let seed = options.seed.unwrap_or_else(|| rand::random::<u32>().into());
+        let size = options.letters();
+        let quiz = match (options.variant, options.preset) {
+            (QuizVariant::Regex, None) => Self::new_with_seed(size, seed),
+            (QuizVariant::Regex, Some(preset)) => {
+                let regex = generate_with_preset(preset, size, &mut StdRng::seed_from_u64(seed));
+                Self::with_hidden(size, seed, HiddenLanguage::Regex(regex))
+            }
+            (QuizVariant::Dfa, None) => Self::new_automaton_with_seed(size, seed),
+            (QuizVariant::Dfa, Some(preset)) => {
+                Self::automaton_with_state_count(size, seed, *preset.state_count().start())
+            }
+        };
+        let mut quiz = Self {
+            max_queries: options.max_queries,
+            daily: options.daily,
+            tournament_match: options.tournament_match,
+            race: options.race,
+            time_limit: options.time_limit,
+            owner: options.owner,
+            teams: options.team_budget.map(Teams::new),
+            guess_limits: options.guess_limits,
+            handicaps: options.handicaps,
+            practice: options.practice,
+            scoring: ScoringRules {
+                per_hint_point: options.hint_penalty.unwrap_or(quiz.scoring.per_hint_point),
+                ..quiz.scoring
+            },
+            settings: QuizOptions {
+                seed: None,
+                daily: None,
+                tournament_match: None,
+                race: None,
+                owner: None,
+                ..*options
+            },
+            origin: Origin::Generated(QuizOptions {
+                seed: Some(seed),
+                ..*options
+            }),
+            ..quiz
+        };
+        // the owner plays a practice quiz alone, without having to `/join` it
+        if let Some(owner) = options.owner.filter(|_| options.practice) {
+            let _ = quiz.join(owner);
+        }
+        quiz
+    }
+
+    /// Host a quiz on the expression `author` has submitted, which they may not answer.
+    pub fn submitted(submission: Submission, author: UserId) -> Self {
+        Self::set_by(submission, author, rand::random(), false)
+    }
+
+    /// Host a classroom quiz on the expression `teacher` has set.
+    pub fn classroom(submission: Submission, teacher: UserId) -> Self {
+        Self::set_by(submission, teacher, rand::random(), true)
+    }
+
+    fn set_by(submission: Submission, author: UserId, seed: u64, classroom: bool) -> Self {
         Self {
-            size: 3u8,
-            regex,
-            history: indexmap! {},
-            participants: indexset! {},
+            author: Some(author),
+            classroom,
+            origin: Origin::Submitted {
+                submission: submission.clone(),
+                author,
+                seed,
+                classroom,
+            },
+            ..Self::with_hidden(
+                submission.letters,
+                seed,
+                HiddenLanguage::Regex(submission.regex),
+            )
         }
     }
 
-    pub fn new_with_difficulty(difficulty: NonZeroU8) -> Self {
-        let regex = randomly_generate(&Difficulty(difficulty));
-        println!("{}", regex);
+    /// Start the quiz `saved` again and replay the moves made in it,
+    /// keeping the time it has been going on for.
+    pub fn restore(saved: &SavedQuiz) -> anyhow::Result<Self> {
+        let mut quiz = match &saved.origin {
+            Origin::Generated(options) => Self::new_with_options(options),
+            Origin::Submitted {
+                submission,
+                author,
+                seed,
+                classroom,
+            } => Self::set_by(submission.clone(), *author, *seed, *classroom),
+        };
+        let elapsed = saved.started_at.elapsed().unwrap_or_default();
+        quiz.started_at = Instant::now()
+            .checked_sub(elapsed)
+            .unwrap_or_else(Instant::now);
+        for (i, played) in saved.moves.iter().enumerate() {
+            quiz.replay(played, &Spoilers::default())
+                .with_context(|| format!("failed to replay move {} of the quiz", i + 1))?;
+        }
+        Ok(quiz)
+    }
+
+    /// The moves of the quiz `saved` made again one by one, each told along with the response
+    /// of the bot and, for queries, the time since the quiz started.
+    pub fn replay_steps(saved: &SavedQuiz, spoilers: &Spoilers) -> anyhow::Result<Vec<String>> {
+        let mut quiz = Self::restore(&SavedQuiz {
+            moves: vec![],
+            ..saved.clone()
+        })?;
+        saved
+            .moves
+            .iter()
+            .enumerate()
+            .map(|(i, played)| {
+                let step = quiz
+                    .replay(played, spoilers)
+                    .with_context(|| format!("failed to replay move {} of the quiz", i + 1))?;
+                Ok(match played {
+                    Move::Query(_, _, at) => {
+                        let elapsed = at.duration_since(saved.started_at).unwrap_or_default();
+                        let seconds = elapsed.as_secs();
+                        format!("`{}:{:02}` {}", seconds / 60, seconds % 60, step)
+                    }
+                    _ => step,
+                })
+            })
+            .collect()
+    }
+
+    /// Make the move `played` again, telling what it was and how the bot responded.
+    fn replay(&mut self, played: &Move, spoilers: &Spoilers) -> anyhow::Result<String> {
+        Ok(match played {
+            Move::Join(user) => {
+                self.register(*user)?;
+                format!("<@{}> joined.", user.0)
+            }
+            Move::JoinTeam(user, name) => {
+                self.join_team(*user, name)?;
+                format!("<@{}> joined team **{}**.", user.0, name)
+            }
+            Move::Query(user, word, at) => {
+                let response = self.query(*user, word)?;
+                // keep the time the query was first made
+                if let Some(record) = self.query_log.last_mut() {
+                    record.at = *at;
+                }
+                if let Some(Move::Query(_, _, time)) = self.moves.last_mut() {
+                    *time = *at;
+                }
+                format!("<@{}> queried: {}", user.0, response)
+            }
+            Move::Guess(user, guess) => {
+                let verdict = self.inspect(*user, guess)?;
+                format!("<@{}> guessed: {}", user.0, verdict.describe(spoilers))
+            }
+            Move::Undo => format!("Retracted {}.", self.undo()?.description),
+            Move::Hint(tier) => format!("Hint ({}): {}", tier.name(), self.hint(*tier).0),
+            Move::Leave(user) => {
+                self.leave(*user)?;
+                format!("<@{}> gave up.", user.0)
+            }
+            Move::VoteGiveUp(user) => {
+                self.vote_give_up(*user)?;
+                format!("<@{}> voted to give up.", user.0)
+            }
+        })
+    }
+
+    /// This quiz as it is now, to be restored when the bot restarts.
+    pub fn saved(&self) -> SavedQuiz {
+        let now = SystemTime::now();
+        SavedQuiz {
+            origin: self.origin.clone(),
+            started_at: now.checked_sub(self.started_at.elapsed()).unwrap_or(now),
+            moves: self.moves.clone(),
+        }
+    }
+
+    /// Number of moves made in the quiz, which changes whenever the quiz has to be saved again.
+    pub fn move_count(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_classroom(&self) -> bool {
+        self.classroom
+    }
+
+    pub fn author(&self) -> Option<UserId> {
+        self.author
+    }
+
+    fn with_hidden(difficulty: NonZeroU8, seed: u64, hidden: HiddenLanguage) -> Self {
+        let regex = hidden.to_regex();
+        let variant = match hidden {
+            HiddenLanguage::Regex(_) => QuizVariant::Regex,
+            HiddenLanguage::Automaton(_) => QuizVariant::Dfa,
+        };
+        let domain = Alphabet::iter()
+            .take(difficulty.get().into())
+            .collect::<HashSet<_>>();
         Self {
+            id: None,
             size: difficulty.into(),
+            seed,
+            answer_dfa: hidden.to_min_dfa(&domain),
+            hidden,
+            difficulty: estimate_difficulty(&regex),
+            hints: Hints::new(regex.clone(), domain.into_iter().collect(), seed),
             regex,
             history: indexmap! {},
+            query_log: vec![],
+            retractable: vec![],
+            next_action: 0,
             participants: indexset! {},
+            query_counts: indexmap! {},
+            final_guesses: indexmap! {},
+            guess_counts: indexmap! {},
+            started_at: Instant::now(),
+            max_queries: None,
+            daily: None,
+            tournament_match: None,
+            race: None,
+            time_limit: None,
+            owner: None,
+            author: None,
+            classroom: false,
+            teams: None,
+            guess_limits: GuessLimits::default(),
+            handicaps: Handicaps::default(),
+            practice: false,
+            settings: QuizOptions {
+                letters: Some(difficulty),
+                ..QuizOptions::default()
+            },
+            origin: Origin::Generated(QuizOptions {
+                variant,
+                letters: Some(difficulty),
+                seed: Some(seed),
+                ..QuizOptions::default()
+            }),
+            moves: vec![],
+            give_up_vote: Vote::new(),
+            scoring: ScoringRules::default(),
+        }
+    }
+
+    pub fn id(&self) -> Option<QuizId> {
+        self.id
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Day of the daily puzzle this quiz is, if it is one.
+    pub fn daily(&self) -> Option<u64> {
+        self.daily
+    }
+
+    /// Match of a tournament this quiz is played for, if any.
+    pub fn tournament_match(&self) -> Option<MatchId> {
+        self.tournament_match
+    }
+
+    /// Record of this quiz for the archive of its guild, once it has ended.
+    pub fn archived(&self, solver: Option<UserId>) -> ArchivedGame {
+        ArchivedGame {
+            ended_at: SystemTime::now(),
+            difficulty: self.difficulty.tier.to_string(),
+            answer: self.regex.to_string(),
+            solver,
+            queries: self.history.len() as u32,
+            players: self
+                .query_counts
+                .keys()
+                .chain(&self.participants)
+                .chain(&solver)
+                .unique()
+                .copied()
+                .collect(),
+            id: None,
+            transcript: Some(self.saved()),
+        }
+    }
+
+    /// Code to start this quiz again anywhere, unless its answer was set by a player.
+    pub fn seed_code(&self) -> Option<SeedCode> {
+        self.author.is_none().then(|| SeedCode {
+            seed: self.seed,
+            letters: self.settings.letters(),
+            variant: self.settings.variant,
+            preset: self.settings.preset,
+        })
+    }
+
+    /// Race this quiz is a lane of, if any.
+    pub fn race(&self) -> Option<u64> {
+        self.race
+    }
+
+    /// Time at which the quiz is closed, if it has a time limit.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.time_limit.map(|limit| self.started_at + limit)
+    }
+
+    /// Letters players may use in queries and guesses, which the answer is drawn over.
+    pub fn domain(&self) -> Vec<Alphabet> {
+        Alphabet::iter().take(self.size.into()).collect()
+    }
+
+    pub fn variant(&self) -> QuizVariant {
+        match self.hidden {
+            HiddenLanguage::Regex(_) => QuizVariant::Regex,
+            HiddenLanguage::Automaton(_) => QuizVariant::Dfa,
+        }
+    }
+
+    pub fn difficulty(&self) -> &DifficultyEstimate {
+        &self.difficulty
+    }
+
+    /// Accepted words shown when the quiz starts, longer for harder quizzes.
+    pub fn opening_examples(&self) -> Vec<Vec<Alphabet>> {
+        let lengths = self.difficulty.tier.example_lengths();
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        self.regex
+            .sample_with_lengths(&lengths, OPENING_EXAMPLE_COUNT, &mut rng)
+            .into_iter()
+            .unique()
+            .collect()
+    }
+
+    /// Facts about the answer revealed by the handicaps of this quiz when it starts.
+    pub fn handicap_reveals(&self) -> Vec<String> {
+        self.handicaps
+            .reveal(&self.regex, &self.opening_examples(), self.seed)
+    }
+
+    /// Points for solving this quiz now, given the queries and hints used so far in the channel.
+    /// Score of `solver`, counting the words their team has queried if the quiz is played in teams.
+    pub fn score(&self, solver: UserId) -> Score {
+        score(
+            &self.scoring,
+            &SolveRecord {
+                tier: self.difficulty.tier,
+                queries: self
+                    .team_of(solver)
+                    .map_or(self.history.len(), |(_, team)| team.queries()),
+                hint_points: self.hints.spent(),
+            },
+        )
+    }
+
+    /// Outcome of this quiz if it ended now, for calibrating the difficulty of its answer
+    pub fn outcome(&self, solved: bool) -> PlayOutcome {
+        PlayOutcome {
+            queries: self.history.len() as u32,
+            duration: self.started_at.elapsed(),
+            solved,
         }
     }
 
-    pub fn query(&mut self, input: &str) -> anyhow::Result<QueryMatch> {
+    /// Number of distinct words that may still be queried, or `None` if queries are unlimited.
+    pub fn remaining_queries(&self) -> Option<usize> {
+        self.max_queries
+            .map(|max| max.saturating_sub(self.history.len()))
+    }
+
+    /// Number of distinct words `user` may still query, within the budget of their team if any.
+    pub fn remaining_queries_for(&self, user: UserId) -> Option<usize> {
+        let team = self
+            .teams
+            .as_ref()
+            .and_then(|teams| teams.remaining_queries(user));
+        match (self.remaining_queries(), team) {
+            (Some(channel), Some(team)) => Some(channel.min(team)),
+            (channel, team) => channel.or(team),
+        }
+    }
+
+    /// Add `user` to the team `name`, registering them if they have not joined yet.
+    pub fn join_team(&mut self, user: UserId, name: &str) -> anyhow::Result<()> {
+        if self.teams.is_none() {
+            return Err(anyhow!("This quiz is not played in teams."));
+        }
+        if !self.is_participant(&user) {
+            self.register(user)?;
+        }
+        if let Some(teams) = &mut self.teams {
+            teams.join(user, name)?;
+        }
+        self.moves.push(Move::JoinTeam(user, name.to_string()));
+        Ok(())
+    }
+
+    /// The name and the team `user` is in, if the quiz is played in teams.
+    pub fn team_of(&self, user: UserId) -> Option<(&str, &Team)> {
+        self.teams.as_ref()?.team_of(user)
+    }
+
+    pub fn teams(&self) -> Option<&Teams> {
+        self.teams.as_ref()
+    }
+
+    /// Answer whether the answer matches `input`.
+    /// Once the query budget is exhausted, only words queried before are answered.
+    pub fn query(&mut self, user: UserId, input: &str) -> anyhow::Result<QueryMatch> {
         let alphabets = if input.eq(r#""""#) {
             vec![]
         } else {
             Alphabet::vec_from_str(input)?
         };
         self.validate(&alphabets)?;
-        let is_match = self.regex.matches(&alphabets);
+        if self.remaining_queries() == Some(0) && !self.history.contains_key(input) {
+            return Err(anyhow!(
+                "The query budget of {} words is exhausted. Only guesses are allowed now.",
+                self.max_queries.unwrap_or_default()
+            ));
+        }
+        if let Some(teams) = &mut self.teams {
+            teams.query(user, input)?;
+        }
+        let is_match = self.answer_dfa.accepts(&alphabets);
         self.history
             .entry(input.to_string())
             .or_insert((if is_match { "Yes" } else { "No" }).to_string());
+        let at = SystemTime::now();
+        self.query_log.push(QueryRecord {
+            word: input.to_string(),
+            accepted: is_match,
+            author: user,
+            at,
+        });
+        self.push_action(Action::Query);
+        self.moves.push(Move::Query(user, input.to_string(), at));
+        *self.query_counts.entry(user).or_default() += 1;
         if is_match {
             Ok(QueryMatch::Yes(input.to_string()))
         } else {
@@ -140,66 +813,325 @@ impl Quiz {
         }
     }
 
-    pub fn inspect(&self, input: &str) -> anyhow::Result<InspectionAcceptance> {
-        let ast = RegexAst::parse_str(input)?;
-        let alphabets = ast.used_alphabets().iter().cloned().collect_vec();
-        self.validate(&alphabets)?;
-        Ok(self
-            .regex
-            .equivalent_to(&ast)
-            .then(|| InspectionAcceptance::Accepted(format!("{} => AC", &input)))
-            .unwrap_or_else(|| InspectionAcceptance::WrongAnswer(format!("{} => WA", &input))))
+    /// Check a guess, given as an expression or as a transition table, against the answer.
+    pub fn inspect(&mut self, user: UserId, input: &str) -> anyhow::Result<InspectionAcceptance> {
+        let domain = self.domain();
+        let guess = Guess::parse(input, &domain)?;
+        if let Guess::Regex(ast) = &guess {
+            let alphabets = ast.used_alphabets().iter().cloned().collect_vec();
+            self.validate(&alphabets)?;
+            self.guess_limits.check(ast)?;
+        }
+        let guess_dfa = guess
+            .to_min_dfa(&domain.into_iter().collect(), EQUIVALENCE_CHECK_BUDGET)
+            .map_err(|_| anyhow!("Your guess is too complex to verify."))?;
+        if let Some(teams) = &mut self.teams {
+            teams.guess(user)?;
+        }
+        let previous = self.final_guesses.get(&user).cloned();
+        self.push_action(Action::Guess { user, previous });
+        self.moves.push(Move::Guess(user, input.to_string()));
+        *self.guess_counts.entry(user).or_default() += 1;
+        self.final_guesses
+            .insert(user, guess.to_final_guess(input, &guess_dfa));
+        Ok(match self.answer_dfa.counterexample(&guess_dfa) {
+            None => InspectionAcceptance::Accepted(input.to_string()),
+            Some(counterexample) => {
+                InspectionAcceptance::WrongAnswer(input.to_string(), counterexample)
+            }
+        })
+    }
+
+    fn push_action(&mut self, action: Action) {
+        self.retractable.push(Retractable {
+            id: self.next_action,
+            action,
+            response: None,
+        });
+        self.next_action += 1;
+    }
+
+    /// Identifier of the latest query or guess, to attach the bot's response to.
+    pub fn last_action(&self) -> Option<u64> {
+        self.retractable.last().map(|retractable| retractable.id)
+    }
+
+    /// Remember `message` as the response to the query or guess `action`, unless it is retracted already.
+    pub fn attach_response(&mut self, action: u64, message: MessageId) {
+        if let Some(retractable) = self
+            .retractable
+            .iter_mut()
+            .rev()
+            .find(|retractable| retractable.id == action)
+        {
+            retractable.response = Some(message);
+        }
+    }
+
+    /// Retract the latest query or guess as if it had never been made.
+    pub fn undo(&mut self) -> anyhow::Result<Retracted> {
+        let Retractable {
+            action, response, ..
+        } = self
+            .retractable
+            .pop()
+            .ok_or_else(|| anyhow!("There is no query or guess to undo."))?;
+        self.moves.push(Move::Undo);
+        let description = match action {
+            Action::Query => {
+                let record = self
+                    .query_log
+                    .pop()
+                    .ok_or_else(|| anyhow!("The query log is out of sync."))?;
+                if let Some(count) = self.query_counts.get_mut(&record.author) {
+                    *count = count.saturating_sub(1);
+                }
+                if !self.query_log.iter().any(|query| query.word == record.word) {
+                    self.history.shift_remove(&record.word);
+                }
+                if let Some(teams) = &mut self.teams {
+                    let team = teams
+                        .team_of(record.author)
+                        .map(|(_, team)| team.members.clone())
+                        .unwrap_or_default();
+                    if !self
+                        .query_log
+                        .iter()
+                        .any(|query| query.word == record.word && team.contains(&query.author))
+                    {
+                        teams.forget_query(record.author, &record.word);
+                    }
+                }
+                format!("the query `{}` by <@{}>", record.word, record.author.0)
+            }
+            Action::Guess { user, previous } => {
+                match previous {
+                    Some(guess) => self.final_guesses.insert(user, guess),
+                    None => self.final_guesses.shift_remove(&user),
+                };
+                if let Some(count) = self.guess_counts.get_mut(&user) {
+                    *count = count.saturating_sub(1);
+                }
+                if let Some(teams) = &mut self.teams {
+                    teams.forget_guess(user);
+                }
+                format!("the last guess by <@{}>", user.0)
+            }
+        };
+        Ok(Retracted {
+            description,
+            response,
+        })
+    }
+
+    /// Reveal a hint of `tier`, avoiding words already queried or revealed,
+    /// together with the points it deducts from the score of the solver.
+    pub fn hint(&mut self, tier: HintTier) -> (Hint, u32) {
+        let mut queried = self.queried_words();
+        // the opening examples are known to players as well
+        queried.extend(self.opening_examples());
+        self.moves.push(Move::Hint(tier));
+        (
+            self.hints.reveal(tier, &queried),
+            self.scoring
+                .per_hint_point
+                .saturating_mul(self.hints.costs().of(tier)),
+        )
+    }
+
+    /// Points the hints revealed so far deduct from the score of the solver.
+    pub fn hint_penalty(&self) -> u32 {
+        self.scoring
+            .per_hint_point
+            .saturating_mul(self.hints.spent())
+    }
+
+    fn queried_words(&self) -> HashSet<Vec<Alphabet>> {
+        self.history
+            .keys()
+            .filter_map(|query| {
+                if query.eq(r#""""#) {
+                    Some(vec![])
+                } else {
+                    Alphabet::vec_from_str(query).ok()
+                }
+            })
+            .collect()
     }
 
     pub fn register(&mut self, user: UserId) -> anyhow::Result<()> {
+        self.join(user)?;
+        self.moves.push(Move::Join(user));
+        Ok(())
+    }
+
+    fn join(&mut self, user: UserId) -> anyhow::Result<()> {
+        if self.author == Some(user) {
+            return Err(anyhow!("You submitted this quiz, so you cannot answer it."));
+        }
+        if self.race.is_some() && self.query_counts.keys().any(|&racer| racer != user) {
+            return Err(anyhow!("This quiz is the lane of another racer."));
+        }
         self.participants
             .insert(user)
             .then_some(())
-            .ok_or_else(|| anyhow!("already registered."))
+            .ok_or_else(|| anyhow!("already registered."))?;
+        // so that players who have given up are still listed in the analysis
+        self.query_counts.entry(user).or_default();
+        Ok(())
     }
 
     pub fn accepts_give_up(
         &mut self,
         user: &User,
-    ) -> anyhow::Result<Either<String, (String, [CreateButton; 2])>> {
-        self.participants
-            .remove(&user.id)
-            .then_some(())
-            .ok_or_else(|| anyhow!("not registered"))?;
+        spoilers: &Spoilers,
+    ) -> anyhow::Result<Either<String, (String, [CreateButton; 3])>> {
+        self.leave(user.id)?;
         Ok(self
             .participants
             .is_empty()
-            .then(|| {
-                let mut good = CreateButton::default();
-                good.style(ButtonStyle::Success)
-                    .custom_id(CustomId::Feedback {
-                        label: "good".to_string(),
-                        regex: format!("{}", &self.regex),
-                    })
-                    .label("Good");
-                let mut bad = CreateButton::default();
-                bad.style(ButtonStyle::Danger)
-                    .custom_id(CustomId::Feedback {
-                        label: "bad".to_string(),
-                        regex: format!("{}", &self.regex),
-                    })
-                    .label("Bad");
-
-                Either::Right((
-                    format!(
-                        indoc! {r#"
-                            There is no longer a challenger.
-                            The answer is `{}`.
-                            Was the regular expression interesting as a problem?
-                        "#},
-                        self.regex
-                    ),
-                    [good, bad],
-                ))
-            })
+            .then(|| Either::Right(self.reveal("There is no longer a challenger.", spoilers)))
             .unwrap_or_else(|| Either::Left(format!("{} is removed.", &user.name))))
     }
 
+    fn leave(&mut self, user: UserId) -> anyhow::Result<()> {
+        self.participants
+            .remove(&user)
+            .then_some(())
+            .ok_or_else(|| anyhow!("not registered"))?;
+        self.moves.push(Move::Leave(user));
+        Ok(())
+    }
+
+    /// Vote by `user` to give up the quiz and reveal the answer. The vote passes once
+    /// a majority of the participants agree, or at once if `user` started the quiz.
+    pub fn vote_give_up(&mut self, user: UserId) -> anyhow::Result<VoteStatus> {
+        if self.owner == Some(user) {
+            return Ok(VoteStatus::Passed);
+        }
+        if !self.is_participant(&user) {
+            return Err(anyhow!("not registered"));
+        }
+        self.give_up_vote.cast(user);
+        self.moves.push(Move::VoteGiveUp(user));
+        Ok(self.give_up_vote.status(&self.participants))
+    }
+
+    /// Button starting a fresh quiz with the settings of this one, once it has ended.
+    pub fn rematch_button(&self) -> CreateButton {
+        let mut button = CreateButton::default();
+        button
+            .style(ButtonStyle::Primary)
+            .custom_id(CustomId::Rematch {
+                quiz: self.id.map_or(0, |id| id.0),
+            })
+            .label("Play again");
+        button
+    }
+
+    /// Message revealing the answer after `headline`, hidden as `spoilers` configure,
+    /// with buttons to rate the quiz.
+    pub fn reveal(&self, headline: &str, spoilers: &Spoilers) -> (String, [CreateButton; 3]) {
+        let mut good = CreateButton::default();
+        good.style(ButtonStyle::Success)
+            .custom_id(CustomId::Feedback {
+                label: "good".to_string(),
+                regex: self.regex.to_string(),
+            })
+            .label("Good");
+        let mut bad = CreateButton::default();
+        bad.style(ButtonStyle::Danger)
+            .custom_id(CustomId::Feedback {
+                label: "bad".to_string(),
+                regex: self.regex.to_string(),
+            })
+            .label("Bad");
+
+        let word_list = self
+            .regex
+            .enumerate_all()
+            .filter(|words| words.len() <= REVEAL_WORD_LIST_SIZE)
+            .map(|words| {
+                format!(
+                    "It matches exactly {}.\n",
+                    spoilers.wrap(
+                        SpoilerKind::Answers,
+                        words
+                            .iter()
+                            .map(|w| match w.as_slice() {
+                                [] => "`ε`".to_string(),
+                                w => format!("`{}`", Alphabet::slice_to_plain_string(w)),
+                            })
+                            .join(", ")
+                    )
+                )
+            })
+            .unwrap_or_default();
+
+        (
+            format!(
+                indoc! {r#"
+                    {}
+                    The answer is {}.
+                    {}Was the language interesting as a problem?
+                "#},
+                headline,
+                spoilers.wrap(SpoilerKind::Answers, self.hidden.reveal(REVEAL_SEARCH_SIZE)),
+                word_list
+            ),
+            [good, bad, self.rematch_button()],
+        )
+    }
+
+    /// Every query made so far as a table of the word, whether it is accepted, and who asked.
+    pub fn query_log(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed
+            .colour(Colour::DARK_BLUE)
+            .title(format!("history ({} queries)", self.query_log.len()))
+            .description(history_table(&self.query_log, HISTORY_LIMIT));
+        embed
+    }
+
+    /// The dashboard of a classroom quiz, which only its teacher may see.
+    pub fn dashboard(&self, user: UserId) -> anyhow::Result<CreateEmbed> {
+        if !self.classroom || self.author != Some(user) {
+            return Err(anyhow!(
+                "Only the teacher of a classroom quiz can see its dashboard."
+            ));
+        }
+        let progress = self
+            .query_counts
+            .keys()
+            .chain(self.participants.iter())
+            .unique()
+            .map(|&student| Progress {
+                student,
+                queries: self
+                    .query_log
+                    .iter()
+                    .filter(|record| record.author == student)
+                    .map(|record| (record.word.clone(), record.accepted))
+                    .collect(),
+                guesses: self.guess_counts.get(&student).cloned().unwrap_or_default(),
+                last_guess: self
+                    .final_guesses
+                    .get(&student)
+                    .map(|guess| guess.to_string()),
+            })
+            .collect_vec();
+        let mut embed = CreateEmbed::default();
+        embed
+            .colour(Colour::DARK_GREEN)
+            .title(format!(
+                "dashboard{}",
+                self.id.map_or_else(String::new, |id| format!(" of {id}"))
+            ))
+            .description(dashboard(&progress))
+            .field("answer", format!("`{}`", self.regex), false);
+        Ok(embed)
+    }
+
     pub fn get_query_history(&self) -> CreateEmbed {
         let mut embed = CreateEmbed::default();
         embed.colour(Colour::DARK_BLUE).title("query history");
@@ -216,10 +1148,25 @@ impl Quiz {
         embed
     }
 
+    /// Players who have joined or queried the quiz other than `winner`, including those who gave up.
+    pub fn opponents(&self, winner: UserId) -> Vec<UserId> {
+        self.query_counts
+            .keys()
+            .chain(&self.participants)
+            .filter(|&&user| user != winner)
+            .unique()
+            .copied()
+            .collect()
+    }
+
     pub fn is_participant(&self, id: &UserId) -> bool {
         self.participants.contains(id)
     }
 
+    pub fn participant_count(&self) -> usize {
+        self.participants.len()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.participants.is_empty()
     }
@@ -232,8 +1179,84 @@ impl Quiz {
         self.regex.clone()
     }
 
+    /// Images explaining the answer, as pairs of file names and contents:
+    /// the railroad diagram of the answer if it is an expression, and its minimal DFA over the domain.
+    /// They are rasterized to PNG so that chats preview them, or kept as SVG if that fails.
+    /// The files are marked as spoilers if `spoilers` hide answers.
+    pub fn answer_diagrams(&self, spoilers: &Spoilers) -> Vec<(String, Vec<u8>)> {
+        let railroad = match &self.hidden {
+            HiddenLanguage::Regex(ast) => Some(("answer", ast.to_railroad_svg())),
+            HiddenLanguage::Automaton(_) => None,
+        };
+
+        railroad
+            .into_iter()
+            .chain(std::iter::once(("answer-dfa", self.answer_dfa.to_svg())))
+            .map(|(name, svg)| match svg_to_png(&svg) {
+                Some(png) => (spoilers.file_name(&format!("{name}.png")), png),
+                None => (spoilers.file_name(&format!("{name}.svg")), svg.into_bytes()),
+            })
+            .collect()
+    }
+
+    /// Report on this quiz, to be posted after the answer is revealed.
+    pub fn analysis(&self) -> Analysis {
+        let domain = self.domain();
+        let players = self
+            .query_counts
+            .keys()
+            .chain(self.final_guesses.keys())
+            .unique()
+            .map(|user| PlayerRecord {
+                name: format!("<@{}>", user.0),
+                queries: self.query_counts.get(user).cloned().unwrap_or_default(),
+                final_guess: self.final_guesses.get(user).cloned(),
+            })
+            .collect_vec();
+        Analysis::new(&self.regex, &domain, &players)
+    }
+
+    /// The analysis as an embed, hiding what gives the answer away as `spoilers` configure.
+    pub fn analysis_embed(&self, spoilers: &Spoilers) -> CreateEmbed {
+        let analysis = self.analysis();
+        let mut embed = CreateEmbed::default();
+        embed
+            .colour(Colour::DARK_PURPLE)
+            .title("analysis")
+            .field(
+                "minimal DFA",
+                format!("{} states", analysis.min_dfa_states),
+                true,
+            )
+            .field(
+                "simplified",
+                spoilers.wrap(SpoilerKind::Answers, format!("`{}`", analysis.simplified)),
+                true,
+            )
+            .field(
+                "shortest words",
+                spoilers.wrap(SpoilerKind::Answers, analysis.shortest_words_line()),
+                false,
+            );
+        if let Some(code) = self.seed_code() {
+            embed.field(
+                "seed code",
+                format!("`{code}`: replay it anywhere with `/quiz from-seed`"),
+                false,
+            );
+        }
+        for player in analysis.players.iter() {
+            embed.field(
+                &player.name,
+                spoilers.wrap(SpoilerKind::Answers, player.summary_line()),
+                false,
+            );
+        }
+        embed
+    }
+
     fn validate(&self, input: &[Alphabet]) -> anyhow::Result<()> {
-        let domain = Alphabet::iter().take(self.size.into()).collect_vec();
+        let domain = self.domain();
         let invalid = input.iter().filter(|c| !domain.contains(c)).collect_vec();
         invalid.is_empty().then_some(()).ok_or_else(|| {
             anyhow!(
@@ -260,14 +1283,155 @@ impl Default for Quiz {
 }
 
 pub struct Container {
-    pub channel_map: IndexMap<ChannelId, Option<Quiz>>,
+    /// Quizzes going on in each channel, in the order they started
+    pub channel_map: IndexMap<ChannelId, IndexMap<QuizId, Quiz>>,
+    /// Answer of the quiz that ended last in each channel
+    pub finished: IndexMap<ChannelId, RegexAst>,
+    /// Ongoing round of regex golf in each channel, independent of the quiz there
+    pub golf_rounds: IndexMap<ChannelId, GolfRound>,
+    /// The latest tournament of each guild, kept after it ends to show its results
+    pub tournaments: IndexMap<GuildId, Tournament>,
+    /// Expression each player has submitted by DM, until they host it in a channel
+    pub submissions: IndexMap<UserId, Submission>,
+    /// Head-to-head races being refereed, keyed by an id unique since the bot started
+    pub races: IndexMap<u64, Race>,
+    /// Multi-round match going on in each channel
+    pub matches: IndexMap<ChannelId, Match>,
+    /// Settings of each ended quiz, for its button to play again
+    rematches: IndexMap<QuizId, QuizOptions>,
+    next_quiz_id: u64,
+    next_race_id: u64,
 }
 
 impl Container {
     pub fn new() -> Self {
         Self {
             channel_map: indexmap! {},
+            finished: indexmap! {},
+            golf_rounds: indexmap! {},
+            submissions: indexmap! {},
+            tournaments: indexmap! {},
+            races: indexmap! {},
+            matches: indexmap! {},
+            rematches: indexmap! {},
+            next_quiz_id: 1,
+            next_race_id: 1,
+        }
+    }
+
+    /// Start refereeing `race`, returning its id.
+    pub fn open_race(&mut self, race: Race) -> u64 {
+        let id = self.next_race_id;
+        self.next_race_id += 1;
+        self.races.insert(id, race);
+        id
+    }
+
+    /// Start a best-of-`best_of` match in `channel`, whose rounds are then begun one by one.
+    pub fn open_match(&mut self, channel: ChannelId, best_of: usize) -> anyhow::Result<()> {
+        if self.matches.contains_key(&channel) {
+            return Err(anyhow!("A match is already going on in this channel."));
+        }
+        self.matches.insert(channel, Match::new(best_of));
+        Ok(())
+    }
+
+    /// Start `quiz` in `channel` alongside the quizzes going on there, giving it an id.
+    pub fn insert(&mut self, channel: ChannelId, mut quiz: Quiz) -> anyhow::Result<QuizId> {
+        if quiz.practice {
+            let practicing = self
+                .channel_map
+                .values()
+                .flat_map(|quizzes| quizzes.values())
+                .filter(|other| other.practice && other.owner == quiz.owner)
+                .count();
+            if practicing >= MAX_PRACTICE_QUIZZES_PER_PLAYER {
+                return Err(anyhow!(
+                    "You are already practicing {} quizzes. Finish or give up one first.",
+                    practicing
+                ));
+            }
+        }
+        let quizzes = self.channel_map.entry(channel).or_default();
+        if quizzes.len() >= MAX_QUIZZES_PER_CHANNEL {
+            return Err(anyhow!(
+                "{} quizzes are already going on in this channel.",
+                quizzes.len()
+            ));
+        }
+        let id = QuizId(self.next_quiz_id);
+        self.next_quiz_id += 1;
+        quiz.id = Some(id);
+        quizzes.insert(id, quiz);
+        Ok(id)
+    }
+
+    /// Put back `quiz` restored after a restart in `channel` under its former id `id`,
+    /// giving later quizzes ids after it.
+    pub fn restore(
+        &mut self,
+        channel: ChannelId,
+        id: QuizId,
+        mut quiz: Quiz,
+    ) -> anyhow::Result<()> {
+        let quizzes = self.channel_map.entry(channel).or_default();
+        if quizzes.contains_key(&id) {
+            return Err(anyhow!("Quiz {} is already going on in this channel.", id));
         }
+        self.next_quiz_id = self.next_quiz_id.max(id.0 + 1);
+        quiz.id = Some(id);
+        quizzes.insert(id, quiz);
+        Ok(())
+    }
+
+    /// The quiz in `channel` a command addresses: the quiz `id` if it is given,
+    /// otherwise the only quiz in the channel or the only one `user` has joined.
+    pub fn resolve(
+        &mut self,
+        channel: ChannelId,
+        id: Option<QuizId>,
+        user: Option<UserId>,
+    ) -> anyhow::Result<&mut Quiz> {
+        let quizzes = self
+            .channel_map
+            .get_mut(&channel)
+            .filter(|quizzes| !quizzes.is_empty())
+            .ok_or_else(|| anyhow!("ゲームが開始していません"))?;
+        let id = match id {
+            Some(id) => id,
+            None if quizzes.len() == 1 => *quizzes.keys().next().unwrap(),
+            None => quizzes
+                .iter()
+                .filter(|(_, quiz)| user.is_some_and(|user| quiz.is_participant(&user)))
+                .map(|(&id, _)| id)
+                .exactly_one()
+                .map_err(|_| {
+                    anyhow!(
+                        "Quizzes {} are going on in this channel. Choose one with the `quiz` \
+                         option.",
+                        quizzes.keys().join(", ")
+                    )
+                })?,
+        };
+        quizzes
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("Quiz {} is not going on in this channel.", id))
+    }
+
+    /// Close the quiz `id` in `channel`, keeping its answer for `/explain`.
+    pub fn remove(&mut self, channel: ChannelId, id: QuizId) -> Option<Quiz> {
+        let quiz = self.channel_map.get_mut(&channel)?.shift_remove(&id)?;
+        self.finished.insert(channel, quiz.get_answer_regex());
+        self.rematches.insert(id, quiz.settings);
+        Some(quiz)
+    }
+
+    /// Settings to play the ended quiz `id` again with.
+    pub fn rematch_options(&self, id: QuizId) -> anyhow::Result<QuizOptions> {
+        self.rematches
+            .get(&id)
+            .copied()
+            .ok_or_else(|| anyhow!("Quiz {} has not ended, or cannot be played again.", id))
     }
 }
 
@@ -276,3 +1440,371 @@ impl Default for Container {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Container, DifficultyPreset, GuessLimits, HintTier, Quiz, QuizId, QuizOptions, SavedQuiz,
+        SeedCode, SpoilerKind, Spoilers, Submission, TeamBudget,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use serenity::model::id::{ChannelId, MessageId, UserId};
+    use std::{convert::TryInto, num::NonZeroU8};
+
+    #[test]
+    fn container_resolves_quizzes_in_a_channel() {
+        let quiz = |seed| Quiz::new_with_seed(2u8.try_into().unwrap(), seed);
+        let mut container = Container::new();
+        let channel = ChannelId(1);
+        assert!(container.resolve(channel, None, None).is_err());
+
+        let first = container.insert(channel, quiz(1)).unwrap();
+        let other = container.insert(ChannelId(2), quiz(2)).unwrap();
+        assert_ne!(first, other);
+        assert_eq!(
+            container.resolve(channel, None, None).unwrap().id(),
+            Some(first)
+        );
+
+        let second = container.insert(channel, quiz(3)).unwrap();
+        assert!(container.resolve(channel, None, None).is_err());
+        assert_eq!(
+            container.resolve(channel, Some(second), None).unwrap().id(),
+            Some(second)
+        );
+        assert!(container.resolve(channel, Some(other), None).is_err());
+
+        container
+            .resolve(channel, Some(second), None)
+            .unwrap()
+            .register(UserId(10))
+            .unwrap();
+        assert_eq!(
+            container
+                .resolve(channel, None, Some(UserId(10)))
+                .unwrap()
+                .id(),
+            Some(second)
+        );
+
+        assert!(container.remove(channel, second).is_some());
+        assert!(container.remove(channel, second).is_none());
+        assert!(container.finished.contains_key(&channel));
+        assert_eq!(
+            container.resolve(channel, None, None).unwrap().id(),
+            Some(first)
+        );
+
+        for seed in 0..4 {
+            container.insert(channel, quiz(seed)).unwrap();
+        }
+        assert!(container.insert(channel, quiz(9)).is_err());
+        assert_eq!(QuizId(3).to_string(), "#3");
+    }
+
+    #[test]
+    fn author_cannot_join_their_quiz() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let submission = Submission::new("(b|ab*ab*a)*", None, &mut rng).unwrap();
+        let mut quiz = Quiz::submitted(submission, UserId(1));
+        assert_eq!(quiz.author(), Some(UserId(1)));
+        assert!(quiz.register(UserId(1)).is_err());
+        assert!(quiz.register(UserId(2)).is_ok());
+    }
+
+    #[test]
+    fn answers_and_counterexamples_are_spoilered_as_configured() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let submission = Submission::new("(b|ab*ab*a)*", None, &mut rng).unwrap();
+        let mut quiz = Quiz::submitted(submission, UserId(1));
+        quiz.register(UserId(2)).unwrap();
+        let verdict = quiz.inspect(UserId(2), "b*").unwrap();
+
+        let hidden = Spoilers::default();
+        assert!(quiz.reveal("", &hidden).0.contains("The answer is ||"));
+        assert!(verdict.describe(&hidden).ends_with("||"));
+        assert!(quiz
+            .answer_diagrams(&hidden)
+            .iter()
+            .all(|(name, _)| name.starts_with("SPOILER_")));
+
+        let mut shown = Spoilers::default();
+        for kind in SpoilerKind::ALL {
+            shown.set(kind, false);
+        }
+        assert!(!quiz.reveal("", &shown).0.contains("||"));
+        assert!(!verdict.describe(&shown).contains("||"));
+        assert!(quiz
+            .answer_diagrams(&shown)
+            .iter()
+            .all(|(name, _)| !name.starts_with("SPOILER_")));
+    }
+
+    #[test]
+    fn restored_quizzes_replay_the_moves_made_in_them() {
+        let mut quiz = Quiz::new_with_options(&QuizOptions {
+            letters: NonZeroU8::new(2),
+            max_queries: Some(5),
+            team_budget: Some(TeamBudget {
+                queries: Some(3),
+                guesses: None,
+            }),
+            ..QuizOptions::default()
+        });
+        let (alice, bob) = (UserId(1), UserId(2));
+        quiz.join_team(alice, "red").unwrap();
+        quiz.join_team(bob, "blue").unwrap();
+        quiz.query(alice, "ab").unwrap();
+        quiz.query(bob, "ba").unwrap();
+        quiz.query(bob, "a").unwrap();
+        quiz.undo().unwrap();
+        quiz.inspect(alice, "a*").unwrap();
+        quiz.hint(HintTier::StateCount);
+        quiz.vote_give_up(bob).unwrap();
+
+        let saved = SavedQuiz::from_json(&quiz.saved().to_json()).unwrap();
+        let restored = Quiz::restore(&saved).unwrap();
+        assert_eq!(restored.get_answer_regex(), quiz.get_answer_regex());
+        assert_eq!(restored.move_count(), quiz.move_count());
+        assert_eq!(restored.history, quiz.history);
+        assert_eq!(restored.participants, quiz.participants);
+        assert_eq!(restored.query_counts, quiz.query_counts);
+        assert_eq!(restored.final_guesses, quiz.final_guesses);
+        assert_eq!(restored.hint_penalty(), quiz.hint_penalty());
+        assert_eq!(restored.remaining_queries_for(alice), Some(2));
+        assert_eq!(restored.saved().origin, saved.origin);
+        assert_eq!(restored.saved().moves, saved.moves);
+    }
+
+    #[test]
+    fn replays_tell_each_move_with_the_response() {
+        let submission = Submission::parse("a*", NonZeroU8::new(2)).unwrap();
+        let (author, alice) = (UserId(1), UserId(2));
+        let mut quiz = Quiz::submitted(submission, author);
+        quiz.register(alice).unwrap();
+        quiz.query(alice, "b").unwrap();
+        quiz.query(alice, "a").unwrap();
+        quiz.undo().unwrap();
+        quiz.inspect(alice, "a*").unwrap();
+
+        let steps = Quiz::replay_steps(&quiz.saved(), &Spoilers::default()).unwrap();
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[0], "<@2> joined.");
+        assert!(
+            steps[1].ends_with("` <@2> queried: b => No"),
+            "{}",
+            steps[1]
+        );
+        assert_eq!(steps[3], "Retracted the query `a` by <@2>.");
+        assert_eq!(steps[4], "<@2> guessed: a* => AC");
+    }
+
+    #[test]
+    fn only_the_teacher_sees_the_dashboard() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let submission = Submission::new("(b|ab*ab*a)*", None, &mut rng).unwrap();
+        let (teacher, student) = (UserId(1), UserId(2));
+        let mut quiz = Quiz::classroom(submission.clone(), teacher);
+        quiz.register(student).unwrap();
+        quiz.query(student, "a").unwrap();
+        quiz.inspect(student, "a*").unwrap();
+        assert!(quiz.is_classroom());
+        assert!(quiz.dashboard(teacher).is_ok());
+        assert!(quiz.dashboard(student).is_err());
+        assert_eq!(quiz.guess_counts[&student], 1);
+        quiz.undo().unwrap();
+        assert_eq!(quiz.guess_counts[&student], 0);
+        assert!(Quiz::submitted(submission, teacher)
+            .dashboard(teacher)
+            .is_err());
+    }
+
+    #[test]
+    fn seed_codes_start_the_same_quiz() {
+        let options = QuizOptions {
+            letters: NonZeroU8::new(2),
+            preset: Some(DifficultyPreset::Hard),
+            ..QuizOptions::default()
+        };
+        let quiz = Quiz::new_with_options(&options);
+        let code = quiz.seed_code().unwrap();
+        assert!(quiz.seed() <= u32::MAX.into());
+        let replayed =
+            Quiz::new_with_options(&code.to_string().parse::<SeedCode>().unwrap().into());
+        assert_eq!(replayed.get_answer_regex(), quiz.get_answer_regex());
+        assert_eq!(replayed.domain(), quiz.domain());
+    }
+
+    #[test]
+    fn ended_quizzes_are_played_again_with_their_settings() {
+        let options = QuizOptions {
+            letters: NonZeroU8::new(2),
+            seed: Some(5),
+            max_queries: Some(4),
+            owner: Some(UserId(1)),
+            ..QuizOptions::default()
+        };
+        let mut container = Container::new();
+        let channel = ChannelId(1);
+        let id = container
+            .insert(channel, Quiz::new_with_options(&options))
+            .unwrap();
+        assert!(container.rematch_options(id).is_err());
+        container.remove(channel, id);
+        let settings = container.rematch_options(id).unwrap();
+        assert_eq!(settings.letters, NonZeroU8::new(2));
+        assert_eq!(settings.max_queries, Some(4));
+        assert_eq!((settings.seed, settings.owner), (None, None));
+    }
+
+    #[test]
+    fn hints_deduct_the_configured_points_from_the_score() {
+        let mut quiz = Quiz::new_with_options(&QuizOptions {
+            seed: Some(4),
+            hint_penalty: Some(3),
+            ..QuizOptions::default()
+        });
+        let unhinted = quiz.score(UserId(1));
+        assert_eq!(quiz.hint_penalty(), 0);
+        let (_, deducted) = quiz.hint(HintTier::StateCount);
+        let (_, more) = quiz.hint(HintTier::UsedAlphabets);
+        assert_eq!((deducted, more), (6, 15));
+        assert_eq!(quiz.hint_penalty(), 21);
+        let score = quiz.score(UserId(1));
+        assert_eq!(score.hint_penalty, 21);
+        assert_eq!(score.total, unhinted.total.saturating_sub(21).max(10));
+    }
+
+    #[test]
+    fn practice_quizzes_are_limited_per_player() {
+        let practice = |owner| {
+            Quiz::new_with_options(&QuizOptions {
+                owner: Some(UserId(owner)),
+                practice: true,
+                ..QuizOptions::default()
+            })
+        };
+        let mut container = Container::new();
+        let first = container.insert(ChannelId(1), practice(1)).unwrap();
+        assert!(container
+            .resolve(ChannelId(1), Some(first), None)
+            .unwrap()
+            .is_participant(&UserId(1)));
+        container.insert(ChannelId(1), practice(1)).unwrap();
+        assert!(container.insert(ChannelId(1), practice(1)).is_err());
+        assert!(container.insert(ChannelId(2), practice(2)).is_ok());
+        container.remove(ChannelId(1), first);
+        assert!(container.insert(ChannelId(1), practice(1)).is_ok());
+    }
+
+    #[test]
+    fn archived_games_list_everyone_who_took_part() {
+        let mut quiz = Quiz::new_with_options(&QuizOptions {
+            seed: Some(2),
+            ..QuizOptions::default()
+        });
+        quiz.register(UserId(1)).unwrap();
+        quiz.register(UserId(2)).unwrap();
+        let game = quiz.archived(Some(UserId(3)));
+        assert_eq!(game.players, vec![UserId(1), UserId(2), UserId(3)]);
+        assert_eq!(game.solver, Some(UserId(3)));
+        assert_eq!(quiz.archived(Some(UserId(1))).players.len(), 2);
+    }
+
+    #[test]
+    fn guesses_over_the_limits_are_rejected_without_counting() {
+        let options = QuizOptions {
+            seed: Some(3),
+            guess_limits: GuessLimits {
+                max_size: Some(5),
+                max_stars: None,
+            },
+            ..QuizOptions::default()
+        };
+        let mut quiz = Quiz::new_with_options(&options);
+        let user = UserId(1);
+        quiz.register(user).unwrap();
+        assert!(quiz.inspect(user, "a|b|ab|ba|aa|bb").is_err());
+        assert_eq!(quiz.last_action(), None);
+        assert!(quiz.inspect(user, "a*").is_ok());
+        assert!(quiz.last_action().is_some());
+    }
+
+    #[test]
+    fn race_lanes_admit_only_their_racer() {
+        let options = QuizOptions {
+            seed: Some(7),
+            race: Some(1),
+            ..QuizOptions::default()
+        };
+        let mut lanes = [
+            Quiz::new_with_options(&options),
+            Quiz::new_with_options(&options),
+        ];
+        assert_eq!(lanes[0].get_answer_regex(), lanes[1].get_answer_regex());
+        assert!(lanes[0].register(UserId(1)).is_ok());
+        assert!(lanes[0].register(UserId(2)).is_err());
+        assert!(lanes[1].register(UserId(2)).is_ok());
+    }
+
+    #[test]
+    fn teams_are_scored_by_their_own_queries() {
+        let options = QuizOptions {
+            letters: NonZeroU8::new(2),
+            seed: Some(1),
+            team_budget: Some(TeamBudget {
+                queries: Some(2),
+                guesses: None,
+            }),
+            ..QuizOptions::default()
+        };
+        let mut quiz = Quiz::new_with_options(&options);
+        let (alice, bob) = (UserId(1), UserId(2));
+        assert!(quiz.query(alice, "a").is_err());
+        quiz.join_team(alice, "red").unwrap();
+        quiz.join_team(bob, "blue").unwrap();
+        assert!(quiz.is_participant(&alice));
+
+        quiz.query(alice, "a").unwrap();
+        quiz.query(alice, "b").unwrap();
+        assert_eq!(quiz.remaining_queries_for(alice), Some(0));
+        assert!(quiz.query(alice, "ab").is_err());
+        assert_eq!(quiz.remaining_queries_for(bob), Some(2));
+        assert!(quiz.score(bob).total > quiz.score(alice).total);
+
+        quiz.undo().unwrap();
+        assert_eq!(quiz.remaining_queries_for(alice), Some(1));
+    }
+
+    #[test]
+    fn undo_retracts_the_latest_query_or_guess() {
+        let mut quiz = Quiz::new_with_seed(2u8.try_into().unwrap(), 1);
+        let (alice, bob) = (UserId(1), UserId(2));
+        assert!(quiz.undo().is_err());
+
+        quiz.query(alice, "a").unwrap();
+        quiz.query(bob, "a").unwrap();
+        quiz.query(bob, "b").unwrap();
+        quiz.attach_response(quiz.last_action().unwrap(), MessageId(7));
+        assert_eq!(quiz.len(), 2);
+
+        let retracted = quiz.undo().unwrap();
+        assert_eq!(retracted.response, Some(MessageId(7)));
+        assert_eq!(quiz.len(), 1);
+        assert_eq!(quiz.query_counts[&bob], 1);
+
+        // the word is still known from the query of alice
+        assert!(quiz.undo().unwrap().response.is_none());
+        assert_eq!(quiz.len(), 1);
+        assert_eq!(quiz.query_counts[&bob], 0);
+
+        quiz.inspect(alice, "a").unwrap();
+        assert!(quiz.final_guesses.contains_key(&alice));
+        quiz.undo().unwrap();
+        assert!(!quiz.final_guesses.contains_key(&alice));
+
+        quiz.undo().unwrap();
+        assert_eq!(quiz.len(), 0);
+        assert!(quiz.undo().is_err());
+    }
+}
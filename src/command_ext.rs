@@ -25,7 +25,8 @@ use serenity::{
     http::Http,
     model::interactions::{
         application_command::ApplicationCommandInteraction,
-        message_component::MessageComponentInteraction, InteractionResponseType,
+        message_component::MessageComponentInteraction,
+        InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
     },
 };
 
@@ -58,6 +59,32 @@ pub trait CommandExt {
     ) -> anyhow::Result<()>
     where
         Button<N>: Satisfied;
+    async fn attachments(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        files: Vec<(String, Vec<u8>)>,
+    ) -> anyhow::Result<()>;
+    /// Respond with an embed only the user who interacted can see, with `buttons` below it.
+    async fn ephemeral_embed(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+        buttons: Vec<CreateButton>,
+    ) -> anyhow::Result<()>;
+    /// Post an embed to the channel as a separate message, e.g. after the interaction is responded.
+    async fn channel_embed(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+    ) -> anyhow::Result<()>;
+    /// Respond with a page of an embed and the `buttons` turning it;
+    /// when a button is pressed the page replaces the message it is on.
+    async fn page(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+        buttons: Vec<CreateButton>,
+    ) -> anyhow::Result<()>;
 }
 
 #[async_trait]
@@ -114,6 +141,90 @@ impl CommandExt for ApplicationCommandInteraction {
         .await
         .with_context(|| anyhow!("serenity error"))
     }
+
+    async fn attachments(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        files: Vec<(String, Vec<u8>)>,
+    ) -> anyhow::Result<()> {
+        self.channel_id
+            .send_message(&http, |message| {
+                message.add_files(
+                    files
+                        .iter()
+                        .map(|(filename, data)| (data.as_slice(), filename.as_str())),
+                )
+            })
+            .await
+            .map(|_| ())
+            .with_context(|| anyhow!("serenity error"))
+    }
+
+    async fn ephemeral_embed(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+        buttons: Vec<CreateButton>,
+    ) -> anyhow::Result<()> {
+        self.create_interaction_response(&http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message
+                        .add_embed(embed)
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                    if !buttons.is_empty() {
+                        message.components(|component| {
+                            component.create_action_row(|action_row| {
+                                for button in buttons {
+                                    action_row.add_button(button);
+                                }
+                                action_row
+                            })
+                        });
+                    }
+                    message
+                })
+        })
+        .await
+        .with_context(|| anyhow!("serenity error"))
+    }
+
+    async fn channel_embed(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+    ) -> anyhow::Result<()> {
+        self.channel_id
+            .send_message(&http, |message| message.set_embed(embed))
+            .await
+            .map(|_| ())
+            .with_context(|| anyhow!("serenity error"))
+    }
+
+    async fn page(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+        buttons: Vec<CreateButton>,
+    ) -> anyhow::Result<()> {
+        self.create_interaction_response(&http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message.add_embed(embed).components(|component| {
+                        component.create_action_row(|action_row| {
+                            for button in buttons {
+                                action_row.add_button(button);
+                            }
+                            action_row
+                        })
+                    })
+                })
+        })
+        .await
+        .with_context(|| anyhow!("serenity error"))
+    }
 }
 
 #[async_trait]
@@ -170,4 +281,88 @@ impl CommandExt for MessageComponentInteraction {
         .await
         .with_context(|| anyhow!("serenity error"))
     }
+
+    async fn attachments(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        files: Vec<(String, Vec<u8>)>,
+    ) -> anyhow::Result<()> {
+        self.channel_id
+            .send_message(&http, |message| {
+                message.add_files(
+                    files
+                        .iter()
+                        .map(|(filename, data)| (data.as_slice(), filename.as_str())),
+                )
+            })
+            .await
+            .map(|_| ())
+            .with_context(|| anyhow!("serenity error"))
+    }
+
+    async fn ephemeral_embed(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+        buttons: Vec<CreateButton>,
+    ) -> anyhow::Result<()> {
+        self.create_interaction_response(&http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message
+                        .add_embed(embed)
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                    if !buttons.is_empty() {
+                        message.components(|component| {
+                            component.create_action_row(|action_row| {
+                                for button in buttons {
+                                    action_row.add_button(button);
+                                }
+                                action_row
+                            })
+                        });
+                    }
+                    message
+                })
+        })
+        .await
+        .with_context(|| anyhow!("serenity error"))
+    }
+
+    async fn channel_embed(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+    ) -> anyhow::Result<()> {
+        self.channel_id
+            .send_message(&http, |message| message.set_embed(embed))
+            .await
+            .map(|_| ())
+            .with_context(|| anyhow!("serenity error"))
+    }
+
+    async fn page(
+        &self,
+        http: impl AsRef<Http> + Send + Sync + 'async_trait,
+        embed: CreateEmbed,
+        buttons: Vec<CreateButton>,
+    ) -> anyhow::Result<()> {
+        self.create_interaction_response(&http, |response| {
+            response
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|message| {
+                    message.add_embed(embed).components(|component| {
+                        component.create_action_row(|action_row| {
+                            for button in buttons {
+                                action_row.add_button(button);
+                            }
+                            action_row
+                        })
+                    })
+                })
+        })
+        .await
+        .with_context(|| anyhow!("serenity error"))
+    }
 }
@@ -17,7 +17,11 @@
  *
  */
 
-use crate::{bot::Quiz, errors::CommandError};
+use crate::{
+    bot::{Quiz, QuizOptions},
+    errors::CommandError,
+    quiz::golf::GolfRound,
+};
 use anyhow::{anyhow, Context};
 use serenity::{
     builder::CreateEmbed,
@@ -28,11 +32,11 @@ use serenity::{
 use std::{num::NonZeroU8, time::Duration};
 use tokio::{sync::oneshot, time::timeout};
 
-pub async fn generate_regex(difficulty: NonZeroU8) -> anyhow::Result<Quiz> {
+pub async fn generate_regex(options: QuizOptions) -> anyhow::Result<Quiz> {
     let (tx, rx) = oneshot::channel();
 
     tokio::task::spawn(async move {
-        let quiz = Quiz::new_with_difficulty(difficulty);
+        let quiz = Quiz::new_with_options(&options);
         let _ = tx.send(quiz);
     });
 
@@ -46,7 +50,25 @@ pub async fn generate_regex(difficulty: NonZeroU8) -> anyhow::Result<Quiz> {
     }
 }
 
-pub fn help() -> CreateEmbed {
+pub async fn generate_golf(letters: NonZeroU8, seed: Option<u64>) -> anyhow::Result<GolfRound> {
+    let (tx, rx) = oneshot::channel();
+
+    tokio::task::spawn(async move {
+        let round = GolfRound::new(letters, seed.unwrap_or_else(rand::random));
+        let _ = tx.send(round);
+    });
+
+    match timeout(Duration::from_millis(1000), rx).await {
+        Ok(round) => round.with_context(|| anyhow!("receive error")),
+        Err(_) => Err(anyhow::Error::from(CommandError::Timeout {
+            limit: "Time Limit Exceeded".to_string(),
+        }))
+        .context("timeout while generating a golf round"),
+    }
+}
+
+/// Help on the commands, split into embeds within the limits of a message each.
+pub fn help() -> [CreateEmbed; 2] {
     use indoc::indoc;
     let mut embed = CreateEmbed::default();
     embed.colour(Colour::DARK_GREEN).title("HELP");
@@ -59,10 +81,47 @@ pub fn help() -> CreateEmbed {
             false,
         )
         .field(
-            "/start [DIFFICULTY]",
-            indoc! {
-                "[DIFFICULTY]: number of alphabets"
-            },
+            "/start [LETTERS] [SEED] [VARIANT] [DIFFICULTY] [MAX_QUERIES] [TIME_LIMIT] [TEAM_QUERIES] [TEAM_GUESSES] [MAX_GUESS_SIZE] [MAX_GUESS_STARS] [HINT_PENALTY] [REVEAL_LITERALS] [REVEAL_EPSILON] [REVEAL_WORD]",
+            indoc! {"
+                [LETTERS]: number of letters the answer and queries may use, e.g. `3` for `a`–`c`
+                [SEED]: seed of a quiz to replay (shown when a quiz starts)
+                [VARIANT]: `regex` hides a regular expression, `dfa` hides a small DFA
+                [DIFFICULTY]: `easy`, `normal`, `hard` or `lunatic`, sizing the minimal DFA of the answer
+                [MAX_QUERIES]: number of distinct words the channel may query, after which only guesses are allowed
+                [TIME_LIMIT]: time after which the answer is revealed, e.g. `10m`, `90s` or `1h30m`
+                [TEAM_QUERIES], [TEAM_GUESSES]: play in teams, each sharing this many distinct queries and guesses
+                [MAX_GUESS_SIZE], [MAX_GUESS_STARS]: reject guessed expressions with more nodes or stars than this
+                [HINT_PENALTY]: points each unit of hint cost deducts from the score of the solver (`10` by default)
+                [REVEAL_LITERALS], [REVEAL_EPSILON], [REVEAL_WORD]: handicaps for mixed-skill groups, revealing at the start
+                how many letters the answer is written with, whether `ε` is accepted, or one more accepted word
+            "},
+            false,
+        )
+        .field(
+            "/practice [LETTERS] [VARIANT] [DIFFICULTY]",
+            indoc! {r#"
+                In a direct message with the bot, starts a quiz you play alone without joining,
+                with no budget or time limit and nothing recorded on the leaderboards.
+                Up to 2 practice quizzes may go on at once.
+            "#},
+            false,
+        )
+        .field(
+            "/match [ROUNDS] [LETTERS] [VARIANT] [DIFFICULTY]",
+            indoc! {r#"
+                Starts a best-of-N match: quizzes played one after another in the channel,
+                each starting once the previous one is solved or given up, with the standings posted in between.
+                The player who wins the most rounds, then earns the most points, takes the match.
+                [ROUNDS]: `3`, `5` or `7`; the match ends early once a player has won a majority of them
+            "#},
+            false,
+        )
+        .field(
+            "/quiz from-seed [CODE]",
+            indoc! {r#"
+                Replays exactly the quiz of [CODE], the seed code shown when a quiz ends, in this channel.
+                Share codes such as `RSOUP-3F9A2K7B` to play the same quiz in another server.
+            "#},
             false,
         )
         .field(
@@ -76,7 +135,9 @@ pub fn help() -> CreateEmbed {
             "/guess [INPUT]",
             indoc! {r#"
                 Check your answer.
-                [INPUT]: regex you guess
+                [INPUT]: regex you guess, or a transition table such as `0 1 0; 1* 1 0`
+                (a row per state listing its targets in alphabetical order,
+                `*` for accepting states and `>` for the initial state if not `0`)
             "#},
             false,
         )
@@ -87,6 +148,174 @@ pub fn help() -> CreateEmbed {
             "#},
             false,
         )
+        .field(
+            "/history",
+            indoc! {r#"
+                Lists every query made so far in order: the word, whether it is accepted, and who asked.
+            "#},
+            false,
+        )
+        .field(
+            "/hint [KIND]",
+            indoc! {r#"
+                Reveals a hint on the answer, announcing the points it deducts from the score of whoever solves the quiz.
+                [KIND]: `examples` shows an accepted word and a rejected word nobody has queried yet (1 unit of cost)
+                `state-count` shows the number of states of the minimal DFA of the answer (2 units)
+                `shortest-word` shows whether `ε` is accepted and the length of the shortest accepted word (2 units)
+                `used-alphabets` shows which alphabets appear in the answer (5 units, expensive!)
+            "#},
+            false,
+        )
+        .field(
+            "/explain [WORD]",
+            indoc! {r#"
+                After a quiz ends, shows how its answer matches the word step by step.
+                [WORD]: alphabets to explain (`""` is accepted as empty string)
+            "#},
+            false,
+        );
+    let mut more = CreateEmbed::default();
+    more.colour(Colour::DARK_GREEN).title("HELP (continued)");
+    more
+        .field(
+            "/golf [LETTERS] [SEED]",
+            indoc! {r#"
+                Starts a round of regex golf, independent of the quiz:
+                find the shortest regex matching all the positive words and none of the negative ones.
+                Submit with `/golf-submit [REGEX]` and close the round with `/golf-end`.
+            "#},
+            false,
+        )
+        .field(
+            "/leaderboard [PERIOD] [TEAMS]",
+            indoc! {r#"
+                Shows the solvers of this server with the most points.
+                [PERIOD]: `week` (the last 7 days), `month` (the last 30 days) or `all-time`
+                [TEAMS]: `True` ranks the teams of team quizzes instead of players
+            "#},
+            false,
+        )
+        .field(
+            "/archive [PAGE]",
+            indoc! {r#"
+                Browses the quizzes played on this server, newest first:
+                its number, when it ended, its difficulty, who solved it with how many queries and the answer behind a spoiler.
+                [PAGE]: the page to open (`1` by default)
+            "#},
+            false,
+        )
+        .field(
+            "/replay GAME",
+            indoc! {r#"
+                Retells a quiz of `/archive` move by move, from the first query to the answer.
+                GAME: the number of the quiz in `/archive`
+            "#},
+            false,
+        )
+        .field(
+            "/records [GLOBAL]",
+            indoc! {r#"
+                Shows the records of each difficulty: the solves with the fewest queries and in the fastest time.
+                Breaking one is announced with the solve.
+                [GLOBAL]: `True` shows the records across every server instead of this one
+            "#},
+            false,
+        )
+        .field(
+            "/rating [PLAYER]",
+            indoc! {r#"
+                Shows the Elo rating of a player (yourself by default) and the top rated players.
+                Solving a quiz before the other players who joined it counts as beating each of them.
+            "#},
+            false,
+        )
+        .field(
+            "/stats [USER]",
+            indoc! {r#"
+                Shows how a player (yourself by default) has done in the quizzes of this server:
+                games played, solve rate, queries per solve, favorite difficulty
+                and the current streak of games solved in a row.
+            "#},
+            false,
+        )
+        .field(
+            "/daily [ACTION] [NAME]",
+            indoc! {r#"
+                The daily puzzle is the same in every server and changes at midnight (UTC).
+                [ACTION]: `start` starts today's puzzle in this channel, `stats` shows how it was solved,
+                `top` ranks the fastest solves in every server, `subscribe` posts each new puzzle to this channel
+                and `unsubscribe` stops posting. Solvers are anonymous in the ranking
+                unless they `show-name` (their own [NAME] or username) until they `hide-name`.
+            "#},
+            false,
+        )
+        .field(
+            "/tournament [ACTION]",
+            indoc! {r#"
+                A week-long knockout tournament of head-to-head regex races in this server.
+                [ACTION]: `open` opens registration, `register` enters you, `start` draws the bracket,
+                `play` starts your current match in this channel and `status` shows the bracket.
+                Matches not played by the end of their round are decided by a coin toss.
+            "#},
+            false,
+        )
+        .field(
+            "/race [OPPONENT] [LETTERS] [DIFFICULTY]",
+            indoc! {r#"
+                Challenges [OPPONENT] to a race: each of you plays the same hidden regex in a thread of your own.
+                The first to guess the answer wins, but if the other also solves within 30 seconds,
+                the one who queried fewer words wins.
+            "#},
+            false,
+        )
+        .field(
+            "/quizzes",
+            indoc! {r#"
+                Lists the quizzes going on in this channel. When there are several,
+                add `[QUIZ]` (the number shown by `/start`) to `/query`, `/guess`, `/summary`,
+                `/history`, `/hint`, `/join` and `/give-up` unless you have joined only one of them.
+            "#},
+            false,
+        )
+        .field(
+            "/submit [REGEX] [LETTERS]",
+            indoc! {r#"
+                Send this to the bot in a direct message to make your own quiz.
+                [REGEX]: the answer, which has to be neither trivial nor too complex
+                [LETTERS]: the number of letters in the domain, by default as many as [REGEX] uses
+            "#},
+            false,
+        )
+        .field(
+            "/host",
+            indoc! {r#"
+                Starts the quiz you have submitted in this channel. You may not answer it yourself.
+            "#},
+            false,
+        )
+        .field(
+            "/classroom [REGEX] [LETTERS]",
+            indoc! {r#"
+                Starts a quiz for a class with the answer you set, keeping it hidden from the channel.
+                You cannot answer it, but you can follow what each student has queried and guessed.
+            "#},
+            false,
+        )
+        .field(
+            "/dashboard",
+            indoc! {r#"
+                For the teacher of a classroom quiz: shows privately what each student has done so far.
+            "#},
+            false,
+        )
+        .field(
+            "/team [NAME]",
+            indoc! {r#"
+                Joins the team [NAME] in a quiz played in teams, forming it if nobody has joined it yet.
+                The members of a team share its queries and guesses, and the team is scored as one.
+            "#},
+            false,
+        )
         .field(
             "/join",
             indoc! {r#"
@@ -101,19 +330,93 @@ pub fn help() -> CreateEmbed {
                 the quiz will end and the answers will be revealed!
             "#},
             false,
+        )
+        .field(
+            "/giveup",
+            indoc! {r#"
+                Votes to give up the quiz together: the answer is revealed
+                once a majority of the participants vote, or at once if the player who started it votes.
+            "#},
+            false,
+        )
+        .field(
+            "/undo",
+            indoc! {r#"
+                For moderators: retracts the last query or guess of the quiz and deletes the response to it.
+            "#},
+            false,
+        )
+        .field(
+            "/spoilers [OUTPUT] [ENABLED]",
+            indoc! {r#"
+                Shows which outputs are hidden behind spoiler tags on this server:
+                revealed `answers` with their diagrams and analysis, `counterexamples` to wrong guesses,
+                and answers in the `archive`. Moderators may turn each of them on or off.
+            "#},
+            false,
+        )
+        .field(
+            "/config [SETTING] [VALUE] [CHANNEL] [ALLOWED] [SPOILER] [HIDDEN]",
+            indoc! {r#"
+                For administrators: chooses the defaults of quizzes started on this server
+                (`difficulty`, `letters`, `max-queries`, `time-limit`), the `locale` (`en` or `ja`),
+                the channels the bot plays in (every channel unless some are allowed) and the spoilers.
+                A `VALUE` of `default` goes back to the default; without options, shows the configuration.
+            "#},
+            false,
+        )
+        .field(
+            "/schedule [ACTION] [CRON] [DIFFICULTY] [REGEX] [LETTERS] [HOURS] [REMINDER] [ID]",
+            indoc! {r#"
+                Lists the puzzles posted on a schedule in this server. Administrators may `add` one
+                posting to this channel on a cron table in UTC such as `0 18 * * 5` (`@weekly` by default),
+                a generated puzzle or their own `REGEX`, or `remove` one by its `ID`.
+                Each puzzle is pinned, stays open for `HOURS` or until the next one,
+                and a reminder is posted `REMINDER` hours (24 by default) before it closes.
+            "#},
+            false,
         );
-    embed
+    [embed, more]
 }
 
 pub async fn create_slash_commands(
     http: impl AsRef<Http>,
 ) -> anyhow::Result<Vec<ApplicationCommand>> {
-    // start [DIFFICULTY]: ゲームセッション開始コマンド
+    // start [LETTERS] [SEED] [VARIANT] [DIFFICULTY] [MAX_QUERIES] [TIME_LIMIT] [TEAM_QUERIES] [TEAM_GUESSES] [MAX_GUESS_SIZE] [MAX_GUESS_STARS] [HINT_PENALTY] [REVEAL_LITERALS] [REVEAL_EPSILON] [REVEAL_WORD]: ゲームセッション開始コマンド
+    // practice [LETTERS] [VARIANT] [DIFFICULTY]: DM で一人で練習 (記録なし)
+    // match [ROUNDS] [LETTERS] [VARIANT] [DIFFICULTY]: 複数ラウンドの試合
+    // quiz from-seed [CODE]: シードコードからクイズを再現して開始
     // query: マッチクエリ
     // guess: 回答試行
     // summary: 今までのクエリのサマリ表示
+    // history: 今までのクエリを質問者・時刻つきで一覧表示
+    // hint [KIND]: ヒント表示
+    // explain [WORD]: 終了したクイズの答えによるマッチの説明
+    // golf [LETTERS] [SEED]: regex golf のラウンド開始
+    // golf-submit [REGEX]: regex golf への回答提出
+    // golf-end: regex golf のラウンド終了
+    // leaderboard [PERIOD] [TEAMS]: サーバーごとの得点ランキング表示
+    // archive [PAGE]: サーバーで遊ばれたクイズの一覧
+    // replay GAME: 終了したクイズの再生
+    // records [GLOBAL]: 難易度ごとの最少クエリ・最速記録
+    // rating [PLAYER]: Elo レーティング表示
+    // stats [USER]: サーバーでのプレイヤーの成績表示
+    // daily [ACTION] [NAME]: 全サーバー共通のデイリーパズル
+    // tournament [ACTION]: 週間トーナメント
+    // race [OPPONENT] [LETTERS] [DIFFICULTY]: スレッドごとの一対一レース
+    // quizzes: チャンネル内で進行中のクイズ一覧
+    // submit [REGEX] [LETTERS]: DM で自作クイズを投稿
+    // host: 投稿したクイズをチャンネルで開始
+    // classroom [REGEX] [LETTERS]: 教師が答えを決める授業用クイズ
+    // dashboard: 授業用クイズの生徒の進捗 (教師のみ)
+    // team [NAME]: チーム戦のチームに参加
     // join: 参加表明
     // give-up: 投了
+    // giveup: 投了の投票
+    // undo: 直前のクエリ・回答の取り消し (モデレーター用)
+    // spoilers [OUTPUT] [ENABLED]: ネタバレ防止タグの設定 (変更はモデレーター用)
+    // config [SETTING] [VALUE] [CHANNEL] [ALLOWED] [SPOILER] [HIDDEN]: サーバーごとの設定 (管理者用)
+    // schedule [ACTION] [CRON] [DIFFICULTY] [REGEX] [LETTERS] [HOURS] [REMINDER] [ID]: 定期出題の設定 (追加・削除は管理者用)
 
     ApplicationCommand::set_global_application_commands(&http, |commands| {
         commands
@@ -122,8 +425,11 @@ pub async fn create_slash_commands(
                     .name("start")
                     .description("Starting new regex-soup")
                     .create_option(|o| {
-                        o.name("size")
-                            .description("Please choice number of characters in the domain-set.")
+                        o.name("letters")
+                            .description(
+                                "Please choice number of letters in the domain-set, e.g. 3 for \
+                                 a-c.",
+                            )
                             .kind(ApplicationCommandOptionType::Integer)
                             .add_int_choice(1, 1)
                             .add_int_choice(2, 2)
@@ -137,43 +443,757 @@ pub async fn create_slash_commands(
                             .add_int_choice(10, 10)
                             .required(false)
                     })
+                    .create_option(|o| {
+                        o.name("seed")
+                            .description("Replay the quiz generated from this seed.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("variant")
+                            .description("Choose what is hidden: a regex or a small DFA.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("regex", "regex")
+                            .add_string_choice("dfa", "dfa")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("difficulty")
+                            .description("Choose how hard the answer is.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("easy", "easy")
+                            .add_string_choice("normal", "normal")
+                            .add_string_choice("hard", "hard")
+                            .add_string_choice("lunatic", "lunatic")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("max_queries")
+                            .description(
+                                "Limit the number of distinct words the channel may query.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("time_limit")
+                            .description("Reveal the answer after this time, e.g. 10m.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("team_queries")
+                            .description("Play in teams, each sharing this many distinct queries.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("team_guesses")
+                            .description("Play in teams, each sharing this many guesses.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("max_guess_size")
+                            .description("Reject guesses with more nodes than this.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("max_guess_stars")
+                            .description("Reject guesses with more stars than this.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("hint_penalty")
+                            .description("Points each unit of hint cost deducts (10 by default).")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("reveal_literals")
+                            .description("Reveal how many letters the answer is written with.")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("reveal_epsilon")
+                            .description("Reveal whether the empty word is accepted.")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("reveal_word")
+                            .description("Reveal one more accepted word.")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
             })
             .create_application_command(|command| {
                 command
-                    .name("query")
-                    .description("Query whether is matched with regular expression.")
+                    .name("practice")
+                    .description("Practice alone in a direct message, off the record.")
                     .create_option(|o| {
-                        o.name("input")
-                            .description("Please enter the input you wish to test for a match.")
+                        o.name("letters")
+                            .description(
+                                "Please choice number of letters in the domain-set, e.g. 3 for \
+                                 a-c.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .add_int_choice(1, 1)
+                            .add_int_choice(2, 2)
+                            .add_int_choice(3, 3)
+                            .add_int_choice(4, 4)
+                            .add_int_choice(5, 5)
+                            .add_int_choice(6, 6)
+                            .add_int_choice(7, 7)
+                            .add_int_choice(8, 8)
+                            .add_int_choice(9, 9)
+                            .add_int_choice(10, 10)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("variant")
+                            .description("Choose what is hidden: a regex or a small DFA.")
                             .kind(ApplicationCommandOptionType::String)
-                            .required(true)
+                            .add_string_choice("regex", "regex")
+                            .add_string_choice("dfa", "dfa")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("difficulty")
+                            .description("Choose how hard the answer is.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("easy", "easy")
+                            .add_string_choice("normal", "normal")
+                            .add_string_choice("hard", "hard")
+                            .add_string_choice("lunatic", "lunatic")
+                            .required(false)
                     })
             })
             .create_application_command(|command| {
                 command
-                    .name("guess")
-                    .description("Check your answer.")
+                    .name("match")
+                    .description("Play a best-of-N match of consecutive quizzes.")
                     .create_option(|o| {
-                        o.name("regex")
-                            .description("Please enter the regex you guess.")
+                        o.name("rounds")
+                            .description("Please choose the number of rounds (3 by default).")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .add_int_choice(3, 3)
+                            .add_int_choice(5, 5)
+                            .add_int_choice(7, 7)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("letters")
+                            .description(
+                                "Please choice number of letters in the domain-set, e.g. 3 for \
+                                 a-c.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .add_int_choice(1, 1)
+                            .add_int_choice(2, 2)
+                            .add_int_choice(3, 3)
+                            .add_int_choice(4, 4)
+                            .add_int_choice(5, 5)
+                            .add_int_choice(6, 6)
+                            .add_int_choice(7, 7)
+                            .add_int_choice(8, 8)
+                            .add_int_choice(9, 9)
+                            .add_int_choice(10, 10)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("variant")
+                            .description("Choose what is hidden: a regex or a small DFA.")
                             .kind(ApplicationCommandOptionType::String)
-                            .required(true)
+                            .add_string_choice("regex", "regex")
+                            .add_string_choice("dfa", "dfa")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("difficulty")
+                            .description("Choose how hard the answer is.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("easy", "easy")
+                            .add_string_choice("normal", "normal")
+                            .add_string_choice("hard", "hard")
+                            .add_string_choice("lunatic", "lunatic")
+                            .required(false)
                     })
             })
             .create_application_command(|command| {
                 command
-                    .name("summary")
-                    .description("Dump the results of the query so far.")
+                    .name("quiz")
+                    .description("Start a quiz shared by a seed code.")
+                    .create_option(|o| {
+                        o.name("from-seed")
+                            .description(
+                                "Replay the quiz of a seed code, e.g. from another server.",
+                            )
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|o| {
+                                o.name("code")
+                                    .description("The seed code shown when a quiz ends.")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("query")
+                    .description("Query whether is matched with regular expression.")
+                    .create_option(|o| {
+                        o.name("input")
+                            .description("Please enter the input you wish to test for a match.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("guess")
+                    .description("Check your answer.")
+                    .create_option(|o| {
+                        o.name("regex")
+                            .description("Please enter the regex or transition table you guess.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("summary")
+                    .description("Dump the results of the query so far.")
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("history")
+                    .description("List every query so far with who asked it.")
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("hint")
+                    .description("Reveal a hint on the answer.")
+                    .create_option(|o| {
+                        o.name("kind")
+                            .description("Please choose what to reveal.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("examples", "examples")
+                            .add_string_choice("state-count", "state-count")
+                            .add_string_choice("shortest-word", "shortest-word")
+                            .add_string_choice("used-alphabets", "used-alphabets")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("explain")
+                    .description("Explain how the answer of the last quiz matches a word.")
+                    .create_option(|o| {
+                        o.name("word")
+                            .description("Please enter the word to explain.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("golf")
+                    .description("Start a round of regex golf.")
+                    .create_option(|o| {
+                        o.name("letters")
+                            .description(
+                                "Please choice number of letters in the domain-set, e.g. 3 for \
+                                 a-c.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .add_int_choice(1, 1)
+                            .add_int_choice(2, 2)
+                            .add_int_choice(3, 3)
+                            .add_int_choice(4, 4)
+                            .add_int_choice(5, 5)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("seed")
+                            .description("Replay the round generated from this seed.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("golf-submit")
+                    .description("Submit a regex to the round of regex golf.")
+                    .create_option(|o| {
+                        o.name("regex")
+                            .description("Please enter your regex.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("golf-end")
+                    .description("End the round of regex golf and reveal the entries.")
+            })
+            .create_application_command(|command| {
+                command
+                    .name("leaderboard")
+                    .description("Show the top solvers of this server.")
+                    .create_option(|o| {
+                        o.name("period")
+                            .description("Please choose the period to rank.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("week", "week")
+                            .add_string_choice("month", "month")
+                            .add_string_choice("all-time", "all-time")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("teams")
+                            .description("Rank the teams of team quizzes instead of players.")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("archive")
+                    .description("Browse the quizzes played on this server.")
+                    .create_option(|o| {
+                        o.name("page")
+                            .description("Please input the page to open.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("replay")
+                    .description("Replay the moves of a quiz played on this server.")
+                    .create_option(|o| {
+                        o.name("game")
+                            .description("Please input the number of the game shown by `/archive`.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(true)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("records")
+                    .description("Show the record solves of each difficulty.")
+                    .create_option(|o| {
+                        o.name("global")
+                            .description(
+                                "Show the records across every server instead of this one.",
+                            )
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("rating")
+                    .description("Show the rating of a player in this server.")
+                    .create_option(|o| {
+                        o.name("player")
+                            .description("Please choose the player to show (yourself by default).")
+                            .kind(ApplicationCommandOptionType::User)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("stats")
+                    .description("Show the statistics of a player in this server.")
+                    .create_option(|o| {
+                        o.name("user")
+                            .description("Please choose the player to show (yourself by default).")
+                            .kind(ApplicationCommandOptionType::User)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("daily")
+                    .description("Play the daily puzzle shared by every server.")
+                    .create_option(|o| {
+                        o.name("action")
+                            .description("Please choose what to do (start by default).")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("start", "start")
+                            .add_string_choice("stats", "stats")
+                            .add_string_choice("top", "top")
+                            .add_string_choice("subscribe", "subscribe")
+                            .add_string_choice("unsubscribe", "unsubscribe")
+                            .add_string_choice("show-name", "show-name")
+                            .add_string_choice("hide-name", "hide-name")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("name")
+                            .description(
+                                "Name to show in the global ranking (your username by default).",
+                            )
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("tournament")
+                    .description("Hold a week-long tournament of regex races.")
+                    .create_option(|o| {
+                        o.name("action")
+                            .description("Please choose what to do (status by default).")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("open", "open")
+                            .add_string_choice("register", "register")
+                            .add_string_choice("start", "start")
+                            .add_string_choice("play", "play")
+                            .add_string_choice("status", "status")
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("race")
+                    .description("Race another player on the same hidden regex.")
+                    .create_option(|o| {
+                        o.name("opponent")
+                            .description("Please choose the player to race.")
+                            .kind(ApplicationCommandOptionType::User)
+                            .required(true)
+                    })
+                    .create_option(|o| {
+                        o.name("letters")
+                            .description("Number of letters in the domain.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .add_int_choice(1, 1)
+                            .add_int_choice(2, 2)
+                            .add_int_choice(3, 3)
+                            .add_int_choice(4, 4)
+                            .add_int_choice(5, 5)
+                            .add_int_choice(6, 6)
+                            .add_int_choice(7, 7)
+                            .add_int_choice(8, 8)
+                            .add_int_choice(9, 9)
+                            .add_int_choice(10, 10)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("difficulty")
+                            .description("Choose how hard the answer is.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("easy", "easy")
+                            .add_string_choice("normal", "normal")
+                            .add_string_choice("hard", "hard")
+                            .add_string_choice("lunatic", "lunatic")
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("quizzes")
+                    .description("List the quizzes going on in this channel.")
+            })
+            .create_application_command(|command| {
+                command
+                    .name("submit")
+                    .description("Submit your own quiz in a direct message to the bot.")
+                    .create_option(|o| {
+                        o.name("regex")
+                            .description("The answer of your quiz.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_option(|o| {
+                        o.name("letters")
+                            .description("Number of letters in the domain.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("host")
+                    .description("Start the quiz you have submitted in this channel.")
+            })
+            .create_application_command(|command| {
+                command
+                    .name("classroom")
+                    .description("Start a quiz for your class with the answer you set.")
+                    .create_option(|o| {
+                        o.name("regex")
+                            .description("The answer, which only you will see.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_option(|o| {
+                        o.name("letters")
+                            .description("Number of letters in the domain.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("dashboard")
+                    .description("See what your students have done (for teachers).")
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("team")
+                    .description("Join a team in a quiz played in teams.")
+                    .create_option(|o| {
+                        o.name("name")
+                            .description("Name of the team to join or form.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
             })
             .create_application_command(|command| {
                 command
                     .name("join")
                     .description("Register your participation.")
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
             })
             .create_application_command(|command| {
                 command
                     .name("give-up")
                     .description("Register your despair.")
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("giveup")
+                    .description("Vote to give up the quiz and reveal the answer.")
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("undo")
+                    .description("Retract the last query or guess (for moderators).")
+                    .create_option(|o| {
+                        o.name("quiz")
+                            .description(
+                                "Choose the quiz when several are going on in the channel.",
+                            )
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("spoilers")
+                    .description("Show or choose which outputs are hidden behind spoiler tags.")
+                    .create_option(|o| {
+                        o.name("output")
+                            .description("Please choose the output to configure (for moderators).")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("answers", "answers")
+                            .add_string_choice("counterexamples", "counterexamples")
+                            .add_string_choice("archive", "archive")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("enabled")
+                            .description("Whether to hide the output behind spoiler tags.")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("config")
+                    .description("Show or choose the settings of this server (for administrators).")
+                    .create_option(|o| {
+                        o.name("setting")
+                            .description("Please choose the setting to change.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("difficulty", "difficulty")
+                            .add_string_choice("letters", "letters")
+                            .add_string_choice("max-queries", "max-queries")
+                            .add_string_choice("time-limit", "time-limit")
+                            .add_string_choice("locale", "locale")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("value")
+                            .description("New value of the setting, or `default` to reset it.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("channel")
+                            .description("Channel to allow or disallow the bot in.")
+                            .kind(ApplicationCommandOptionType::Channel)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("allowed")
+                            .description("Whether the bot may play in the channel (default: true).")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("spoiler")
+                            .description("Please choose the output to hide or show.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("answers", "answers")
+                            .add_string_choice("counterexamples", "counterexamples")
+                            .add_string_choice("archive", "archive")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("hidden")
+                            .description("Whether to hide the output behind spoiler tags.")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("schedule")
+                    .description("Show or change the puzzles posted on a schedule.")
+                    .create_option(|o| {
+                        o.name("action")
+                            .description("Please choose what to do.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("list", "list")
+                            .add_string_choice("add", "add")
+                            .add_string_choice("remove", "remove")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("cron")
+                            .description(
+                                "When to post, e.g. `0 18 * * 5` for Fridays at 18:00 UTC.",
+                            )
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("difficulty")
+                            .description("Difficulty of the generated puzzles.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .add_string_choice("easy", "easy")
+                            .add_string_choice("normal", "normal")
+                            .add_string_choice("hard", "hard")
+                            .add_string_choice("lunatic", "lunatic")
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("regex")
+                            .description("Curated answer to post instead of a generated puzzle.")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("letters")
+                            .description("Number of letters of the curated answer.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("hours")
+                            .description("Hours each puzzle stays open (default: until the next).")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("reminder")
+                            .description("Hours before closing to post a reminder (default: 24).")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
+                    .create_option(|o| {
+                        o.name("id")
+                            .description("The schedule to remove.")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(false)
+                    })
             })
             .create_application_command(|command| command.name("help").description("helpful"))
     })
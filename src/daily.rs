@@ -0,0 +1,428 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    bot::QuizOptions,
+    database::{from_sql_id, to_sql_id},
+    puzzle_bank::PlayOutcome,
+    regex::DifficultyPreset,
+};
+use anyhow::{anyhow, Context};
+use indoc::indoc;
+use rusqlite::{params, Connection};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Longest name a player may show in the global ranking
+pub const MAX_DISPLAY_NAME: usize = 32;
+
+/// Number of days from the Unix epoch to `time`, which identifies the daily puzzle in UTC.
+pub fn day_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / SECONDS_PER_DAY)
+}
+
+/// Time from `now` to the next midnight in UTC, when the next daily puzzle is posted.
+pub fn until_next_day(now: SystemTime) -> Duration {
+    let elapsed = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let next = Duration::from_secs((elapsed.as_secs() / SECONDS_PER_DAY + 1) * SECONDS_PER_DAY);
+    next - elapsed
+}
+
+/// Seed of the daily puzzle of `day`, scrambled (by SplitMix64) so that
+/// puzzles of consecutive days are unrelated.
+pub fn daily_seed(day: u64) -> u64 {
+    let mut z = day.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Options of the daily puzzle of `day`, the same in every guild.
+pub fn daily_options(day: u64) -> QuizOptions {
+    QuizOptions {
+        preset: Some(DifficultyPreset::Normal),
+        seed: Some(daily_seed(day)),
+        daily: Some(day),
+        ..QuizOptions::default()
+    }
+}
+
+/// Solves of a daily puzzle aggregated over all guilds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyStats {
+    pub solvers: u32,
+    /// Number of guilds in which the puzzle was solved
+    pub guilds: u32,
+    /// Mean number of distinct words queried before the solves
+    pub average_queries: Option<f64>,
+    pub fastest: Option<Duration>,
+}
+
+/// A solve of a daily puzzle in the global ranking, which tells nothing of the player
+/// or their guild but the name they chose to show.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlobalStanding {
+    pub rank: usize,
+    /// Name the player has chosen to show, anonymous if none
+    pub name: Option<String>,
+    pub queries: u32,
+    pub duration: Duration,
+    /// Whether the solve is of the player looking at the ranking
+    pub is_viewer: bool,
+}
+
+/// `input` checked as a name to show in the global ranking: seen in every guild,
+/// it may not mention anyone, link anywhere or carry markdown.
+pub fn display_name(input: &str) -> anyhow::Result<String> {
+    let name = input.trim();
+    if name.is_empty() || name.chars().count() > MAX_DISPLAY_NAME {
+        return Err(anyhow!("A name has 1 to {} characters.", MAX_DISPLAY_NAME));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '.'))
+    {
+        return Err(anyhow!(
+            "A name has only letters, digits, spaces, `-` and `.`."
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Channels subscribed to the daily puzzle and its solves in every guild,
+/// stored in an SQLite database.
+pub struct DailyPuzzles<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> DailyPuzzles<'a> {
+    /// Create the tables of the daily puzzle records in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS daily_channels (
+                    guild_id INTEGER PRIMARY KEY,
+                    channel_id INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS daily_solves (
+                    day INTEGER NOT NULL,
+                    guild_id INTEGER NOT NULL,
+                    user_id INTEGER NOT NULL,
+                    queries INTEGER NOT NULL,
+                    duration_ms INTEGER NOT NULL,
+                    PRIMARY KEY (day, user_id)
+                );
+                CREATE TABLE IF NOT EXISTS daily_names (
+                    user_id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL
+                );
+            "})
+            .context("failed to initialize the daily puzzle records")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        DailyPuzzles { connection }
+    }
+
+    /// Post the daily puzzle of `guild` to `channel` from now on, instead of any other channel.
+    pub fn subscribe(&self, guild: GuildId, channel: ChannelId) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO daily_channels (guild_id, channel_id) VALUES (?1, ?2)",
+                params![to_sql_id(guild.0), to_sql_id(channel.0)],
+            )
+            .map(|_| ())
+            .context("failed to subscribe to the daily puzzle")
+    }
+
+    /// Stop posting the daily puzzle in `guild`, returning whether it was posted.
+    pub fn unsubscribe(&self, guild: GuildId) -> anyhow::Result<bool> {
+        self.connection
+            .execute(
+                "DELETE FROM daily_channels WHERE guild_id = ?1",
+                params![to_sql_id(guild.0)],
+            )
+            .map(|deleted| deleted > 0)
+            .context("failed to unsubscribe from the daily puzzle")
+    }
+
+    /// Channels the daily puzzle is posted to, one for each guild.
+    pub fn channels(&self) -> anyhow::Result<Vec<(GuildId, ChannelId)>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT guild_id, channel_id FROM daily_channels")?;
+        let channels = statement
+            .query_map(params![], |row| {
+                Ok((
+                    GuildId(from_sql_id(row.get(0)?)),
+                    ChannelId(from_sql_id(row.get(1)?)),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the daily channels")?;
+        Ok(channels)
+    }
+
+    /// Record that `user` solved the daily puzzle of `day` in `guild`.
+    /// Only the first solve of each player counts, whichever guild it was in.
+    pub fn record_solve(
+        &self,
+        day: u64,
+        guild: GuildId,
+        user: UserId,
+        outcome: &PlayOutcome,
+    ) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT OR IGNORE INTO daily_solves (day, guild_id, user_id, queries, duration_ms)
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+                "},
+                params![
+                    day as i64,
+                    to_sql_id(guild.0),
+                    to_sql_id(user.0),
+                    outcome.queries,
+                    outcome.duration.as_millis() as i64
+                ],
+            )
+            .map(|_| ())
+            .context("failed to record a daily solve")
+    }
+
+    /// Statistics of the solves of the daily puzzle of `day` in every guild.
+    pub fn stats(&self, day: u64) -> anyhow::Result<DailyStats> {
+        self.connection
+            .query_row(
+                indoc! {"
+                    SELECT COUNT(*), COUNT(DISTINCT guild_id), AVG(queries), MIN(duration_ms)
+                    FROM daily_solves
+                    WHERE day = ?1
+                "},
+                params![day as i64],
+                |row| {
+                    Ok(DailyStats {
+                        solvers: row.get(0)?,
+                        guilds: row.get(1)?,
+                        average_queries: row.get(2)?,
+                        fastest: row
+                            .get::<_, Option<i64>>(3)?
+                            .map(|millis| Duration::from_millis(millis as u64)),
+                    })
+                },
+            )
+            .context("failed to query the daily stats")
+    }
+
+    /// Players who solved the daily puzzle of `day` in `guild`, fastest first.
+    pub fn solvers(&self, day: u64, guild: GuildId) -> anyhow::Result<Vec<UserId>> {
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT user_id
+            FROM daily_solves
+            WHERE day = ?1 AND guild_id = ?2
+            ORDER BY duration_ms
+        "})?;
+        let solvers = statement
+            .query_map(params![day as i64, to_sql_id(guild.0)], |row| {
+                Ok(UserId(from_sql_id(row.get(0)?)))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the daily solvers")?;
+        Ok(solvers)
+    }
+
+    /// Show `user` as `name` in the global ranking from now on, which must be a [display_name].
+    pub fn show_name(&self, user: UserId, name: &str) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO daily_names (user_id, name) VALUES (?1, ?2)
+                    ON CONFLICT (user_id) DO UPDATE SET name = excluded.name
+                "},
+                params![to_sql_id(user.0), name],
+            )
+            .map(|_| ())
+            .context("failed to record a display name")
+    }
+
+    /// Show `user` anonymously in the global ranking, returning whether they showed a name.
+    pub fn hide_name(&self, user: UserId) -> anyhow::Result<bool> {
+        self.connection
+            .execute(
+                "DELETE FROM daily_names WHERE user_id = ?1",
+                params![to_sql_id(user.0)],
+            )
+            .map(|deleted| deleted > 0)
+            .context("failed to remove a display name")
+    }
+
+    /// The `limit` fastest solves of the daily puzzle of `day` in every guild, fewer queries
+    /// breaking ties, followed by the solve of `viewer` if it is not among them.
+    pub fn top(
+        &self,
+        day: u64,
+        viewer: UserId,
+        limit: usize,
+    ) -> anyhow::Result<Vec<GlobalStanding>> {
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT daily_solves.user_id, daily_names.name, queries, duration_ms
+            FROM daily_solves
+            LEFT JOIN daily_names ON daily_names.user_id = daily_solves.user_id
+            WHERE day = ?1
+            ORDER BY duration_ms, queries
+        "})?;
+        let standings = statement
+            .query_map(params![day as i64], |row| {
+                Ok((
+                    UserId(from_sql_id(row.get(0)?)),
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, u32>(2)?,
+                    Duration::from_millis(row.get::<_, i64>(3)? as u64),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the global ranking")?;
+        Ok(standings
+            .into_iter()
+            .enumerate()
+            .filter(|(index, (user, ..))| *index < limit || *user == viewer)
+            .map(|(index, (user, name, queries, duration))| GlobalStanding {
+                rank: index + 1,
+                name,
+                queries,
+                duration,
+                is_viewer: user == viewer,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{daily_seed, day_of, display_name, until_next_day};
+    use crate::{database::Database, puzzle_bank::PlayOutcome};
+    use serenity::model::id::{ChannelId, GuildId, UserId};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn days_start_at_midnight() {
+        let noon = UNIX_EPOCH + Duration::from_secs(3 * 24 * 60 * 60 + 12 * 60 * 60);
+        assert_eq!(day_of(noon), 3);
+        assert_eq!(until_next_day(noon), Duration::from_secs(12 * 60 * 60));
+        assert_eq!(day_of(noon + until_next_day(noon)), 4);
+        assert_ne!(daily_seed(3), daily_seed(4));
+    }
+
+    #[test]
+    fn daily_stats_span_guilds() {
+        let database = Database::open_in_memory().unwrap();
+        let daily = database.daily();
+        daily.subscribe(GuildId(1), ChannelId(10)).unwrap();
+        daily.subscribe(GuildId(1), ChannelId(11)).unwrap();
+        daily.subscribe(GuildId(2), ChannelId(20)).unwrap();
+        assert_eq!(daily.channels().unwrap().len(), 2);
+        assert!(daily.unsubscribe(GuildId(2)).unwrap());
+        assert_eq!(daily.channels().unwrap(), vec![(GuildId(1), ChannelId(11))]);
+
+        let outcome = |queries, seconds| PlayOutcome {
+            queries,
+            duration: Duration::from_secs(seconds),
+            solved: true,
+        };
+        assert_eq!(daily.stats(7).unwrap().solvers, 0);
+        daily
+            .record_solve(7, GuildId(1), UserId(100), &outcome(10, 300))
+            .unwrap();
+        daily
+            .record_solve(7, GuildId(2), UserId(200), &outcome(4, 60))
+            .unwrap();
+        // a second solve in another guild does not count
+        daily
+            .record_solve(7, GuildId(2), UserId(100), &outcome(0, 1))
+            .unwrap();
+        daily
+            .record_solve(8, GuildId(1), UserId(300), &outcome(1, 1))
+            .unwrap();
+
+        let stats = daily.stats(7).unwrap();
+        assert_eq!(stats.solvers, 2);
+        assert_eq!(stats.guilds, 2);
+        assert_eq!(stats.average_queries, Some(7.0));
+        assert_eq!(stats.fastest, Some(Duration::from_secs(60)));
+        assert_eq!(daily.solvers(7, GuildId(1)).unwrap(), vec![UserId(100)]);
+    }
+
+    #[test]
+    fn global_ranking_shows_only_chosen_names() {
+        let database = Database::open_in_memory().unwrap();
+        let daily = database.daily();
+        let outcome = |queries, seconds| PlayOutcome {
+            queries,
+            duration: Duration::from_secs(seconds),
+            solved: true,
+        };
+        daily
+            .record_solve(7, GuildId(1), UserId(100), &outcome(10, 300))
+            .unwrap();
+        daily
+            .record_solve(7, GuildId(2), UserId(200), &outcome(4, 60))
+            .unwrap();
+        daily
+            .record_solve(7, GuildId(2), UserId(300), &outcome(3, 60))
+            .unwrap();
+        daily.show_name(UserId(200), "alice").unwrap();
+        daily.show_name(UserId(200), "Alice B.").unwrap();
+
+        let top = daily.top(7, UserId(100), 2).unwrap();
+        let names = top
+            .iter()
+            .map(|standing| (standing.rank, standing.name.as_deref(), standing.is_viewer))
+            .collect::<Vec<_>>();
+        // fewer queries break the tie, and the viewer follows outside the top
+        assert_eq!(
+            names,
+            vec![
+                (1, None, false),
+                (2, Some("Alice B."), false),
+                (3, None, true)
+            ]
+        );
+        assert_eq!(top[1].queries, 4);
+        assert_eq!(daily.top(7, UserId(200), 2).unwrap().len(), 2);
+
+        assert!(daily.hide_name(UserId(200)).unwrap());
+        assert!(!daily.hide_name(UserId(200)).unwrap());
+        assert_eq!(daily.top(7, UserId(100), 3).unwrap()[1].name, None);
+
+        assert_eq!(display_name("  Alice B. ").unwrap(), "Alice B.");
+        for invalid in [
+            "",
+            "<@100>",
+            "**bold**",
+            "a_b",
+            "https://example.com",
+            "a".repeat(33).as_str(),
+        ] {
+            assert!(display_name(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
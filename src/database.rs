@@ -0,0 +1,217 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    archive::Archive, daily::DailyPuzzles, guild_config::GuildConfig, leaderboard::Leaderboard,
+    puzzle_bank::PuzzleBank, records::Records, saved_games::SavedGames, schedule::Schedules,
+};
+use anyhow::Context;
+use rusqlite::Connection;
+use std::path::Path;
+
+/// Everything the bot keeps across restarts, stored in one SQLite database.
+///
+/// The database owns the connection, and each store borrows it,
+/// so that writes to several stores can be made at once with [Database::transaction].
+pub struct Database {
+    connection: Connection,
+}
+
+impl Database {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let connection = Connection::open(path).context("failed to open the database")?;
+        Self::with_connection(connection)
+    }
+
+    pub fn open_in_memory() -> anyhow::Result<Self> {
+        let connection = Connection::open_in_memory().context("failed to open the database")?;
+        Self::with_connection(connection)
+    }
+
+    fn with_connection(connection: Connection) -> anyhow::Result<Self> {
+        PuzzleBank::create_tables(&connection)?;
+        Leaderboard::create_tables(&connection)?;
+        DailyPuzzles::create_tables(&connection)?;
+        Archive::create_tables(&connection)?;
+        Records::create_tables(&connection)?;
+        GuildConfig::create_tables(&connection)?;
+        SavedGames::create_tables(&connection)?;
+        Schedules::create_tables(&connection)?;
+
+        Ok(Database { connection })
+    }
+
+    pub fn puzzle_bank(&self) -> PuzzleBank<'_> {
+        PuzzleBank::new(&self.connection)
+    }
+
+    pub fn leaderboard(&self) -> Leaderboard<'_> {
+        Leaderboard::new(&self.connection)
+    }
+
+    pub fn daily(&self) -> DailyPuzzles<'_> {
+        DailyPuzzles::new(&self.connection)
+    }
+
+    pub fn archive(&self) -> Archive<'_> {
+        Archive::new(&self.connection)
+    }
+
+    pub fn records(&self) -> Records<'_> {
+        Records::new(&self.connection)
+    }
+
+    pub fn guild_config(&self) -> GuildConfig<'_> {
+        GuildConfig::new(&self.connection)
+    }
+
+    pub fn saved_games(&self) -> SavedGames<'_> {
+        SavedGames::new(&self.connection)
+    }
+
+    pub fn schedules(&self) -> Schedules<'_> {
+        Schedules::new(&self.connection)
+    }
+
+    /// Run `write` in a transaction, so that either all of its writes are kept or,
+    /// if it fails, none of them.
+    pub fn transaction<T>(
+        &self,
+        write: impl FnOnce(&Self) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        atomically(&self.connection, || write(self))
+    }
+}
+
+/// Run `write` in a savepoint of `connection`, which is released if it succeeds
+/// and rolled back if it fails.
+/// Savepoints nest, so this may be used by a store within a [Database::transaction].
+pub(crate) fn atomically<T>(
+    connection: &Connection,
+    write: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    connection
+        .execute_batch("SAVEPOINT atomically")
+        .context("failed to begin a transaction")?;
+    match write() {
+        Ok(value) => {
+            connection
+                .execute_batch("RELEASE atomically")
+                .context("failed to commit a transaction")?;
+            Ok(value)
+        }
+        Err(why) => {
+            connection
+                .execute_batch("ROLLBACK TO atomically; RELEASE atomically")
+                .context("failed to roll back a transaction")?;
+            Err(why)
+        }
+    }
+}
+
+/// The value of an id or seed as stored in SQLite.
+///
+/// SQLite has no unsigned integers, so a `u64` is stored as the `i64` of the same bit pattern.
+/// Every value survives the round trip through [from_sql_id],
+/// but those from 2^63 up are ordered before the others.
+pub(crate) fn to_sql_id(id: u64) -> i64 {
+    id as i64
+}
+
+/// The id or seed stored as `id` by [to_sql_id].
+pub(crate) fn from_sql_id(id: i64) -> u64 {
+    id as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Database;
+    use crate::{
+        bot::{Quiz, QuizId, QuizOptions},
+        leaderboard::Period,
+        quiz::rating::{EloRules, INITIAL_RATING},
+    };
+    use anyhow::anyhow;
+    use serenity::model::id::{ChannelId, GuildId, UserId};
+    use std::time::SystemTime;
+
+    #[test]
+    fn ids_keep_every_bit() {
+        let database = Database::open_in_memory().unwrap();
+        let quiz = Quiz::new_with_options(&QuizOptions::default());
+        let (channel, id) = (ChannelId(u64::MAX), QuizId(1 << 63));
+        database
+            .saved_games()
+            .save(channel, id, &quiz.saved())
+            .unwrap();
+
+        let games = database.saved_games().games().unwrap();
+        assert_eq!((games[0].channel, games[0].id), (channel, id));
+    }
+
+    #[test]
+    fn failed_transactions_keep_no_writes() {
+        let database = Database::open_in_memory().unwrap();
+        let (guild, now) = (GuildId(1), SystemTime::now());
+        let quiz = Quiz::new_with_options(&QuizOptions::default());
+
+        let failed = database.transaction(|database| {
+            database
+                .saved_games()
+                .save(ChannelId(2), QuizId(3), &quiz.saved())?;
+            database.leaderboard().record(guild, UserId(4), 10, now)?;
+            database.leaderboard().record_match(
+                guild,
+                UserId(4),
+                &[UserId(5)],
+                &EloRules::default(),
+            )?;
+            Err::<(), _>(anyhow!("the solve could not be recorded"))
+        });
+        assert!(failed.is_err());
+        assert!(database.saved_games().games().unwrap().is_empty());
+        let leaderboard = database.leaderboard();
+        assert!(leaderboard
+            .top(guild, Period::AllTime, now, 10)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            leaderboard.rating(guild, UserId(4)).unwrap().rating,
+            INITIAL_RATING
+        );
+
+        database
+            .transaction(|database| {
+                database
+                    .saved_games()
+                    .save(ChannelId(2), QuizId(3), &quiz.saved())?;
+                database.leaderboard().record(guild, UserId(4), 10, now)
+            })
+            .unwrap();
+        assert_eq!(database.saved_games().games().unwrap().len(), 1);
+        assert_eq!(
+            database
+                .leaderboard()
+                .top(guild, Period::AllTime, now, 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,406 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    bot::QuizOptions,
+    database::to_sql_id,
+    locale::Locale,
+    quiz::{
+        spoilers::{SpoilerKind, Spoilers},
+        time_limit::parse_time_limit,
+    },
+    regex::DifficultyPreset,
+};
+use anyhow::{anyhow, Context};
+use indoc::indoc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serenity::model::id::{ChannelId, GuildId};
+use std::{convert::TryInto, num::NonZeroU8, str::FromStr, time::Duration};
+
+/// Most letters a quiz may be played over, as offered by `/start`
+const MAX_LETTERS: u8 = 10;
+const CHANNELS_KEY: &str = "channels";
+
+/// A setting of a guild chosen by `/config`, besides spoilers and channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Setting {
+    /// Difficulty of quizzes started without choosing one
+    Difficulty,
+    /// Number of letters of quizzes started without choosing one or a difficulty
+    Letters,
+    /// Query budget of quizzes started without choosing one
+    MaxQueries,
+    /// Time limit of quizzes started without choosing one
+    TimeLimit,
+    Locale,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 5] = [
+        Setting::Difficulty,
+        Setting::Letters,
+        Setting::MaxQueries,
+        Setting::TimeLimit,
+        Setting::Locale,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Difficulty => "difficulty",
+            Setting::Letters => "letters",
+            Setting::MaxQueries => "max-queries",
+            Setting::TimeLimit => "time-limit",
+            Setting::Locale => "locale",
+        }
+    }
+
+    /// `value` checked and written as it is stored.
+    fn normalize(&self, value: &str) -> anyhow::Result<String> {
+        let value = value.trim();
+        Ok(match self {
+            Setting::Difficulty => value.parse::<DifficultyPreset>()?.name().to_string(),
+            Setting::Letters => value
+                .parse::<u8>()
+                .ok()
+                .filter(|letters| (1..=MAX_LETTERS).contains(letters))
+                .ok_or_else(|| anyhow!("The number of letters must be from 1 to {}.", MAX_LETTERS))?
+                .to_string(),
+            Setting::MaxQueries => value
+                .parse::<usize>()
+                .context("The query budget must be a number of words.")?
+                .to_string(),
+            // in seconds
+            Setting::TimeLimit => parse_time_limit(value)?.as_secs().to_string(),
+            Setting::Locale => value.parse::<Locale>()?.name().to_string(),
+        })
+    }
+}
+
+impl FromStr for Setting {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Setting::ALL
+            .iter()
+            .copied()
+            .find(|setting| setting.name() == s)
+            .ok_or_else(|| anyhow!("unknown setting: {}", s))
+    }
+}
+
+/// Options a quiz started in a guild takes unless the command chooses otherwise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuizDefaults {
+    pub preset: Option<DifficultyPreset>,
+    pub letters: Option<NonZeroU8>,
+    pub max_queries: Option<usize>,
+    pub time_limit: Option<Duration>,
+}
+
+impl QuizDefaults {
+    /// `options` with the options left unchosen filled in. The default number of letters is
+    /// taken only if no difficulty is chosen either, since each difficulty has its own.
+    pub fn apply(&self, options: QuizOptions) -> QuizOptions {
+        let sized = options.letters.is_some() || options.preset.is_some();
+        QuizOptions {
+            preset: options.preset.or(self.preset),
+            letters: if sized { options.letters } else { self.letters },
+            max_queries: options.max_queries.or(self.max_queries),
+            time_limit: options.time_limit.or(self.time_limit),
+            ..options
+        }
+    }
+}
+
+/// Settings of each guild stored in an SQLite database, as values of named keys.
+pub struct GuildConfig<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> GuildConfig<'a> {
+    /// Create the tables of the guild configuration in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS guild_config (
+                    guild_id INTEGER NOT NULL,
+                    key TEXT NOT NULL,
+                    value TEXT NOT NULL,
+                    PRIMARY KEY (guild_id, key)
+                );
+            "})
+            .context("failed to initialize the guild configuration")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        GuildConfig { connection }
+    }
+
+    fn get(&self, guild: GuildId, key: &str) -> anyhow::Result<Option<String>> {
+        self.connection
+            .query_row(
+                "SELECT value FROM guild_config WHERE guild_id = ?1 AND key = ?2",
+                params![to_sql_id(guild.0), key],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("failed to query the guild configuration")
+    }
+
+    /// The value of `key` in `guild` parsed, if it is set.
+    fn parsed<T>(&self, guild: GuildId, key: &str) -> anyhow::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Into<anyhow::Error>,
+    {
+        self.get(guild, key)?
+            .map(|value| value.parse::<T>().map_err(Into::into))
+            .transpose()
+            .with_context(|| format!("the configuration of {} is broken", key))
+    }
+
+    fn unset(&self, guild: GuildId, key: &str) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                "DELETE FROM guild_config WHERE guild_id = ?1 AND key = ?2",
+                params![to_sql_id(guild.0), key],
+            )
+            .context("failed to update the guild configuration")?;
+        Ok(())
+    }
+
+    fn set(&self, guild: GuildId, key: &str, value: &str) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO guild_config (guild_id, key, value) VALUES (?1, ?2, ?3)
+                    ON CONFLICT (guild_id, key) DO UPDATE SET value = excluded.value
+                "},
+                params![to_sql_id(guild.0), key, value],
+            )
+            .context("failed to update the guild configuration")?;
+        Ok(())
+    }
+
+    /// Choose `value` for `setting` in `guild`, or go back to the default if `value` is `None`.
+    pub fn set_setting(
+        &self,
+        guild: GuildId,
+        setting: Setting,
+        value: Option<&str>,
+    ) -> anyhow::Result<()> {
+        match value {
+            Some(value) => self.set(guild, setting.name(), &setting.normalize(value)?),
+            None => self.unset(guild, setting.name()),
+        }
+    }
+
+    /// The value of `setting` in `guild` as stored, if it is set.
+    pub fn setting(&self, guild: GuildId, setting: Setting) -> anyhow::Result<Option<String>> {
+        self.get(guild, setting.name())
+    }
+
+    /// Options of quizzes started in `guild` unless chosen otherwise.
+    pub fn defaults(&self, guild: GuildId) -> anyhow::Result<QuizDefaults> {
+        Ok(QuizDefaults {
+            preset: self.parsed(guild, Setting::Difficulty.name())?,
+            letters: self
+                .parsed::<u8>(guild, Setting::Letters.name())?
+                .and_then(|letters| letters.try_into().ok()),
+            max_queries: self.parsed(guild, Setting::MaxQueries.name())?,
+            time_limit: self
+                .parsed(guild, Setting::TimeLimit.name())?
+                .map(Duration::from_secs),
+        })
+    }
+
+    pub fn locale(&self, guild: GuildId) -> anyhow::Result<Locale> {
+        Ok(self
+            .parsed(guild, Setting::Locale.name())?
+            .unwrap_or_default())
+    }
+
+    /// Channels the bot may operate in within `guild`, every channel being allowed if there is none.
+    pub fn channels(&self, guild: GuildId) -> anyhow::Result<Vec<ChannelId>> {
+        self.get(guild, CHANNELS_KEY)?
+            .iter()
+            .flat_map(|channels| channels.split(','))
+            .map(|channel| {
+                channel
+                    .parse()
+                    .map(ChannelId)
+                    .context("the configuration of channels is broken")
+            })
+            .collect()
+    }
+
+    /// Allow the bot to operate in `channel` of `guild`, or stop allowing it.
+    pub fn allow_channel(
+        &self,
+        guild: GuildId,
+        channel: ChannelId,
+        allowed: bool,
+    ) -> anyhow::Result<()> {
+        let mut channels = self.channels(guild)?;
+        channels.retain(|&other| other != channel);
+        if allowed {
+            channels.push(channel);
+        }
+        if channels.is_empty() {
+            self.unset(guild, CHANNELS_KEY)
+        } else {
+            let channels = channels
+                .iter()
+                .map(|channel| channel.0.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            self.set(guild, CHANNELS_KEY, &channels)
+        }
+    }
+
+    /// Which outputs are spoilered in `guild`, all of them unless configured otherwise.
+    pub fn spoilers(&self, guild: GuildId) -> anyhow::Result<Spoilers> {
+        let mut spoilers = Spoilers::default();
+        for kind in SpoilerKind::ALL {
+            if let Some(value) = self.get(guild, &spoiler_key(kind))? {
+                spoilers.set(kind, value == "on");
+            }
+        }
+        Ok(spoilers)
+    }
+
+    /// Choose whether outputs of `kind` are spoilered in `guild`.
+    pub fn set_spoiler(
+        &self,
+        guild: GuildId,
+        kind: SpoilerKind,
+        enabled: bool,
+    ) -> anyhow::Result<()> {
+        self.set(
+            guild,
+            &spoiler_key(kind),
+            if enabled { "on" } else { "off" },
+        )
+    }
+}
+
+fn spoiler_key(kind: SpoilerKind) -> String {
+    format!("spoilers.{}", kind.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuizDefaults, Setting};
+    use crate::{
+        bot::QuizOptions,
+        database::Database,
+        locale::Locale,
+        quiz::spoilers::{SpoilerKind, Spoilers},
+        regex::DifficultyPreset,
+    };
+    use serenity::model::id::{ChannelId, GuildId};
+    use std::{num::NonZeroU8, time::Duration};
+
+    #[test]
+    fn settings_are_checked_and_fill_in_unchosen_options() {
+        let database = Database::open_in_memory().unwrap();
+        let config = database.guild_config();
+        let guild = GuildId(1);
+        assert_eq!(config.defaults(guild).unwrap(), QuizDefaults::default());
+        assert!(config
+            .set_setting(guild, Setting::Letters, Some("11"))
+            .is_err());
+        assert!(config
+            .set_setting(guild, Setting::Locale, Some("fr"))
+            .is_err());
+
+        config
+            .set_setting(guild, Setting::Letters, Some("4"))
+            .unwrap();
+        config
+            .set_setting(guild, Setting::TimeLimit, Some("1h30m"))
+            .unwrap();
+        config
+            .set_setting(guild, Setting::Locale, Some("ja"))
+            .unwrap();
+        let defaults = config.defaults(guild).unwrap();
+        assert_eq!(defaults.letters, NonZeroU8::new(4));
+        assert_eq!(defaults.time_limit, Some(Duration::from_secs(90 * 60)));
+        assert_eq!(config.locale(guild).unwrap(), Locale::Japanese);
+
+        let options = defaults.apply(QuizOptions::default());
+        assert_eq!(options.letters, NonZeroU8::new(4));
+        let options = defaults.apply(QuizOptions {
+            preset: Some(DifficultyPreset::Easy),
+            time_limit: Some(Duration::from_secs(60)),
+            ..QuizOptions::default()
+        });
+        assert_eq!(options.letters, None);
+        assert_eq!(options.time_limit, Some(Duration::from_secs(60)));
+
+        config.set_setting(guild, Setting::Letters, None).unwrap();
+        assert_eq!(config.defaults(guild).unwrap().letters, None);
+    }
+
+    #[test]
+    fn channels_are_allowed_one_by_one() {
+        let database = Database::open_in_memory().unwrap();
+        let config = database.guild_config();
+        let guild = GuildId(1);
+        assert!(config.channels(guild).unwrap().is_empty());
+        config.allow_channel(guild, ChannelId(2), true).unwrap();
+        config.allow_channel(guild, ChannelId(3), true).unwrap();
+        config.allow_channel(guild, ChannelId(2), true).unwrap();
+        assert_eq!(
+            config.channels(guild).unwrap(),
+            vec![ChannelId(3), ChannelId(2)]
+        );
+        config.allow_channel(guild, ChannelId(3), false).unwrap();
+        config.allow_channel(guild, ChannelId(2), false).unwrap();
+        assert!(config.channels(guild).unwrap().is_empty());
+    }
+
+    #[test]
+    fn spoiler_settings_are_kept_per_guild() {
+        let database = Database::open_in_memory().unwrap();
+        let config = database.guild_config();
+        let (guild, other) = (GuildId(1), GuildId(2));
+        assert_eq!(config.spoilers(guild).unwrap(), Spoilers::default());
+
+        config
+            .set_spoiler(guild, SpoilerKind::Answers, false)
+            .unwrap();
+        config
+            .set_spoiler(guild, SpoilerKind::Archive, false)
+            .unwrap();
+        config
+            .set_spoiler(guild, SpoilerKind::Archive, true)
+            .unwrap();
+        assert_eq!(
+            config.spoilers(guild).unwrap(),
+            Spoilers {
+                answers: false,
+                counterexamples: true,
+                archive: true,
+            }
+        );
+        assert_eq!(config.spoilers(other).unwrap(), Spoilers::default());
+    }
+}
@@ -0,0 +1,491 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    database::{atomically, from_sql_id, to_sql_id},
+    quiz::rating::{rate_win, EloRules, INITIAL_RATING},
+};
+use anyhow::{anyhow, Context};
+use indoc::indoc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serenity::model::id::{GuildId, UserId};
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Time span a leaderboard covers, ending now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Period {
+    /// The last 7 days
+    Week,
+    /// The last 30 days
+    Month,
+    AllTime,
+}
+
+impl Period {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+            Period::AllTime => "all-time",
+        }
+    }
+
+    fn length(&self) -> Option<Duration> {
+        match self {
+            Period::Week => Some(Duration::from_secs(7 * SECONDS_PER_DAY)),
+            Period::Month => Some(Duration::from_secs(30 * SECONDS_PER_DAY)),
+            Period::AllTime => None,
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            "all-time" => Ok(Period::AllTime),
+            _ => Err(anyhow!("unknown period: {}", s)),
+        }
+    }
+}
+
+/// Total points of a player over a period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Standing {
+    pub user: UserId,
+    pub points: u64,
+    pub solves: u32,
+}
+
+/// Total points of a team over a period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TeamStanding {
+    pub team: String,
+    pub points: u64,
+    pub solves: u32,
+}
+
+/// Elo rating of a player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerRating {
+    pub user: UserId,
+    pub rating: f64,
+    /// Number of matches the player has been rated by
+    pub matches: u32,
+}
+
+/// Rating of a player before and after a match.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RatingChange {
+    pub user: UserId,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// Seconds since the Unix epoch, as stored in the database.
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Points and ratings of players stored in an SQLite database, kept separately for each guild.
+pub struct Leaderboard<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> Leaderboard<'a> {
+    /// Create the tables of the leaderboard in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS scores (
+                    guild_id INTEGER NOT NULL,
+                    user_id INTEGER NOT NULL,
+                    points INTEGER NOT NULL,
+                    recorded_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS scores_by_guild ON scores (guild_id, recorded_at);
+                CREATE TABLE IF NOT EXISTS team_scores (
+                    guild_id INTEGER NOT NULL,
+                    team TEXT NOT NULL,
+                    points INTEGER NOT NULL,
+                    recorded_at INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS ratings (
+                    guild_id INTEGER NOT NULL,
+                    user_id INTEGER NOT NULL,
+                    rating REAL NOT NULL,
+                    matches INTEGER NOT NULL,
+                    PRIMARY KEY (guild_id, user_id)
+                );
+            "})
+            .context("failed to initialize the leaderboard")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        Leaderboard { connection }
+    }
+
+    /// Record that `user` earned `points` in `guild` at `at`.
+    pub fn record(
+        &self,
+        guild: GuildId,
+        user: UserId,
+        points: u32,
+        at: SystemTime,
+    ) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO scores (guild_id, user_id, points, recorded_at)
+                    VALUES (?1, ?2, ?3, ?4)
+                "},
+                params![
+                    to_sql_id(guild.0),
+                    to_sql_id(user.0),
+                    points,
+                    unix_seconds(at)
+                ],
+            )
+            .map(|_| ())
+            .context("failed to record a score")
+    }
+
+    /// Players of `guild` with the most points earned during `period` ending at `now`,
+    /// at most `limit` of them, from the top.
+    pub fn top(
+        &self,
+        guild: GuildId,
+        period: Period,
+        now: SystemTime,
+        limit: usize,
+    ) -> anyhow::Result<Vec<Standing>> {
+        let since = period
+            .length()
+            .map(|length| unix_seconds(now.checked_sub(length).unwrap_or(UNIX_EPOCH)));
+
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT user_id, SUM(points) AS total, COUNT(*)
+            FROM scores
+            WHERE guild_id = ?1 AND (?2 IS NULL OR recorded_at > ?2)
+            GROUP BY user_id
+            ORDER BY total DESC, MIN(recorded_at)
+            LIMIT ?3
+        "})?;
+        let standings = statement
+            .query_map(params![to_sql_id(guild.0), since, limit as i64], |row| {
+                Ok(Standing {
+                    user: UserId(from_sql_id(row.get(0)?)),
+                    points: row.get::<_, i64>(1)? as u64,
+                    solves: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the leaderboard")?;
+        Ok(standings)
+    }
+
+    /// Record that the team `team` earned `points` in `guild` at `at`.
+    pub fn record_team(
+        &self,
+        guild: GuildId,
+        team: &str,
+        points: u32,
+        at: SystemTime,
+    ) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO team_scores (guild_id, team, points, recorded_at)
+                    VALUES (?1, ?2, ?3, ?4)
+                "},
+                params![to_sql_id(guild.0), team, points, unix_seconds(at)],
+            )
+            .map(|_| ())
+            .context("failed to record a team score")
+    }
+
+    /// Teams of `guild` with the most points earned during `period` ending at `now`,
+    /// at most `limit` of them, from the top. Teams of the same name are counted together.
+    pub fn top_teams(
+        &self,
+        guild: GuildId,
+        period: Period,
+        now: SystemTime,
+        limit: usize,
+    ) -> anyhow::Result<Vec<TeamStanding>> {
+        let since = period
+            .length()
+            .map(|length| unix_seconds(now.checked_sub(length).unwrap_or(UNIX_EPOCH)));
+
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT team, SUM(points) AS total, COUNT(*)
+            FROM team_scores
+            WHERE guild_id = ?1 AND (?2 IS NULL OR recorded_at > ?2)
+            GROUP BY team
+            ORDER BY total DESC, MIN(recorded_at)
+            LIMIT ?3
+        "})?;
+        let standings = statement
+            .query_map(params![to_sql_id(guild.0), since, limit as i64], |row| {
+                Ok(TeamStanding {
+                    team: row.get(0)?,
+                    points: row.get::<_, i64>(1)? as u64,
+                    solves: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the team leaderboard")?;
+        Ok(standings)
+    }
+
+    /// Rating of `user` in `guild`, which is [INITIAL_RATING] until they play a match.
+    pub fn rating(&self, guild: GuildId, user: UserId) -> anyhow::Result<PlayerRating> {
+        let rating = self
+            .connection
+            .query_row(
+                "SELECT rating, matches FROM ratings WHERE guild_id = ?1 AND user_id = ?2",
+                params![to_sql_id(guild.0), to_sql_id(user.0)],
+                |row| {
+                    Ok(PlayerRating {
+                        user,
+                        rating: row.get(0)?,
+                        matches: row.get(1)?,
+                    })
+                },
+            )
+            .optional()
+            .context("failed to query a rating")?;
+        Ok(rating.unwrap_or(PlayerRating {
+            user,
+            rating: INITIAL_RATING,
+            matches: 0,
+        }))
+    }
+
+    /// Update the ratings in `guild` after `winner` beats `losers` in a match.
+    pub fn record_match(
+        &self,
+        guild: GuildId,
+        winner: UserId,
+        losers: &[UserId],
+        rules: &EloRules,
+    ) -> anyhow::Result<Vec<RatingChange>> {
+        let before = std::iter::once(winner)
+            .chain(losers.iter().copied())
+            .map(|user| self.rating(guild, user).map(|rating| rating.rating))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let (winner_after, losers_after) = rate_win(rules, before[0], &before[1..]);
+        let changes = std::iter::once(winner)
+            .chain(losers.iter().copied())
+            .zip(before)
+            .zip(std::iter::once(winner_after).chain(losers_after))
+            .map(|((user, before), after)| RatingChange {
+                user,
+                before,
+                after,
+            })
+            .collect::<Vec<_>>();
+
+        atomically(self.connection, || {
+            for change in &changes {
+                self.connection
+                    .execute(
+                        indoc! {"
+                            INSERT INTO ratings (guild_id, user_id, rating, matches)
+                            VALUES (?1, ?2, ?3, 1)
+                            ON CONFLICT (guild_id, user_id)
+                            DO UPDATE SET rating = excluded.rating, matches = matches + 1
+                        "},
+                        params![to_sql_id(guild.0), to_sql_id(change.user.0), change.after],
+                    )
+                    .context("failed to update a rating")?;
+            }
+            Ok(())
+        })
+        .context("failed to update the ratings")?;
+        Ok(changes)
+    }
+
+    /// Players of `guild` with the highest ratings, at most `limit` of them, from the top.
+    pub fn top_ratings(&self, guild: GuildId, limit: usize) -> anyhow::Result<Vec<PlayerRating>> {
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT user_id, rating, matches
+            FROM ratings
+            WHERE guild_id = ?1
+            ORDER BY rating DESC
+            LIMIT ?2
+        "})?;
+        let ratings = statement
+            .query_map(params![to_sql_id(guild.0), limit as i64], |row| {
+                Ok(PlayerRating {
+                    user: UserId(from_sql_id(row.get(0)?)),
+                    rating: row.get(1)?,
+                    matches: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the ratings")?;
+        Ok(ratings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Period, Standing};
+    use crate::{
+        database::Database,
+        quiz::rating::{EloRules, INITIAL_RATING},
+    };
+    use serenity::model::id::{GuildId, UserId};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn leaderboard_sums_points_per_period() {
+        let database = Database::open_in_memory().unwrap();
+        let leaderboard = database.leaderboard();
+        let now = SystemTime::now();
+        let days_ago = |days: u64| now - Duration::from_secs(days * 24 * 60 * 60);
+
+        leaderboard
+            .record(GuildId(1), UserId(10), 100, days_ago(40))
+            .unwrap();
+        leaderboard
+            .record(GuildId(1), UserId(10), 50, days_ago(1))
+            .unwrap();
+        leaderboard
+            .record(GuildId(1), UserId(20), 80, days_ago(10))
+            .unwrap();
+        leaderboard
+            .record(GuildId(1), UserId(30), 60, days_ago(2))
+            .unwrap();
+        leaderboard
+            .record(GuildId(2), UserId(20), 1000, days_ago(1))
+            .unwrap();
+
+        let top = |period| {
+            leaderboard
+                .top(GuildId(1), period, now, 10)
+                .unwrap()
+                .into_iter()
+                .map(|standing| (standing.user.0, standing.points))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(top(Period::Week), vec![(30, 60), (10, 50)]);
+        assert_eq!(top(Period::Month), vec![(20, 80), (30, 60), (10, 50)]);
+        assert_eq!(top(Period::AllTime), vec![(10, 150), (20, 80), (30, 60)]);
+
+        assert_eq!(
+            leaderboard
+                .top(GuildId(1), Period::AllTime, now, 1)
+                .unwrap(),
+            vec![Standing {
+                user: UserId(10),
+                points: 150,
+                solves: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn team_leaderboard_sums_points_by_name() {
+        let database = Database::open_in_memory().unwrap();
+        let leaderboard = database.leaderboard();
+        let now = SystemTime::now();
+        let days_ago = |days: u64| now - Duration::from_secs(days * 24 * 60 * 60);
+
+        leaderboard
+            .record_team(GuildId(1), "red", 100, days_ago(40))
+            .unwrap();
+        leaderboard
+            .record_team(GuildId(1), "red", 30, days_ago(1))
+            .unwrap();
+        leaderboard
+            .record_team(GuildId(1), "blue", 50, days_ago(2))
+            .unwrap();
+        leaderboard
+            .record_team(GuildId(2), "blue", 1000, days_ago(1))
+            .unwrap();
+
+        let top = |period| {
+            leaderboard
+                .top_teams(GuildId(1), period, now, 10)
+                .unwrap()
+                .into_iter()
+                .map(|standing| (standing.team, standing.points, standing.solves))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            top(Period::Week),
+            vec![("blue".to_string(), 50, 1), ("red".to_string(), 30, 1)]
+        );
+        assert_eq!(
+            top(Period::AllTime),
+            vec![("red".to_string(), 130, 2), ("blue".to_string(), 50, 1)]
+        );
+    }
+
+    #[test]
+    fn ratings_are_updated_per_guild() {
+        let database = Database::open_in_memory().unwrap();
+        let leaderboard = database.leaderboard();
+        let rules = EloRules::default();
+
+        assert_eq!(
+            leaderboard.rating(GuildId(1), UserId(10)).unwrap().rating,
+            INITIAL_RATING
+        );
+
+        let changes = leaderboard
+            .record_match(GuildId(1), UserId(10), &[UserId(20)], &rules)
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].after > changes[0].before);
+        assert!(changes[1].after < changes[1].before);
+
+        leaderboard
+            .record_match(GuildId(1), UserId(10), &[UserId(30)], &rules)
+            .unwrap();
+        let rating = leaderboard.rating(GuildId(1), UserId(10)).unwrap();
+        assert_eq!(rating.matches, 2);
+        assert!(rating.rating > changes[0].after);
+        assert_eq!(
+            leaderboard.rating(GuildId(2), UserId(10)).unwrap().matches,
+            0
+        );
+
+        let top = leaderboard
+            .top_ratings(GuildId(1), 10)
+            .unwrap()
+            .into_iter()
+            .map(|rating| rating.user.0)
+            .collect::<Vec<_>>();
+        assert_eq!(top[0], 10);
+        assert_eq!(top.len(), 3);
+    }
+}
@@ -25,12 +25,23 @@
 //  ~~~~~~~~~~~~~~~~~~             ~~~~~~~
 //  const generics (stable)        generic_const_exprs
 
+pub mod archive;
 pub mod bot;
 pub mod command_ext;
 pub mod commands;
 pub mod concepts;
+pub mod daily;
+pub mod database;
 pub mod errors;
+pub mod guild_config;
+pub mod leaderboard;
+pub mod locale;
 pub mod notification;
 pub mod parser;
+pub mod puzzle_bank;
+pub mod quiz;
+pub mod records;
 pub mod regex;
 pub mod response;
+pub mod saved_games;
+pub mod schedule;
@@ -0,0 +1,91 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use anyhow::anyhow;
+use serenity::model::id::ChannelId;
+use std::str::FromStr;
+
+/// Language the bot speaks in a guild, as configured by `/config`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    English,
+    Japanese,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Japanese];
+
+    /// Code of the language, as chosen in `/config`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Japanese => "ja",
+        }
+    }
+
+    /// Refusal of a command outside `allowed`, the channels the bot may operate in.
+    pub fn channel_not_allowed(&self, allowed: &[ChannelId]) -> String {
+        let channels = allowed
+            .iter()
+            .map(|channel| format!("<#{}>", channel.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self {
+            Locale::English => format!("This bot only plays in {channels} on this server."),
+            Locale::Japanese => format!("このサーバーでは {channels} でのみ遊べます。"),
+        }
+    }
+
+    /// Refusal of `/config` to members who are not administrators.
+    pub fn admin_only(&self) -> &'static str {
+        match self {
+            Locale::English => "Only administrators may change the configuration.",
+            Locale::Japanese => "設定を変更できるのは管理者だけです。",
+        }
+    }
+
+    /// Title of the configuration of a guild.
+    pub fn configuration(&self) -> &'static str {
+        match self {
+            Locale::English => "Configuration",
+            Locale::Japanese => "設定",
+        }
+    }
+
+    /// Headline revealing the answer of a quiz whose time is up.
+    pub fn time_is_up(&self, quiz: impl std::fmt::Display) -> String {
+        match self {
+            Locale::English => format!("Time is up for quiz {quiz}!"),
+            Locale::Japanese => format!("クイズ {quiz} は時間切れです！"),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Locale::ALL
+            .iter()
+            .copied()
+            .find(|locale| locale.name() == s)
+            .ok_or_else(|| anyhow!("unknown locale: {}", s))
+    }
+}
@@ -26,31 +26,66 @@ use counted_array::counted_array;
 use itertools::Either;
 use once_cell::sync::Lazy;
 use regexsoup::{
-    bot::{Container, InspectionAcceptance, Msg, Quiz, Tsx},
+    archive::ArchivedGame,
+    bot::{Container, InspectionAcceptance, Msg, Quiz, QuizId, QuizOptions, Tsx},
     command_ext::CommandExt,
     commands,
     concepts::SameAs,
+    daily::{daily_options, day_of, display_name, until_next_day},
+    database::Database,
+    guild_config::{GuildConfig, Setting},
+    leaderboard::{Period, RatingChange},
     notification::{Notification, SlashCommand, To},
     parser::{ComponentParser, CustomId},
-    regex::Alphabet,
+    puzzle_bank::PlayOutcome,
+    quiz::{
+        explain::explain,
+        golf::GolfRound,
+        guess_limits::GuessLimits,
+        handicap::Handicaps,
+        hidden::QuizVariant,
+        hints::HintTier,
+        multi_round::{Match, RoundWin, BEST_OF},
+        race::{Lane, Race, RaceResult, RaceStatus, Solve, TIE_WINDOW},
+        rating::EloRules,
+        scoring::Score,
+        seed_code::SeedCode,
+        spoilers::{SpoilerKind, Spoilers},
+        submission::Submission,
+        team::TeamBudget,
+        time_limit::parse_time_limit,
+        tournament::{MatchId, Tournament},
+        vote::VoteStatus,
+    },
+    records::{BrokenRecord, Record, TIERS},
+    regex::{Alphabet, DifficultyPreset, DifficultyTier, RegexAst},
+    schedule::{Cron, Due, NewSchedule, Posted, PuzzleSource, Schedule, Schedules},
 };
 use serenity::{
     async_trait,
-    builder::CreateEmbed,
+    builder::{CreateButton, CreateEmbed},
     client::{Client, EventHandler},
+    http::Http,
     model::{
+        channel::PartialChannel,
         gateway::Ready,
-        id::{ChannelId, UserId},
-        interactions::{application_command::ApplicationCommand, Interaction},
+        id::{ChannelId, GuildId, UserId},
+        interactions::{
+            application_command::{ApplicationCommand, ApplicationCommandInteraction},
+            message_component::ButtonStyle,
+            Interaction, InteractionResponseType,
+        },
+        user::User,
     },
     utils::Colour,
 };
 use std::{
-    collections::{HashMap, HashSet},
-    convert::TryInto,
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
     fmt::{Debug, Display},
     num::NonZeroU8,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, MutexGuard, Once},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use strum::IntoEnumIterator;
 use tokio::sync::mpsc::channel;
@@ -58,11 +93,40 @@ use tokio::sync::mpsc::channel;
 counted_array!(
     const COMMANDS: [&'static str; _] = [
         "start",
+        "practice",
+        "match",
+        "quiz",
         "query",
         "guess",
         "summary",
+        "history",
+        "hint",
+        "explain",
+        "golf",
+        "golf-submit",
+        "golf-end",
+        "leaderboard",
+        "archive",
+        "replay",
+        "records",
+        "rating",
+        "stats",
+        "daily",
+        "tournament",
+        "race",
+        "quizzes",
+        "submit",
+        "host",
+        "classroom",
+        "dashboard",
+        "team",
         "join",
         "give-up",
+        "giveup",
+        "undo",
+        "spoilers",
+        "config",
+        "schedule",
         "help",
     ]
 );
@@ -72,39 +136,1967 @@ pub static CONTAINER: Lazy<Arc<Mutex<Container>>> = Lazy::new(|| {
     Arc::new(Mutex::new(container))
 });
 
+/// Database at `REGEX_SOUP_DB`, if set, where everything kept across restarts is stored
+pub static DATABASE: Lazy<Option<Mutex<Database>>> = Lazy::new(|| {
+    let path = std::env::var("REGEX_SOUP_DB").ok()?;
+    match Database::open(&path) {
+        Ok(database) => Some(Mutex::new(database)),
+        Err(why) => {
+            println!("ERROR: failed to open the database at {path}: {why:#}");
+            None
+        }
+    }
+});
+
+/// The database, locked, or an error saying `missing` if there is none.
+fn database(missing: &str) -> anyhow::Result<MutexGuard<'static, Database>> {
+    DATABASE
+        .as_ref()
+        .ok_or_else(|| anyhow!("{missing}"))?
+        .lock()
+        .map_err(|_| anyhow!("the database is poisoned"))
+}
+
+/// Read the database with `read`, or `None` if there is none.
+fn read_database<T>(
+    read: impl FnOnce(&Database) -> anyhow::Result<T>,
+) -> Option<anyhow::Result<T>> {
+    DATABASE.as_ref().map(|database| {
+        database
+            .lock()
+            .map_err(|_| anyhow!("the database is poisoned"))
+            .and_then(|database| read(&database))
+    })
+}
+
+/// Make the writes of `write` in one transaction, logging that it failed to `what` if it did.
+/// Nothing is written without a database, and the default is returned then as when it fails.
+fn write_database<T: Default>(what: &str, write: impl FnOnce(&Database) -> anyhow::Result<T>) -> T {
+    read_database(|database| database.transaction(write))
+        .unwrap_or_else(|| Ok(T::default()))
+        .unwrap_or_else(|why| {
+            println!("ERROR: failed to {what}: {why:#}");
+            T::default()
+        })
+}
+
+/// Record the outcome of a quiz played in `guild` in the puzzle bank and keep `game` in the archive.
+fn record_game(
+    guild: Option<GuildId>,
+    regex: &RegexAst,
+    seed: u64,
+    outcome: &PlayOutcome,
+    game: &ArchivedGame,
+) {
+    if let Some(guild) = guild {
+        write_database("archive the game", |database| {
+            database
+                .puzzle_bank()
+                .record_outcome(regex, Some(seed), guild, outcome)?;
+            database.archive().record(guild, game)
+        });
+    }
+}
+
+/// Lines announcing the records `broken` by `user` solving a quiz of `tier` in `guild`.
+/// A record broken across every guild is announced instead of the same record in the guild.
+fn announce_records(
+    guild: GuildId,
+    tier: DifficultyTier,
+    user: UserId,
+    outcome: &PlayOutcome,
+    broken: &[BrokenRecord],
+) -> String {
+    broken
+        .iter()
+        .filter(|record| {
+            record.global
+                || !broken
+                    .iter()
+                    .any(|other| other.global && other.previous.kind == record.previous.kind)
+        })
+        .map(|BrokenRecord { global, previous }| {
+            let scope = if *global {
+                "across every server"
+            } else {
+                "in this server"
+            };
+            format!(
+                "\n🎉 **New record!** <@{}> sets the {} of {tier} quizzes {scope}: {}, beating {}.",
+                user.0,
+                previous.kind,
+                feat(outcome.queries, outcome.duration),
+                record_holder(guild, previous),
+            )
+        })
+        .collect()
+}
+
+/// A solve of `queries` in `duration`, as told in records.
+fn feat(queries: u32, duration: Duration) -> String {
+    format!("{} queries in {}s", queries, duration.as_secs())
+}
+
+/// The holder of `record` with their solve as seen from `guild`,
+/// which names no player of another guild.
+fn record_holder(guild: GuildId, record: &Record) -> String {
+    let feat = feat(record.queries, record.duration);
+    if record.guild == guild {
+        format!("<@{}> ({feat})", record.holder.0)
+    } else {
+        format!("a player in another server ({feat})")
+    }
+}
+
+/// Records held in `guild`, or across every guild if `global`, by tier.
+fn records_embed(guild: Option<GuildId>, global: bool) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Records are kept only in servers."))?;
+    let records = database("Records are not kept on this bot.")?
+        .records()
+        .all((!global).then_some(guild))?;
+
+    let mut embed = CreateEmbed::default();
+    embed.colour(Colour::GOLD).title(if global {
+        "Records across every server"
+    } else {
+        "Records of this server"
+    });
+    if records.is_empty() {
+        embed.description("No quiz has been solved yet.");
+    }
+    for tier in TIERS {
+        let lines = records
+            .iter()
+            .filter(|record| record.tier == tier)
+            .map(|record| {
+                format!(
+                    "{}: {} <t:{}:d>",
+                    record.kind,
+                    record_holder(guild, record),
+                    unix_seconds(record.set_at)
+                )
+            })
+            .collect::<Vec<_>>();
+        if !lines.is_empty() {
+            embed.field(tier, lines.join("\n"), false);
+        }
+    }
+    Ok(embed)
+}
+
+/// A setting of `guild` read from its configuration, or the default outside servers,
+/// without a configuration or if it cannot be read.
+fn configured<T: Default>(
+    guild: Option<GuildId>,
+    read: impl FnOnce(&GuildConfig, GuildId) -> anyhow::Result<T>,
+) -> T {
+    let guild = match guild {
+        Some(guild) => guild,
+        None => return T::default(),
+    };
+    read_database(|database| read(&database.guild_config(), guild))
+        .unwrap_or_else(|| Ok(T::default()))
+        .unwrap_or_else(|why| {
+            println!("ERROR: failed to read the configuration of {guild}: {why:#}");
+            T::default()
+        })
+}
+
+/// Which outputs are spoilered in `guild`, all of them outside servers or without a configuration.
+fn spoilers(guild: Option<GuildId>) -> Spoilers {
+    configured(guild, |config, guild| config.spoilers(guild))
+}
+
+fn save_game(channel: ChannelId, quiz: &Quiz) {
+    // races are refereed in memory only, so their lanes are not restored
+    if quiz.race().is_some() {
+        return;
+    }
+    if let Some(id) = quiz.id() {
+        write_database(&format!("save quiz {id} in {channel}"), |database| {
+            database.saved_games().save(channel, id, &quiz.saved())
+        });
+    }
+}
+
+fn forget_game(channel: ChannelId, id: QuizId) {
+    write_database(&format!("forget quiz {id} in {channel}"), |database| {
+        database.saved_games().remove(channel, id)
+    });
+}
+
+/// Put back the quizzes saved before the bot restarted, closing those whose time is up.
+async fn restore_games(http: Arc<Http>) {
+    let stored = match read_database(|database| database.saved_games().games()) {
+        Some(Ok(stored)) => stored,
+        Some(Err(why)) => {
+            println!("ERROR: failed to read the saved games: {why:#}");
+            return;
+        }
+        None => return,
+    };
+    for game in stored {
+        let quiz = match game.state.and_then(|state| Quiz::restore(&state)) {
+            Ok(quiz) => quiz,
+            Err(why) => {
+                println!(
+                    "ERROR: failed to restore quiz {} in {}: {why:#}",
+                    game.id, game.channel
+                );
+                forget_game(game.channel, game.id);
+                continue;
+            }
+        };
+        let deadline = quiz.deadline();
+        match CONTAINER.restore(game.channel, game.id, quiz).await {
+            Ok(()) => {
+                println!("restored quiz {} in {}", game.id, game.channel);
+                if let Some(deadline) = deadline {
+                    let guild = game
+                        .channel
+                        .to_channel(&http)
+                        .await
+                        .ok()
+                        .and_then(|channel| channel.guild())
+                        .map(|channel| channel.guild_id);
+                    tokio::task::spawn(close_at_deadline(
+                        Arc::clone(&http),
+                        game.channel,
+                        guild,
+                        game.id,
+                        deadline,
+                    ));
+                }
+            }
+            Err(why) => println!(
+                "ERROR: failed to put back quiz {} in {}: {why:#}",
+                game.id, game.channel
+            ),
+        }
+    }
+}
+
+/// Guards the saved quizzes from being restored again on reconnection
+static GAME_RESTORATION: Once = Once::new();
+
+/// Guards the scheduler of the daily puzzle from being spawned again on reconnection
+static DAILY_SCHEDULER: Once = Once::new();
+
+/// Start the daily puzzle of `day` in every subscribed channel.
+async fn post_daily(http: &Http, day: u64) {
+    let channels = match read_database(|database| database.daily().channels()) {
+        Some(Ok(channels)) => channels,
+        Some(Err(why)) => {
+            println!("ERROR: failed to list the daily channels: {why:#}");
+            return;
+        }
+        None => return,
+    };
+    for (_, channel) in channels {
+        let posted = match CONTAINER.fresh(channel, daily_options(day)).await {
+            Ok((_, embed)) => channel
+                .send_message(http, |message| message.set_embed(embed))
+                .await
+                .map(|_| ())
+                .with_context(|| anyhow!("serenity error")),
+            Err(why) => Err(why),
+        };
+        if let Err(why) = posted {
+            println!("ERROR: failed to post the daily puzzle to {channel}: {why:#}");
+        }
+    }
+}
+
+/// Solves of the daily puzzle of `day` over every guild and in `guild`.
+fn daily_stats_embed(guild: Option<GuildId>, day: u64) -> anyhow::Result<CreateEmbed> {
+    let database = database("The daily puzzle is not recorded on this bot.")?;
+    let daily = database.daily();
+    let stats = daily.stats(day)?;
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::GOLD)
+        .title(format!("Daily puzzle #{day}"))
+        .field(
+            "solvers",
+            format!("{} in {} servers", stats.solvers, stats.guilds),
+            false,
+        );
+    if let Some(average) = stats.average_queries {
+        embed.field("average queries", format!("{average:.1}"), false);
+    }
+    if let Some(fastest) = stats.fastest {
+        embed.field("fastest", format!("{}s", fastest.as_secs()), false);
+    }
+    if let Some(guild) = guild {
+        let solvers = daily.solvers(day, guild)?;
+        if !solvers.is_empty() {
+            embed.field(
+                "solved in this server",
+                solvers
+                    .iter()
+                    .map(|user| format!("<@{}>", user.0))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                false,
+            );
+        }
+    }
+    Ok(embed)
+}
+
+/// Fastest solves of the daily puzzle of `day` over every guild, with the solve of `viewer`.
+fn daily_top_embed(day: u64, viewer: UserId) -> anyhow::Result<CreateEmbed> {
+    let top = database("The daily puzzle is not recorded on this bot.")?
+        .daily()
+        .top(day, viewer, LEADERBOARD_SIZE)?;
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::GOLD)
+        .title(format!("Daily puzzle #{day}: global ranking"))
+        .footer(|footer| footer.text("Run `/daily show-name` to appear by name."));
+    if top.is_empty() {
+        embed.description("Nobody has solved today's puzzle yet.");
+    } else {
+        embed.description(
+            top.iter()
+                .map(|standing| {
+                    let name = standing.name.as_deref().unwrap_or("anonymous");
+                    let you = if standing.is_viewer { " (you)" } else { "" };
+                    format!(
+                        "{}. **{name}**{you}: {}s, {} queries",
+                        standing.rank,
+                        standing.duration.as_secs(),
+                        standing.queries
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+    Ok(embed)
+}
+
+/// Interval at which the schedules are checked, the finest a schedule can be
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Hours before a scheduled puzzle closes its reminder is posted, unless chosen otherwise
+const DEFAULT_REMINDER_HOURS: u64 = 24;
+
+/// Guards the scheduler of scheduled puzzles from being spawned again on reconnection
+static PUZZLE_SCHEDULER: Once = Once::new();
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Apply `update` to the schedules, logging why it failed if it did.
+fn update_schedules(update: impl FnOnce(&Schedules) -> anyhow::Result<()>) {
+    write_database("update the schedules", |database| {
+        update(&database.schedules())
+    });
+}
+
+/// Post, remind of and close the puzzles of every schedule due at `now`.
+async fn run_schedules(http: &Http, now: SystemTime) {
+    let schedules = match read_database(|database| database.schedules().all()) {
+        Some(Ok(schedules)) => schedules,
+        Some(Err(why)) => {
+            println!("ERROR: failed to list the schedules: {why:#}");
+            return;
+        }
+        None => return,
+    };
+    for schedule in schedules {
+        for due in schedule.due(now) {
+            match due {
+                Due::Close => close_scheduled(http, &schedule).await,
+                Due::Remind => remind_scheduled(http, &schedule).await,
+                Due::Post => post_scheduled(http, &schedule, now).await,
+            }
+        }
+    }
+}
+
+/// Start the next puzzle of `schedule` and pin its announcement.
+async fn post_scheduled(http: &Http, schedule: &Schedule, now: SystemTime) {
+    let closes_at = schedule.closing_time(now);
+    let started = match &schedule.source {
+        PuzzleSource::Generated(preset) => {
+            let options = QuizOptions {
+                preset: Some(*preset),
+                ..QuizOptions::default()
+            };
+            CONTAINER.fresh(schedule.channel, options).await
+        }
+        PuzzleSource::Curated { regex, letters } => match Submission::parse(regex, *letters) {
+            Ok(submission) => {
+                CONTAINER
+                    .start_custom(
+                        schedule.channel,
+                        Quiz::submitted(submission, schedule.author),
+                    )
+                    .await
+            }
+            Err(why) => Err(why),
+        },
+    };
+    let posted = match started {
+        Ok((quiz, embed)) => schedule
+            .channel
+            .send_message(http, |message| {
+                message
+                    .content(format!(
+                        "Scheduled puzzle: quiz {quiz} is open until <t:{}:f>.",
+                        unix_seconds(closes_at)
+                    ))
+                    .set_embed(embed)
+            })
+            .await
+            .map(|message| (quiz, message.id))
+            .with_context(|| anyhow!("serenity error")),
+        Err(why) => Err(why),
+    };
+    match posted {
+        Ok((quiz, message)) => {
+            if let Err(why) = schedule.channel.pin(http, message).await {
+                println!(
+                    "ERROR: failed to pin the scheduled puzzle in {}: {why}",
+                    schedule.channel
+                );
+            }
+            let posted = Posted {
+                quiz,
+                message,
+                closes_at,
+                reminded: false,
+            };
+            update_schedules(|schedules| schedules.posted(schedule.id, &posted, now));
+        }
+        Err(why) => {
+            println!(
+                "ERROR: failed to post the scheduled puzzle to {}: {why:#}",
+                schedule.channel
+            );
+            update_schedules(|schedules| schedules.skipped(schedule.id, now));
+        }
+    }
+}
+
+/// Remind the channel of `schedule` that its puzzle is about to close, unless it is already over.
+async fn remind_scheduled(http: &Http, schedule: &Schedule) {
+    if let Some(posted) = &schedule.posted {
+        let open = CONTAINER
+            .command(schedule.channel, Some(posted.quiz), |_| ())
+            .await
+            .is_ok();
+        if open {
+            let reminder = format!(
+                "Quiz {} closes <t:{}:R>! Make your guesses before the answer is revealed.",
+                posted.quiz,
+                unix_seconds(posted.closes_at)
+            );
+            if let Err(why) = schedule.channel.say(http, reminder).await {
+                println!(
+                    "ERROR: failed to remind {} of the scheduled puzzle: {why}",
+                    schedule.channel
+                );
+            }
+        }
+        update_schedules(|schedules| schedules.reminded(schedule.id));
+    }
+}
+
+/// Reveal the answer of the puzzle of `schedule` unless it is already over, and unpin it.
+async fn close_scheduled(http: &Http, schedule: &Schedule) {
+    if let Some(posted) = &schedule.posted {
+        close_quiz(
+            http,
+            schedule.channel,
+            Some(schedule.guild),
+            posted.quiz,
+            &format!("Scheduled quiz {} is closed!", posted.quiz),
+        )
+        .await;
+        if let Err(why) = schedule.channel.unpin(http, posted.message).await {
+            println!(
+                "ERROR: failed to unpin the scheduled puzzle in {}: {why}",
+                schedule.channel
+            );
+        }
+        update_schedules(|schedules| schedules.closed(schedule.id));
+    }
+}
+
+/// Schedules of the guild `command` is sent in, after adding or removing one as chosen.
+/// Only administrators may add or remove schedules.
+async fn schedule_embed(
+    http: &Http,
+    command: &ApplicationCommandInteraction,
+    dictionary: &HashMap<String, Notification>,
+) -> anyhow::Result<CreateEmbed> {
+    let guild = command
+        .guild_id
+        .ok_or_else(|| anyhow!("Puzzles are scheduled only in servers."))?;
+    // the database is locked only while it is used, as the removed schedules are closed in between
+    let stored = || database("Schedules are not stored on this bot.");
+    let action = dictionary
+        .get("action")
+        .map(|action| action.to::<String>())
+        .transpose()?;
+    let hours = |name: &str| {
+        dictionary
+            .get(name)
+            .map(|hours| -> anyhow::Result<Duration> {
+                let hours: u64 = hours
+                    .to::<i64>()?
+                    .try_into()
+                    .with_context(|| anyhow!("invalid number of hours"))?;
+                Ok(Duration::from_secs(hours.saturating_mul(60 * 60)))
+            })
+            .transpose()
+    };
+    let now = SystemTime::now();
+
+    let headline = match action.as_deref() {
+        None | Some("list") => None,
+        Some(_) if !is_admin(command) => {
+            return Err(anyhow!("Only administrators may schedule puzzles."))
+        }
+        Some("add") => {
+            let cron = dictionary
+                .get("cron")
+                .map_or(Ok("@weekly".to_string()), |cron| cron.to::<String>())?
+                .parse::<Cron>()?;
+            if cron.next_after(now).is_none() {
+                return Err(anyhow!("The schedule `{cron}` never runs."));
+            }
+            let letters = letters(dictionary)?;
+            let source = match (dictionary.get("regex"), dictionary.get("difficulty")) {
+                (Some(_), Some(_)) => {
+                    return Err(anyhow!("Choose either a regex or a difficulty."));
+                }
+                (Some(regex), None) => {
+                    let regex = regex.to::<String>()?;
+                    // check the expression now rather than when it is due
+                    Submission::parse(&regex, letters)?;
+                    PuzzleSource::Curated { regex, letters }
+                }
+                (None, difficulty) => PuzzleSource::Generated(
+                    difficulty
+                        .map(|difficulty| difficulty.to::<String>()?.parse())
+                        .transpose()?
+                        .unwrap_or(DifficultyPreset::Normal),
+                ),
+            };
+            let open_for = hours("hours")?;
+            if open_for == Some(Duration::ZERO) {
+                return Err(anyhow!(
+                    "A scheduled puzzle stays open for an hour at least."
+                ));
+            }
+            let schedule = NewSchedule {
+                guild,
+                channel: command.channel_id,
+                cron,
+                source,
+                open_for,
+                remind_before: hours("reminder")?
+                    .unwrap_or(Duration::from_secs(DEFAULT_REMINDER_HOURS * 60 * 60)),
+                author: command.user.id,
+            };
+            let id = stored()?.schedules().add(&schedule, now)?;
+            Some(format!("Schedule #{id} will post puzzles to this channel."))
+        }
+        Some("remove") => {
+            let id = dictionary
+                .get("id")
+                .ok_or_else(|| anyhow!("Choose the schedule to remove by its `id`."))?
+                .to::<i64>()?;
+            let removed = stored()?
+                .schedules()
+                .remove(guild, id)?
+                .ok_or_else(|| anyhow!("There is no schedule #{id} in this server."))?;
+            close_scheduled(http, &removed).await;
+            Some(format!("Schedule #{id} is removed."))
+        }
+        Some(action) => return Err(anyhow!("unknown action: {action}")),
+    };
+
+    let listed = stored()?.schedules().list(guild)?;
+    let mut embed = CreateEmbed::default();
+    embed.colour(Colour::BLUE).title("Scheduled puzzles");
+    let lines = listed
+        .iter()
+        .map(|schedule| {
+            // curated expressions stay hidden from the list
+            let puzzle = match &schedule.source {
+                PuzzleSource::Generated(preset) => preset.name(),
+                PuzzleSource::Curated { .. } => "curated",
+            };
+            let next = schedule.next_run().map_or_else(
+                || "never".to_string(),
+                |next| format!("<t:{}:f>", unix_seconds(next)),
+            );
+            let mut line = format!(
+                "#{} · <#{}> · `{}` · {puzzle} · next {next}",
+                schedule.id, schedule.channel.0, schedule.cron
+            );
+            if let Some(posted) = &schedule.posted {
+                line.push_str(&format!(
+                    "\n  quiz {} is open until <t:{}:f>",
+                    posted.quiz,
+                    unix_seconds(posted.closes_at)
+                ));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    let listing = if lines.is_empty() {
+        "No puzzle is scheduled in this server.".to_string()
+    } else {
+        lines.join("\n")
+    };
+    embed.description(match headline {
+        Some(headline) => format!("{headline}\n\n{listing}"),
+        None => listing,
+    });
+    Ok(embed)
+}
+
+/// Interval at which the deadlines of tournaments are checked
+const TOURNAMENT_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Guards the scheduler of tournaments from being spawned again on reconnection
+static TOURNAMENT_SCHEDULER: Once = Once::new();
+
+fn mention(user: Option<UserId>) -> String {
+    user.map_or_else(|| "(bye)".to_string(), |user| format!("<@{}>", user.0))
+}
+
+/// Bracket of `tournament` with the results so far.
+fn tournament_embed(tournament: &Tournament) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.colour(Colour::GOLD).title("Tournament");
+    if !tournament.is_started() {
+        let players = tournament
+            .players()
+            .iter()
+            .map(|&user| mention(Some(user)))
+            .collect::<Vec<_>>();
+        embed
+            .description("Register with `/tournament register`, then `/tournament start`.")
+            .field(
+                format!("players ({})", players.len()),
+                if players.is_empty() {
+                    "-".to_string()
+                } else {
+                    players.join(", ")
+                },
+                false,
+            );
+        return embed;
+    }
+
+    for (round, matches) in tournament.rounds().iter().enumerate() {
+        let deadline = tournament
+            .deadline(round)
+            .and_then(|deadline| deadline.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |deadline| deadline.as_secs());
+        let lines = matches
+            .iter()
+            .map(|m| {
+                let result = match m.winner {
+                    Some(winner) if m.walkover => format!(" → <@{}> (walkover)", winner.0),
+                    Some(winner) => format!(" → <@{}>", winner.0),
+                    None => String::new(),
+                };
+                format!(
+                    "{} vs {}{}",
+                    mention(m.players[0]),
+                    mention(m.players[1]),
+                    result
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        embed.field(
+            format!("round {} (until <t:{}:f>)", round + 1, deadline),
+            lines,
+            false,
+        );
+    }
+    if let Some(champion) = tournament.champion() {
+        embed.field("champion", format!("🏆 <@{}>", champion.0), false);
+    }
+    embed
+}
+
+/// Run an action of `/tournament` by `user` in `channel` of `guild`.
+async fn tournament_action(
+    guild: Option<GuildId>,
+    channel: ChannelId,
+    user: UserId,
+    action: &str,
+) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Tournaments are held only in servers."))?;
+    match action {
+        "open" => {
+            CONTAINER.tournament_open(guild, channel).await?;
+            CONTAINER
+                .tournament_command(guild, |tournament| tournament_embed(tournament))
+                .await
+        }
+        "register" => Ok(CONTAINER
+            .tournament_command(guild, |tournament| {
+                tournament
+                    .register(user)
+                    .map(|_| tournament_embed(tournament))
+            })
+            .await??),
+        "start" => Ok(CONTAINER
+            .tournament_command(guild, |tournament| {
+                tournament
+                    .start(SystemTime::now())
+                    .map(|_| tournament_embed(tournament))
+            })
+            .await??),
+        "play" => {
+            let (id, m) = CONTAINER
+                .tournament_command(guild, |tournament| {
+                    tournament
+                        .pending_match(user)
+                        .map(|(id, m)| (id, m.clone()))
+                })
+                .await??;
+            let options = QuizOptions {
+                seed: Some(m.seed),
+                tournament_match: Some(id),
+                ..QuizOptions::default()
+            };
+            let (quiz_id, mut embed) = CONTAINER.fresh(channel, options).await?;
+            CONTAINER
+                .command(channel, Some(quiz_id), |quiz| {
+                    for &player in m.players.iter().flatten() {
+                        let _ = quiz.register(player);
+                    }
+                })
+                .await?;
+            embed.field(
+                "tournament match",
+                format!(
+                    "round {}: {} vs {}, the first to guess the answer wins",
+                    id.round + 1,
+                    mention(m.players[0]),
+                    mention(m.players[1])
+                ),
+                false,
+            );
+            Ok(embed)
+        }
+        "status" => {
+            CONTAINER
+                .tournament_command(guild, |tournament| tournament_embed(tournament))
+                .await
+        }
+        action => Err(anyhow!("unknown action: {action}")),
+    }
+}
+
+/// Record that `winner` solved the quiz of the match `id`, as a line announcing the result.
+async fn report_tournament_match(guild: GuildId, id: MatchId, winner: UserId) -> String {
+    let reported = CONTAINER
+        .tournament_command(guild, |tournament| {
+            tournament.report(id, winner).map(|_| tournament.champion())
+        })
+        .await
+        .and_then(|reported| reported.map_err(anyhow::Error::from));
+    match reported {
+        Ok(Some(champion)) => format!("\n🏆 <@{}> wins the tournament!", champion.0),
+        Ok(None) => format!("\n<@{}> wins the match and advances.", winner.0),
+        Err(why) => format!("\nThe match is not recorded: {why}"),
+    }
+}
+
+/// Start a race between `players` where the challenge was posted in `channel`,
+/// each playing in a thread of their own the quiz generated from the same seed.
+async fn start_race(
+    http: &Http,
+    channel: ChannelId,
+    players: [&User; 2],
+    options: QuizOptions,
+) -> anyhow::Result<()> {
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut lanes = vec![];
+    for player in players {
+        let start = channel
+            .say(http, format!("🏁 Lane of <@{}>", player.id.0))
+            .await?;
+        let thread = channel
+            .create_public_thread(http, start.id, |thread| {
+                thread.name(format!("race: {}", player.name))
+            })
+            .await?;
+        lanes.push(Lane {
+            player: player.id,
+            thread: thread.id,
+        });
+    }
+    let race = CONTAINER
+        .race_open(Race::new([lanes[0], lanes[1]], seed, channel))
+        .await;
+    for lane in lanes {
+        let options = QuizOptions {
+            seed: Some(seed),
+            race: Some(race),
+            owner: Some(lane.player),
+            ..options
+        };
+        let (id, embed) = CONTAINER.fresh(lane.thread, options).await?;
+        CONTAINER
+            .command(lane.thread, Some(id), |quiz| quiz.register(lane.player))
+            .await??;
+        lane.thread
+            .send_message(http, |message| {
+                message
+                    .content(format!(
+                        "<@{}>, this is your lane: the first to guess the answer wins.",
+                        lane.player.0
+                    ))
+                    .set_embed(embed)
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Report to the referee of the race `id` that a player solved their lane,
+/// as a line announcing the standing.
+async fn report_race(http: Arc<Http>, guild: Option<GuildId>, id: u64, solve: Solve) -> String {
+    let reported = CONTAINER
+        .race_command(id, |race| race.solve(solve))
+        .await
+        .and_then(|reported| reported.map_err(anyhow::Error::from));
+    match reported {
+        Ok(RaceStatus::Provisional(first)) => {
+            tokio::task::spawn(settle_race(Arc::clone(&http), guild, id));
+            format!(
+                "\n🏁 <@{}> solves first! The opponent has {} seconds left to tie.",
+                first.player.0,
+                TIE_WINDOW.as_secs()
+            )
+        }
+        Ok(RaceStatus::Decided(result)) => finish_race(&http, guild, id, &result).await,
+        Err(why) => format!("\nThe race is not recorded: {why}"),
+    }
+}
+
+/// Decide the race `id` for the first solver unless the opponent has tied by the end of [`TIE_WINDOW`].
+async fn settle_race(http: Arc<Http>, guild: Option<GuildId>, id: u64) {
+    tokio::time::sleep(TIE_WINDOW).await;
+    if let Ok(Some(result)) = CONTAINER.race_command(id, |race| race.settle()).await {
+        finish_race(&http, guild, id, &result).await;
+    }
+}
+
+/// End the race `id` decided as `result`, closing the lanes still being played
+/// and announcing the winner where the race started, as a line announcing the result.
+async fn finish_race(http: &Http, guild: Option<GuildId>, id: u64, result: &RaceResult) -> String {
+    let race = match CONTAINER.race_end(id).await {
+        Some(race) => race,
+        None => return String::new(),
+    };
+    let winner = result.winner;
+    let announcement = match result.runner_up {
+        Some(runner_up) if result.tiebreak => format!(
+            "🏆 <@{}> wins the race on the tiebreak, with {} queries against {}!",
+            winner.player.0, winner.queries, runner_up.queries
+        ),
+        Some(runner_up) => format!(
+            "🏆 <@{}> wins the race, solving first with {} queries against {}!",
+            winner.player.0, winner.queries, runner_up.queries
+        ),
+        None => format!(
+            "🏆 <@{}> wins the race in {} seconds with {} queries!",
+            winner.player.0,
+            winner.elapsed.as_secs(),
+            winner.queries
+        ),
+    };
+    for lane in race.lanes {
+        if let Ok(Some(quiz)) = CONTAINER.command(lane.thread, None, |quiz| quiz.id()).await {
+            close_quiz(http, lane.thread, guild, quiz, &announcement).await;
+        }
+    }
+    if let Err(why) = race.channel.say(http, &announcement).await {
+        println!(
+            "ERROR: failed to announce the race in {}: {why}",
+            race.channel
+        );
+    }
+    format!("\n{announcement}")
+}
+
+/// What `/guess` takes out of a quiz solved by `solver` before the quiz is deleted
+struct SolvedQuiz {
+    solver: UserId,
+    id: Option<QuizId>,
+    regex: RegexAst,
+    seed: u64,
+    tier: DifficultyTier,
+    outcome: PlayOutcome,
+    archived: ArchivedGame,
+    score: Score,
+    /// Team of the solver, which earns the points instead
+    team: Option<String>,
+    opponents: Vec<UserId>,
+    daily: Option<u64>,
+    tournament_match: Option<MatchId>,
+    race: Option<u64>,
+    analysis: CreateEmbed,
+    rematch: CreateButton,
+}
+
+impl SolvedQuiz {
+    fn new(quiz: &Quiz, solver: UserId, spoilers: &Spoilers) -> Self {
+        SolvedQuiz {
+            solver,
+            id: quiz.id(),
+            regex: quiz.get_answer_regex(),
+            seed: quiz.seed(),
+            tier: quiz.difficulty().tier,
+            outcome: quiz.outcome(true),
+            archived: quiz.archived(Some(solver)),
+            score: quiz.score(solver),
+            team: quiz.team_of(solver).map(|(name, _)| name.to_string()),
+            opponents: quiz.opponents(solver),
+            daily: quiz.daily(),
+            tournament_match: quiz.tournament_match(),
+            race: quiz.race(),
+            analysis: quiz.analysis_embed(spoilers),
+            rematch: quiz.rematch_button(),
+        }
+    }
+
+    /// Record the solve in `guild` everywhere or nowhere: the outcome, the game, the records,
+    /// the daily puzzle, the points and the ratings.
+    /// Returns the lines announcing the broken records and the changes of ratings.
+    fn record(&self, guild: Option<GuildId>) -> (String, Vec<RatingChange>) {
+        let guild = match guild {
+            Some(guild) => guild,
+            None => return Default::default(),
+        };
+        let (solver, now) = (self.solver, SystemTime::now());
+        write_database("record the solve", |database| {
+            database.puzzle_bank().record_outcome(
+                &self.regex,
+                Some(self.seed),
+                guild,
+                &self.outcome,
+            )?;
+            database.archive().record(guild, &self.archived)?;
+            let broken =
+                database
+                    .records()
+                    .record_solve(guild, self.tier, solver, &self.outcome, now)?;
+            if let Some(day) = self.daily {
+                database
+                    .daily()
+                    .record_solve(day, guild, solver, &self.outcome)?;
+            }
+            let leaderboard = database.leaderboard();
+            let changes = match &self.team {
+                Some(team) => {
+                    leaderboard.record_team(guild, team, self.score.total, now)?;
+                    vec![]
+                }
+                None => {
+                    leaderboard.record(guild, solver, self.score.total, now)?;
+                    if self.opponents.is_empty() {
+                        vec![]
+                    } else {
+                        leaderboard.record_match(
+                            guild,
+                            solver,
+                            &self.opponents,
+                            &EloRules::default(),
+                        )?
+                    }
+                }
+            };
+            Ok((
+                announce_records(guild, self.tier, solver, &self.outcome, &broken),
+                changes,
+            ))
+        })
+    }
+
+    /// Record the solve in `guild` and report it to its tournament and race if any,
+    /// announcing the points earned after `res`.
+    async fn announce(&self, http: &Arc<Http>, guild: Option<GuildId>, res: &str) -> String {
+        let (records, changes) = self.record(guild);
+        let tournament = match (self.tournament_match, guild) {
+            (Some(id), Some(guild)) => report_tournament_match(guild, id, self.solver).await,
+            _ => String::new(),
+        };
+        let race = match self.race {
+            Some(race) => {
+                let solve = Solve {
+                    player: self.solver,
+                    elapsed: self.outcome.duration,
+                    queries: self.outcome.queries,
+                };
+                report_race(Arc::clone(http), guild, race, solve).await
+            }
+            None => String::new(),
+        };
+        let results = [tournament, race, records].concat();
+        let score = &self.score;
+        match &self.team {
+            Some(team) => format!("{res}\nTeam **{team}** earns {score}.{results}"),
+            None => {
+                let ratings = if changes.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "\nRatings: {}",
+                        changes
+                            .iter()
+                            .map(|change| format!(
+                                "<@{}> {:.0} → {:.0}",
+                                change.user.0, change.before, change.after
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                };
+                format!(
+                    "{res}\n<@{}> earns {score}.{ratings}{results}",
+                    self.solver.0
+                )
+            }
+        }
+    }
+}
+
+/// Number of players `/leaderboard` shows
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Rating of `user` along with the highest rated players of `guild`.
+fn rating_embed(guild: Option<GuildId>, user: UserId) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Ratings are kept only in servers."))?;
+    let database = database("Ratings are not recorded on this bot.")?;
+    let leaderboard = database.leaderboard();
+    let rating = leaderboard.rating(guild, user)?;
+    let top = leaderboard.top_ratings(guild, LEADERBOARD_SIZE)?;
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::GOLD)
+        .title("Rating")
+        .description(format!(
+            "<@{}>: {:.0} ({} matches)",
+            user.0, rating.rating, rating.matches
+        ));
+    if !top.is_empty() {
+        embed.field(
+            "top players",
+            top.iter()
+                .enumerate()
+                .map(|(rank, rating)| {
+                    format!("{}. <@{}>: {:.0}", rank + 1, rating.user.0, rating.rating)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            false,
+        );
+    }
+    Ok(embed)
+}
+
+fn stats_embed(guild: Option<GuildId>, user: UserId) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Statistics are kept only in servers."))?;
+    let stats = database("Games are not archived on this bot.")?
+        .archive()
+        .stats(guild, user)?;
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::BLUE)
+        .title("Statistics")
+        .description(format!("<@{}>", user.0))
+        .field("games played", stats.played, true)
+        .field(
+            "solve rate",
+            stats.solve_rate().map_or_else(
+                || "-".to_string(),
+                |rate| format!("{:.0}% ({} solved)", rate * 100.0, stats.solved),
+            ),
+            true,
+        )
+        .field(
+            "queries per solve",
+            stats
+                .queries_per_solve()
+                .map_or_else(|| "-".to_string(), |queries| format!("{queries:.1}")),
+            true,
+        )
+        .field(
+            "favorite difficulty",
+            stats.favorite_difficulty.as_deref().unwrap_or("-"),
+            true,
+        )
+        .field("current streak", stats.streak, true);
+    Ok(embed)
+}
+
+/// The spoiler settings of `guild`, after turning the output in `change` on or off if any.
+fn spoilers_embed(
+    guild: Option<GuildId>,
+    change: Option<(SpoilerKind, bool)>,
+) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Spoiler settings are kept only in servers."))?;
+    let database = database("Settings are not stored on this bot.")?;
+    let config = database.guild_config();
+    if let Some((kind, enabled)) = change {
+        config.set_spoiler(guild, kind, enabled)?;
+    }
+    let spoilers = config.spoilers(guild)?;
+
+    let mut embed = CreateEmbed::default();
+    embed.colour(Colour::BLUE).title("Spoilers");
+    for kind in SpoilerKind::ALL {
+        embed.field(
+            kind.name(),
+            if spoilers.get(kind) {
+                "hidden"
+            } else {
+                "shown"
+            },
+            true,
+        );
+    }
+    Ok(embed)
+}
+
+/// The configuration of `guild`, after applying the changes chosen in the options of `/config`.
+/// Only administrators may make changes.
+fn config_embed(
+    guild: Option<GuildId>,
+    dictionary: &HashMap<String, Notification>,
+    admin: bool,
+) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Settings are kept only in servers."))?;
+    let database = database("Settings are not stored on this bot.")?;
+    let config = database.guild_config();
+
+    let paired = |first: &str, second: &str| {
+        dictionary.contains_key(first) == dictionary.contains_key(second)
+    };
+    if !paired("setting", "value") {
+        return Err(anyhow!("Choose both the setting and its value."));
+    }
+    if !paired("spoiler", "hidden") {
+        return Err(anyhow!("Choose both the output and whether to hide it."));
+    }
+    let changes = ["setting", "channel", "spoiler"]
+        .iter()
+        .any(|name| dictionary.contains_key(*name));
+    if changes && !admin {
+        return Err(anyhow!(config.locale(guild)?.admin_only()));
+    }
+    if let Some(setting) = dictionary.get("setting") {
+        let setting = setting.to::<String>()?.parse::<Setting>()?;
+        let value = dictionary.get("value").unwrap().to::<String>()?;
+        let value = Some(value.as_str()).filter(|value| *value != "default");
+        config.set_setting(guild, setting, value)?;
+    }
+    if let Some(channel) = dictionary.get("channel") {
+        let allowed = dictionary
+            .get("allowed")
+            .map_or(Ok(true), |allowed| allowed.to::<bool>())?;
+        config.allow_channel(guild, channel.to::<PartialChannel>()?.id, allowed)?;
+    }
+    if let Some(spoiler) = dictionary.get("spoiler") {
+        let kind = spoiler.to::<String>()?.parse::<SpoilerKind>()?;
+        let hidden = dictionary.get("hidden").unwrap().to::<bool>()?;
+        config.set_spoiler(guild, kind, hidden)?;
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::BLUE)
+        .title(config.locale(guild)?.configuration());
+    for setting in Setting::ALL {
+        let value = match (setting, config.setting(guild, setting)?) {
+            (_, None) => "-".to_string(),
+            (Setting::TimeLimit, Some(seconds)) => format!("{seconds}s"),
+            (_, Some(value)) => value,
+        };
+        embed.field(setting.name(), value, true);
+    }
+    let spoilers = config.spoilers(guild)?;
+    embed.field(
+        "spoilers",
+        SpoilerKind::ALL
+            .iter()
+            .map(|&kind| {
+                let state = if spoilers.get(kind) {
+                    "hidden"
+                } else {
+                    "shown"
+                };
+                format!("{}: {state}", kind.name())
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        false,
+    );
+    let channels = config.channels(guild)?;
+    embed.field(
+        "channels",
+        if channels.is_empty() {
+            "every channel".to_string()
+        } else {
+            channels
+                .iter()
+                .map(|channel| format!("<#{}>", channel.0))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+        false,
+    );
+    Ok(embed)
+}
+
+fn team_leaderboard_embed(guild: Option<GuildId>, period: Period) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Leaderboards are kept only in servers."))?;
+    let standings = database("Scores are not recorded on this bot.")?
+        .leaderboard()
+        .top_teams(guild, period, SystemTime::now(), LEADERBOARD_SIZE)?;
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::GOLD)
+        .title(format!("Team leaderboard ({})", period.name()));
+    if standings.is_empty() {
+        embed.description("No team has solved a quiz yet.");
+    } else {
+        embed.description(
+            standings
+                .iter()
+                .enumerate()
+                .map(|(rank, standing)| {
+                    format!(
+                        "{}. **{}**: {} points ({} solved)",
+                        rank + 1,
+                        standing.team,
+                        standing.points,
+                        standing.solves
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+    Ok(embed)
+}
+
+fn leaderboard_embed(guild: Option<GuildId>, period: Period) -> anyhow::Result<CreateEmbed> {
+    let guild = guild.ok_or_else(|| anyhow!("Leaderboards are kept only in servers."))?;
+    let standings = database("Scores are not recorded on this bot.")?
+        .leaderboard()
+        .top(guild, period, SystemTime::now(), LEADERBOARD_SIZE)?;
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::GOLD)
+        .title(format!("Leaderboard ({})", period.name()));
+    if standings.is_empty() {
+        embed.description("Nobody has solved a quiz yet.");
+    } else {
+        embed.description(
+            standings
+                .iter()
+                .enumerate()
+                .map(|(rank, standing)| {
+                    format!(
+                        "{}. <@{}>: {} points ({} solved)",
+                        rank + 1,
+                        standing.user.0,
+                        standing.points,
+                        standing.solves
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+    Ok(embed)
+}
+
+/// Number of games a page of `/archive` shows
+const ARCHIVE_PAGE_SIZE: usize = 10;
+
+/// Page `page` (from 0) of the games archived in `guild`, with the buttons turning it.
+fn archive_page(
+    guild: Option<GuildId>,
+    page: usize,
+) -> anyhow::Result<(CreateEmbed, Vec<CreateButton>)> {
+    let guild = guild.ok_or_else(|| anyhow!("Games are archived only in servers."))?;
+    let spoilers = spoilers(Some(guild));
+    let database = database("Games are not archived on this bot.")?;
+    let archive = database.archive();
+    let pages = archive.count(guild)?.div_ceil(ARCHIVE_PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+    let games = archive.games(guild, page * ARCHIVE_PAGE_SIZE, ARCHIVE_PAGE_SIZE)?;
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::BLUE)
+        .title("Archive")
+        .footer(|footer| footer.text(format!("page {}/{}", page + 1, pages)));
+    if games.is_empty() {
+        embed.description("No quiz has ended on this server yet.");
+    } else {
+        embed.description(
+            games
+                .iter()
+                .map(|game| {
+                    let ended_at = game
+                        .ended_at
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |ended_at| ended_at.as_secs());
+                    let result = match game.solver {
+                        Some(solver) => {
+                            format!("solved by <@{}> with {} queries", solver.0, game.queries)
+                        }
+                        None => format!("unsolved after {} queries", game.queries),
+                    };
+                    format!(
+                        "#{} · <t:{ended_at}:d> · {} · {result} · {}",
+                        game.id.unwrap_or_default(),
+                        game.difficulty,
+                        spoilers.wrap(SpoilerKind::Archive, format!("`{}`", game.answer))
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    let turn = |label: &str, to: usize, disabled: bool| {
+        let mut button = CreateButton::default();
+        button
+            .style(ButtonStyle::Secondary)
+            .label(label)
+            .custom_id(CustomId::Archive { page: to })
+            .disabled(disabled);
+        button
+    };
+    let buttons = vec![
+        turn("previous", page.saturating_sub(1), page == 0),
+        turn("next", page + 1, page + 1 == pages),
+    ];
+    Ok((embed, buttons))
+}
+
+const REPLAY_PAGE_SIZE: usize = 10;
+
+/// Build the page `page` (from 0) of `/replay`, retelling the moves of the archived game `id` in order.
+fn replay_page(
+    guild: Option<GuildId>,
+    id: i64,
+    page: usize,
+) -> anyhow::Result<(CreateEmbed, Vec<CreateButton>)> {
+    let guild = guild.ok_or_else(|| anyhow!("Games are archived only in servers."))?;
+    let spoilers = spoilers(Some(guild));
+    let game = database("Games are not archived on this bot.")?
+        .archive()
+        .game(guild, id)?
+        .ok_or_else(|| anyhow!("There is no game #{} in this server.", id))?;
+    let transcript = game
+        .transcript
+        .as_ref()
+        .ok_or_else(|| anyhow!("Game #{} was archived before transcripts were kept.", id))?;
+
+    let mut steps = Quiz::replay_steps(transcript, &spoilers)?;
+    steps.push(format!(
+        "{} The answer was {}.",
+        match game.solver {
+            Some(solver) => format!("Solved by <@{}> with {} queries.", solver.0, game.queries),
+            None => format!("Unsolved after {} queries.", game.queries),
+        },
+        spoilers.wrap(SpoilerKind::Archive, format!("`{}`", game.answer))
+    ));
+    let pages = steps.len().div_ceil(REPLAY_PAGE_SIZE);
+    let page = page.min(pages - 1);
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::BLUE)
+        .title(format!("Replay of game #{}", id))
+        .description(
+            steps
+                .iter()
+                .skip(page * REPLAY_PAGE_SIZE)
+                .take(REPLAY_PAGE_SIZE)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .footer(|footer| footer.text(format!("page {}/{}", page + 1, pages)));
+
+    let turn = |label: &str, to: usize, disabled: bool| {
+        let mut button = CreateButton::default();
+        button
+            .style(ButtonStyle::Secondary)
+            .label(label)
+            .custom_id(CustomId::Replay { game: id, page: to })
+            .disabled(disabled);
+        button
+    };
+    let buttons = vec![
+        turn("previous", page.saturating_sub(1), page == 0),
+        turn("next", page + 1, page + 1 == pages),
+    ];
+    Ok((embed, buttons))
+}
+
+/// Start a fresh quiz in `channel`, closing it at its deadline if it has a time limit.
+async fn start_quiz(
+    http: &Arc<Http>,
+    channel: ChannelId,
+    guild: Option<GuildId>,
+    options: QuizOptions,
+) -> anyhow::Result<(QuizId, CreateEmbed)> {
+    let (id, embed) = CONTAINER.fresh(channel, options).await?;
+    if let Ok(Some(deadline)) = CONTAINER
+        .command(channel, Some(id), |quiz| quiz.deadline())
+        .await
+    {
+        tokio::task::spawn(close_at_deadline(
+            Arc::clone(http),
+            channel,
+            guild,
+            id,
+            deadline,
+        ));
+    }
+    Ok((id, embed))
+}
+
+/// Standings of the match `played` so far, as its podium once it is over.
+fn match_embed(played: &Match) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    if played.is_over() {
+        embed.colour(Colour::GOLD).title("The match is over!");
+    } else {
+        embed.colour(Colour::BLUE).title(format!(
+            "Standings after round {} of best of {}",
+            played.round() - 1,
+            played.best_of
+        ));
+    }
+    let standings = played.standings();
+    if standings.is_empty() {
+        embed.description("Nobody has solved a round.");
+    } else {
+        embed.description(
+            standings
+                .iter()
+                .enumerate()
+                .map(|(rank, standing)| {
+                    let place = match rank {
+                        0 if played.is_over() => "🥇".to_string(),
+                        1 if played.is_over() => "🥈".to_string(),
+                        2 if played.is_over() => "🥉".to_string(),
+                        _ => format!("{}.", rank + 1),
+                    };
+                    format!(
+                        "{place} <@{}>: {} rounds won, {} points",
+                        standing.player.0, standing.wins, standing.points
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+    embed
+}
+
+/// Once the quiz `id` in `channel` has ended, won by `winner` if anyone solved it,
+/// post the standings of the match it was the round of and begin the next round,
+/// or the podium if the match is over.
+async fn next_round(
+    http: &Arc<Http>,
+    channel: ChannelId,
+    guild: Option<GuildId>,
+    id: QuizId,
+    winner: Option<RoundWin>,
+) {
+    let played = match CONTAINER
+        .match_command(channel, |played| {
+            played.end_round(id, winner).then(|| played.clone())
+        })
+        .await
+    {
+        Ok(Some(played)) => played,
+        _ => return,
+    };
+    let standings = match_embed(&played);
+    let next = if played.is_over() {
+        CONTAINER.match_end(channel).await;
+        None
+    } else {
+        let begun = match CONTAINER.rematch_options(id).await {
+            Ok(options) => start_quiz(http, channel, guild, options).await,
+            Err(why) => Err(why),
+        };
+        Some(match begun {
+            Ok((next, mut embed)) => {
+                let _ = CONTAINER
+                    .match_command(channel, |played| played.begin_round(next))
+                    .await;
+                embed.field(
+                    "match",
+                    format!("round {} of best of {}", played.round(), played.best_of),
+                    false,
+                );
+                embed
+            }
+            Err(why) => {
+                CONTAINER.match_end(channel).await;
+                why.as_embed()
+            }
+        })
+    };
+
+    let mut posted = channel
+        .send_message(http, |message| message.set_embed(standings))
+        .await
+        .map(|_| ());
+    if let Some(next) = next {
+        posted = posted.and(
+            channel
+                .send_message(http, |message| message.set_embed(next))
+                .await
+                .map(|_| ()),
+        );
+    }
+    if let Err(why) = posted {
+        println!("ERROR: failed to post the next round in {channel}: {why}");
+    }
+}
+
+/// Reveal the answer of the quiz `id` in `channel` at `deadline`
+/// unless it has been solved or given up by then.
+async fn close_at_deadline(
+    http: Arc<Http>,
+    channel: ChannelId,
+    guild: Option<GuildId>,
+    id: QuizId,
+    deadline: Instant,
+) {
+    tokio::time::sleep_until(deadline.into()).await;
+    let headline = configured(guild, |config, guild| config.locale(guild)).time_is_up(id);
+    close_quiz(&http, channel, guild, id, &headline).await;
+}
+
+/// Reveal the answer of the quiz `id` in `channel` after `headline`
+/// unless it has already been closed.
+async fn close_quiz(
+    http: &Http,
+    channel: ChannelId,
+    guild: Option<GuildId>,
+    id: QuizId,
+    headline: &str,
+) {
+    let quiz = match CONTAINER.take(channel, id).await {
+        Some(quiz) => quiz,
+        None => return,
+    };
+    record_game(
+        guild,
+        &quiz.get_answer_regex(),
+        quiz.seed(),
+        &quiz.outcome(false),
+        &quiz.archived(None),
+    );
+
+    let spoilers = spoilers(guild);
+    let (content, buttons) = quiz.reveal(headline, &spoilers);
+    let diagrams = quiz.answer_diagrams(&spoilers);
+    let analysis = quiz.analysis_embed(&spoilers);
+    let posted = channel
+        .send_message(http, |message| {
+            message.content(content).components(|component| {
+                component.create_action_row(|action_row| {
+                    for button in buttons {
+                        action_row.add_button(button);
+                    }
+                    action_row
+                })
+            })
+        })
+        .await
+        .and(
+            channel
+                .send_message(http, |message| {
+                    message.add_files(
+                        diagrams
+                            .iter()
+                            .map(|(filename, data)| (data.as_slice(), filename.as_str())),
+                    )
+                })
+                .await,
+        )
+        .and(
+            channel
+                .send_message(http, |message| message.set_embed(analysis))
+                .await,
+        );
+    if let Err(why) = posted {
+        println!("ERROR: failed to reveal the answer in {channel}: {why}");
+    }
+}
+
+/// Remember the response to `command` as that to the query or guess `action` of the quiz `id`,
+/// so that `/undo` can delete it.
+async fn attach_response(
+    http: &Http,
+    command: &ApplicationCommandInteraction,
+    id: Option<QuizId>,
+    action: Option<u64>,
+) {
+    if let (Some(id), Some(action)) = (id, action) {
+        match command.get_interaction_response(http).await {
+            Ok(message) => {
+                let _ = CONTAINER
+                    .command(command.channel_id, Some(id), |quiz| {
+                        quiz.attach_response(action, message.id)
+                    })
+                    .await;
+            }
+            Err(why) => println!(
+                "ERROR: failed to look up the response in {}: {why}",
+                command.channel_id
+            ),
+        }
+    }
+}
+
+/// Whether the member who sent `command` may retract queries and guesses.
+fn is_moderator(command: &ApplicationCommandInteraction) -> bool {
+    command
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.administrator() || permissions.manage_messages())
+}
+
+/// Whether the member who sent `command` may change the configuration of the guild.
+fn is_admin(command: &ApplicationCommandInteraction) -> bool {
+    command
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.administrator())
+}
+
+/// Whether `channel` is a thread started in one of `parents`.
+async fn is_thread_of(http: &Http, channel: ChannelId, parents: &[ChannelId]) -> bool {
+    match channel.to_channel(http).await {
+        Ok(channel) => channel
+            .guild()
+            .and_then(|channel| channel.category_id)
+            .is_some_and(|parent| parents.contains(&parent)),
+        Err(why) => {
+            println!("ERROR: failed to look up {channel}: {why}");
+            false
+        }
+    }
+}
+
+/// The words shown when `quiz` starts, as a list of code spans.
+fn opening_examples(quiz: &Quiz) -> String {
+    quiz.opening_examples()
+        .iter()
+        .map(|word| match word.as_slice() {
+            [] => "`ε`".to_string(),
+            w => format!("`{}`", Alphabet::slice_to_plain_string(w)),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Button refreshing the dashboard of the classroom quiz `id` in `channel`.
+fn refresh_button(channel: ChannelId, id: QuizId) -> CreateButton {
+    let mut button = CreateButton::default();
+    button
+        .style(ButtonStyle::Secondary)
+        .label("refresh")
+        .custom_id(CustomId::Dashboard {
+            channel: channel.0,
+            quiz: id.0,
+        });
+    button
+}
+
+/// Quiz chosen with the `quiz` option, if any.
+fn quiz_id(dictionary: &HashMap<String, Notification>) -> Option<QuizId> {
+    dictionary
+        .get("quiz")
+        .and_then(|id| id.to::<i64>().ok())
+        .map(|id| QuizId(id as u64))
+}
+
+/// The `letters` option, which must be from 1 to the number of alphabets.
+fn letters(dictionary: &HashMap<String, Notification>) -> anyhow::Result<Option<NonZeroU8>> {
+    let most = Alphabet::iter().count();
+    dictionary
+        .get("letters")
+        .map(|letters| -> anyhow::Result<NonZeroU8> {
+            usize::try_from(letters.to::<i64>()?)
+                .ok()
+                .filter(|letters| (1..=most).contains(letters))
+                .and_then(|letters| NonZeroU8::new(letters as u8))
+                .ok_or_else(|| anyhow!("The number of letters must be from 1 to {}.", most))
+        })
+        .transpose()
+}
+
+/// Options of `/start`, each left to the default of `guild` when omitted.
+fn quiz_options(
+    dictionary: &HashMap<String, Notification>,
+    guild: Option<GuildId>,
+) -> anyhow::Result<QuizOptions> {
+    let letters = letters(dictionary)?;
+    let seed = dictionary
+        .get("seed")
+        .map(|seed| -> anyhow::Result<u64> {
+            seed.to::<String>()?
+                .trim()
+                .parse()
+                .with_context(|| anyhow!("invalid seed"))
+        })
+        .transpose()?;
+    let variant = dictionary.get("variant").map_or_else(
+        || Ok(QuizVariant::Regex),
+        |variant| variant.to::<String>()?.parse(),
+    )?;
+    let preset = dictionary
+        .get("difficulty")
+        .map(|preset| preset.to::<String>()?.parse())
+        .transpose()?;
+    let max_queries = dictionary
+        .get("max_queries")
+        .map(|max| -> anyhow::Result<usize> {
+            max.to::<i64>()?
+                .try_into()
+                .with_context(|| anyhow!("invalid query budget"))
+        })
+        .transpose()?;
+    let time_limit = dictionary
+        .get("time_limit")
+        .map(|limit| parse_time_limit(&limit.to::<String>()?))
+        .transpose()?;
+    let guess_limit = |name: &str| {
+        dictionary
+            .get(name)
+            .map(|max| -> anyhow::Result<usize> {
+                max.to::<i64>()?
+                    .try_into()
+                    .with_context(|| anyhow!("invalid guess limit"))
+            })
+            .transpose()
+    };
+    let guess_limits = GuessLimits {
+        max_size: guess_limit("max_guess_size")?,
+        max_stars: guess_limit("max_guess_stars")?,
+    };
+    let team_allowance = |name: &str| {
+        dictionary
+            .get(name)
+            .map(|max| -> anyhow::Result<usize> {
+                max.to::<i64>()?
+                    .try_into()
+                    .with_context(|| anyhow!("invalid team budget"))
+            })
+            .transpose()
+    };
+    let team_budget = match (
+        team_allowance("team_queries")?,
+        team_allowance("team_guesses")?,
+    ) {
+        (None, None) => None,
+        (queries, guesses) => Some(TeamBudget { queries, guesses }),
+    };
+    let reveal = |name: &str| {
+        dictionary
+            .get(name)
+            .map(|reveal| reveal.to::<bool>())
+            .transpose()
+            .map(|reveal| reveal.unwrap_or(false))
+    };
+    let hint_penalty = dictionary
+        .get("hint_penalty")
+        .map(|penalty| -> anyhow::Result<u32> {
+            penalty
+                .to::<i64>()?
+                .try_into()
+                .with_context(|| anyhow!("invalid hint penalty"))
+        })
+        .transpose()?;
+    let handicaps = Handicaps {
+        literal_count: reveal("reveal_literals")?,
+        nullable: reveal("reveal_epsilon")?,
+        accepted_word: reveal("reveal_word")?,
+    };
+
+    Ok(
+        configured(guild, |config, guild| config.defaults(guild)).apply(QuizOptions {
+            variant,
+            letters,
+            preset,
+            seed,
+            max_queries,
+            daily: None,
+            tournament_match: None,
+            race: None,
+            time_limit,
+            owner: None,
+            team_budget,
+            guess_limits,
+            handicaps,
+            hint_penalty,
+            practice: false,
+        }),
+    )
+}
+
+/// Leaderboard of a golf round, listing the regexes of the entries once `revealed`.
+fn golf_leaderboard(round: &GolfRound, revealed: bool) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.colour(Colour::GOLD).title("golf leaderboard");
+    let leaderboard = round.leaderboard();
+    if leaderboard.is_empty() {
+        embed.field("Nothing to show", "-", false);
+    }
+    for (rank, (player, entry)) in leaderboard.iter().enumerate() {
+        let value = if revealed {
+            format!("{player}: {} (`{}`)", entry.score, entry.regex)
+        } else {
+            format!("{player}: {}", entry.score)
+        };
+        embed.field(format!("#{}", rank + 1), value, false);
+    }
+    embed
+}
+
+/// Run `cmd` on `quiz` in `channel`, saving the quiz again if a move is made in it.
+fn saving<F, R>(channel: ChannelId, quiz: &mut Quiz, cmd: F) -> R
+where
+    F: FnOnce(&mut Quiz) -> R,
+{
+    let moves = quiz.move_count();
+    let result = cmd(&mut *quiz);
+    if quiz.move_count() != moves {
+        save_game(channel, quiz);
+    }
+    result
+}
+
 #[async_trait]
 trait Containerized {
-    async fn command<F, R>(&self, channel: ChannelId, cmd: F) -> anyhow::Result<R>
+    async fn command<F, R>(
+        &self,
+        channel: ChannelId,
+        quiz: Option<QuizId>,
+        cmd: F,
+    ) -> anyhow::Result<R>
     where
         F: FnOnce(&mut Quiz) -> R + Send + Sync + 'async_trait;
     async fn checked_command<F, R>(
         &self,
         channel: ChannelId,
         user: UserId,
+        quiz: Option<QuizId>,
         cmd: F,
     ) -> anyhow::Result<R>
     where
         F: FnOnce(&mut Quiz) -> R + Send + Sync + 'async_trait;
-    async fn fresh(&self, channel: ChannelId, difficulty: NonZeroU8)
-        -> anyhow::Result<CreateEmbed>;
-    async fn delete(&self, channel: ChannelId);
+    async fn fresh(
+        &self,
+        channel: ChannelId,
+        options: QuizOptions,
+    ) -> anyhow::Result<(QuizId, CreateEmbed)>;
+    async fn delete(&self, channel: ChannelId, quiz: QuizId);
+    /// Put back `quiz`, restored after a restart, in `channel` under its former id `id`.
+    async fn restore(&self, channel: ChannelId, id: QuizId, quiz: Quiz) -> anyhow::Result<()>;
+    /// Close the quiz `quiz` in `channel`, returning it unless it has already been closed.
+    async fn take(&self, channel: ChannelId, quiz: QuizId) -> Option<Quiz>;
+    /// A line describing each quiz going on in `channel`.
+    async fn quiz_list(&self, channel: ChannelId) -> Vec<String>;
+    async fn finished_answer(&self, channel: ChannelId) -> anyhow::Result<RegexAst>;
+    async fn golf_fresh(
+        &self,
+        channel: ChannelId,
+        letters: NonZeroU8,
+        seed: Option<u64>,
+    ) -> anyhow::Result<CreateEmbed>;
+    async fn golf_command<F, R>(&self, channel: ChannelId, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut GolfRound) -> R + Send + Sync + 'async_trait;
+    async fn golf_end(&self, channel: ChannelId) -> anyhow::Result<GolfRound>;
+    async fn tournament_open(&self, guild: GuildId, channel: ChannelId) -> anyhow::Result<()>;
+    async fn tournament_command<F, R>(&self, guild: GuildId, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut Tournament) -> R + Send + Sync + 'async_trait;
+    async fn tournaments_expire(&self, now: SystemTime) -> Vec<(ChannelId, String)>;
+    /// Keep `submission` until `user` hosts it, replacing the one they submitted before.
+    async fn submit(&self, user: UserId, submission: Submission);
+    /// Start the quiz `user` has submitted in `channel`.
+    async fn host(&self, channel: ChannelId, user: UserId)
+        -> anyhow::Result<(QuizId, CreateEmbed)>;
+    /// Start `quiz`, whose answer a player has set, in `channel`.
+    async fn start_custom(
+        &self,
+        channel: ChannelId,
+        quiz: Quiz,
+    ) -> anyhow::Result<(QuizId, CreateEmbed)>;
+    async fn race_open(&self, race: Race) -> u64;
+    async fn race_command<F, R>(&self, race: u64, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut Race) -> R + Send + Sync + 'async_trait;
+    /// Stop refereeing the race `race`, returning it unless it has already ended.
+    async fn race_end(&self, race: u64) -> Option<Race>;
+    async fn rematch_options(&self, quiz: QuizId) -> anyhow::Result<QuizOptions>;
+    async fn match_open(&self, channel: ChannelId, best_of: usize) -> anyhow::Result<()>;
+    async fn match_command<F, R>(&self, channel: ChannelId, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut Match) -> R + Send + Sync + 'async_trait;
+    async fn match_end(&self, channel: ChannelId) -> Option<Match>;
 }
 
 #[async_trait]
 impl Containerized for Lazy<Arc<Mutex<Container>>> {
-    async fn command<F, R>(&self, channel: ChannelId, cmd: F) -> anyhow::Result<R>
+    async fn command<F, R>(
+        &self,
+        channel: ChannelId,
+        quiz: Option<QuizId>,
+        cmd: F,
+    ) -> anyhow::Result<R>
     where
         F: FnOnce(&mut Quiz) -> R + Send + Sync + 'async_trait,
     {
         loop {
             if let Ok(mut lock) = self.try_lock() {
                 return lock
-                    .channel_map
-                    .get_mut(&channel)
-                    .ok_or_else(|| anyhow!("ゲームが開始していません"))?
-                    .as_mut()
-                    .map(cmd)
-                    .ok_or_else(|| anyhow!("not started"));
+                    .resolve(channel, quiz, None)
+                    .map(|quiz| saving(channel, quiz, cmd));
             }
         }
     }
@@ -113,6 +2105,7 @@ impl Containerized for Lazy<Arc<Mutex<Container>>> {
         &self,
         channel: ChannelId,
         user: UserId,
+        quiz: Option<QuizId>,
         cmd: F,
     ) -> anyhow::Result<R>
     where
@@ -121,59 +2114,458 @@ impl Containerized for Lazy<Arc<Mutex<Container>>> {
         loop {
             if let Ok(mut lock) = self.try_lock() {
                 return lock
-                    .channel_map
-                    .get_mut(&channel)
-                    .ok_or_else(|| anyhow!("ゲームが開始していません"))?
-                    .as_mut()
-                    .ok_or_else(|| anyhow!("ゲームが開始していません"))
+                    .resolve(channel, quiz, Some(user))
                     .and_then(|quiz: &mut Quiz| {
                         quiz.is_participant(&user).then_some(quiz).ok_or_else(|| {
                             anyhow!("まずは`start`コマンドでゲームを開始してください")
                         })
                     })
-                    .map(cmd);
+                    .map(|quiz| saving(channel, quiz, cmd));
+            }
+        }
+    }
+
+    async fn fresh(
+        &self,
+        channel: ChannelId,
+        options: QuizOptions,
+    ) -> anyhow::Result<(QuizId, CreateEmbed)> {
+        let quiz = commands::generate_regex(options).await?;
+        let seed = quiz.seed();
+        let tier = quiz.difficulty().tier;
+        let examples = opening_examples(&quiz);
+        let handicaps = quiz.handicap_reveals();
+
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                let domain = quiz
+                    .domain()
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let difficulty = match options.preset {
+                    Some(preset) => format!("{preset} (estimated: {tier})"),
+                    None => format!("{tier}"),
+                };
+
+                let mut embed = CreateEmbed::default();
+                embed
+                    .colour(Colour::BLITZ_BLUE)
+                    .title("Starts a fresh REGEX-SOUP")
+                    .field("domain", format!("Σ = {{{domain}}}"), false)
+                    .field("variant", options.variant.name(), false)
+                    .field("difficulty", difficulty, false)
+                    .field("accepted examples", examples, false)
+                    .field("seed", format!("`{seed}`"), false);
+                if let Some(limit) = options.time_limit {
+                    let deadline = (SystemTime::now() + limit)
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |deadline| deadline.as_secs());
+                    embed.field(
+                        "time limit",
+                        format!("The answer is revealed <t:{deadline}:R>."),
+                        false,
+                    );
+                }
+                if let Some(day) = options.daily {
+                    embed.field(
+                        "daily puzzle",
+                        format!("#{day}, the same puzzle in every server"),
+                        false,
+                    );
+                }
+                if let Some(budget) = options.team_budget {
+                    let allowance = |max: Option<usize>, what: &str| {
+                        max.map_or_else(
+                            || format!("unlimited {what}"),
+                            |max| format!("{max} {what}"),
+                        )
+                    };
+                    embed.field(
+                        "teams",
+                        format!(
+                            "Join a team with `/team`. Each team shares {} and {}.",
+                            allowance(budget.queries, "queries"),
+                            allowance(budget.guesses, "guesses")
+                        ),
+                        false,
+                    );
+                }
+                if !options.guess_limits.is_unlimited() {
+                    embed.field(
+                        "guess limits",
+                        format!("Guessed expressions may have {}.", options.guess_limits),
+                        false,
+                    );
+                }
+                if let Some(max_queries) = options.max_queries {
+                    embed.field(
+                        "query budget",
+                        format!("{max_queries} distinct words for the whole channel"),
+                        false,
+                    );
+                }
+                if let Some(penalty) = options.hint_penalty {
+                    embed.field(
+                        "hint penalty",
+                        format!("Each hint deducts {penalty} points per unit of its cost."),
+                        false,
+                    );
+                }
+                if !handicaps.is_empty() {
+                    embed.field("handicaps", handicaps.join("\n"), false);
+                }
+                if options.practice {
+                    embed.field(
+                        "practice",
+                        "You play alone and may query and guess as often as you like. Nothing is \
+                         recorded on any leaderboard.",
+                        false,
+                    );
+                }
+
+                let running = lock
+                    .channel_map
+                    .get(&channel)
+                    .map_or(0, |quizzes| quizzes.len());
+                let id = lock.insert(channel, quiz)?;
+                if let Ok(quiz) = lock.resolve(channel, Some(id), None) {
+                    save_game(channel, quiz);
+                }
+                embed.title(format!("Starts a fresh REGEX-SOUP {id}"));
+                if running > 0 {
+                    embed.field(
+                        "ATTENTION:",
+                        format!(
+                            "{} other quizzes are going on in this channel. Add `quiz: {}` to \
+                             commands for this one.",
+                            running, id.0
+                        ),
+                        false,
+                    );
+                }
+                return Ok((id, embed));
+            }
+        }
+    }
+
+    async fn delete(&self, channel: ChannelId, quiz: QuizId) {
+        self.take(channel, quiz).await;
+    }
+
+    async fn restore(&self, channel: ChannelId, id: QuizId, quiz: Quiz) -> anyhow::Result<()> {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock.restore(channel, id, quiz);
+            }
+        }
+    }
+
+    async fn take(&self, channel: ChannelId, quiz: QuizId) -> Option<Quiz> {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                forget_game(channel, quiz);
+                return lock.remove(channel, quiz);
+            }
+        }
+    }
+
+    async fn quiz_list(&self, channel: ChannelId) -> Vec<String> {
+        loop {
+            if let Ok(lock) = self.try_lock() {
+                return lock
+                    .channel_map
+                    .get(&channel)
+                    .into_iter()
+                    .flatten()
+                    .map(|(id, quiz)| {
+                        let domain = quiz
+                            .domain()
+                            .iter()
+                            .map(|a| a.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        format!(
+                            "{id}: {} over Σ = {{{domain}}}, {} players, {} queries",
+                            quiz.variant().name(),
+                            quiz.participant_count(),
+                            quiz.len()
+                        )
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    async fn finished_answer(&self, channel: ChannelId) -> anyhow::Result<RegexAst> {
+        loop {
+            if let Ok(lock) = self.try_lock() {
+                return lock
+                    .finished
+                    .get(&channel)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No quiz has ended in this channel yet."));
+            }
+        }
+    }
+
+    async fn golf_fresh(
+        &self,
+        channel: ChannelId,
+        letters: NonZeroU8,
+        seed: Option<u64>,
+    ) -> anyhow::Result<CreateEmbed> {
+        let round = commands::generate_golf(letters, seed).await?;
+        let (positives, negatives) = round.words_lines();
+
+        let mut embed = CreateEmbed::default();
+        embed
+            .colour(Colour::GOLD)
+            .title("Starts a round of regex golf")
+            .field("match all of", positives, false)
+            .field("match none of", negatives, false)
+            .field("seed", format!("`{}`", round.seed()), false);
+
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                if lock.golf_rounds.insert(channel, round).is_some() {
+                    embed.field(
+                        "ATTENTION:",
+                        "An old round of regex golf is expired.",
+                        false,
+                    );
+                }
+                return Ok(embed);
+            }
+        }
+    }
+
+    async fn golf_command<F, R>(&self, channel: ChannelId, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut GolfRound) -> R + Send + Sync + 'async_trait,
+    {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock.golf_rounds.get_mut(&channel).map(cmd).ok_or_else(|| {
+                    anyhow!("No round of regex golf is going on. Start one with `/golf`.")
+                });
+            }
+        }
+    }
+
+    async fn golf_end(&self, channel: ChannelId) -> anyhow::Result<GolfRound> {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock.golf_rounds.remove(&channel).ok_or_else(|| {
+                    anyhow!("No round of regex golf is going on. Start one with `/golf`.")
+                });
+            }
+        }
+    }
+
+    async fn tournament_open(&self, guild: GuildId, channel: ChannelId) -> anyhow::Result<()> {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                if lock
+                    .tournaments
+                    .get(&guild)
+                    .is_some_and(|tournament| tournament.champion().is_none())
+                {
+                    return Err(anyhow!("A tournament is already going on in this server."));
+                }
+                lock.tournaments
+                    .insert(guild, Tournament::new(channel, rand::random()));
+                return Ok(());
+            }
+        }
+    }
+
+    async fn tournament_command<F, R>(&self, guild: GuildId, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut Tournament) -> R + Send + Sync + 'async_trait,
+    {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock.tournaments.get_mut(&guild).map(cmd).ok_or_else(|| {
+                    anyhow!(
+                        "No tournament is held in this server. Open one with `/tournament open`."
+                    )
+                });
+            }
+        }
+    }
+
+    async fn tournaments_expire(&self, now: SystemTime) -> Vec<(ChannelId, String)> {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock
+                    .tournaments
+                    .values_mut()
+                    .filter_map(|tournament| {
+                        let expired = tournament.expire(now);
+                        if expired.is_empty() {
+                            return None;
+                        }
+                        let mut lines = expired
+                            .into_iter()
+                            .filter_map(|id| tournament.get(id))
+                            .map(|m| {
+                                format!(
+                                    "{} vs {}: {} advances by walkover.",
+                                    mention(m.players[0]),
+                                    mention(m.players[1]),
+                                    mention(m.winner)
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        if let Some(champion) = tournament.champion() {
+                            lines.push(format!("🏆 <@{}> wins the tournament!", champion.0));
+                        }
+                        Some((tournament.channel(), lines.join("\n")))
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    async fn submit(&self, user: UserId, submission: Submission) {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                lock.submissions.insert(user, submission);
+                return;
+            }
+        }
+    }
+
+    async fn host(
+        &self,
+        channel: ChannelId,
+        user: UserId,
+    ) -> anyhow::Result<(QuizId, CreateEmbed)> {
+        let submission = loop {
+            if let Ok(lock) = self.try_lock() {
+                break lock.submissions.get(&user).cloned().ok_or_else(|| {
+                    anyhow!(
+                        "You have no quiz to host. Send `/submit` to me in a direct message first."
+                    )
+                })?;
+            }
+        };
+        let started = self
+            .start_custom(channel, Quiz::submitted(submission, user))
+            .await?;
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                lock.submissions.shift_remove(&user);
+                return Ok(started);
+            }
+        }
+    }
+
+    async fn start_custom(
+        &self,
+        channel: ChannelId,
+        quiz: Quiz,
+    ) -> anyhow::Result<(QuizId, CreateEmbed)> {
+        let domain = quiz
+            .domain()
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let examples = opening_examples(&quiz);
+        let (title, role) = if quiz.is_classroom() {
+            ("Starts a classroom REGEX-SOUP", "teacher")
+        } else {
+            ("Starts a REGEX-SOUP submitted by a player", "author")
+        };
+        let author = mention(quiz.author());
+
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                let id = lock.insert(channel, quiz)?;
+                if let Ok(quiz) = lock.resolve(channel, Some(id), None) {
+                    save_game(channel, quiz);
+                }
+                let mut embed = CreateEmbed::default();
+                embed
+                    .colour(Colour::BLITZ_BLUE)
+                    .title(format!("{title} {id}"))
+                    .field("domain", format!("Σ = {{{domain}}}"), false)
+                    .field("accepted examples", examples, false)
+                    .field(role, format!("{author}, who may not answer it"), false);
+                return Ok((id, embed));
+            }
+        }
+    }
+
+    async fn race_open(&self, race: Race) -> u64 {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock.open_race(race);
+            }
+        }
+    }
+
+    async fn race_command<F, R>(&self, race: u64, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut Race) -> R + Send + Sync + 'async_trait,
+    {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock
+                    .races
+                    .get_mut(&race)
+                    .map(cmd)
+                    .ok_or_else(|| anyhow!("The race is already over."));
+            }
+        }
+    }
+
+    async fn race_end(&self, race: u64) -> Option<Race> {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock.races.shift_remove(&race);
             }
         }
     }
 
-    async fn fresh(
-        &self,
-        channel: ChannelId,
-        difficulty: NonZeroU8,
-    ) -> anyhow::Result<CreateEmbed> {
-        let quiz = commands::generate_regex(difficulty).await?;
+    async fn rematch_options(&self, quiz: QuizId) -> anyhow::Result<QuizOptions> {
+        loop {
+            if let Ok(lock) = self.try_lock() {
+                return lock.rematch_options(quiz);
+            }
+        }
+    }
 
+    async fn match_open(&self, channel: ChannelId, best_of: usize) -> anyhow::Result<()> {
         loop {
             if let Ok(mut lock) = self.try_lock() {
-                let domain = Alphabet::iter()
-                    .take(difficulty.get().into())
-                    .collect::<HashSet<_>>();
-
-                let mut embed = CreateEmbed::default();
-                embed
-                    .colour(Colour::BLITZ_BLUE)
-                    .title("Starts a fresh REGEX-SOUP")
-                    .field("domain", format!("Σ = {domain:?}"), false);
+                return lock.open_match(channel, best_of);
+            }
+        }
+    }
 
-                return Ok(lock
-                    .channel_map
-                    .insert(channel, Some(quiz))
-                    .map(|_| embed.clone())
-                    .unwrap_or_else(move || {
-                        embed.field("ATTENTION:", "An old REGEX-SOUP is expired.", false);
-                        embed
-                    }));
+    async fn match_command<F, R>(&self, channel: ChannelId, cmd: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut Match) -> R + Send + Sync + 'async_trait,
+    {
+        loop {
+            if let Ok(mut lock) = self.try_lock() {
+                return lock
+                    .matches
+                    .get_mut(&channel)
+                    .map(cmd)
+                    .ok_or_else(|| anyhow!("No match is going on in this channel."));
             }
         }
     }
 
-    async fn delete(&self, channel: ChannelId) {
+    async fn match_end(&self, channel: ChannelId) -> Option<Match> {
         loop {
             if let Ok(mut lock) = self.try_lock() {
-                lock.channel_map
-                    .entry(channel)
-                    .and_modify(|quiz| *quiz = None);
-                break;
+                return lock.matches.shift_remove(&channel);
             }
         }
     }
@@ -270,6 +2662,43 @@ impl EventHandler for Handler {
                     ApplicationCommand::delete_global_application_command(&ctx.http, cmd.id).await;
             }
         }
+        GAME_RESTORATION.call_once(|| {
+            tokio::task::spawn(restore_games(Arc::clone(&ctx.http)));
+        });
+        TOURNAMENT_SCHEDULER.call_once(|| {
+            let http = Arc::clone(&ctx.http);
+            tokio::task::spawn(async move {
+                loop {
+                    tokio::time::sleep(TOURNAMENT_CHECK_INTERVAL).await;
+                    for (channel, content) in CONTAINER.tournaments_expire(SystemTime::now()).await
+                    {
+                        if let Err(why) = channel.say(&http, content).await {
+                            println!("ERROR: failed to announce walkovers to {channel}: {why}");
+                        }
+                    }
+                }
+            });
+        });
+        if DATABASE.is_some() {
+            PUZZLE_SCHEDULER.call_once(|| {
+                let http = Arc::clone(&ctx.http);
+                tokio::task::spawn(async move {
+                    loop {
+                        run_schedules(&http, SystemTime::now()).await;
+                        tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+                    }
+                });
+            });
+            DAILY_SCHEDULER.call_once(|| {
+                let http = Arc::clone(&ctx.http);
+                tokio::task::spawn(async move {
+                    loop {
+                        tokio::time::sleep(until_next_day(SystemTime::now())).await;
+                        post_daily(&http, day_of(SystemTime::now())).await;
+                    }
+                });
+            });
+        }
         println!("successfully connected!!");
         let commands = ApplicationCommand::get_global_application_commands(&ctx.http).await;
         println!("I now have the following global slash commands: {commands:#?}");
@@ -283,83 +2712,984 @@ impl EventHandler for Handler {
             let (head, tail) = flat_data.split_first().unwrap();
             let dictionary = tail.iter().cloned().collect::<HashMap<_, _>>();
 
+            // `/config` stays reachable everywhere so that the channels can be changed again
+            if command.data.name != "config" {
+                let allowed = configured(command.guild_id, |config, guild| config.channels(guild));
+                if !allowed.is_empty()
+                    && !allowed.contains(&command.channel_id)
+                    && !is_thread_of(&ctx.http, command.channel_id, &allowed).await
+                {
+                    let locale = configured(command.guild_id, |config, guild| config.locale(guild));
+                    let _ = command
+                        .ephemeral_embed(
+                            &ctx.http,
+                            anyhow!(locale.channel_not_allowed(&allowed)).as_embed(),
+                            vec![],
+                        )
+                        .await
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "refused a command outside the allowed channels.")
+                        .await;
+                    return;
+                }
+            }
+
             match head {
                 (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("start") => {
                     println!("cmd: start");
-                    let difficulty: NonZeroU8 = (dictionary
-                        .get("size")
-                        .map_or_else(|| Ok(3i64), |size| size.to::<i64>())
+                    let res = match quiz_options(&dictionary, command.guild_id) {
+                        Ok(options) => {
+                            let options = QuizOptions {
+                                owner: Some(command.user.id),
+                                ..options
+                            };
+                            start_quiz(&ctx.http, command.channel_id, command.guild_id, options)
+                                .await
+                                .map(|(_, embed)| embed)
+                        }
+                        Err(why) => Err(why),
+                    };
+                    let _ = command
+                        .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                        .await
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| {
+                            "parse error: successfully finished to send error message."
+                        })
+                        .await;
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("practice") =>
+                {
+                    println!("cmd: practice");
+                    let res = if command.guild_id.is_some() {
+                        Err(anyhow!(
+                            "Send `/practice` to me in a direct message to play alone."
+                        ))
+                    } else {
+                        match quiz_options(&dictionary, None) {
+                            Ok(options) => {
+                                let options = QuizOptions {
+                                    owner: Some(command.user.id),
+                                    practice: true,
+                                    ..options
+                                };
+                                start_quiz(&ctx.http, command.channel_id, None, options)
+                                    .await
+                                    .map(|(_, embed)| embed)
+                            }
+                            Err(why) => Err(why),
+                        }
+                    };
+                    let _ = command
+                        .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                        .await
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "successfully finished practice command.")
+                        .await;
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("match") => {
+                    println!("cmd: match");
+                    let best_of = dictionary
+                        .get("rounds")
+                        .map_or(Ok(BEST_OF[0] as i64), |rounds| rounds.to::<i64>())
+                        .and_then(|rounds| {
+                            BEST_OF
+                                .iter()
+                                .copied()
+                                .find(|&best_of| best_of as i64 == rounds)
+                                .ok_or_else(|| anyhow!("A match is played over 3, 5 or 7 rounds."))
+                        });
+                    let res = match (best_of, quiz_options(&dictionary, command.guild_id)) {
+                        (Ok(best_of), Ok(options)) => {
+                            match CONTAINER.match_open(command.channel_id, best_of).await {
+                                Ok(()) => {
+                                    let started = start_quiz(
+                                        &ctx.http,
+                                        command.channel_id,
+                                        command.guild_id,
+                                        options,
+                                    )
+                                    .await;
+                                    match started {
+                                        Ok((id, mut embed)) => {
+                                            let _ = CONTAINER
+                                                .match_command(command.channel_id, |played| {
+                                                    played.begin_round(id)
+                                                })
+                                                .await;
+                                            embed.field(
+                                                "match",
+                                                format!("round 1 of best of {best_of}"),
+                                                false,
+                                            );
+                                            Ok(embed)
+                                        }
+                                        Err(why) => {
+                                            CONTAINER.match_end(command.channel_id).await;
+                                            Err(why)
+                                        }
+                                    }
+                                }
+                                Err(why) => Err(why),
+                            }
+                        }
+                        (Err(why), _) | (_, Err(why)) => Err(why),
+                    };
+                    let _ = command
+                        .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                        .await
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "successfully finished match command.")
+                        .await;
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("quiz") => {
+                    println!("cmd: quiz");
+                    let code = dictionary
+                        .get("code")
+                        .ok_or_else(|| anyhow!("Give the seed code of a quiz to replay."))
+                        .and_then(|code| code.to::<String>())
+                        .and_then(|code| code.parse::<SeedCode>());
+                    let res = match code {
+                        Ok(code) => {
+                            let options = QuizOptions {
+                                owner: Some(command.user.id),
+                                ..code.into()
+                            };
+                            start_quiz(&ctx.http, command.channel_id, command.guild_id, options)
+                                .await
+                                .map(|(_, embed)| embed)
+                        }
+                        Err(why) => Err(why),
+                    };
+                    let _ = command
+                        .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                        .await
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "successfully finished quiz command.")
+                        .await;
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("query") => {
+                    println!("cmd: query");
+                    tokio::task::spawn(async move {
+                        let input = dictionary.get("input").unwrap().to::<String>().unwrap();
+                        let is_match = CONTAINER
+                            .checked_command(
+                                command.channel_id,
+                                command.user.id,
+                                quiz_id(&dictionary),
+                                |quiz| {
+                                    quiz.query(command.user.id, &input).map(|is_match| {
+                                        let content = match quiz
+                                            .remaining_queries_for(command.user.id)
+                                        {
+                                            Some(remaining) => {
+                                                format!("{is_match}\n({remaining} queries left)")
+                                            }
+                                            None => format!("{is_match}"),
+                                        };
+                                        (content, quiz.id(), quiz.last_action())
+                                    })
+                                },
+                            )
+                            .await
+                            .flatten();
+
+                        match is_match {
+                            Ok((is_match, id, action)) => {
+                                let _ = command
+                                    .message(&ctx.http, is_match)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished query command.")
+                                    .await;
+                                attach_response(&ctx.http, &command, id, action).await;
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .embed(&ctx.http, why.as_embed())
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why:#?}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("guess") => {
+                    println!("cmd: guess");
+                    tokio::task::spawn(async move {
+                        let input = dictionary.get("regex").unwrap().to::<String>().unwrap();
+                        let spoilers = spoilers(command.guild_id);
+
+                        let inspection = CONTAINER
+                            .checked_command(
+                                command.channel_id,
+                                command.user.id,
+                                quiz_id(&dictionary),
+                                |quiz| {
+                                    quiz.inspect(command.user.id, &input).map(|res| {
+                                        let solved = matches!(
+                                            res,
+                                            InspectionAcceptance::Accepted(_)
+                                        )
+                                        .then(|| SolvedQuiz::new(quiz, command.user.id, &spoilers));
+                                        (res, solved, quiz.id(), quiz.last_action())
+                                    })
+                                },
+                            )
+                            .await
+                            .flatten();
+
+                        match inspection {
+                            Ok((res, solved, quiz, action)) => {
+                                let res = res.describe(&spoilers);
+                                if let Some(id) = solved.as_ref().and_then(|solved| solved.id) {
+                                    CONTAINER.delete(command.channel_id, id).await;
+                                }
+                                let response = match &solved {
+                                    Some(solved) => {
+                                        let content = solved
+                                            .announce(&ctx.http, command.guild_id, &res)
+                                            .await;
+                                        command
+                                            .button(&ctx.http, content, [solved.rematch.clone()])
+                                            .await
+                                    }
+                                    None => command.message(&ctx.http, res).await,
+                                };
+                                let _ = response
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished guess command.")
+                                    .await;
+                                match solved {
+                                    None => {
+                                        attach_response(&ctx.http, &command, quiz, action).await
+                                    }
+                                    Some(solved) => {
+                                        let _ = command
+                                            .channel_embed(&ctx.http, solved.analysis)
+                                            .await
+                                            .with_context(|| {
+                                                anyhow!("ERROR: fail to send analysis")
+                                            })
+                                            .logging_with(|_| "successfully sent analysis.")
+                                            .await;
+                                        if let Some(id) = solved.id {
+                                            let winner = RoundWin {
+                                                player: solved.solver,
+                                                points: solved.score.total,
+                                            };
+                                            next_round(
+                                                &ctx.http,
+                                                command.channel_id,
+                                                command.guild_id,
+                                                id,
+                                                Some(winner),
+                                            )
+                                            .await;
+                                        }
+                                    }
+                                }
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .embed(&ctx.http, why.as_embed())
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why:#?}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("summary") =>
+                {
+                    println!("cmd: summary");
+                    tokio::task::spawn(async move {
+                        let summary = CONTAINER
+                            .checked_command(
+                                command.channel_id,
+                                command.user.id,
+                                quiz_id(&dictionary),
+                                |quiz| quiz.get_query_history(),
+                            )
+                            .await;
+                        match summary {
+                            Ok(summary) => {
+                                let _ = command
+                                    .embed(&ctx.http, summary)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished summary command.")
+                                    .await;
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .message(&ctx.http, format!("{why}"))
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("history") =>
+                {
+                    println!("cmd: history");
+                    tokio::task::spawn(async move {
+                        let history = CONTAINER
+                            .checked_command(
+                                command.channel_id,
+                                command.user.id,
+                                quiz_id(&dictionary),
+                                |quiz| quiz.query_log(),
+                            )
+                            .await;
+                        match history {
+                            Ok(history) => {
+                                let _ = command
+                                    .embed(&ctx.http, history)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished history command.")
+                                    .await;
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .message(&ctx.http, format!("{why}"))
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("hint") => {
+                    println!("cmd: hint");
+                    tokio::task::spawn(async move {
+                        let tier: anyhow::Result<HintTier> = dictionary.get("kind").map_or_else(
+                            || Ok(HintTier::Examples),
+                            |kind| kind.to::<String>()?.parse(),
+                        );
+                        let hint = match tier {
+                            Ok(tier) => {
+                                CONTAINER
+                                    .checked_command(
+                                        command.channel_id,
+                                        command.user.id,
+                                        quiz_id(&dictionary),
+                                        |quiz| {
+                                            let (hint, cost) = quiz.hint(tier);
+                                            format!(
+                                                "{hint}\n(This hint deducts {cost} points from \
+                                                 the score of whoever solves the quiz, {} points \
+                                                 in total so far.)",
+                                                quiz.hint_penalty()
+                                            )
+                                        },
+                                    )
+                                    .await
+                            }
+                            Err(why) => Err(why),
+                        };
+
+                        match hint {
+                            Ok(hint) => {
+                                let _ = command
+                                    .message(&ctx.http, hint)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished hint command.")
+                                    .await;
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .message(&ctx.http, format!("{why}"))
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("explain") =>
+                {
+                    println!("cmd: explain");
+                    tokio::task::spawn(async move {
+                        let input = dictionary.get("word").unwrap().to::<String>().unwrap();
+                        let explanation = CONTAINER
+                            .finished_answer(command.channel_id)
+                            .await
+                            .and_then(|answer| explain(&answer, &input));
+
+                        match explanation {
+                            Ok(explanation) => {
+                                let _ = command
+                                    .message(&ctx.http, explanation)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished explain command.")
+                                    .await;
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .message(&ctx.http, format!("{why}"))
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("golf") => {
+                    println!("cmd: golf");
+                    let letters: NonZeroU8 = (dictionary
+                        .get("letters")
+                        .map_or_else(|| Ok(3i64), |letters| letters.to::<i64>())
                         .unwrap() as u8)
                         .try_into()
                         .unwrap();
-                    let res = CONTAINER.fresh(command.channel_id, difficulty).await;
+                    let seed = dictionary
+                        .get("seed")
+                        .map(|seed| -> anyhow::Result<u64> {
+                            seed.to::<String>()?
+                                .trim()
+                                .parse()
+                                .with_context(|| anyhow!("invalid seed"))
+                        })
+                        .transpose();
+                    let res = match seed {
+                        Ok(seed) => {
+                            CONTAINER
+                                .golf_fresh(command.channel_id, letters, seed)
+                                .await
+                        }
+                        Err(why) => Err(why),
+                    };
                     let _ = command
                         .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
                         .await
                         .with_context(|| anyhow!("ERROR: fail to interaction"))
-                        .logging_with(|_| {
-                            "parse error: successfully finished to send error message."
-                        })
+                        .logging_with(|_| "successfully finished golf command.")
+                        .await;
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("golf-submit") =>
+                {
+                    println!("cmd: golf-submit");
+                    tokio::task::spawn(async move {
+                        let input = dictionary.get("regex").unwrap().to::<String>().unwrap();
+                        let player = format!("<@{}>", command.user.id.0);
+                        let res = CONTAINER
+                            .golf_command(command.channel_id, |round| {
+                                round
+                                    .submit(&player, &input)
+                                    .map(|submission| (submission, golf_leaderboard(round, false)))
+                            })
+                            .await
+                            .flatten();
+
+                        match res {
+                            Ok((submission, leaderboard)) => {
+                                let msg = if submission.improved {
+                                    format!("{player} scored {}.", submission.score)
+                                } else {
+                                    format!(
+                                        "{player} scored {}, not beating the best entry.",
+                                        submission.score
+                                    )
+                                };
+                                let _ = command
+                                    .message(&ctx.http, &msg)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished golf-submit command.")
+                                    .await;
+                                let _ = command
+                                    .channel_embed(&ctx.http, leaderboard)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to send leaderboard"))
+                                    .logging_with(|_| "successfully sent golf leaderboard.")
+                                    .await;
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .message(&ctx.http, format!("{why}"))
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("golf-end") =>
+                {
+                    println!("cmd: golf-end");
+                    tokio::task::spawn(async move {
+                        let res = CONTAINER.golf_end(command.channel_id).await.map(|round| {
+                            let mut embed = golf_leaderboard(&round, true);
+                            embed.field("reference", format!("`{}`", round.reference()), false);
+                            embed
+                        });
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished golf-end command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("leaderboard") =>
+                {
+                    println!("cmd: leaderboard");
+                    tokio::task::spawn(async move {
+                        let res = dictionary
+                            .get("period")
+                            .map(|period| period.to::<String>()?.parse::<Period>())
+                            .transpose()
+                            .and_then(|period| {
+                                let period = period.unwrap_or(Period::Week);
+                                let teams = dictionary
+                                    .get("teams")
+                                    .map(|teams| teams.to::<bool>())
+                                    .transpose()?
+                                    .unwrap_or(false);
+                                if teams {
+                                    team_leaderboard_embed(command.guild_id, period)
+                                } else {
+                                    leaderboard_embed(command.guild_id, period)
+                                }
+                            });
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished leaderboard command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("archive") =>
+                {
+                    println!("cmd: archive");
+                    tokio::task::spawn(async move {
+                        let res = dictionary
+                            .get("page")
+                            .map(|page| -> anyhow::Result<usize> {
+                                usize::try_from(page.to::<i64>()?)
+                                    .ok()
+                                    .and_then(|page| page.checked_sub(1))
+                                    .ok_or_else(|| anyhow!("invalid page"))
+                            })
+                            .transpose()
+                            .and_then(|page| archive_page(command.guild_id, page.unwrap_or(0)));
+                        let _ = match res {
+                            Ok((embed, buttons)) => command.page(&ctx.http, embed, buttons).await,
+                            Err(why) => command.embed(&ctx.http, why.as_embed()).await,
+                        }
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "successfully finished archive command.")
                         .await;
+                    });
                 }
-                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("query") => {
-                    println!("cmd: query");
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("replay") => {
+                    println!("cmd: replay");
                     tokio::task::spawn(async move {
-                        let input = dictionary.get("input").unwrap().to::<String>().unwrap();
-                        let is_match = CONTAINER
-                            .checked_command(command.channel_id, command.user.id, |quiz| {
-                                quiz.query(&input)
-                            })
+                        let res = dictionary
+                            .get("game")
+                            .ok_or_else(|| anyhow!("Choose the game to replay by its number."))
+                            .and_then(|game| game.to::<i64>())
+                            .and_then(|game| replay_page(command.guild_id, game, 0));
+                        let _ = match res {
+                            Ok((embed, buttons)) => command.page(&ctx.http, embed, buttons).await,
+                            Err(why) => command.embed(&ctx.http, why.as_embed()).await,
+                        }
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "successfully finished replay command.")
+                        .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("records") =>
+                {
+                    println!("cmd: records");
+                    tokio::task::spawn(async move {
+                        let res = dictionary
+                            .get("global")
+                            .map(|global| global.to::<bool>())
+                            .transpose()
+                            .and_then(|global| {
+                                records_embed(command.guild_id, global.unwrap_or(false))
+                            });
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
                             .await
-                            .flatten();
-
-                        match is_match {
-                            Ok(is_match) => {
-                                let _ = command
-                                    .message(&ctx.http, is_match)
-                                    .await
-                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
-                                    .logging_with(|_| "successfully finished query command.")
-                                    .await;
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished records command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("rating") => {
+                    println!("cmd: rating");
+                    tokio::task::spawn(async move {
+                        let res = dictionary
+                            .get("player")
+                            .map(|player| player.to::<User>().map(|user| user.id))
+                            .transpose()
+                            .and_then(|player| {
+                                rating_embed(command.guild_id, player.unwrap_or(command.user.id))
+                            });
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished rating command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("stats") => {
+                    println!("cmd: stats");
+                    tokio::task::spawn(async move {
+                        let res = dictionary
+                            .get("user")
+                            .map(|user| user.to::<User>().map(|user| user.id))
+                            .transpose()
+                            .and_then(|user| {
+                                stats_embed(command.guild_id, user.unwrap_or(command.user.id))
+                            });
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished stats command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("daily") => {
+                    println!("cmd: daily");
+                    tokio::task::spawn(async move {
+                        let action = dictionary
+                            .get("action")
+                            .map(|action| action.to::<String>())
+                            .transpose();
+                        let day = day_of(SystemTime::now());
+                        let res = match action.as_ref().map(|action| action.as_deref()) {
+                            Ok(None | Some("start")) => CONTAINER
+                                .fresh(command.channel_id, daily_options(day))
+                                .await
+                                .map(|(_, embed)| embed),
+                            Ok(Some("stats")) => daily_stats_embed(command.guild_id, day),
+                            Ok(Some(action @ ("subscribe" | "unsubscribe"))) => {
+                                let subscribe = action == "subscribe";
+                                command
+                                    .guild_id
+                                    .ok_or_else(|| {
+                                        anyhow!("The daily puzzle is posted only in servers.")
+                                    })
+                                    .and_then(|guild| {
+                                        let database = database(
+                                            "The daily puzzle is not recorded on this bot.",
+                                        )?;
+                                        let daily = database.daily();
+                                        if subscribe {
+                                            daily.subscribe(guild, command.channel_id)?;
+                                            Ok("The daily puzzle will be posted to this channel \
+                                                at midnight (UTC).")
+                                        } else if daily.unsubscribe(guild)? {
+                                            Ok("The daily puzzle will no longer be posted.")
+                                        } else {
+                                            Err(anyhow!(
+                                                "The daily puzzle is not posted in this server."
+                                            ))
+                                        }
+                                    })
+                                    .map(|description| {
+                                        let mut embed = CreateEmbed::default();
+                                        embed
+                                            .colour(Colour::GOLD)
+                                            .title("Daily puzzle")
+                                            .description(description);
+                                        embed
+                                    })
+                            }
+                            Ok(Some("top")) => daily_top_embed(day, command.user.id),
+                            Ok(Some(action @ ("show-name" | "hide-name"))) => {
+                                database("The daily puzzle is not recorded on this bot.")
+                                    .and_then(|database| {
+                                        let daily = database.daily();
+                                        if action == "show-name" {
+                                            let name = match dictionary.get("name") {
+                                                Some(name) => name.to::<String>()?,
+                                                None => command.user.name.clone(),
+                                            };
+                                            let name = display_name(&name)?;
+                                            daily.show_name(command.user.id, &name)?;
+                                            Ok(format!(
+                                                "You appear as **{name}** in the global ranking."
+                                            ))
+                                        } else if daily.hide_name(command.user.id)? {
+                                            Ok("You appear anonymously in the global ranking."
+                                                .to_string())
+                                        } else {
+                                            Err(anyhow!(
+                                                "You already appear anonymously in the global \
+                                                 ranking."
+                                            ))
+                                        }
+                                    })
+                                    .map(|description| {
+                                        let mut embed = CreateEmbed::default();
+                                        embed
+                                            .colour(Colour::GOLD)
+                                            .title("Daily puzzle")
+                                            .description(description);
+                                        embed
+                                    })
+                            }
+                            Ok(Some(action)) => Err(anyhow!("unknown action: {action}")),
+                            Err(why) => Err(anyhow!("{why}")),
+                        };
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished daily command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("tournament") =>
+                {
+                    println!("cmd: tournament");
+                    tokio::task::spawn(async move {
+                        let res = match dictionary
+                            .get("action")
+                            .map(|action| action.to::<String>())
+                            .transpose()
+                        {
+                            Ok(action) => {
+                                tournament_action(
+                                    command.guild_id,
+                                    command.channel_id,
+                                    command.user.id,
+                                    action.as_deref().unwrap_or("status"),
+                                )
+                                .await
                             }
+                            Err(why) => Err(why),
+                        };
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished tournament command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("race") => {
+                    println!("cmd: race");
+                    tokio::task::spawn(async move {
+                        let challenge = command
+                            .guild_id
+                            .ok_or_else(|| anyhow!("Races are held only in servers."))
+                            .and_then(|_| dictionary.get("opponent").unwrap().to::<User>())
+                            .and_then(|opponent| {
+                                if opponent.id == command.user.id || opponent.bot {
+                                    return Err(anyhow!("Challenge another player to a race."));
+                                }
+                                Ok((opponent, quiz_options(&dictionary, command.guild_id)?))
+                            });
+                        let (opponent, options) = match challenge {
+                            Ok(challenge) => challenge,
                             Err(why) => {
                                 let _ = command
-                                    .embed(&ctx.http, why.as_embed())
+                                    .message(&ctx.http, format!("{why}"))
                                     .await
                                     .with_context(|| anyhow!("ERROR: fail to interaction"))
                                     .logging_with(move |_| format!("{why:#?}"))
                                     .await;
+                                return;
                             }
+                        };
+                        let _ = command
+                            .message(
+                                &ctx.http,
+                                format!(
+                                    "🏁 <@{}> challenges <@{}> to a race on the same hidden regex!",
+                                    command.user.id.0, opponent.id.0
+                                ),
+                            )
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished race command.")
+                            .await;
+                        let started = start_race(
+                            &ctx.http,
+                            command.channel_id,
+                            [&command.user, &opponent],
+                            options,
+                        )
+                        .await;
+                        if let Err(why) = started {
+                            let _ = command
+                                .channel_id
+                                .say(&ctx.http, format!("The race could not start: {why}"))
+                                .await;
                         }
                     });
                 }
-                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("guess") => {
-                    println!("cmd: guess");
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("quizzes") =>
+                {
+                    println!("cmd: quizzes");
                     tokio::task::spawn(async move {
-                        let input = dictionary.get("regex").unwrap().to::<String>().unwrap();
-
-                        let inspection = CONTAINER
-                            .checked_command(command.channel_id, command.user.id, |quiz| {
-                                quiz.inspect(&input)
-                            })
+                        let quizzes = CONTAINER.quiz_list(command.channel_id).await;
+                        let mut embed = CreateEmbed::default();
+                        embed
+                            .colour(Colour::BLITZ_BLUE)
+                            .title("quizzes")
+                            .description(if quizzes.is_empty() {
+                                "No quiz is going on in this channel.".to_string()
+                            } else {
+                                quizzes.join("\n")
+                            });
+                        let _ = command
+                            .embed(&ctx.http, embed)
                             .await
-                            .flatten();
-
-                        match inspection {
-                            Ok(res) => {
-                                if let InspectionAcceptance::Accepted(_) = res {
-                                    CONTAINER.delete(command.channel_id).await;
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished quizzes command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("submit") => {
+                    println!("cmd: submit");
+                    tokio::task::spawn(async move {
+                        let res = if command.guild_id.is_some() {
+                            Err(anyhow!(
+                                "Send `/submit` to me in a direct message to keep the answer \
+                                 secret."
+                            ))
+                        } else {
+                            match letters(&dictionary) {
+                                Ok(letters) => {
+                                    let input =
+                                        dictionary.get("regex").unwrap().to::<String>().unwrap();
+                                    Submission::new(&input, letters, &mut rand::thread_rng())
+                                }
+                                Err(why) => Err(why),
+                            }
+                        };
+                        let content = match res {
+                            Ok(submission) => {
+                                let letters = submission.letters;
+                                CONTAINER.submit(command.user.id, submission).await;
+                                format!(
+                                    "Your quiz over {letters} letters is accepted. Run `/host` in \
+                                     the channel where it should be played."
+                                )
+                            }
+                            Err(why) => format!("{why}"),
+                        };
+                        let _ = command
+                            .message(&ctx.http, content)
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished submit command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("host") => {
+                    println!("cmd: host");
+                    tokio::task::spawn(async move {
+                        let res = if command.guild_id.is_none() {
+                            Err(anyhow!(
+                                "Run `/host` in the channel where the quiz should be played."
+                            ))
+                        } else {
+                            CONTAINER
+                                .host(command.channel_id, command.user.id)
+                                .await
+                                .map(|(_, embed)| embed)
+                        };
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished host command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("classroom") =>
+                {
+                    println!("cmd: classroom");
+                    tokio::task::spawn(async move {
+                        let res = if command.guild_id.is_none() {
+                            Err(anyhow!(
+                                "Run `/classroom` in the channel where the class plays the quiz."
+                            ))
+                        } else {
+                            match letters(&dictionary) {
+                                Ok(letters) => {
+                                    let input =
+                                        dictionary.get("regex").unwrap().to::<String>().unwrap();
+                                    match Submission::parse(&input, letters) {
+                                        Ok(submission) => {
+                                            CONTAINER
+                                                .start_custom(
+                                                    command.channel_id,
+                                                    Quiz::classroom(submission, command.user.id),
+                                                )
+                                                .await
+                                        }
+                                        Err(why) => Err(why),
+                                    }
                                 }
+                                Err(why) => Err(why),
+                            }
+                        };
+                        match res {
+                            Ok((id, announcement)) => {
+                                // the answer stays with the teacher, and the class sees the announcement
+                                let mut embed = CreateEmbed::default();
+                                embed
+                                    .colour(Colour::DARK_GREEN)
+                                    .title(format!("classroom quiz {id}"))
+                                    .description(
+                                        "The quiz is started. Press the button or run \
+                                         `/dashboard` to follow your students.",
+                                    );
                                 let _ = command
-                                    .message(&ctx.http, res)
+                                    .ephemeral_embed(
+                                        &ctx.http,
+                                        embed,
+                                        vec![refresh_button(command.channel_id, id)],
+                                    )
                                     .await
                                     .with_context(|| anyhow!("ERROR: fail to interaction"))
-                                    .logging_with(|_| "successfully finished guess command.")
+                                    .logging_with(|_| "successfully finished classroom command.")
+                                    .await;
+                                let _ = command
+                                    .channel_embed(&ctx.http, announcement)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to announce the quiz"))
+                                    .logging_with(|_| "successfully announced classroom quiz.")
                                     .await;
                             }
                             Err(why) => {
                                 let _ = command
-                                    .embed(&ctx.http, why.as_embed())
+                                    .ephemeral_embed(&ctx.http, why.as_embed(), vec![])
                                     .await
                                     .with_context(|| anyhow!("ERROR: fail to interaction"))
                                     .logging_with(move |_| format!("{why:#?}"))
@@ -369,40 +3699,82 @@ impl EventHandler for Handler {
                     });
                 }
                 (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
-                    if cmd.eq("summary") =>
+                    if cmd.eq("dashboard") =>
                 {
-                    println!("cmd: summary");
+                    println!("cmd: dashboard");
                     tokio::task::spawn(async move {
-                        let summary = CONTAINER
-                            .checked_command(command.channel_id, command.user.id, |quiz| {
-                                quiz.get_query_history()
+                        let res = CONTAINER
+                            .command(command.channel_id, quiz_id(&dictionary), |quiz| {
+                                quiz.dashboard(command.user.id)
+                                    .map(|embed| (embed, quiz.id()))
                             })
-                            .await;
-                        match summary {
-                            Ok(summary) => {
+                            .await
+                            .flatten();
+                        match res {
+                            Ok((embed, Some(id))) => {
                                 let _ = command
-                                    .embed(&ctx.http, summary)
+                                    .ephemeral_embed(
+                                        &ctx.http,
+                                        embed,
+                                        vec![refresh_button(command.channel_id, id)],
+                                    )
                                     .await
                                     .with_context(|| anyhow!("ERROR: fail to interaction"))
-                                    .logging_with(|_| "successfully finished summary command.")
+                                    .logging_with(|_| "successfully finished dashboard command.")
+                                    .await;
+                            }
+                            Ok((embed, None)) => {
+                                let _ = command
+                                    .ephemeral_embed(&ctx.http, embed, vec![])
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished dashboard command.")
                                     .await;
                             }
                             Err(why) => {
                                 let _ = command
-                                    .message(&ctx.http, format!("{why}"))
+                                    .ephemeral_embed(&ctx.http, why.as_embed(), vec![])
                                     .await
                                     .with_context(|| anyhow!("ERROR: fail to interaction"))
-                                    .logging_with(move |_| format!("{why}"))
+                                    .logging_with(move |_| format!("{why:#?}"))
                                     .await;
                             }
                         }
                     });
                 }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("team") => {
+                    println!("cmd: team");
+                    tokio::task::spawn(async move {
+                        let name = dictionary.get("name").unwrap().to::<String>().unwrap();
+                        let res = CONTAINER
+                            .command(command.channel_id, quiz_id(&dictionary), |quiz| {
+                                quiz.join_team(command.user.id, &name).map(|_| {
+                                    let members = quiz
+                                        .team_of(command.user.id)
+                                        .map(|(_, team)| team.members.len())
+                                        .unwrap_or_default();
+                                    format!(
+                                        "<@{}> joins team **{}** ({members} members).",
+                                        command.user.id.0,
+                                        name.trim()
+                                    )
+                                })
+                            })
+                            .await
+                            .flatten();
+                        let _ = command
+                            .message(&ctx.http, res.unwrap_or_else(|why| format!("{why}")))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished team command.")
+                            .await;
+                    });
+                }
                 (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("join") => {
                     println!("cmd: join");
                     tokio::task::spawn(async move {
                         let res = CONTAINER
-                            .checked_command(command.channel_id, command.user.id, |quiz| {
+                            .command(command.channel_id, quiz_id(&dictionary), |quiz| {
                                 quiz.register(command.user.id)
                             })
                             .await
@@ -434,23 +3806,83 @@ impl EventHandler for Handler {
                 {
                     println!("cmd: give-up");
                     tokio::task::spawn(async move {
+                        let spoilers = spoilers(command.guild_id);
                         let res = CONTAINER
-                            .checked_command(command.channel_id, command.user.id, |quiz| {
-                                quiz.accepts_give_up(&command.user)
-                            })
+                            .checked_command(
+                                command.channel_id,
+                                command.user.id,
+                                quiz_id(&dictionary),
+                                |quiz| {
+                                    quiz.accepts_give_up(&command.user, &spoilers)
+                                        .map(|either| {
+                                            either.map_right(|(content, buttons)| {
+                                                let finished = (
+                                                    quiz.get_answer_regex(),
+                                                    quiz.seed(),
+                                                    quiz.outcome(false),
+                                                    quiz.analysis_embed(&spoilers),
+                                                    quiz.id(),
+                                                    quiz.archived(None),
+                                                );
+                                                (
+                                                    content,
+                                                    buttons,
+                                                    quiz.answer_diagrams(&spoilers),
+                                                    finished,
+                                                )
+                                            })
+                                        })
+                                },
+                            )
                             .await
                             .flatten();
 
                         match res {
                             Ok(either) => match either {
-                                Either::Right((content, buttons)) => {
-                                    CONTAINER.delete(command.channel_id).await;
+                                Either::Right((
+                                    content,
+                                    buttons,
+                                    diagrams,
+                                    (regex, seed, outcome, analysis, id, archived),
+                                )) => {
+                                    if let Some(id) = id {
+                                        CONTAINER.delete(command.channel_id, id).await;
+                                    }
+                                    record_game(
+                                        command.guild_id,
+                                        &regex,
+                                        seed,
+                                        &outcome,
+                                        &archived,
+                                    );
                                     let _ = command
                                         .button(&ctx.http, content, buttons)
                                         .await
                                         .with_context(|| anyhow!("ERROR: fail to interaction"))
                                         .logging_with(|_| "successfully finished give-up command.")
                                         .await;
+                                    let _ = command
+                                        .attachments(&ctx.http, diagrams)
+                                        .await
+                                        .with_context(|| anyhow!("ERROR: fail to send diagrams"))
+                                        .logging_with(|_| "successfully sent answer diagrams.")
+                                        .await;
+                                    let _ = command
+                                        .channel_embed(&ctx.http, analysis)
+                                        .await
+                                        .with_context(|| anyhow!("ERROR: fail to send analysis"))
+                                        .logging_with(|_| "successfully sent analysis.")
+                                        .await;
+                                    if let Some(id) = id {
+                                        next_round(
+                                            &ctx.http,
+                                            command.channel_id,
+                                            command.guild_id,
+                                            id,
+                                            None,
+                                        )
+                                        .await;
+                                    }
                                 }
                                 Either::Left(msg) => {
                                     let _ = command
@@ -472,13 +3904,238 @@ impl EventHandler for Handler {
                         }
                     });
                 }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("giveup") => {
+                    println!("cmd: giveup");
+                    tokio::task::spawn(async move {
+                        let spoilers = spoilers(command.guild_id);
+                        let res = CONTAINER
+                            .command(command.channel_id, quiz_id(&dictionary), |quiz| {
+                                quiz.vote_give_up(command.user.id)
+                                    .map(|status| match status {
+                                        VoteStatus::Pending { votes, needed } => {
+                                            Either::Left(format!(
+                                                "<@{}> votes to give up ({votes}/{needed} votes).",
+                                                command.user.id.0
+                                            ))
+                                        }
+                                        VoteStatus::Passed => {
+                                            let (content, buttons) = quiz.reveal(
+                                                "The players have voted to give up.",
+                                                &spoilers,
+                                            );
+                                            let finished = (
+                                                quiz.get_answer_regex(),
+                                                quiz.seed(),
+                                                quiz.outcome(false),
+                                                quiz.analysis_embed(&spoilers),
+                                                quiz.id(),
+                                                quiz.archived(None),
+                                            );
+                                            Either::Right((
+                                                content,
+                                                buttons,
+                                                quiz.answer_diagrams(&spoilers),
+                                                finished,
+                                            ))
+                                        }
+                                    })
+                            })
+                            .await
+                            .flatten();
+
+                        match res {
+                            Ok(either) => match either {
+                                Either::Right((
+                                    content,
+                                    buttons,
+                                    diagrams,
+                                    (regex, seed, outcome, analysis, id, archived),
+                                )) => {
+                                    if let Some(id) = id {
+                                        CONTAINER.delete(command.channel_id, id).await;
+                                    }
+                                    record_game(
+                                        command.guild_id,
+                                        &regex,
+                                        seed,
+                                        &outcome,
+                                        &archived,
+                                    );
+                                    let _ = command
+                                        .button(&ctx.http, content, buttons)
+                                        .await
+                                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                        .logging_with(|_| "successfully finished giveup command.")
+                                        .await;
+                                    let _ = command
+                                        .attachments(&ctx.http, diagrams)
+                                        .await
+                                        .with_context(|| anyhow!("ERROR: fail to send diagrams"))
+                                        .logging_with(|_| "successfully sent answer diagrams.")
+                                        .await;
+                                    let _ = command
+                                        .channel_embed(&ctx.http, analysis)
+                                        .await
+                                        .with_context(|| anyhow!("ERROR: fail to send analysis"))
+                                        .logging_with(|_| "successfully sent analysis.")
+                                        .await;
+                                    if let Some(id) = id {
+                                        next_round(
+                                            &ctx.http,
+                                            command.channel_id,
+                                            command.guild_id,
+                                            id,
+                                            None,
+                                        )
+                                        .await;
+                                    }
+                                }
+                                Either::Left(msg) => {
+                                    let _ = command
+                                        .message(&ctx.http, &msg)
+                                        .await
+                                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                        .logging_with(|_| "successfully finished giveup command.")
+                                        .await;
+                                }
+                            },
+                            Err(why) => {
+                                let _ = command
+                                    .message(&ctx.http, format!("{why}"))
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("spoilers") =>
+                {
+                    println!("cmd: spoilers");
+                    tokio::task::spawn(async move {
+                        let res = dictionary
+                            .get("output")
+                            .map(|output| output.to::<String>()?.parse::<SpoilerKind>())
+                            .transpose()
+                            .and_then(|output| {
+                                let enabled = dictionary
+                                    .get("enabled")
+                                    .map(|enabled| enabled.to::<bool>())
+                                    .transpose()?;
+                                match (output, enabled) {
+                                    (None, None) => Ok(None),
+                                    (Some(output), Some(enabled)) if is_moderator(&command) => {
+                                        Ok(Some((output, enabled)))
+                                    }
+                                    (Some(_), Some(_)) => Err(anyhow!(
+                                        "Only moderators may change the spoiler settings."
+                                    )),
+                                    _ => Err(anyhow!(
+                                        "Choose both the output and whether to hide it."
+                                    )),
+                                }
+                            })
+                            .and_then(|change| spoilers_embed(command.guild_id, change));
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished spoilers command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd)))
+                    if cmd.eq("schedule") =>
+                {
+                    println!("cmd: schedule");
+                    tokio::task::spawn(async move {
+                        // answered only to the sender, so that curated expressions stay secret
+                        let res = schedule_embed(&ctx.http, &command, &dictionary).await;
+                        let _ = command
+                            .ephemeral_embed(
+                                &ctx.http,
+                                res.unwrap_or_else(|why| why.as_embed()),
+                                vec![],
+                            )
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished schedule command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("config") => {
+                    println!("cmd: config");
+                    tokio::task::spawn(async move {
+                        let res = config_embed(command.guild_id, &dictionary, is_admin(&command));
+                        let _ = command
+                            .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                            .await
+                            .with_context(|| anyhow!("ERROR: fail to interaction"))
+                            .logging_with(|_| "successfully finished config command.")
+                            .await;
+                    });
+                }
+                (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("undo") => {
+                    println!("cmd: undo");
+                    tokio::task::spawn(async move {
+                        let res = if is_moderator(&command) {
+                            CONTAINER
+                                .command(command.channel_id, quiz_id(&dictionary), |quiz| {
+                                    quiz.undo()
+                                })
+                                .await
+                                .flatten()
+                        } else {
+                            Err(anyhow!("Only moderators may undo queries and guesses."))
+                        };
+
+                        match res {
+                            Ok(retracted) => {
+                                let deleted = match retracted.response {
+                                    Some(message) => command
+                                        .channel_id
+                                        .delete_message(&ctx.http, message)
+                                        .await
+                                        .is_ok(),
+                                    None => false,
+                                };
+                                let content = if deleted {
+                                    format!(
+                                        "Retracted {} and deleted its response.",
+                                        retracted.description
+                                    )
+                                } else {
+                                    format!("Retracted {}.", retracted.description)
+                                };
+                                let _ = command
+                                    .message(&ctx.http, content)
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(|_| "successfully finished undo command.")
+                                    .await;
+                            }
+                            Err(why) => {
+                                let _ = command
+                                    .message(&ctx.http, format!("{why}"))
+                                    .await
+                                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                                    .logging_with(move |_| format!("{why}"))
+                                    .await;
+                            }
+                        }
+                    });
+                }
                 (_, Notification::SlashCommand(SlashCommand::Command(cmd))) if cmd.eq("help") => {
-                    let _ = command
-                        .embed(&ctx.http, commands::help())
-                        .await
-                        .with_context(|| anyhow!("ERROR: fail to interaction"))
-                        .logging_with(|_| "successfully finished help command.")
-                        .await;
+                    let [help, more] = commands::help();
+                    let _ = match command.embed(&ctx.http, help).await {
+                        Ok(()) => command.channel_embed(&ctx.http, more).await,
+                        Err(why) => Err(why),
+                    }
+                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                    .logging_with(|_| "successfully finished help command.")
+                    .await;
                 }
                 (_, unknown) => {
                     let _ = CENTRAL
@@ -499,6 +4156,65 @@ impl EventHandler for Handler {
                         .logging_with(|_| "successfully finished feedback.")
                         .await;
                 }
+                CustomId::Dashboard { channel, quiz } => {
+                    let res = CONTAINER
+                        .command(ChannelId(channel), Some(QuizId(quiz)), |quiz| {
+                            quiz.dashboard(component.user.id)
+                        })
+                        .await
+                        .flatten();
+                    let _ = component
+                        .create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(InteractionResponseType::UpdateMessage)
+                                .interaction_response_data(|message| {
+                                    message.add_embed(res.unwrap_or_else(|why| why.as_embed()))
+                                })
+                        })
+                        .await
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "successfully refreshed dashboard.")
+                        .await;
+                }
+                CustomId::Rematch { quiz } => {
+                    println!("rematch of quiz {quiz}");
+                    let res = match CONTAINER.rematch_options(QuizId(quiz)).await {
+                        Ok(options) => {
+                            let options = QuizOptions {
+                                owner: Some(component.user.id),
+                                ..options
+                            };
+                            start_quiz(&ctx.http, component.channel_id, component.guild_id, options)
+                                .await
+                                .map(|(_, embed)| embed)
+                        }
+                        Err(why) => Err(why),
+                    };
+                    let _ = component
+                        .embed(&ctx.http, res.unwrap_or_else(|why| why.as_embed()))
+                        .await
+                        .with_context(|| anyhow!("ERROR: fail to interaction"))
+                        .logging_with(|_| "successfully started a rematch.")
+                        .await;
+                }
+                CustomId::Archive { page } => {
+                    let _ = match archive_page(component.guild_id, page) {
+                        Ok((embed, buttons)) => component.page(&ctx.http, embed, buttons).await,
+                        Err(why) => component.embed(&ctx.http, why.as_embed()).await,
+                    }
+                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                    .logging_with(|_| "successfully turned the archive page.")
+                    .await;
+                }
+                CustomId::Replay { game, page } => {
+                    let _ = match replay_page(component.guild_id, game, page) {
+                        Ok((embed, buttons)) => component.page(&ctx.http, embed, buttons).await,
+                        Err(why) => component.embed(&ctx.http, why.as_embed()).await,
+                    }
+                    .with_context(|| anyhow!("ERROR: fail to interaction"))
+                    .logging_with(|_| "successfully turned the replay page.")
+                    .await;
+                }
             }
         }
     }
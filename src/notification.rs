@@ -18,7 +18,7 @@
  */
 
 use crate::concepts::SameAs;
-use serenity::model::{interactions::application_command, user::User};
+use serenity::model::{channel::PartialChannel, interactions::application_command, user::User};
 
 type OptionValue = serenity::model::interactions::application_command::ApplicationCommandInteractionDataOptionValue;
 
@@ -81,6 +81,20 @@ impl To<i64> for Notification {
     }
 }
 
+impl To<bool> for Notification {
+    fn to<T>(&self) -> anyhow::Result<bool>
+    where
+        T: SameAs<bool>,
+    {
+        if let Notification::SlashCommand(SlashCommand::Option(boxed)) = self {
+            if let OptionValue::Boolean(value) = &**boxed {
+                return Ok(*value);
+            }
+        }
+        Err(anyhow::anyhow!("cannot convert self to bool: {:?}", self))
+    }
+}
+
 impl To<User> for Notification {
     fn to<T>(&self) -> anyhow::Result<User>
     where
@@ -91,6 +105,23 @@ impl To<User> for Notification {
                 return Ok(user.clone());
             }
         }
-        Err(anyhow::anyhow!("cannot convert self to User {:?}", &self))
+        Err(anyhow::anyhow!("cannot convert self to User: {:?}", self))
+    }
+}
+
+impl To<PartialChannel> for Notification {
+    fn to<T>(&self) -> anyhow::Result<PartialChannel>
+    where
+        T: SameAs<PartialChannel>,
+    {
+        if let Notification::SlashCommand(SlashCommand::Option(boxed)) = self {
+            if let OptionValue::Channel(channel) = &**boxed {
+                return Ok(channel.clone());
+            }
+        }
+        Err(anyhow::anyhow!(
+            "cannot convert self to Channel: {:?}",
+            self
+        ))
     }
 }
@@ -105,7 +105,28 @@ impl CommandParser for ApplicationCommandInteractionData {
 
 #[derive(Serialize, Deserialize)]
 pub enum CustomId {
-    Feedback { label: String, regex: String },
+    Feedback {
+        label: String,
+        regex: String,
+    },
+    /// Refresh the dashboard of the classroom quiz `quiz` in `channel`
+    Dashboard {
+        channel: u64,
+        quiz: u64,
+    },
+    /// Start a fresh quiz with the settings of the ended quiz `quiz`
+    Rematch {
+        quiz: u64,
+    },
+    /// Turn `/archive` to page `page` (from 0)
+    Archive {
+        page: usize,
+    },
+    /// Turn `/replay` of the archived game `game` to page `page` (from 0)
+    Replay {
+        game: i64,
+        page: usize,
+    },
 }
 
 impl std::fmt::Display for CustomId {
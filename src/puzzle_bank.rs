@@ -0,0 +1,488 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    database::{from_sql_id, to_sql_id},
+    regex::{estimate_difficulty, DifficultyTier, RegexAst},
+};
+use anyhow::Context;
+use indoc::indoc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serenity::model::id::GuildId;
+use std::time::Duration;
+
+// constants mapping observed outcomes of plays to difficulty scores
+// (see [crate::regex::DifficultyEstimate])
+const QUERY_SCORE_SCALE: f64 = 2.5;
+const MINUTE_SCORE_SCALE: f64 = 1.0;
+const GIVE_UP_SCORE: f64 = 6.0;
+/// Number of plays the estimated difficulty counts as when calibrating
+const ESTIMATE_WEIGHT_IN_PLAYS: f64 = 5.0;
+
+/// A puzzle stored in the bank.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub id: i64,
+    pub regex: RegexAst,
+    /// Seed the puzzle was generated from, if it was generated
+    pub seed: Option<u64>,
+    /// Score of [crate::regex::DifficultyEstimate] at the time of insertion
+    pub estimated_difficulty: f64,
+    /// Difficulty score calibrated by the outcomes of past plays
+    pub difficulty: f64,
+    pub tier: DifficultyTier,
+    pub tags: Vec<String>,
+    pub times_played: u32,
+    pub times_solved: u32,
+}
+
+/// How a play of a puzzle ended.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayOutcome {
+    /// Number of distinct words queried
+    pub queries: u32,
+    /// Time from the start of the quiz to the correct guess or the last give-up
+    pub duration: Duration,
+    /// Whether the puzzle was solved, as opposed to given up
+    pub solved: bool,
+}
+
+/// Difficulty score suggested by the outcomes of plays, on the same scale as the estimates.
+///
+/// Many queries, long plays and give-ups make a puzzle harder.
+pub fn observed_difficulty(outcomes: &[PlayOutcome]) -> Option<f64> {
+    if outcomes.is_empty() {
+        return None;
+    }
+
+    let count = outcomes.len() as f64;
+    let mean = |f: &dyn Fn(&PlayOutcome) -> f64| outcomes.iter().map(f).sum::<f64>() / count;
+
+    Some(
+        QUERY_SCORE_SCALE * mean(&|outcome| (1.0 + outcome.queries as f64).ln())
+            + MINUTE_SCORE_SCALE
+                * mean(&|outcome| (1.0 + outcome.duration.as_secs_f64() / 60.0).ln())
+            + GIVE_UP_SCORE * mean(&|outcome| if outcome.solved { 0.0 } else { 1.0 }),
+    )
+}
+
+/// Blend an estimated difficulty score with the outcomes of plays,
+/// trusting the outcomes more as more of them are observed.
+pub fn calibrated_difficulty(estimate: f64, outcomes: &[PlayOutcome]) -> f64 {
+    match observed_difficulty(outcomes) {
+        Some(observed) => {
+            let plays = outcomes.len() as f64;
+            (ESTIMATE_WEIGHT_IN_PLAYS * estimate + plays * observed)
+                / (ESTIMATE_WEIGHT_IN_PLAYS + plays)
+        }
+        None => estimate,
+    }
+}
+
+/// Conditions on puzzles returned by [PuzzleBank::find].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PuzzleFilter {
+    pub tier: Option<DifficultyTier>,
+    pub tag: Option<String>,
+    /// Exclude puzzles already played in this guild
+    pub unplayed_in: Option<GuildId>,
+}
+
+/// Puzzles stored in an SQLite database.
+///
+/// Each language is stored at most once, keyed by its canonical minimal DFA,
+/// so equivalent spellings of a puzzle are never stored twice.
+pub struct PuzzleBank<'a> {
+    connection: &'a Connection,
+}
+
+/// Key identifying the language of `regex`: the serialized minimal DFA over the used alphabets,
+/// which is equal for two regexes if and only if they are equivalent.
+fn language_key(regex: &RegexAst) -> String {
+    regex.to_min_dfa(&regex.used_alphabets()).to_json()
+}
+
+impl<'a> PuzzleBank<'a> {
+    /// Create the tables of the puzzle bank in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS puzzles (
+                    id INTEGER PRIMARY KEY,
+                    regex TEXT NOT NULL,
+                    language TEXT NOT NULL UNIQUE,
+                    seed INTEGER,
+                    estimated_difficulty REAL NOT NULL,
+                    difficulty REAL NOT NULL,
+                    tier TEXT NOT NULL,
+                    times_played INTEGER NOT NULL DEFAULT 0,
+                    times_solved INTEGER NOT NULL DEFAULT 0
+                );
+                CREATE TABLE IF NOT EXISTS puzzle_tags (
+                    puzzle_id INTEGER NOT NULL REFERENCES puzzles (id),
+                    tag TEXT NOT NULL,
+                    PRIMARY KEY (puzzle_id, tag)
+                );
+                CREATE TABLE IF NOT EXISTS plays (
+                    puzzle_id INTEGER NOT NULL REFERENCES puzzles (id),
+                    guild_id INTEGER NOT NULL,
+                    queries INTEGER NOT NULL,
+                    duration_secs REAL NOT NULL,
+                    solved INTEGER NOT NULL
+                );
+            "})
+            .context("failed to initialize the puzzle bank")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        PuzzleBank { connection }
+    }
+
+    /// Store `regex` with its seed and tags.
+    ///
+    /// Returns the id of the new puzzle,
+    /// or `None` if an equivalent puzzle is already stored.
+    pub fn insert(
+        &self,
+        regex: &RegexAst,
+        seed: Option<u64>,
+        tags: &[&str],
+    ) -> anyhow::Result<Option<i64>> {
+        let difficulty = estimate_difficulty(regex);
+
+        let inserted = self
+            .connection
+            .execute(
+                indoc! {"
+                    INSERT INTO puzzles (regex, language, seed, estimated_difficulty, difficulty, tier)
+                    VALUES (?1, ?2, ?3, ?4, ?4, ?5)
+                    ON CONFLICT (language) DO NOTHING
+                "},
+                params![
+                    format!("{}", regex),
+                    language_key(regex),
+                    seed.map(to_sql_id),
+                    difficulty.score,
+                    format!("{}", difficulty.tier),
+                ],
+            )
+            .context("failed to insert a puzzle")?;
+        if inserted == 0 {
+            return Ok(None);
+        }
+
+        let id = self.connection.last_insert_rowid();
+        for tag in tags {
+            self.connection
+                .execute(
+                    "INSERT OR IGNORE INTO puzzle_tags (puzzle_id, tag) VALUES (?1, ?2)",
+                    params![id, tag],
+                )
+                .context("failed to tag a puzzle")?;
+        }
+
+        Ok(Some(id))
+    }
+
+    /// The id of the stored puzzle equivalent to `regex`, if any.
+    pub fn find_equivalent(&self, regex: &RegexAst) -> anyhow::Result<Option<i64>> {
+        self.connection
+            .query_row(
+                "SELECT id FROM puzzles WHERE language = ?1",
+                params![language_key(regex)],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("failed to look up a puzzle")
+    }
+
+    fn tags_of(&self, id: i64) -> anyhow::Result<Vec<String>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT tag FROM puzzle_tags WHERE puzzle_id = ?1 ORDER BY tag")?;
+        let tags = statement
+            .query_map(params![id], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(tags)
+    }
+
+    /// Stored puzzles satisfying `filter`, in the order of insertion.
+    pub fn find(&self, filter: &PuzzleFilter) -> anyhow::Result<Vec<Puzzle>> {
+        let mut statement = self.connection.prepare(indoc! {"
+            SELECT id, regex, seed, estimated_difficulty, difficulty, times_played, times_solved
+            FROM puzzles
+            WHERE (?1 IS NULL OR tier = ?1)
+              AND (?2 IS NULL OR id IN (SELECT puzzle_id FROM puzzle_tags WHERE tag = ?2))
+              AND (?3 IS NULL OR id NOT IN (SELECT puzzle_id FROM plays WHERE guild_id = ?3))
+            ORDER BY id
+        "})?;
+
+        let rows = statement
+            .query_map(
+                params![
+                    filter.tier.map(|tier| format!("{}", tier)),
+                    filter.tag,
+                    filter.unplayed_in.map(|guild| to_sql_id(guild.0)),
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<i64>>(2)?,
+                        row.get::<_, f64>(3)?,
+                        row.get::<_, f64>(4)?,
+                        row.get::<_, u32>(5)?,
+                        row.get::<_, u32>(6)?,
+                    ))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query puzzles")?;
+
+        rows.into_iter()
+            .map(
+                |(
+                    id,
+                    regex,
+                    seed,
+                    estimated_difficulty,
+                    difficulty,
+                    times_played,
+                    times_solved,
+                )| {
+                    Ok(Puzzle {
+                        id,
+                        regex: RegexAst::parse_str(&regex)
+                            .with_context(|| format!("corrupted puzzle {}", id))?,
+                        seed: seed.map(from_sql_id),
+                        estimated_difficulty,
+                        difficulty,
+                        tier: DifficultyTier::of_score(difficulty),
+                        tags: self.tags_of(id)?,
+                        times_played,
+                        times_solved,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Record that the puzzle `id` was played in `guild`,
+    /// and recalibrate its difficulty with the outcomes of all of its plays.
+    pub fn record_play(
+        &self,
+        id: i64,
+        guild: GuildId,
+        outcome: &PlayOutcome,
+    ) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO plays (puzzle_id, guild_id, queries, duration_secs, solved)
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+                "},
+                params![
+                    id,
+                    to_sql_id(guild.0),
+                    outcome.queries,
+                    outcome.duration.as_secs_f64(),
+                    outcome.solved
+                ],
+            )
+            .and_then(|_| {
+                self.connection.execute(
+                    indoc! {"
+                        UPDATE puzzles
+                        SET times_played = times_played + 1,
+                            times_solved = times_solved + ?2
+                        WHERE id = ?1
+                    "},
+                    params![id, outcome.solved as u32],
+                )
+            })
+            .context("failed to record a play")?;
+
+        self.recalibrate(id)
+    }
+
+    /// Record a play of `regex` in `guild` against the stored puzzle of the same language,
+    /// storing `regex` first if there is none. Returns the id of the puzzle.
+    pub fn record_outcome(
+        &self,
+        regex: &RegexAst,
+        seed: Option<u64>,
+        guild: GuildId,
+        outcome: &PlayOutcome,
+    ) -> anyhow::Result<i64> {
+        let id = match self.find_equivalent(regex)? {
+            Some(id) => id,
+            None => self
+                .insert(regex, seed, &[])?
+                .context("failed to store a puzzle")?,
+        };
+
+        self.record_play(id, guild, outcome)?;
+        Ok(id)
+    }
+
+    fn recalibrate(&self, id: i64) -> anyhow::Result<()> {
+        let estimate: f64 = self
+            .connection
+            .query_row(
+                "SELECT estimated_difficulty FROM puzzles WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .context("failed to look up a puzzle")?;
+
+        let mut statement = self
+            .connection
+            .prepare("SELECT queries, duration_secs, solved FROM plays WHERE puzzle_id = ?1")?;
+        let outcomes = statement
+            .query_map(params![id], |row| {
+                Ok(PlayOutcome {
+                    queries: row.get(0)?,
+                    duration: Duration::from_secs_f64(row.get(1)?),
+                    solved: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query plays")?;
+
+        let difficulty = calibrated_difficulty(estimate, &outcomes);
+        self.connection
+            .execute(
+                "UPDATE puzzles SET difficulty = ?2, tier = ?3 WHERE id = ?1",
+                params![
+                    id,
+                    difficulty,
+                    format!("{}", DifficultyTier::of_score(difficulty))
+                ],
+            )
+            .map(|_| ())
+            .context("failed to calibrate a puzzle")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calibrated_difficulty, PlayOutcome, PuzzleFilter};
+    use crate::{database::Database, regex::RegexAst};
+    use serenity::model::id::GuildId;
+    use std::time::Duration;
+
+    fn outcome(queries: u32, minutes: u64, solved: bool) -> PlayOutcome {
+        PlayOutcome {
+            queries,
+            duration: Duration::from_secs(minutes * 60),
+            solved,
+        }
+    }
+
+    #[test]
+    fn puzzle_bank_dedups_and_filters() {
+        let database = Database::open_in_memory().unwrap();
+        let bank = database.puzzle_bank();
+        let regex = |s: &str| RegexAst::parse_str(s).unwrap();
+
+        let first = bank
+            .insert(&regex("(a|b)*c"), Some(42), &["night"])
+            .unwrap()
+            .unwrap();
+        let second = bank.insert(&regex("a*b"), None, &[]).unwrap().unwrap();
+        assert_eq!(
+            bank.insert(&regex("(a*b*)*c"), None, &["night"]).unwrap(),
+            None
+        );
+        assert_eq!(
+            bank.find_equivalent(&regex("(b|a)*c")).unwrap(),
+            Some(first)
+        );
+
+        let all = bank.find(&PuzzleFilter::default()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].seed, Some(42));
+        assert_eq!(all[0].tags, vec!["night".to_string()]);
+
+        let tagged = bank
+            .find(&PuzzleFilter {
+                tag: Some("night".to_string()),
+                ..PuzzleFilter::default()
+            })
+            .unwrap();
+        assert_eq!(tagged.iter().map(|p| p.id).collect::<Vec<_>>(), vec![first]);
+
+        bank.record_play(first, GuildId(1), &outcome(5, 3, true))
+            .unwrap();
+        let unplayed = bank
+            .find(&PuzzleFilter {
+                unplayed_in: Some(GuildId(1)),
+                ..PuzzleFilter::default()
+            })
+            .unwrap();
+        assert_eq!(
+            unplayed.iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![second]
+        );
+
+        let played = &bank.find(&PuzzleFilter::default()).unwrap()[0];
+        assert_eq!((played.times_played, played.times_solved), (1, 1));
+    }
+
+    #[test]
+    fn calibration_follows_outcomes() {
+        let estimate = 8.0;
+        let struggles = vec![outcome(40, 30, false); 10];
+        let quick_wins = vec![outcome(2, 1, true); 10];
+
+        assert_eq!(calibrated_difficulty(estimate, &[]), estimate);
+        assert!(calibrated_difficulty(estimate, &struggles) > estimate);
+        assert!(calibrated_difficulty(estimate, &quick_wins) < estimate);
+        // more observations move the score further
+        assert!(
+            calibrated_difficulty(estimate, &struggles)
+                > calibrated_difficulty(estimate, &struggles[..2])
+        );
+    }
+
+    #[test]
+    fn puzzle_bank_recalibrates_equivalent_languages() {
+        let database = Database::open_in_memory().unwrap();
+        let bank = database.puzzle_bank();
+        let regex = |s: &str| RegexAst::parse_str(s).unwrap();
+
+        let id = bank.insert(&regex("(a|b)*c"), None, &[]).unwrap().unwrap();
+        for _ in 0..5 {
+            let recorded = bank
+                .record_outcome(
+                    &regex("(a*b*)*c"),
+                    None,
+                    GuildId(1),
+                    &outcome(40, 30, false),
+                )
+                .unwrap();
+            assert_eq!(recorded, id);
+        }
+
+        let puzzle = &bank.find(&PuzzleFilter::default()).unwrap()[0];
+        assert!(puzzle.difficulty > puzzle.estimated_difficulty);
+        assert_eq!((puzzle.times_played, puzzle.times_solved), (5, 0));
+    }
+}
@@ -0,0 +1,40 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+pub mod analysis;
+pub mod classroom;
+pub mod explain;
+pub mod golf;
+pub mod guess_limits;
+pub mod handicap;
+pub mod hidden;
+pub mod hints;
+pub mod history;
+pub mod multi_round;
+pub mod race;
+pub mod rating;
+pub mod saved;
+pub mod scoring;
+pub mod seed_code;
+pub mod spoilers;
+pub mod submission;
+pub mod team;
+pub mod time_limit;
+pub mod tournament;
+pub mod vote;
@@ -0,0 +1,210 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    quiz::hidden::FinalGuess,
+    regex::{Alphabet, Dfa, DiffExample, RegexAst},
+};
+use itertools::Itertools;
+use std::collections::HashSet;
+
+/// Upper bound of AST size searched when simplifying the answer
+const SIMPLIFY_SEARCH_SIZE: usize = 6;
+/// Number of shortest accepted words listed
+const SHORTEST_WORD_COUNT: usize = 5;
+/// Number of words listed on which a final guess and the answer disagree
+const DIFF_EXAMPLE_COUNT: usize = 3;
+
+/// What a player did during a quiz.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerRecord {
+    pub name: String,
+    pub queries: usize,
+    /// The last guess that was checked against the answer
+    pub final_guess: Option<FinalGuess>,
+}
+
+/// How a player's final guess compares to the answer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerAnalysis {
+    pub name: String,
+    pub queries: usize,
+    pub final_guess: Option<FinalGuess>,
+    /// Up to [DIFF_EXAMPLE_COUNT] words on which the answer (left-hand side) and the final guess disagree,
+    /// which is empty if the guess is correct
+    pub differences: Vec<DiffExample>,
+}
+
+/// Report on a finished quiz.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    /// Number of states of the minimal DFA of the answer over the domain
+    pub min_dfa_states: usize,
+    pub simplified: RegexAst,
+    pub shortest_words: Vec<Vec<Alphabet>>,
+    pub players: Vec<PlayerAnalysis>,
+}
+
+fn format_word(word: &[Alphabet]) -> String {
+    match word {
+        [] => "`ε`".to_string(),
+        word => format!("`{}`", Alphabet::slice_to_plain_string(word)),
+    }
+}
+
+impl Analysis {
+    pub fn new(answer: &RegexAst, domain: &[Alphabet], players: &[PlayerRecord]) -> Self {
+        let domain_set = domain.iter().cloned().collect::<HashSet<_>>();
+        let answer_dfa = answer.to_min_dfa(&domain_set);
+
+        Self {
+            min_dfa_states: answer_dfa.state_count(),
+            simplified: answer.minimize_syntax(SIMPLIFY_SEARCH_SIZE),
+            shortest_words: answer.words().take(SHORTEST_WORD_COUNT).collect(),
+            players: players
+                .iter()
+                .map(|player| PlayerAnalysis {
+                    name: player.name.clone(),
+                    queries: player.queries,
+                    final_guess: player.final_guess.clone(),
+                    differences: match &player.final_guess {
+                        None => vec![],
+                        Some(FinalGuess::Regex(guess)) => {
+                            answer.diff_examples(guess, DIFF_EXAMPLE_COUNT)
+                        }
+                        Some(FinalGuess::Table(table)) => Dfa::parse_table(table, domain)
+                            .map(|guess| answer_dfa.diff_examples(&guess, DIFF_EXAMPLE_COUNT))
+                            .unwrap_or_default(),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    /// The shortest accepted words as a line of text.
+    pub fn shortest_words_line(&self) -> String {
+        self.shortest_words
+            .iter()
+            .map(|word| format_word(word))
+            .join(", ")
+    }
+}
+
+impl PlayerAnalysis {
+    /// A summary of the player's queries and final guess as a line of text.
+    pub fn summary_line(&self) -> String {
+        let guess = match &self.final_guess {
+            None => "no guess".to_string(),
+            Some(guess) if self.differences.is_empty() => format!("guessed `{}` (correct)", guess),
+            Some(guess) => format!(
+                "guessed `{}`, which differs on {}",
+                guess,
+                self.differences
+                    .iter()
+                    .map(|example| match example {
+                        DiffExample::OnlyLeft(word) => format!("{} (missed)", format_word(word)),
+                        DiffExample::OnlyRight(word) => format!("{} (extra)", format_word(word)),
+                    })
+                    .join(", ")
+            ),
+        };
+        format!("{} queries, {}", self.queries, guess)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Analysis, PlayerRecord};
+    use crate::{
+        quiz::hidden::FinalGuess,
+        regex::{Alphabet, DiffExample, RegexAst},
+    };
+
+    #[test]
+    fn analysis_of_finished_quiz() {
+        let word = |w: &str| Alphabet::vec_from_str(w).unwrap();
+        let regex = |s: &str| FinalGuess::Regex(RegexAst::parse_str(s).unwrap());
+
+        let players = vec![
+            PlayerRecord {
+                name: "alice".to_string(),
+                queries: 4,
+                final_guess: Some(regex("(a*b*)*a")),
+            },
+            PlayerRecord {
+                name: "bob".to_string(),
+                queries: 2,
+                final_guess: Some(regex("a*")),
+            },
+            PlayerRecord {
+                name: "carol".to_string(),
+                queries: 0,
+                final_guess: None,
+            },
+            PlayerRecord {
+                name: "dave".to_string(),
+                queries: 1,
+                // words ending with `b`
+                final_guess: Some(FinalGuess::Table("0 0 1 0; 1* 0 1 0".to_string())),
+            },
+        ];
+        let analysis = Analysis::new(
+            &RegexAst::parse_str("(a|b)*a").unwrap(),
+            &word("abc"),
+            &players,
+        );
+
+        // states after a word ending with `a`, with `b` or at the start, and the dead state
+        assert_eq!(analysis.min_dfa_states, 3);
+        assert_eq!(
+            analysis.shortest_words,
+            ["a", "aa", "ba", "aaa", "aba"]
+                .iter()
+                .map(|w| word(w))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            analysis.shortest_words_line(),
+            "`a`, `aa`, `ba`, `aaa`, `aba`"
+        );
+
+        assert!(analysis.players[0].differences.is_empty());
+        assert_eq!(
+            analysis.players[1].differences,
+            vec![
+                DiffExample::OnlyRight(word("")),
+                DiffExample::OnlyLeft(word("ba")),
+                DiffExample::OnlyLeft(word("aba")),
+            ]
+        );
+        assert_eq!(
+            analysis.players[1].summary_line(),
+            "2 queries, guessed `a*`, which differs on `ε` (extra), `ba` (missed), `aba` (missed)"
+        );
+        assert_eq!(analysis.players[2].summary_line(), "0 queries, no guess");
+        assert_eq!(
+            analysis.players[3].differences,
+            vec![
+                DiffExample::OnlyLeft(word("a")),
+                DiffExample::OnlyRight(word("b")),
+                DiffExample::OnlyLeft(word("aa")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+use crate::quiz::history::show_word;
+use serenity::model::id::UserId;
+
+/// Number of the latest words listed for each student on the dashboard
+pub const DASHBOARD_WORDS: usize = 8;
+/// Longest description Discord accepts in an embed
+const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+
+/// What a student of a classroom quiz has done so far, as the teacher sees it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    pub student: UserId,
+    /// Words the student has queried in order, with whether each is accepted
+    pub queries: Vec<(String, bool)>,
+    pub guesses: usize,
+    /// The last guess checked against the answer, if it is an expression
+    pub last_guess: Option<String>,
+}
+
+impl Progress {
+    /// A line of the dashboard listing the latest `DASHBOARD_WORDS` queries of the student.
+    pub fn line(&self) -> String {
+        let skipped = self.queries.len().saturating_sub(DASHBOARD_WORDS);
+        let mut words = self.queries[skipped..]
+            .iter()
+            .map(|(word, accepted)| {
+                format!(
+                    "`{}` {}",
+                    show_word(word),
+                    if *accepted { "✅" } else { "❌" }
+                )
+            })
+            .collect::<Vec<_>>();
+        if skipped > 0 {
+            words.insert(0, format!("({skipped} earlier)"));
+        }
+        let last_guess = self
+            .last_guess
+            .as_ref()
+            .map_or_else(String::new, |guess| format!(", last `{guess}`"));
+        format!(
+            "<@{}>: {} queries {} / {} guesses{last_guess}",
+            self.student.0,
+            self.queries.len(),
+            words.join(" "),
+            self.guesses,
+        )
+    }
+}
+
+/// The dashboard of a classroom quiz, a line per student, cut to fit in an embed.
+pub fn dashboard(progress: &[Progress]) -> String {
+    if progress.is_empty() {
+        return "No student has joined yet.".to_string();
+    }
+    let mut description = String::new();
+    for (i, line) in progress.iter().map(Progress::line).enumerate() {
+        let rest = format!("… and {} more students", progress.len() - i);
+        if description.len() + line.len() + rest.len() + 2 > EMBED_DESCRIPTION_LIMIT {
+            description.push_str(&rest);
+            break;
+        }
+        description.push_str(&line);
+        description.push('\n');
+    }
+    description.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dashboard, Progress, DASHBOARD_WORDS};
+    use serenity::model::id::UserId;
+
+    #[test]
+    fn dashboard_lists_progress_of_each_student() {
+        let progress = Progress {
+            student: UserId(1),
+            queries: vec![("ab".to_string(), true), (r#""""#.to_string(), false)],
+            guesses: 1,
+            last_guess: Some("a*b".to_string()),
+        };
+        assert_eq!(
+            progress.line(),
+            "<@1>: 2 queries `ab` ✅ `ε` ❌ / 1 guesses, last `a*b`"
+        );
+
+        let busy = Progress {
+            student: UserId(2),
+            queries: vec![("a".to_string(), true); DASHBOARD_WORDS + 2],
+            guesses: 0,
+            last_guess: None,
+        };
+        assert!(busy
+            .line()
+            .starts_with("<@2>: 10 queries (2 earlier) `a` ✅"));
+
+        assert_eq!(dashboard(&[]), "No student has joined yet.");
+        let line = progress.line();
+        assert_eq!(dashboard(&[progress]), line);
+        let crowd = vec![busy; 200];
+        let description = dashboard(&crowd);
+        assert!(description.len() <= 4096);
+        assert!(description.ends_with("more students"));
+    }
+}
@@ -0,0 +1,63 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::regex::{Alphabet, RegexAst};
+use anyhow::anyhow;
+
+/// Explain step by step how `answer` matches `input`, which is written as in queries
+/// (`""` being the empty word), as a message in Markdown.
+pub fn explain(answer: &RegexAst, input: &str) -> anyhow::Result<String> {
+    let word = if input.eq(r#""""#) {
+        vec![]
+    } else {
+        Alphabet::vec_from_str(input)?
+    };
+
+    let trace = answer
+        .match_trace(&word)
+        .ok_or_else(|| anyhow!("`{}` is not matched by the answer `{}`.", input, answer))?;
+    Ok(format!("```\n{}```", trace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::explain;
+    use crate::regex::RegexAst;
+    use indoc::indoc;
+
+    #[test]
+    fn explain_shows_match_trace() {
+        let answer = RegexAst::parse_str("a(b|c)*").unwrap();
+        assert_eq!(
+            explain(&answer, "acb").unwrap(),
+            indoc! {"
+                ```
+                a(b|c)* matches acb
+                  a matches a
+                  (b|c)* matches cb
+                    iteration 1: b|c matches c
+                      branch 2: c matches c
+                    iteration 2: b|c matches b
+                      branch 1: b matches b
+                ```"}
+        );
+        assert!(explain(&answer, "ba").is_err());
+        assert!(explain(&RegexAst::parse_str("a*").unwrap(), r#""""#).is_ok());
+    }
+}
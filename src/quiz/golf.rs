@@ -0,0 +1,240 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::regex::{golf_score, randomly_generate, Alphabet, Difficulty, RegexAst};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::num::NonZeroU8;
+use strum::IntoEnumIterator;
+
+/// Number of positive words, and of negative words, posted in a round
+const GOLF_WORD_COUNT: usize = 6;
+/// Number of random words tried when near misses do not yield enough negative words
+const NEGATIVE_SAMPLE_ATTEMPTS: usize = 200;
+
+fn format_word(word: &[Alphabet]) -> String {
+    match word {
+        [] => "`ε`".to_string(),
+        w => format!("`{}`", Alphabet::slice_to_plain_string(w)),
+    }
+}
+
+/// The best entry of a player in a round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GolfEntry {
+    pub regex: RegexAst,
+    pub score: usize,
+    /// Number of valid submissions in the round before this one, breaking ties in the leaderboard
+    order: usize,
+}
+
+/// Result of a valid submission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GolfSubmission {
+    pub score: usize,
+    /// Whether the submission improved the best score of the player
+    pub improved: bool,
+}
+
+/// A round of regex golf: players look for the shortest expression
+/// matching all of `positives` and none of `negatives`.
+#[derive(Clone, Debug)]
+pub struct GolfRound {
+    seed: u64,
+    /// Expression the words were drawn from, revealed when the round ends
+    reference: RegexAst,
+    pub positives: Vec<Vec<Alphabet>>,
+    pub negatives: Vec<Vec<Alphabet>>,
+    /// Best entry of each player, keyed by the mention of the player
+    entries: IndexMap<String, GolfEntry>,
+    submissions: usize,
+}
+
+impl GolfRound {
+    /// Draw the words of a round reproducibly from an expression over `size` alphabets
+    /// generated like the answer of a quiz.
+    ///
+    /// Negative words are preferably near misses, i.e. rejected words one edit away from a positive one.
+    pub fn new(size: NonZeroU8, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let reference = randomly_generate(&Difficulty(size), &mut rng);
+        let domain = Alphabet::iter().take(size.get().into()).collect_vec();
+        let compiled = reference.compile();
+
+        let mut positives = reference.sample_matches(GOLF_WORD_COUNT, &mut rng);
+        positives.sort_by_key(|word| (word.len(), word.clone()));
+
+        let mut near_misses = positives
+            .iter()
+            .flat_map(|word| {
+                let deletions = (0..word.len()).map(move |i| {
+                    let mut w = word.clone();
+                    w.remove(i);
+                    w
+                });
+                let edits =
+                    (0..=word.len())
+                        .cartesian_product(domain.iter())
+                        .flat_map(move |(i, a)| {
+                            let mut inserted = word.clone();
+                            inserted.insert(i, *a);
+                            let substituted = (i < word.len()).then(|| {
+                                let mut w = word.clone();
+                                w[i] = *a;
+                                w
+                            });
+                            std::iter::once(inserted).chain(substituted)
+                        });
+                deletions.chain(edits).collect_vec()
+            })
+            .filter(|word| !compiled.matches(word))
+            .unique()
+            .collect_vec();
+        near_misses.shuffle(&mut rng);
+
+        let max_len = positives.iter().map(Vec::len).max().unwrap_or_default() + 1;
+        let random_words = (0..NEGATIVE_SAMPLE_ATTEMPTS)
+            .map(|_| {
+                let len = rng.gen_range(0..=max_len);
+                (0..len)
+                    .map(|_| domain[rng.gen_range(0..domain.len())])
+                    .collect_vec()
+            })
+            .filter(|word| !compiled.matches(word))
+            .collect_vec();
+
+        let mut negatives = near_misses
+            .into_iter()
+            .chain(random_words)
+            .unique()
+            .take(GOLF_WORD_COUNT)
+            .collect_vec();
+        negatives.sort_by_key(|word| (word.len(), word.clone()));
+
+        GolfRound {
+            seed,
+            reference,
+            positives,
+            negatives,
+            entries: IndexMap::new(),
+            submissions: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn reference(&self) -> &RegexAst {
+        &self.reference
+    }
+
+    /// Words to match and not to match, as a line each in Markdown.
+    pub fn words_lines(&self) -> (String, String) {
+        let line = |words: &[Vec<Alphabet>]| words.iter().map(|w| format_word(w)).join(", ");
+        (line(&self.positives), line(&self.negatives))
+    }
+
+    /// Score `input` submitted by `player`, keeping it if it is the best entry of the player so far.
+    pub fn submit(&mut self, player: &str, input: &str) -> anyhow::Result<GolfSubmission> {
+        let regex = RegexAst::parse_str(input)?;
+        let score = golf_score(&regex, &self.positives, &self.negatives)?;
+
+        let order = self.submissions;
+        self.submissions += 1;
+        let improved = self
+            .entries
+            .get(player)
+            .is_none_or(|best| score < best.score);
+        if improved {
+            self.entries.insert(
+                player.to_string(),
+                GolfEntry {
+                    regex,
+                    score,
+                    order,
+                },
+            );
+        }
+        Ok(GolfSubmission { score, improved })
+    }
+
+    /// Players with their best entries, from the best score.
+    /// Ties are broken in favor of the earlier submission.
+    pub fn leaderboard(&self) -> Vec<(&str, &GolfEntry)> {
+        self.entries
+            .iter()
+            .map(|(player, entry)| (player.as_str(), entry))
+            .sorted_by_key(|(_, entry)| (entry.score, entry.order))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GolfRound, GolfSubmission};
+    use std::convert::TryInto;
+
+    #[test]
+    fn golf_round_words_and_leaderboard() {
+        let round = GolfRound::new(3.try_into().unwrap(), 42);
+        let compiled = round.reference().compile();
+        assert!(!round.positives.is_empty());
+        assert!(round.positives.iter().all(|w| compiled.matches(w)));
+        assert!(round.negatives.iter().all(|w| !compiled.matches(w)));
+        assert_eq!(
+            GolfRound::new(3.try_into().unwrap(), 42).words_lines(),
+            round.words_lines()
+        );
+
+        let mut round = round;
+        let reference = format!("{}", round.reference());
+        let par = reference.chars().count();
+        assert_eq!(
+            round.submit("alice", &reference).unwrap(),
+            GolfSubmission {
+                score: par,
+                improved: true
+            }
+        );
+        assert_eq!(
+            round.submit("bob", &reference).unwrap(),
+            GolfSubmission {
+                score: par,
+                improved: true
+            }
+        );
+        assert_eq!(
+            round.submit("alice", &reference).unwrap(),
+            GolfSubmission {
+                score: par,
+                improved: false
+            }
+        );
+        assert!(round.submit("bob", "(").is_err());
+
+        let leaderboard = round
+            .leaderboard()
+            .into_iter()
+            .map(|(player, entry)| (player, entry.score))
+            .collect::<Vec<_>>();
+        assert_eq!(leaderboard, vec![("alice", par), ("bob", par)]);
+    }
+}
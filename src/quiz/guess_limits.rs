@@ -0,0 +1,112 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::regex::RegexAst;
+use std::fmt::{self, Display, Formatter};
+use thiserror::Error;
+
+/// Error for a guess exceeding the limits of a quiz.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum GuessLimitError {
+    #[error("Your guess has {0} nodes, but guesses may have at most {1} in this quiz.")]
+    TooLarge(usize, usize),
+    #[error("Your guess has {0} stars, but guesses may have at most {1} in this quiz.")]
+    TooManyStars(usize, usize),
+}
+
+/// Limits on the size of guessed expressions, so that a quiz cannot be won
+/// by guessing an alternation of every accepted word padded with starred catch-alls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GuessLimits {
+    /// Most nodes a guessed expression may have
+    pub max_size: Option<usize>,
+    /// Most `Star` nodes a guessed expression may have
+    pub max_stars: Option<usize>,
+}
+
+impl GuessLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_size.is_none() && self.max_stars.is_none()
+    }
+
+    /// Check that `guess` stays within the limits.
+    pub fn check(&self, guess: &RegexAst) -> Result<(), GuessLimitError> {
+        let stats = guess.stats();
+        if let Some(max) = self.max_size.filter(|&max| stats.node_count > max) {
+            return Err(GuessLimitError::TooLarge(stats.node_count, max));
+        }
+        if let Some(max) = self.max_stars.filter(|&max| stats.star_count > max) {
+            return Err(GuessLimitError::TooManyStars(stats.star_count, max));
+        }
+        Ok(())
+    }
+}
+
+impl Display for GuessLimits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let limits = [
+            self.max_size.map(|max| format!("{max} nodes")),
+            self.max_stars.map(|max| format!("{max} stars")),
+        ];
+        match limits.iter().flatten().collect::<Vec<_>>().as_slice() {
+            [] => write!(f, "no limit"),
+            [limit] => write!(f, "at most {limit}"),
+            [size, stars] => write!(f, "at most {size} and {stars}"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GuessLimitError, GuessLimits};
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn guesses_are_checked_against_limits() {
+        let listing = RegexAst::parse_str("a|ab|abb|ba|bab|(a|b)*a(a|b)*").unwrap();
+        let small = RegexAst::parse_str("(a|b)*a").unwrap();
+        let limits = GuessLimits {
+            max_size: Some(12),
+            max_stars: Some(1),
+        };
+        assert!(limits.check(&small).is_ok());
+        assert!(matches!(
+            limits.check(&listing),
+            Err(GuessLimitError::TooLarge(_, 12))
+        ));
+        let limits = GuessLimits {
+            max_size: None,
+            max_stars: Some(1),
+        };
+        assert!(matches!(
+            limits.check(&listing),
+            Err(GuessLimitError::TooManyStars(2, 1))
+        ));
+        assert!(GuessLimits::default().check(&listing).is_ok());
+        assert_eq!(
+            GuessLimits {
+                max_size: Some(30),
+                max_stars: Some(2),
+            }
+            .to_string(),
+            "at most 30 nodes and 2 stars"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+use crate::regex::{Alphabet, RegexAst};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Number of words sampled when looking for an accepted word to reveal
+const WORD_SAMPLE_SIZE: usize = 20;
+/// Number of shortest words searched when sampling finds only known words
+const WORD_SEARCH_SIZE: usize = 100;
+
+/// Facts about the answer revealed when a quiz starts, so that players new to the game
+/// can play along with experienced ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Handicaps {
+    /// Reveal the number of letters written in the answer
+    pub literal_count: bool,
+    /// Reveal whether `ε` is accepted
+    pub nullable: bool,
+    /// Reveal an accepted word besides the opening examples
+    pub accepted_word: bool,
+}
+
+impl Handicaps {
+    pub fn is_empty(&self) -> bool {
+        !(self.literal_count || self.nullable || self.accepted_word)
+    }
+
+    /// The facts revealed about `answer`, one per handicap, choosing the accepted word
+    /// reproducibly from `seed` among the words not in `known`.
+    pub fn reveal(&self, answer: &RegexAst, known: &[Vec<Alphabet>], seed: u64) -> Vec<String> {
+        let mut facts = vec![];
+        if self.literal_count {
+            facts.push(format!(
+                "The answer is written with {} letters.",
+                answer.stats().literal_count
+            ));
+        }
+        if self.nullable {
+            facts.push(format!(
+                "`ε` is {}.",
+                if answer.nullable() {
+                    "accepted"
+                } else {
+                    "rejected"
+                }
+            ));
+        }
+        if self.accepted_word {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let word = answer
+                .sample_matches(WORD_SAMPLE_SIZE, &mut rng)
+                .into_iter()
+                .chain(answer.words().take(WORD_SEARCH_SIZE))
+                .find(|word| !known.contains(word));
+            facts.push(match word {
+                Some(word) if word.is_empty() => "`ε` is accepted.".to_string(),
+                Some(word) => format!("`{}` is accepted.", Alphabet::slice_to_plain_string(&word)),
+                None => "There is no other accepted word to show.".to_string(),
+            });
+        }
+        facts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Handicaps;
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn handicaps_reveal_facts_about_the_answer() {
+        let answer = RegexAst::parse_str("(ab|c)*").unwrap();
+        assert!(Handicaps::default().is_empty());
+        assert!(Handicaps::default().reveal(&answer, &[], 0).is_empty());
+
+        let handicaps = Handicaps {
+            literal_count: true,
+            nullable: true,
+            accepted_word: true,
+        };
+        let known = ["", "ab", "c"]
+            .iter()
+            .map(|word| Alphabet::vec_from_str(word).unwrap())
+            .collect::<Vec<_>>();
+        let facts = handicaps.reveal(&answer, &known, 0);
+        assert_eq!(facts[0], "The answer is written with 3 letters.");
+        assert_eq!(facts[1], "`ε` is accepted.");
+        let word = facts[2]
+            .strip_prefix('`')
+            .and_then(|fact| fact.strip_suffix("` is accepted."))
+            .unwrap();
+        let word = Alphabet::vec_from_str(word).unwrap();
+        assert!(answer.matches(&word));
+        assert!(!known.contains(&word));
+
+        let finite = RegexAst::parse_str("a").unwrap();
+        let only_a = [Alphabet::vec_from_str("a").unwrap()];
+        assert_eq!(
+            handicaps.reveal(&finite, &only_a, 0)[2],
+            "There is no other accepted word to show."
+        );
+    }
+}
@@ -0,0 +1,276 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::regex::{Alphabet, BudgetExceeded, Dfa, RegexAst};
+use anyhow::anyhow;
+use itertools::Itertools;
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// Upper bound of the number of nodes built when converting a guessed transition table
+/// into an expression, beyond which the table itself is kept
+const GUESS_REGEX_SIZE_BUDGET: usize = 200;
+
+/// Kinds of objects hidden in a quiz.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QuizVariant {
+    /// A generated regular expression
+    Regex,
+    /// A small random DFA, which often has no short regular expression
+    Dfa,
+}
+
+impl QuizVariant {
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuizVariant::Regex => "regex",
+            QuizVariant::Dfa => "dfa",
+        }
+    }
+}
+
+impl FromStr for QuizVariant {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "regex" => Ok(QuizVariant::Regex),
+            "dfa" => Ok(QuizVariant::Dfa),
+            _ => Err(anyhow!("unknown variant: {}", s)),
+        }
+    }
+}
+
+/// The language players try to identify, given either as an expression or as an automaton.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HiddenLanguage {
+    Regex(RegexAst),
+    /// An automaton accepting at least one word over the domain of the quiz
+    Automaton(Dfa),
+}
+
+impl HiddenLanguage {
+    /// The minimal DFA of the language over `domain`, on which queries and guesses are checked.
+    pub fn to_min_dfa(&self, domain: &HashSet<Alphabet>) -> Dfa {
+        match self {
+            HiddenLanguage::Regex(ast) => ast.to_min_dfa(domain),
+            HiddenLanguage::Automaton(dfa) => dfa.minimized(),
+        }
+    }
+
+    /// An expression denoting the language, for hints and analyses working on expressions.
+    ///
+    /// # Panics
+    /// Panics if the hidden automaton accepts no word.
+    pub fn to_regex(&self) -> RegexAst {
+        match self {
+            HiddenLanguage::Regex(ast) => ast.clone(),
+            HiddenLanguage::Automaton(dfa) => dfa
+                .to_regex()
+                .expect("a hidden automaton accepts some word"),
+        }
+    }
+
+    /// Description of the language revealed when a quiz ends, in Markdown.
+    /// Expressions are shown in their smallest form among ASTs of at most `search_size` nodes.
+    pub fn reveal(&self, search_size: usize) -> String {
+        match self {
+            HiddenLanguage::Regex(ast) => format!("`{}`", ast.minimize_syntax(search_size)),
+            HiddenLanguage::Automaton(dfa) => format!(
+                "the automaton\n```\n{}```\nequivalent to `{}`",
+                dfa.to_markdown_table(),
+                self.to_regex().minimize_syntax(search_size)
+            ),
+        }
+    }
+}
+
+/// A guess submitted by a player, either as an expression or as a transition table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Guess {
+    Regex(RegexAst),
+    Automaton(Dfa),
+}
+
+impl Guess {
+    /// Parse `input` as a transition table over `domain` (see [Dfa::parse_table])
+    /// if it starts like one, i.e. with a state number, a marker of the initial state or a cell border,
+    /// and as an expression otherwise.
+    pub fn parse(input: &str, domain: &[Alphabet]) -> anyhow::Result<Guess> {
+        let is_table = input
+            .trim_start()
+            .starts_with(|c: char| c.is_ascii_digit() || matches!(c, '>' | '→' | '|'));
+
+        if is_table {
+            Ok(Guess::Automaton(Dfa::parse_table(input, domain)?))
+        } else {
+            Ok(Guess::Regex(RegexAst::parse_str(input)?))
+        }
+    }
+
+    /// The minimal DFA of the guess over `domain`,
+    /// giving up if determinizing an expression needs more than `budget` states.
+    pub fn to_min_dfa(
+        &self,
+        domain: &HashSet<Alphabet>,
+        budget: usize,
+    ) -> Result<Dfa, BudgetExceeded> {
+        match self {
+            Guess::Regex(ast) => ast.to_min_dfa_with_budget(domain, budget),
+            Guess::Automaton(dfa) => Ok(dfa.minimized()),
+        }
+    }
+
+    /// What is kept of the guess once it is checked, given the guess `input` as submitted
+    /// and `min_dfa`, its minimal DFA.
+    ///
+    /// A transition table is converted into an expression from `min_dfa`,
+    /// unless the language is empty or state elimination builds more than
+    /// [GUESS_REGEX_SIZE_BUDGET] nodes, in which case the table is kept as submitted.
+    pub fn to_final_guess(&self, input: &str, min_dfa: &Dfa) -> FinalGuess {
+        match self {
+            Guess::Regex(ast) => FinalGuess::Regex(ast.clone()),
+            Guess::Automaton(_) => match min_dfa.to_regex_with_budget(GUESS_REGEX_SIZE_BUDGET) {
+                Ok(Some(ast)) => FinalGuess::Regex(ast),
+                Ok(None) | Err(_) => FinalGuess::Table(input.trim().to_string()),
+            },
+        }
+    }
+}
+
+/// The last guess of a player kept by a quiz.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FinalGuess {
+    Regex(RegexAst),
+    /// A transition table as submitted, whose language has no small enough expression
+    Table(String),
+}
+
+impl Display for FinalGuess {
+    /// Tables are shown on one line, with rows separated by `;` as [Dfa::parse_table] accepts.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FinalGuess::Regex(ast) => write!(f, "{}", ast),
+            FinalGuess::Table(table) => write!(
+                f,
+                "{}",
+                table
+                    .split(['\n', ';'])
+                    .map(str::trim)
+                    .filter(|row| !row.is_empty())
+                    .join("; ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FinalGuess, Guess, HiddenLanguage};
+    use crate::regex::{blowup, Alphabet, BudgetExceeded, Dfa, DiffExample, RegexAst};
+    use itertools::Itertools;
+    use std::collections::HashSet;
+
+    #[test]
+    fn guesses_are_checked_on_automata() {
+        let domain = Alphabet::vec_from_str("ab").unwrap();
+        let domain_set = domain.iter().cloned().collect::<HashSet<_>>();
+        // words with an odd number of `a`s
+        let hidden = HiddenLanguage::Automaton(Dfa::parse_table("0 1 0; 1* 0 1", &domain).unwrap());
+        let answer = hidden.to_min_dfa(&domain_set);
+
+        let check = |input: &str| {
+            let guess = Guess::parse(input, &domain).unwrap();
+            answer.counterexample(&guess.to_min_dfa(&domain_set, 1000).unwrap())
+        };
+        assert_eq!(check("b*a(b|ab*a)*"), None);
+        assert_eq!(
+            check("0* 2 2; >1 0 0; 2 2 2"),
+            Some(DiffExample::OnlyRight(Alphabet::vec_from_str("b").unwrap()))
+        );
+        assert_eq!(check("0 1 0; 1* 0 1"), None);
+        assert_eq!(
+            check("(a|b)*a"),
+            Some(DiffExample::OnlyRight(
+                Alphabet::vec_from_str("aa").unwrap()
+            ))
+        );
+
+        let regex = hidden.to_regex();
+        assert_eq!(regex.to_min_dfa(&domain_set), answer);
+        assert!(matches!(
+            Guess::parse("ab*", &domain).unwrap(),
+            Guess::Regex(ast) if ast == RegexAst::parse_str("ab*").unwrap()
+        ));
+        assert!(Guess::parse("0 1", &domain).is_err());
+    }
+
+    #[test]
+    fn complex_guesses_are_not_checked() {
+        let domain = Alphabet::vec_from_str("ab").unwrap();
+        let domain_set = domain.iter().cloned().collect::<HashSet<_>>();
+        let guess = Guess::parse(&blowup(20).to_string(), &domain).unwrap();
+
+        assert_eq!(
+            guess.to_min_dfa(&domain_set, 100),
+            Err(BudgetExceeded { budget: 100 })
+        );
+    }
+
+    #[test]
+    fn guessed_tables_are_kept_as_small_expressions_only() {
+        let domain = Alphabet::vec_from_str("ab").unwrap();
+        let domain_set = domain.iter().cloned().collect::<HashSet<_>>();
+        let final_guess = |input: &str| {
+            let guess = Guess::parse(input, &domain).unwrap();
+            guess.to_final_guess(input, &guess.to_min_dfa(&domain_set, 1000).unwrap())
+        };
+
+        // words with an odd number of `a`s
+        let odd = final_guess("0 1 0; 1* 0 1");
+        assert!(matches!(
+            &odd,
+            FinalGuess::Regex(ast) if ast.equivalent_to(&RegexAst::parse_str("b*a(b|ab*a)*").unwrap())
+        ));
+        assert_eq!(
+            final_guess("ab*"),
+            FinalGuess::Regex(RegexAst::parse_str("ab*").unwrap())
+        );
+
+        // the empty language has no expression
+        assert_eq!(
+            final_guess("0 0 0\n1 1 1\n"),
+            FinalGuess::Table("0 0 0\n1 1 1".to_string())
+        );
+        assert_eq!(final_guess("0 0 0\n1 1 1\n").to_string(), "0 0 0; 1 1 1");
+
+        // words whose 5th letter from the end is `a`, whose expressions are long
+        let table = (0..32)
+            .map(|q| {
+                let next = |bit| ((q << 1) | bit) & 31;
+                let marker = if q & 16 != 0 { "*" } else { "" };
+                format!("{}{} {} {}", q, marker, next(1), next(0))
+            })
+            .join("; ");
+        assert_eq!(final_guess(&table), FinalGuess::Table(table));
+    }
+}
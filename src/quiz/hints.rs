@@ -0,0 +1,389 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::regex::{Alphabet, RegexAst};
+use anyhow::anyhow;
+use itertools::Itertools;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::HashSet, fmt, str::FromStr};
+
+/// Number of words sampled when looking for a word not known to players
+const EXAMPLE_SAMPLE_SIZE: usize = 20;
+/// Number of shortest words searched when sampling fails to find a word not known to players
+const EXAMPLE_SEARCH_SIZE: usize = 100;
+
+/// Kinds of hints, from the least to the most revealing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HintTier {
+    /// An accepted word and a rejected word close to accepted ones, both unknown to players
+    Examples,
+    /// Number of states of the minimal DFA of the answer over the domain
+    StateCount,
+    /// Whether the empty word is accepted and the length of the shortest accepted nonempty word
+    ShortestWord,
+    /// Letters of the domain appearing in the answer, which narrows the search drastically
+    UsedAlphabets,
+}
+
+impl HintTier {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HintTier::Examples => "examples",
+            HintTier::StateCount => "state-count",
+            HintTier::ShortestWord => "shortest-word",
+            HintTier::UsedAlphabets => "used-alphabets",
+        }
+    }
+}
+
+impl FromStr for HintTier {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "examples" => Ok(HintTier::Examples),
+            "state-count" => Ok(HintTier::StateCount),
+            "shortest-word" => Ok(HintTier::ShortestWord),
+            "used-alphabets" => Ok(HintTier::UsedAlphabets),
+            _ => Err(anyhow!("unknown hint: {}", s)),
+        }
+    }
+}
+
+/// A hint revealed to players.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Hint {
+    /// `None` means that no word unknown to players was found.
+    Examples {
+        accepted: Option<Vec<Alphabet>>,
+        rejected: Option<Vec<Alphabet>>,
+    },
+    StateCount(usize),
+    /// `shortest_length` is `None` if the empty word is the only accepted word.
+    ShortestWord {
+        nullable: bool,
+        shortest_length: Option<usize>,
+    },
+    /// Sorted
+    UsedAlphabets(Vec<Alphabet>),
+}
+
+/// Points each tier of hints costs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HintCosts {
+    pub examples: u32,
+    pub state_count: u32,
+    pub shortest_word: u32,
+    pub used_alphabets: u32,
+}
+
+impl Default for HintCosts {
+    fn default() -> Self {
+        Self {
+            examples: 1,
+            state_count: 2,
+            shortest_word: 2,
+            used_alphabets: 5,
+        }
+    }
+}
+
+impl HintCosts {
+    pub fn of(&self, tier: HintTier) -> u32 {
+        match tier {
+            HintTier::Examples => self.examples,
+            HintTier::StateCount => self.state_count,
+            HintTier::ShortestWord => self.shortest_word,
+            HintTier::UsedAlphabets => self.used_alphabets,
+        }
+    }
+}
+
+fn format_word(word: &[Alphabet]) -> String {
+    match word {
+        [] => "`ε`".to_string(),
+        word => format!("`{}`", Alphabet::slice_to_plain_string(word)),
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hint::Examples { accepted, rejected } => {
+                match accepted {
+                    Some(word) => writeln!(f, "{} is accepted.", format_word(word))?,
+                    None => writeln!(f, "There is no other accepted word to show.")?,
+                }
+                match rejected {
+                    Some(word) => write!(f, "{} is rejected.", format_word(word)),
+                    None => write!(f, "There is no other rejected word to show."),
+                }
+            }
+            Hint::StateCount(count) => {
+                write!(f, "The minimal DFA of the answer has {} states.", count)
+            }
+            Hint::ShortestWord {
+                nullable,
+                shortest_length,
+            } => {
+                writeln!(
+                    f,
+                    "`ε` is {}.",
+                    if *nullable { "accepted" } else { "rejected" }
+                )?;
+                match shortest_length {
+                    Some(length) => write!(
+                        f,
+                        "The shortest accepted nonempty word has length {}.",
+                        length
+                    ),
+                    None => write!(f, "No nonempty word is accepted."),
+                }
+            }
+            Hint::UsedAlphabets(alphabets) => write!(
+                f,
+                "Only {} appear in the answer.",
+                alphabets.iter().map(|a| format!("`{}`", a)).join(", ")
+            ),
+        }
+    }
+}
+
+/// Hints of a quiz together with what they have revealed so far.
+pub struct Hints {
+    answer: RegexAst,
+    domain: Vec<Alphabet>,
+    /// Words revealed by hints
+    revealed: HashSet<Vec<Alphabet>>,
+    given: Vec<Hint>,
+    costs: HintCosts,
+    /// Total cost of hints given so far
+    spent: u32,
+    rng: StdRng,
+}
+
+impl Hints {
+    /// Hints on `answer` of a quiz over `domain`, choosing random words reproducibly from `seed`.
+    pub fn new(answer: RegexAst, domain: Vec<Alphabet>, seed: u64) -> Self {
+        Self::with_costs(answer, domain, seed, HintCosts::default())
+    }
+
+    pub fn with_costs(
+        answer: RegexAst,
+        domain: Vec<Alphabet>,
+        seed: u64,
+        costs: HintCosts,
+    ) -> Self {
+        Self {
+            answer,
+            domain,
+            revealed: HashSet::new(),
+            given: vec![],
+            costs,
+            spent: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Hints given so far, in order.
+    pub fn given(&self) -> &[Hint] {
+        &self.given
+    }
+
+    pub fn costs(&self) -> &HintCosts {
+        &self.costs
+    }
+
+    /// Total cost of hints given so far.
+    pub fn spent(&self) -> u32 {
+        self.spent
+    }
+
+    /// Reveal a hint of `tier`, avoiding words in `queried` and words revealed before.
+    pub fn reveal(&mut self, tier: HintTier, queried: &HashSet<Vec<Alphabet>>) -> Hint {
+        let hint = match tier {
+            HintTier::Examples => {
+                let accepted = self.fresh_accepted_word(queried);
+                let rejected = self.fresh_rejected_word(queried);
+                self.revealed
+                    .extend(accepted.iter().chain(rejected.iter()).cloned());
+                Hint::Examples { accepted, rejected }
+            }
+            HintTier::StateCount => Hint::StateCount(
+                self.answer
+                    .to_min_dfa(&self.domain.iter().cloned().collect())
+                    .state_count(),
+            ),
+            HintTier::ShortestWord => Hint::ShortestWord {
+                nullable: self.answer.nullable(),
+                // a nonempty word is a letter followed by a word matched by the derivative
+                shortest_length: self
+                    .answer
+                    .used_alphabets()
+                    .into_iter()
+                    .filter_map(|a| self.answer.derivative(a)?.shortest_word())
+                    .map(|word| word.len() + 1)
+                    .min(),
+            },
+            HintTier::UsedAlphabets => {
+                Hint::UsedAlphabets(self.answer.used_alphabets().into_iter().sorted().collect())
+            }
+        };
+
+        self.spent += self.costs.of(tier);
+        self.given.push(hint.clone());
+        hint
+    }
+
+    fn is_fresh(&self, word: &[Alphabet], queried: &HashSet<Vec<Alphabet>>) -> bool {
+        !queried.contains(word) && !self.revealed.contains(word)
+    }
+
+    fn fresh_accepted_word(&mut self, queried: &HashSet<Vec<Alphabet>>) -> Option<Vec<Alphabet>> {
+        let sampled = self
+            .answer
+            .sample_matches(EXAMPLE_SAMPLE_SIZE, &mut self.rng);
+        sampled
+            .into_iter()
+            .chain(self.answer.words().take(EXAMPLE_SEARCH_SIZE))
+            .find(|word| self.is_fresh(word, queried))
+    }
+
+    fn fresh_rejected_word(&mut self, queried: &HashSet<Vec<Alphabet>>) -> Option<Vec<Alphabet>> {
+        self.answer
+            .sample_near_misses(&self.domain, EXAMPLE_SAMPLE_SIZE, &mut self.rng)
+            .into_iter()
+            .find(|word| self.is_fresh(word, queried))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hint, HintCosts, HintTier, Hints};
+    use crate::regex::{Alphabet, RegexAst};
+    use std::collections::HashSet;
+
+    #[test]
+    fn hints_reveal_fresh_examples() {
+        let answer = RegexAst::parse_str("a(b|c)*").unwrap();
+        let domain = Alphabet::vec_from_str("abc").unwrap();
+        let mut hints = Hints::new(answer.clone(), domain, 0);
+
+        let queried = ["a", "ab", "ac"]
+            .iter()
+            .map(|w| Alphabet::vec_from_str(w).unwrap())
+            .collect::<HashSet<_>>();
+
+        let mut revealed = HashSet::new();
+        for _ in 0..3 {
+            match hints.reveal(HintTier::Examples, &queried) {
+                Hint::Examples { accepted, rejected } => {
+                    let (accepted, rejected) = (accepted.unwrap(), rejected.unwrap());
+                    assert!(answer.matches(&accepted));
+                    assert!(!answer.matches(&rejected));
+                    for word in [accepted, rejected] {
+                        assert!(!queried.contains(&word));
+                        assert!(revealed.insert(word));
+                    }
+                }
+                hint => panic!("unexpected hint: {:?}", hint),
+            }
+        }
+        assert_eq!(hints.given().len(), 3);
+
+        // every matched word has been queried
+        let mut hints = Hints::new(
+            RegexAst::parse_str("a").unwrap(),
+            Alphabet::vec_from_str("ab").unwrap(),
+            0,
+        );
+        let queried = vec![Alphabet::vec_from_str("a").unwrap()]
+            .into_iter()
+            .collect();
+        assert!(matches!(
+            hints.reveal(HintTier::Examples, &queried),
+            Hint::Examples {
+                accepted: None,
+                rejected: Some(_)
+            }
+        ));
+    }
+
+    #[test]
+    fn hints_reveal_state_count_at_cost() {
+        let costs = HintCosts {
+            examples: 1,
+            state_count: 5,
+            shortest_word: 5,
+            used_alphabets: 10,
+        };
+        let mut hints = Hints::with_costs(
+            RegexAst::parse_str("(ab)*").unwrap(),
+            Alphabet::vec_from_str("abc").unwrap(),
+            0,
+            costs,
+        );
+
+        // the initial state, the state after `a` and the dead state
+        assert_eq!(
+            hints.reveal(HintTier::StateCount, &HashSet::new()),
+            Hint::StateCount(3)
+        );
+        hints.reveal(HintTier::Examples, &HashSet::new());
+        assert_eq!(hints.spent(), 6);
+    }
+
+    #[test]
+    fn hints_reveal_used_alphabets() {
+        let mut hints = Hints::new(
+            RegexAst::parse_str("(d|a)*b").unwrap(),
+            Alphabet::vec_from_str("abcd").unwrap(),
+            0,
+        );
+        assert_eq!(
+            hints.reveal(HintTier::UsedAlphabets, &HashSet::new()),
+            Hint::UsedAlphabets(Alphabet::vec_from_str("abd").unwrap())
+        );
+    }
+
+    #[test]
+    fn hints_reveal_shortest_word() {
+        let cases = vec![
+            ("(ab)*", true, Some(2)),
+            ("a*bc", false, Some(2)),
+            ("(abc|ab)(c|cc)", false, Some(3)),
+        ];
+        for (regex_str, nullable, shortest_length) in cases {
+            let mut hints = Hints::new(
+                RegexAst::parse_str(regex_str).unwrap(),
+                Alphabet::vec_from_str("abc").unwrap(),
+                0,
+            );
+            assert_eq!(
+                hints.reveal(HintTier::ShortestWord, &HashSet::new()),
+                Hint::ShortestWord {
+                    nullable,
+                    shortest_length
+                },
+                "{}",
+                regex_str
+            );
+        }
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+use serenity::model::id::UserId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of the latest queries `/history` lists, keeping the table within an embed
+pub const HISTORY_LIMIT: usize = 50;
+
+/// A membership query as it was asked, kept even when the word had been queried before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryRecord {
+    /// The word as typed, `""` standing for the empty word
+    pub word: String,
+    pub accepted: bool,
+    pub author: UserId,
+    pub at: SystemTime,
+}
+
+/// How a queried word is shown, with the empty word `""` shown as `ε`.
+pub fn show_word(word: &str) -> &str {
+    if word.eq(r#""""#) {
+        "ε"
+    } else {
+        word
+    }
+}
+
+impl QueryRecord {
+    /// A row of the history table: number, word, result, author and time.
+    pub fn row(&self, number: usize) -> String {
+        let word = show_word(&self.word);
+        let secs = self
+            .at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        format!(
+            "`{number:>3}` `{word}` {} <@{}> <t:{secs}:T>",
+            if self.accepted { "✅" } else { "❌" },
+            self.author.0,
+        )
+    }
+}
+
+/// Render `records` as a compact table, one row per query, listing at most `limit` of the latest.
+pub fn history_table(records: &[QueryRecord], limit: usize) -> String {
+    if records.is_empty() {
+        return "No queries yet.".to_string();
+    }
+    let skipped = records.len().saturating_sub(limit);
+    let mut rows = Vec::with_capacity(records.len() - skipped + 1);
+    if skipped > 0 {
+        rows.push(format!("… {skipped} earlier queries"));
+    }
+    rows.extend(
+        records
+            .iter()
+            .enumerate()
+            .skip(skipped)
+            .map(|(i, record)| record.row(i + 1)),
+    );
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{history_table, QueryRecord};
+    use serenity::model::id::UserId;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn history_table_lists_latest_queries() {
+        let records = ["ab", r#""""#, "ab"]
+            .iter()
+            .enumerate()
+            .map(|(i, word)| QueryRecord {
+                word: word.to_string(),
+                accepted: i != 1,
+                author: UserId(i as u64 + 1),
+                at: UNIX_EPOCH + Duration::from_secs(60 * i as u64),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(history_table(&[], 10), "No queries yet.");
+        assert_eq!(
+            history_table(&records, 10),
+            "`  1` `ab` ✅ <@1> <t:0:T>\n`  2` `ε` ❌ <@2> <t:60:T>\n`  3` `ab` ✅ <@3> <t:120:T>"
+        );
+        assert_eq!(
+            history_table(&records, 1),
+            "… 2 earlier queries\n`  3` `ab` ✅ <@3> <t:120:T>"
+        );
+    }
+}
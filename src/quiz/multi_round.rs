@@ -0,0 +1,174 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+use crate::bot::QuizId;
+use serenity::model::id::UserId;
+use std::cmp::Reverse;
+
+/// Numbers of rounds a match may be played over
+pub const BEST_OF: [usize; 3] = [3, 5, 7];
+
+/// The winner of a round and the points they earned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundWin {
+    pub player: UserId,
+    pub points: u32,
+}
+
+/// Cumulative result of a player over the rounds played so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Standing {
+    pub player: UserId,
+    pub wins: usize,
+    pub points: u32,
+}
+
+/// A best-of-N match: consecutive quizzes in a channel, the standings of which
+/// rank players by rounds won and then by points earned over the rounds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub best_of: usize,
+    /// The quiz of the round being played, if it has started
+    current: Option<QuizId>,
+    /// The winner of each round played to the end, `None` if nobody solved it
+    results: Vec<Option<RoundWin>>,
+}
+
+impl Match {
+    pub fn new(best_of: usize) -> Self {
+        Match {
+            best_of,
+            current: None,
+            results: vec![],
+        }
+    }
+
+    /// Number of the round being played or to be played next, from 1.
+    pub fn round(&self) -> usize {
+        self.results.len() + 1
+    }
+
+    pub fn current(&self) -> Option<QuizId> {
+        self.current
+    }
+
+    /// Play the next round on `quiz`.
+    pub fn begin_round(&mut self, quiz: QuizId) {
+        self.current = Some(quiz);
+    }
+
+    /// Record the end of the round played on `quiz`, returning whether it was the current round.
+    pub fn end_round(&mut self, quiz: QuizId, winner: Option<RoundWin>) -> bool {
+        if self.current != Some(quiz) {
+            return false;
+        }
+        self.current = None;
+        self.results.push(winner);
+        true
+    }
+
+    /// Players who have won a round, the leader first.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings = Vec::<Standing>::new();
+        for win in self.results.iter().flatten() {
+            match standings
+                .iter_mut()
+                .find(|standing| standing.player == win.player)
+            {
+                Some(standing) => {
+                    standing.wins += 1;
+                    standing.points += win.points;
+                }
+                None => standings.push(Standing {
+                    player: win.player,
+                    wins: 1,
+                    points: win.points,
+                }),
+            }
+        }
+        // stable, so that the player who won a round first is ahead on ties
+        standings.sort_by_key(|standing| Reverse((standing.wins, standing.points)));
+        standings
+    }
+
+    /// Whether every round has been played or a player has won a majority of them.
+    pub fn is_over(&self) -> bool {
+        self.results.len() >= self.best_of
+            || self
+                .standings()
+                .first()
+                .is_some_and(|leader| leader.wins > self.best_of / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Match, RoundWin, Standing};
+    use crate::bot::QuizId;
+    use serenity::model::id::UserId;
+
+    fn win(player: u64, points: u32) -> Option<RoundWin> {
+        Some(RoundWin {
+            player: UserId(player),
+            points,
+        })
+    }
+
+    #[test]
+    fn match_ends_once_a_player_wins_a_majority() {
+        let mut best_of_3 = Match::new(3);
+        assert!(!best_of_3.end_round(QuizId(1), win(1, 10)));
+        best_of_3.begin_round(QuizId(1));
+        assert!(best_of_3.end_round(QuizId(1), win(1, 10)));
+        assert!(!best_of_3.end_round(QuizId(1), win(1, 10)));
+        best_of_3.begin_round(QuizId(2));
+        assert!(best_of_3.end_round(QuizId(2), win(2, 30)));
+        assert_eq!(
+            best_of_3.standings(),
+            vec![
+                Standing {
+                    player: UserId(2),
+                    wins: 1,
+                    points: 30
+                },
+                Standing {
+                    player: UserId(1),
+                    wins: 1,
+                    points: 10
+                },
+            ]
+        );
+        assert!(!best_of_3.is_over());
+        best_of_3.begin_round(QuizId(3));
+        best_of_3.end_round(QuizId(3), win(1, 5));
+        assert_eq!(best_of_3.standings()[0].player, UserId(1));
+        assert!(best_of_3.is_over());
+
+        let mut best_of_5 = Match::new(5);
+        for (round, winner) in [win(1, 10), win(1, 10), None, win(1, 10)]
+            .iter()
+            .enumerate()
+        {
+            assert!(!best_of_5.is_over());
+            best_of_5.begin_round(QuizId(round as u64));
+            best_of_5.end_round(QuizId(round as u64), *winner);
+        }
+        assert_eq!(best_of_5.round(), 5);
+        assert!(best_of_5.is_over());
+    }
+}
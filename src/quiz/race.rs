@@ -0,0 +1,215 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use serenity::model::id::{ChannelId, UserId};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Time after the first solve within which the opponent may still tie by solving too,
+/// the tie then being broken by the number of queries
+pub const TIE_WINDOW: Duration = Duration::from_secs(30);
+
+/// Error in reporting to the referee of a race.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum RaceError {
+    #[error("<@{0}> is not running in the race")]
+    NotInRace(u64),
+    #[error("The race is already decided")]
+    AlreadyDecided,
+}
+
+/// The game instance of one player of a race.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Lane {
+    pub player: UserId,
+    /// The thread in which the player plays their own quiz
+    pub thread: ChannelId,
+}
+
+/// A correct guess of a player, with the elapsed time since the race started.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Solve {
+    pub player: UserId,
+    pub elapsed: Duration,
+    /// Number of distinct words queried
+    pub queries: u32,
+}
+
+/// The decided result of a race.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaceResult {
+    pub winner: Solve,
+    /// The solve of the other player, if they tied within [`TIE_WINDOW`]
+    pub runner_up: Option<Solve>,
+    /// Whether the winner solved later but won by asking fewer queries
+    pub tiebreak: bool,
+}
+
+/// State of a race after a solve is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaceStatus {
+    /// Someone solved first, and the opponent has [`TIE_WINDOW`] to tie
+    Provisional(Solve),
+    Decided(RaceResult),
+}
+
+/// The referee of a head-to-head race, in which two players play separate quizzes
+/// generated from the same `seed`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Race {
+    pub lanes: [Lane; 2],
+    pub seed: u64,
+    /// The channel in which the race was started and its result is announced
+    pub channel: ChannelId,
+    solves: Vec<Solve>,
+    result: Option<RaceResult>,
+}
+
+impl Race {
+    pub fn new(lanes: [Lane; 2], seed: u64, channel: ChannelId) -> Self {
+        Race {
+            lanes,
+            seed,
+            channel,
+            solves: vec![],
+            result: None,
+        }
+    }
+
+    pub fn result(&self) -> Option<RaceResult> {
+        self.result
+    }
+
+    /// Record that a player solved their quiz.
+    /// The race is decided once both have solved or [`Race::settle`] is called.
+    pub fn solve(&mut self, solve: Solve) -> Result<RaceStatus, RaceError> {
+        if self.result.is_some() {
+            return Err(RaceError::AlreadyDecided);
+        }
+        if !self.lanes.iter().any(|lane| lane.player == solve.player) {
+            return Err(RaceError::NotInRace(solve.player.0));
+        }
+        if self.solves.iter().any(|s| s.player == solve.player) {
+            return Err(RaceError::AlreadyDecided);
+        }
+        self.solves.push(solve);
+        match *self.solves.as_slice() {
+            [first] => Ok(RaceStatus::Provisional(first)),
+            [first, second] => {
+                let result = compare(first, second);
+                self.result = Some(result);
+                Ok(RaceStatus::Decided(result))
+            }
+            _ => unreachable!("a race has two players"),
+        }
+    }
+
+    /// Decide the race in favor of the first solver once [`TIE_WINDOW`] has passed,
+    /// returning `None` if nobody has solved or the race is already decided.
+    pub fn settle(&mut self) -> Option<RaceResult> {
+        if self.result.is_some() {
+            return None;
+        }
+        let first = *self.solves.first()?;
+        let result = RaceResult {
+            winner: first,
+            runner_up: None,
+            tiebreak: false,
+        };
+        self.result = Some(result);
+        Some(result)
+    }
+}
+
+/// Compare two solves, `first` being reported earlier.
+fn compare(first: Solve, second: Solve) -> RaceResult {
+    let tied = second.elapsed.saturating_sub(first.elapsed) <= TIE_WINDOW;
+    if tied && second.queries < first.queries {
+        RaceResult {
+            winner: second,
+            runner_up: Some(first),
+            tiebreak: true,
+        }
+    } else {
+        RaceResult {
+            winner: first,
+            runner_up: tied.then_some(second),
+            tiebreak: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lane, Race, RaceError, RaceStatus, Solve};
+    use serenity::model::id::{ChannelId, UserId};
+    use std::time::Duration;
+
+    fn new_race() -> Race {
+        let lane = |player| Lane {
+            player: UserId(player),
+            thread: ChannelId(10 + player),
+        };
+        Race::new([lane(1), lane(2)], 42, ChannelId(10))
+    }
+
+    fn solve(player: u64, secs: u64, queries: u32) -> Solve {
+        Solve {
+            player: UserId(player),
+            elapsed: Duration::from_secs(secs),
+            queries,
+        }
+    }
+
+    #[test]
+    fn first_solve_wins_and_queries_break_ties() {
+        let mut race = new_race();
+        assert_eq!(race.settle(), None);
+        assert_eq!(race.solve(solve(3, 10, 1)), Err(RaceError::NotInRace(3)));
+        assert_eq!(
+            race.solve(solve(1, 100, 5)),
+            Ok(RaceStatus::Provisional(solve(1, 100, 5)))
+        );
+        let result = match race.solve(solve(2, 110, 3)) {
+            Ok(RaceStatus::Decided(result)) => result,
+            status => panic!("unexpected status: {:?}", status),
+        };
+        assert_eq!(result.winner, solve(2, 110, 3));
+        assert!(result.tiebreak);
+        assert_eq!(race.solve(solve(2, 120, 1)), Err(RaceError::AlreadyDecided));
+
+        let mut race = new_race();
+        race.solve(solve(1, 100, 5)).unwrap();
+        let result = match race.solve(solve(2, 200, 3)) {
+            Ok(RaceStatus::Decided(result)) => result,
+            status => panic!("unexpected status: {:?}", status),
+        };
+        assert_eq!(result.winner, solve(1, 100, 5));
+        assert_eq!(result.runner_up, None);
+        assert!(!result.tiebreak);
+
+        let mut race = new_race();
+        race.solve(solve(2, 50, 9)).unwrap();
+        assert_eq!(
+            race.settle().map(|result| result.winner),
+            Some(solve(2, 50, 9))
+        );
+        assert_eq!(race.settle(), None);
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+/// Rating of a player who has not played a match yet
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// Parameters of the Elo rating updated after each match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EloRules {
+    /// Largest change of rating a single match between two players can cause
+    pub k_factor: f64,
+}
+
+impl Default for EloRules {
+    fn default() -> Self {
+        EloRules { k_factor: 32.0 }
+    }
+}
+
+/// Probability that a player rated `rating` beats a player rated `opponent`.
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// New ratings after a player rated `winner` solves a quiz before players rated `losers`.
+///
+/// The winner is considered to beat each loser in a game weighted by `1 / losers.len()`,
+/// so that the total change does not grow with the number of players and no rating is created.
+pub fn rate_win(rules: &EloRules, winner: f64, losers: &[f64]) -> (f64, Vec<f64>) {
+    if losers.is_empty() {
+        return (winner, vec![]);
+    }
+    let k = rules.k_factor / losers.len() as f64;
+    let changes = losers
+        .iter()
+        .map(|&loser| k * (1.0 - expected_score(winner, loser)))
+        .collect::<Vec<_>>();
+    (
+        winner + changes.iter().sum::<f64>(),
+        losers
+            .iter()
+            .zip(&changes)
+            .map(|(loser, change)| loser - change)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expected_score, rate_win, EloRules, INITIAL_RATING};
+
+    #[test]
+    fn rating_moves_from_losers_to_winner() {
+        let rules = EloRules::default();
+        assert!((expected_score(INITIAL_RATING, INITIAL_RATING) - 0.5).abs() < 1e-9);
+
+        let (winner, losers) = rate_win(&rules, INITIAL_RATING, &[INITIAL_RATING]);
+        assert!((winner - 1516.0).abs() < 1e-9);
+        assert!((losers[0] - 1484.0).abs() < 1e-9);
+
+        // an upset moves ratings more than an expected win
+        let (upset, _) = rate_win(&rules, 1400.0, &[1600.0]);
+        let (expected, _) = rate_win(&rules, 1600.0, &[1400.0]);
+        assert!(upset - 1400.0 > expected - 1600.0);
+
+        let (winner, losers) = rate_win(&rules, 1550.0, &[1500.0, 1450.0, 1700.0]);
+        assert!((winner + losers.iter().sum::<f64>() - 6200.0).abs() < 1e-9);
+        assert!(winner - 1550.0 < rules.k_factor);
+
+        assert_eq!(rate_win(&rules, 1500.0, &[]), (1500.0, vec![]));
+    }
+}
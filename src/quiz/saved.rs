@@ -0,0 +1,383 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    bot::QuizOptions,
+    quiz::{
+        guess_limits::GuessLimits, handicap::Handicaps, hidden::QuizVariant, hints::HintTier,
+        submission::Submission, team::TeamBudget, tournament::MatchId,
+    },
+    regex::DifficultyPreset,
+};
+use anyhow::{anyhow, ensure, Context};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::UserId;
+use std::{
+    convert::TryInto,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Version of the serialized format of saved quizzes.
+/// Bump this whenever the layout of `SerializedQuiz` changes,
+/// so that quizzes saved by an older version are dropped instead of misread.
+pub const SAVED_QUIZ_FORMAT_VERSION: u32 = 1;
+
+/// A change players made to a quiz, kept so that the quiz can be restored by making it again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Move {
+    Join(UserId),
+    JoinTeam(UserId, String),
+    /// A query along with the time it was made, which the query log shows
+    Query(UserId, String, SystemTime),
+    Guess(UserId, String),
+    Undo,
+    Hint(HintTier),
+    /// A player giving up alone, leaving the quiz
+    Leave(UserId),
+    VoteGiveUp(UserId),
+}
+
+/// How a quiz was started, to start it again before its moves are replayed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Origin {
+    /// Generated as chosen by the options, whose seed is always set
+    Generated(QuizOptions),
+    /// Set by a player, who hosts it or teaches a class with it
+    Submitted {
+        submission: Submission,
+        author: UserId,
+        seed: u64,
+        classroom: bool,
+    },
+}
+
+/// A quiz going on, saved so that it survives a restart of the bot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedQuiz {
+    pub origin: Origin,
+    pub started_at: SystemTime,
+    pub moves: Vec<Move>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedQuiz {
+    version: u32,
+    generated: Option<SerializedOptions>,
+    submitted: Option<SerializedSubmission>,
+    /// Seconds since the Unix epoch
+    started_at: u64,
+    moves: Vec<SerializedMove>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedOptions {
+    variant: String,
+    letters: Option<u8>,
+    preset: Option<String>,
+    seed: u64,
+    max_queries: Option<usize>,
+    daily: Option<u64>,
+    /// Round and index of the tournament match
+    tournament_match: Option<(usize, usize)>,
+    race: Option<u64>,
+    /// Seconds until the quiz is closed
+    time_limit: Option<u64>,
+    owner: Option<u64>,
+    team_budget: Option<SerializedTeamBudget>,
+    max_guess_size: Option<usize>,
+    max_guess_stars: Option<usize>,
+    reveal_literals: bool,
+    reveal_epsilon: bool,
+    reveal_word: bool,
+    hint_penalty: Option<u32>,
+    practice: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedTeamBudget {
+    queries: Option<usize>,
+    guesses: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedSubmission {
+    answer: String,
+    letters: u8,
+    author: u64,
+    seed: u64,
+    classroom: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedMove {
+    kind: String,
+    user: Option<u64>,
+    /// The word queried, the guess, the team joined or the hint revealed
+    text: Option<String>,
+    /// Seconds since the Unix epoch at which a query was made
+    at: Option<u64>,
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+impl From<&QuizOptions> for SerializedOptions {
+    fn from(options: &QuizOptions) -> Self {
+        SerializedOptions {
+            variant: options.variant.name().to_string(),
+            letters: options.letters.map(|letters| letters.get()),
+            preset: options.preset.map(|preset| preset.name().to_string()),
+            seed: options.seed.unwrap_or_default(),
+            max_queries: options.max_queries,
+            daily: options.daily,
+            tournament_match: options.tournament_match.map(|id| (id.round, id.index)),
+            race: options.race,
+            time_limit: options.time_limit.map(|limit| limit.as_secs()),
+            owner: options.owner.map(|owner| owner.0),
+            team_budget: options.team_budget.map(|budget| SerializedTeamBudget {
+                queries: budget.queries,
+                guesses: budget.guesses,
+            }),
+            max_guess_size: options.guess_limits.max_size,
+            max_guess_stars: options.guess_limits.max_stars,
+            reveal_literals: options.handicaps.literal_count,
+            reveal_epsilon: options.handicaps.nullable,
+            reveal_word: options.handicaps.accepted_word,
+            hint_penalty: options.hint_penalty,
+            practice: options.practice,
+        }
+    }
+}
+
+impl SerializedOptions {
+    fn restore(&self) -> anyhow::Result<QuizOptions> {
+        Ok(QuizOptions {
+            variant: self.variant.parse::<QuizVariant>()?,
+            letters: self
+                .letters
+                .map(|letters| letters.try_into())
+                .transpose()
+                .context("A quiz needs at least one letter")?,
+            preset: self
+                .preset
+                .as_deref()
+                .map(str::parse::<DifficultyPreset>)
+                .transpose()?,
+            seed: Some(self.seed),
+            max_queries: self.max_queries,
+            daily: self.daily,
+            tournament_match: self
+                .tournament_match
+                .map(|(round, index)| MatchId { round, index }),
+            race: self.race,
+            time_limit: self.time_limit.map(Duration::from_secs),
+            owner: self.owner.map(UserId),
+            team_budget: self.team_budget.as_ref().map(|budget| TeamBudget {
+                queries: budget.queries,
+                guesses: budget.guesses,
+            }),
+            guess_limits: GuessLimits {
+                max_size: self.max_guess_size,
+                max_stars: self.max_guess_stars,
+            },
+            handicaps: Handicaps {
+                literal_count: self.reveal_literals,
+                nullable: self.reveal_epsilon,
+                accepted_word: self.reveal_word,
+            },
+            hint_penalty: self.hint_penalty,
+            practice: self.practice,
+        })
+    }
+}
+
+impl From<&Move> for SerializedMove {
+    fn from(played: &Move) -> Self {
+        let (kind, user, text, at) = match played {
+            Move::Join(user) => ("join", Some(user), None, None),
+            Move::JoinTeam(user, team) => ("join-team", Some(user), Some(team.clone()), None),
+            Move::Query(user, word, at) => ("query", Some(user), Some(word.clone()), Some(*at)),
+            Move::Guess(user, guess) => ("guess", Some(user), Some(guess.clone()), None),
+            Move::Undo => ("undo", None, None, None),
+            Move::Hint(tier) => ("hint", None, Some(tier.name().to_string()), None),
+            Move::Leave(user) => ("leave", Some(user), None, None),
+            Move::VoteGiveUp(user) => ("vote-give-up", Some(user), None, None),
+        };
+        SerializedMove {
+            kind: kind.to_string(),
+            user: user.map(|user| user.0),
+            text,
+            at: at.map(unix_seconds),
+        }
+    }
+}
+
+impl SerializedMove {
+    fn restore(&self) -> anyhow::Result<Move> {
+        let user = || {
+            self.user
+                .map(UserId)
+                .ok_or_else(|| anyhow!("The move {} has no player", self.kind))
+        };
+        let text = || {
+            self.text
+                .clone()
+                .ok_or_else(|| anyhow!("The move {} has no text", self.kind))
+        };
+        Ok(match self.kind.as_str() {
+            "join" => Move::Join(user()?),
+            "join-team" => Move::JoinTeam(user()?, text()?),
+            "query" => Move::Query(
+                user()?,
+                text()?,
+                UNIX_EPOCH + Duration::from_secs(self.at.unwrap_or_default()),
+            ),
+            "guess" => Move::Guess(user()?, text()?),
+            "undo" => Move::Undo,
+            "hint" => Move::Hint(text()?.parse()?),
+            "leave" => Move::Leave(user()?),
+            "vote-give-up" => Move::VoteGiveUp(user()?),
+            kind => return Err(anyhow!("Unknown move {}", kind)),
+        })
+    }
+}
+
+impl SavedQuiz {
+    /// Serialize the quiz into a versioned JSON document,
+    /// which can be restored by [`SavedQuiz::from_json`].
+    pub fn to_json(&self) -> String {
+        let (generated, submitted) = match &self.origin {
+            Origin::Generated(options) => (Some(SerializedOptions::from(options)), None),
+            Origin::Submitted {
+                submission,
+                author,
+                seed,
+                classroom,
+            } => (
+                None,
+                Some(SerializedSubmission {
+                    answer: submission.regex.to_string(),
+                    letters: submission.letters.get(),
+                    author: author.0,
+                    seed: *seed,
+                    classroom: *classroom,
+                }),
+            ),
+        };
+        serde_json::to_string(&SerializedQuiz {
+            version: SAVED_QUIZ_FORMAT_VERSION,
+            generated,
+            submitted,
+            started_at: unix_seconds(self.started_at),
+            moves: self.moves.iter().map(SerializedMove::from).collect(),
+        })
+        .expect("valid json")
+    }
+
+    /// Restore a quiz serialized by [`SavedQuiz::to_json`].
+    ///
+    /// Fails if the document was written in another format version
+    /// or does not describe how the quiz was started.
+    pub fn from_json(json: &str) -> anyhow::Result<SavedQuiz> {
+        let serialized: SerializedQuiz =
+            serde_json::from_str(json).context("Malformed saved quiz")?;
+        ensure!(
+            serialized.version == SAVED_QUIZ_FORMAT_VERSION,
+            "Unsupported saved quiz format version {} (expected {})",
+            serialized.version,
+            SAVED_QUIZ_FORMAT_VERSION
+        );
+        let origin = match (&serialized.generated, &serialized.submitted) {
+            (Some(options), None) => Origin::Generated(options.restore()?),
+            (None, Some(submitted)) => Origin::Submitted {
+                submission: Submission::parse(
+                    &submitted.answer,
+                    Some(
+                        submitted
+                            .letters
+                            .try_into()
+                            .context("A quiz needs at least one letter")?,
+                    ),
+                )?,
+                author: UserId(submitted.author),
+                seed: submitted.seed,
+                classroom: submitted.classroom,
+            },
+            _ => {
+                return Err(anyhow!(
+                    "A saved quiz must be either generated or submitted"
+                ))
+            }
+        };
+        Ok(SavedQuiz {
+            origin,
+            started_at: UNIX_EPOCH + Duration::from_secs(serialized.started_at),
+            moves: serialized
+                .moves
+                .iter()
+                .map(SerializedMove::restore)
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Move, Origin, SavedQuiz, SAVED_QUIZ_FORMAT_VERSION};
+    use crate::quiz::{hints::HintTier, submission::Submission};
+    use serenity::model::id::UserId;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn saved_quizzes_survive_serialization() {
+        let at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let saved = SavedQuiz {
+            origin: Origin::Submitted {
+                submission: Submission::parse("(ab|c)*", None).unwrap(),
+                author: UserId(7),
+                seed: 42,
+                classroom: true,
+            },
+            started_at: at,
+            moves: vec![
+                Move::Join(UserId(1)),
+                Move::JoinTeam(UserId(2), "red".to_string()),
+                Move::Query(UserId(1), "abc".to_string(), at),
+                Move::Guess(UserId(2), "(ab)*".to_string()),
+                Move::Undo,
+                Move::Hint(HintTier::UsedAlphabets),
+                Move::Leave(UserId(1)),
+                Move::VoteGiveUp(UserId(2)),
+            ],
+        };
+        assert_eq!(SavedQuiz::from_json(&saved.to_json()).unwrap(), saved);
+    }
+
+    #[test]
+    fn quizzes_saved_in_other_formats_are_rejected() {
+        let json = format!(
+            r#"{{"version":{},"generated":null,"submitted":null,"started_at":0,"moves":[]}}"#,
+            SAVED_QUIZ_FORMAT_VERSION + 1
+        );
+        assert!(SavedQuiz::from_json(&json).is_err());
+        assert!(SavedQuiz::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::regex::DifficultyTier;
+use std::{convert::TryInto, fmt};
+
+/// Parameters of the score awarded for solving a quiz.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoringRules {
+    /// Points for solving a quiz of [DifficultyTier::Easy] without queries or hints
+    pub base: u32,
+    /// Extra points for each tier above [DifficultyTier::Easy]
+    pub tier_bonus: u32,
+    /// Points lost for each distinct word queried in the channel
+    pub per_query: u32,
+    /// Points lost for each point of hint cost (see [super::hints::HintCosts])
+    pub per_hint_point: u32,
+    /// Points a solve is worth however many queries and hints it took
+    pub minimum: u32,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        ScoringRules {
+            base: 100,
+            tier_bonus: 25,
+            per_query: 4,
+            per_hint_point: 10,
+            minimum: 10,
+        }
+    }
+}
+
+/// What a solve took, as far as scoring is concerned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolveRecord {
+    pub tier: DifficultyTier,
+    /// Number of distinct words queried in the channel
+    pub queries: usize,
+    /// Total cost of the hints revealed in the channel
+    pub hint_points: u32,
+}
+
+/// Points awarded for a solve, with the penalties they were reduced by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    pub base: u32,
+    pub query_penalty: u32,
+    pub hint_penalty: u32,
+    pub total: u32,
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} points (base {}, -{} for queries, -{} for hints)",
+            self.total, self.base, self.query_penalty, self.hint_penalty
+        )
+    }
+}
+
+fn tier_rank(tier: DifficultyTier) -> u32 {
+    match tier {
+        DifficultyTier::Easy => 0,
+        DifficultyTier::Medium => 1,
+        DifficultyTier::Hard => 2,
+        DifficultyTier::Expert => 3,
+    }
+}
+
+/// Score of a solve under `rules`: the base for its tier minus penalties
+/// for queries and hints, but never below `rules.minimum`.
+pub fn score(rules: &ScoringRules, solve: &SolveRecord) -> Score {
+    let base = rules.base + rules.tier_bonus * tier_rank(solve.tier);
+    let query_penalty = rules
+        .per_query
+        .saturating_mul(solve.queries.try_into().unwrap_or(u32::MAX));
+    let hint_penalty = rules.per_hint_point.saturating_mul(solve.hint_points);
+    let total = base
+        .saturating_sub(query_penalty)
+        .saturating_sub(hint_penalty)
+        .max(rules.minimum);
+
+    Score {
+        base,
+        query_penalty,
+        hint_penalty,
+        total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{score, ScoringRules, SolveRecord};
+    use crate::regex::DifficultyTier;
+
+    #[test]
+    fn score_decreases_with_queries_and_hints() {
+        let rules = ScoringRules::default();
+        let total = |tier, queries, hint_points| {
+            score(
+                &rules,
+                &SolveRecord {
+                    tier,
+                    queries,
+                    hint_points,
+                },
+            )
+            .total
+        };
+
+        assert_eq!(total(DifficultyTier::Easy, 0, 0), 100);
+        assert_eq!(total(DifficultyTier::Expert, 0, 0), 175);
+        assert_eq!(total(DifficultyTier::Medium, 5, 2), 125 - 20 - 20);
+        assert!(total(DifficultyTier::Hard, 10, 0) < total(DifficultyTier::Hard, 9, 0));
+        assert!(total(DifficultyTier::Hard, 0, 3) < total(DifficultyTier::Hard, 0, 2));
+        assert_eq!(total(DifficultyTier::Easy, 1000, 100), rules.minimum);
+    }
+}
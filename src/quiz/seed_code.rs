@@ -0,0 +1,174 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    quiz::hidden::QuizVariant,
+    regex::{Alphabet, DifficultyPreset},
+};
+use anyhow::anyhow;
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    num::NonZeroU8,
+    str::FromStr,
+};
+use strum::IntoEnumIterator;
+
+/// Prefix of every seed code
+const PREFIX: &str = "RSOUP-";
+
+/// Crockford's base32 digits, which leave out `I`, `L`, `O` and `U` to avoid misreading
+const DIGITS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+const VARIANTS: [QuizVariant; 2] = [QuizVariant::Regex, QuizVariant::Dfa];
+
+const PRESETS: [Option<DifficultyPreset>; 5] = [
+    None,
+    Some(DifficultyPreset::Easy),
+    Some(DifficultyPreset::Normal),
+    Some(DifficultyPreset::Hard),
+    Some(DifficultyPreset::Lunatic),
+];
+
+/// A short code such as `RSOUP-3F9A2K7B` naming a generated quiz,
+/// from which it can be started again in any server.
+///
+/// The seed and the settings are packed into a single number, so that the 32-bit seeds
+/// of fresh quizzes take eight digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeedCode {
+    pub seed: u64,
+    pub letters: NonZeroU8,
+    pub variant: QuizVariant,
+    pub preset: Option<DifficultyPreset>,
+}
+
+impl SeedCode {
+    /// Number of combinations of settings a code may carry.
+    fn settings_count() -> u128 {
+        (Alphabet::iter().count() * VARIANTS.len() * PRESETS.len()) as u128
+    }
+
+    fn settings_index(&self) -> u128 {
+        let variant = VARIANTS.iter().position(|&v| v == self.variant).unwrap();
+        let preset = PRESETS.iter().position(|&p| p == self.preset).unwrap();
+        (((self.letters.get() as usize - 1) * VARIANTS.len() + variant) * PRESETS.len() + preset)
+            as u128
+    }
+}
+
+impl Display for SeedCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut value = self.seed as u128 * Self::settings_count() + self.settings_index();
+        let mut digits = vec![];
+        loop {
+            digits.push(DIGITS[(value % 32) as usize]);
+            value /= 32;
+            if value == 0 {
+                break;
+            }
+        }
+        digits.reverse();
+        write!(f, "{PREFIX}{}", String::from_utf8_lossy(&digits))
+    }
+}
+
+impl FromStr for SeedCode {
+    type Err = anyhow::Error;
+
+    /// Parse a code, ignoring case and the prefix and reading `I`, `L` as `1` and `O` as `0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            anyhow!(
+                "`{}` is not a seed code like `{}3F9A2K7B`.",
+                s.trim(),
+                PREFIX
+            )
+        };
+        let code = s.trim().to_ascii_uppercase();
+        let code = code.strip_prefix(PREFIX).unwrap_or(&code);
+        if code.is_empty() || code.len() > 26 {
+            return Err(invalid());
+        }
+        let mut value = 0u128;
+        for c in code.chars() {
+            let c = match c {
+                'I' | 'L' => '1',
+                'O' => '0',
+                c => c,
+            };
+            let digit = DIGITS
+                .iter()
+                .position(|&d| d as char == c)
+                .ok_or_else(invalid)?;
+            value = value
+                .checked_mul(32)
+                .and_then(|value| value.checked_add(digit as u128))
+                .ok_or_else(invalid)?;
+        }
+
+        let seed = u64::try_from(value / Self::settings_count()).map_err(|_| invalid())?;
+        let settings = (value % Self::settings_count()) as usize;
+        let preset = PRESETS[settings % PRESETS.len()];
+        let settings = settings / PRESETS.len();
+        let variant = VARIANTS[settings % VARIANTS.len()];
+        let letters = NonZeroU8::new((settings / VARIANTS.len() + 1) as u8).unwrap();
+        Ok(SeedCode {
+            seed,
+            letters,
+            variant,
+            preset,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeedCode;
+    use crate::{quiz::hidden::QuizVariant, regex::DifficultyPreset};
+    use std::num::NonZeroU8;
+
+    #[test]
+    fn seed_codes_round_trip() {
+        let code = SeedCode {
+            seed: u32::MAX.into(),
+            letters: NonZeroU8::new(10).unwrap(),
+            variant: QuizVariant::Dfa,
+            preset: Some(DifficultyPreset::Lunatic),
+        };
+        let printed = code.to_string();
+        assert!(printed.starts_with("RSOUP-"));
+        assert_eq!(printed.len(), "RSOUP-".len() + 8);
+        assert_eq!(printed.parse::<SeedCode>().unwrap(), code);
+        let lenient = printed.trim_start_matches("RSOUP-").to_lowercase();
+        assert_eq!(lenient.parse::<SeedCode>().unwrap(), code);
+
+        let code = SeedCode {
+            seed: u64::MAX,
+            letters: NonZeroU8::new(1).unwrap(),
+            variant: QuizVariant::Regex,
+            preset: None,
+        };
+        assert_eq!(code.to_string().parse::<SeedCode>().unwrap(), code);
+
+        assert!("RSOUP-".parse::<SeedCode>().is_err());
+        assert!("RSOUP-3F9A2U".parse::<SeedCode>().is_err());
+        assert!("RSOUP-ZZZZZZZZZZZZZZZZ".parse::<SeedCode>().is_err());
+    }
+}
@@ -0,0 +1,139 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use anyhow::anyhow;
+use std::{fmt::Display, str::FromStr};
+
+/// Kind of output that may spoil the answer for spectators in the channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpoilerKind {
+    /// The answer revealed when a quiz ends, with its diagrams and analysis
+    Answers,
+    /// Words on which a wrong guess disagrees with the answer
+    Counterexamples,
+    /// Answers listed by `/archive`
+    Archive,
+}
+
+impl SpoilerKind {
+    pub const ALL: [SpoilerKind; 3] = [
+        SpoilerKind::Answers,
+        SpoilerKind::Counterexamples,
+        SpoilerKind::Archive,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpoilerKind::Answers => "answers",
+            SpoilerKind::Counterexamples => "counterexamples",
+            SpoilerKind::Archive => "archive",
+        }
+    }
+}
+
+impl FromStr for SpoilerKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SpoilerKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| anyhow!("unknown kind of output: {}", s))
+    }
+}
+
+/// Which outputs are hidden behind Discord spoiler tags, as configured for a guild.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spoilers {
+    pub answers: bool,
+    pub counterexamples: bool,
+    pub archive: bool,
+}
+
+impl Default for Spoilers {
+    fn default() -> Self {
+        Spoilers {
+            answers: true,
+            counterexamples: true,
+            archive: true,
+        }
+    }
+}
+
+impl Spoilers {
+    pub fn get(&self, kind: SpoilerKind) -> bool {
+        match kind {
+            SpoilerKind::Answers => self.answers,
+            SpoilerKind::Counterexamples => self.counterexamples,
+            SpoilerKind::Archive => self.archive,
+        }
+    }
+
+    pub fn set(&mut self, kind: SpoilerKind, enabled: bool) {
+        match kind {
+            SpoilerKind::Answers => self.answers = enabled,
+            SpoilerKind::Counterexamples => self.counterexamples = enabled,
+            SpoilerKind::Archive => self.archive = enabled,
+        }
+    }
+
+    /// `text` in spoiler tags if outputs of `kind` are spoilered.
+    pub fn wrap(&self, kind: SpoilerKind, text: impl Display) -> String {
+        if self.get(kind) {
+            format!("||{text}||")
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Name of an attached file showing the answer, which Discord blurs if it starts with `SPOILER_`.
+    pub fn file_name(&self, name: &str) -> String {
+        if self.answers {
+            format!("SPOILER_{name}")
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SpoilerKind, Spoilers};
+
+    #[test]
+    fn spoilers_wrap_only_the_configured_outputs() {
+        let mut spoilers = Spoilers::default();
+        assert_eq!(spoilers.wrap(SpoilerKind::Answers, "`a*`"), "||`a*`||");
+        assert_eq!(spoilers.file_name("answer.svg"), "SPOILER_answer.svg");
+
+        spoilers.set(SpoilerKind::Answers, false);
+        assert_eq!(spoilers.wrap(SpoilerKind::Answers, "`a*`"), "`a*`");
+        assert_eq!(spoilers.file_name("answer.svg"), "answer.svg");
+        assert_eq!(spoilers.wrap(SpoilerKind::Archive, "`a*`"), "||`a*`||");
+    }
+
+    #[test]
+    fn spoiler_kinds_are_parsed_from_their_names() {
+        for kind in SpoilerKind::ALL {
+            assert_eq!(kind.name().parse::<SpoilerKind>().unwrap(), kind);
+        }
+        assert!("diagrams".parse::<SpoilerKind>().is_err());
+    }
+}
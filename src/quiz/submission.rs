@@ -0,0 +1,96 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+use crate::regex::{check_submitted_quiz, Alphabet, RegexAst};
+use anyhow::anyhow;
+use rand::Rng;
+use std::{convert::TryInto, num::NonZeroU8};
+use strum::IntoEnumIterator;
+
+/// Fewest letters a submitted quiz is played over
+const MINIMUM_SUBMITTED_LETTERS: u8 = 2;
+
+/// An expression a player has submitted by DM, waiting to be hosted in a channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Submission {
+    pub regex: RegexAst,
+    /// Number of letters in the domain of the quiz
+    pub letters: NonZeroU8,
+}
+
+impl Submission {
+    /// Parse and check `input` as a quiz over `letters` alphabets,
+    /// defaulting to as many as the expression uses.
+    pub fn new<R: Rng + ?Sized>(
+        input: &str,
+        letters: Option<NonZeroU8>,
+        rng: &mut R,
+    ) -> anyhow::Result<Self> {
+        let submission = Self::parse(input, letters)?;
+        check_submitted_quiz(&submission.regex, submission.letters, rng)?;
+        Ok(submission)
+    }
+
+    /// Parse `input` as a quiz over `letters` alphabets without checking whether it is fit to play,
+    /// for teachers who know what their class needs.
+    pub fn parse(input: &str, letters: Option<NonZeroU8>) -> anyhow::Result<Self> {
+        let regex = RegexAst::parse_str(input)?;
+        let used = regex.used_alphabets();
+        let needed = Alphabet::iter()
+            .enumerate()
+            .filter(|(_, alphabet)| used.contains(alphabet))
+            .map(|(i, _)| i + 1)
+            .max()
+            .unwrap_or_default()
+            .max(MINIMUM_SUBMITTED_LETTERS.into());
+        let most = Alphabet::iter().count();
+        let letters = match letters {
+            Some(letters) if usize::from(letters.get()) > most => {
+                return Err(anyhow!("The number of letters must be from 1 to {most}."))
+            }
+            Some(letters) if usize::from(letters.get()) < needed => {
+                return Err(anyhow!(
+                    "The expression uses {needed} letters, more than {letters}."
+                ))
+            }
+            Some(letters) => letters,
+            None => (needed as u8).try_into()?,
+        };
+        Ok(Self { regex, letters })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Submission;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::num::NonZeroU8;
+
+    #[test]
+    fn submission_is_played_over_the_letters_it_uses() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let submission = Submission::new("(b|ab*ab*a)*", None, &mut rng).unwrap();
+        assert_eq!(submission.letters.get(), 2);
+        assert!(Submission::new("(b|ab*ab*a)*", NonZeroU8::new(1), &mut rng).is_err());
+        assert!(Submission::new("(a|b", None, &mut rng).is_err());
+        assert!(Submission::new("ab|ba", None, &mut rng).is_err());
+        assert!(Submission::parse("ab|ba", None).is_ok());
+        assert!(Submission::parse("ab|ba", NonZeroU8::new(10)).is_ok());
+        assert!(Submission::parse("ab|ba", NonZeroU8::new(11)).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+use indexmap::{IndexMap, IndexSet};
+use serenity::model::id::UserId;
+use thiserror::Error;
+
+/// Longest name a team may have
+const MAX_TEAM_NAME_LENGTH: usize = 32;
+
+/// Queries and guesses each team of a cooperative quiz shares among its members.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TeamBudget {
+    /// Number of distinct words each team may query, or `None` if unlimited
+    pub queries: Option<usize>,
+    /// Number of guesses each team may make, or `None` if unlimited
+    pub guesses: Option<usize>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TeamError {
+    #[error("Join a team with `/team` first.")]
+    NoTeam,
+    #[error("You are already in team {0}.")]
+    AlreadyInTeam(String),
+    #[error("A team name has 1 to {} characters.", MAX_TEAM_NAME_LENGTH)]
+    InvalidName,
+    #[error("Your team has used all of its {0} queries. Only guesses are allowed now.")]
+    QueriesExhausted(usize),
+    #[error("Your team has used all of its {0} guesses.")]
+    GuessesExhausted(usize),
+}
+
+/// Players sharing one budget of queries and guesses.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Team {
+    pub members: IndexSet<UserId>,
+    /// Distinct words the members have queried
+    words: IndexSet<String>,
+    guesses: usize,
+}
+
+impl Team {
+    /// Number of distinct words the members have queried.
+    pub fn queries(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn guesses(&self) -> usize {
+        self.guesses
+    }
+}
+
+/// The teams of a cooperative quiz, by name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Teams {
+    budget: TeamBudget,
+    teams: IndexMap<String, Team>,
+}
+
+impl Teams {
+    pub fn new(budget: TeamBudget) -> Self {
+        Self {
+            budget,
+            teams: IndexMap::new(),
+        }
+    }
+
+    pub fn budget(&self) -> TeamBudget {
+        self.budget
+    }
+
+    /// Add `user` to the team `name`, forming it if nobody has joined it yet.
+    pub fn join(&mut self, user: UserId, name: &str) -> Result<(), TeamError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_TEAM_NAME_LENGTH {
+            return Err(TeamError::InvalidName);
+        }
+        if let Some((current, _)) = self.team_of(user) {
+            return Err(TeamError::AlreadyInTeam(current.to_string()));
+        }
+        self.teams
+            .entry(name.to_string())
+            .or_default()
+            .members
+            .insert(user);
+        Ok(())
+    }
+
+    /// The name and the team `user` is in.
+    pub fn team_of(&self, user: UserId) -> Option<(&str, &Team)> {
+        self.teams
+            .iter()
+            .find(|(_, team)| team.members.contains(&user))
+            .map(|(name, team)| (name.as_str(), team))
+    }
+
+    fn team_of_mut(&mut self, user: UserId) -> Result<&mut Team, TeamError> {
+        self.teams
+            .values_mut()
+            .find(|team| team.members.contains(&user))
+            .ok_or(TeamError::NoTeam)
+    }
+
+    /// Charge the query of `word` by `user` to their team;
+    /// words the team has queried before are free.
+    pub fn query(&mut self, user: UserId, word: &str) -> Result<(), TeamError> {
+        let max = self.budget.queries;
+        let team = self.team_of_mut(user)?;
+        if team.words.contains(word) {
+            return Ok(());
+        }
+        if let Some(max) = max.filter(|max| team.words.len() >= *max) {
+            return Err(TeamError::QueriesExhausted(max));
+        }
+        team.words.insert(word.to_string());
+        Ok(())
+    }
+
+    /// Charge a guess by `user` to their team.
+    pub fn guess(&mut self, user: UserId) -> Result<(), TeamError> {
+        let max = self.budget.guesses;
+        let team = self.team_of_mut(user)?;
+        if let Some(max) = max.filter(|max| team.guesses >= *max) {
+            return Err(TeamError::GuessesExhausted(max));
+        }
+        team.guesses += 1;
+        Ok(())
+    }
+
+    /// Refund the query of `word` to the team of `user`.
+    pub fn forget_query(&mut self, user: UserId, word: &str) {
+        if let Ok(team) = self.team_of_mut(user) {
+            team.words.shift_remove(word);
+        }
+    }
+
+    /// Refund a guess to the team of `user`.
+    pub fn forget_guess(&mut self, user: UserId) {
+        if let Ok(team) = self.team_of_mut(user) {
+            team.guesses = team.guesses.saturating_sub(1);
+        }
+    }
+
+    /// Number of distinct words the team of `user` may still query, or `None` if unlimited.
+    pub fn remaining_queries(&self, user: UserId) -> Option<usize> {
+        let (_, team) = self.team_of(user)?;
+        self.budget
+            .queries
+            .map(|max| max.saturating_sub(team.words.len()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Team)> {
+        self.teams.iter().map(|(name, team)| (name.as_str(), team))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TeamBudget, TeamError, Teams};
+    use serenity::model::id::UserId;
+
+    #[test]
+    fn teams_share_their_budget() {
+        let mut teams = Teams::new(TeamBudget {
+            queries: Some(2),
+            guesses: Some(1),
+        });
+        let (alice, bob, carol) = (UserId(1), UserId(2), UserId(3));
+        assert_eq!(teams.query(alice, "a"), Err(TeamError::NoTeam));
+        teams.join(alice, "red").unwrap();
+        teams.join(bob, " red ").unwrap();
+        teams.join(carol, "blue").unwrap();
+        assert_eq!(
+            teams.join(alice, "blue"),
+            Err(TeamError::AlreadyInTeam("red".to_string()))
+        );
+        assert_eq!(teams.join(UserId(4), "  "), Err(TeamError::InvalidName));
+
+        teams.query(alice, "a").unwrap();
+        teams.query(bob, "a").unwrap();
+        teams.query(bob, "b").unwrap();
+        assert_eq!(teams.remaining_queries(alice), Some(0));
+        assert_eq!(teams.query(alice, "c"), Err(TeamError::QueriesExhausted(2)));
+        assert_eq!(teams.remaining_queries(carol), Some(2));
+        teams.forget_query(bob, "b");
+        teams.query(alice, "c").unwrap();
+
+        teams.guess(bob).unwrap();
+        assert_eq!(teams.guess(alice), Err(TeamError::GuessesExhausted(1)));
+        teams.forget_guess(bob);
+        teams.guess(alice).unwrap();
+        teams.guess(carol).unwrap();
+
+        let (name, team) = teams.team_of(bob).unwrap();
+        assert_eq!((name, team.queries(), team.guesses()), ("red", 2, 1));
+    }
+}
@@ -0,0 +1,89 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use anyhow::{anyhow, ensure};
+use std::time::Duration;
+
+/// Longest time limit a quiz may have
+pub const MAX_TIME_LIMIT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Parse a time limit such as `10m`, `90s` or `1h30m`; a bare number is taken as minutes.
+pub fn parse_time_limit(input: &str) -> anyhow::Result<Duration> {
+    let input = input.trim();
+    if let Ok(minutes) = input.parse::<u64>() {
+        return checked(Duration::from_secs(minutes.saturating_mul(60)));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in input.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let value = number
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("`{}` is not a time limit such as `10m`", input))?;
+                let unit = match c {
+                    'h' => 60 * 60,
+                    'm' => 60,
+                    _ => 1,
+                };
+                total = total.saturating_add(value.saturating_mul(unit));
+                number.clear();
+            }
+            _ => return Err(anyhow!("`{}` is not a time limit such as `10m`", input)),
+        }
+    }
+    ensure!(
+        number.is_empty() && !input.is_empty(),
+        "`{}` is not a time limit such as `10m`",
+        input
+    );
+    checked(Duration::from_secs(total))
+}
+
+fn checked(limit: Duration) -> anyhow::Result<Duration> {
+    ensure!(limit > Duration::ZERO, "The time limit must be positive");
+    ensure!(
+        limit <= MAX_TIME_LIMIT,
+        "The time limit must be at most {} hours",
+        MAX_TIME_LIMIT.as_secs() / 3600
+    );
+    Ok(limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_time_limit;
+    use std::time::Duration;
+
+    #[test]
+    fn time_limits_are_parsed() {
+        assert_eq!(parse_time_limit("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_time_limit("10").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_time_limit("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(
+            parse_time_limit("1h30m").unwrap(),
+            Duration::from_secs(5400)
+        );
+        for invalid in ["", "m", "10x", "10m5", "0m", "25h"] {
+            assert!(parse_time_limit(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,354 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use indexmap::IndexSet;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serenity::model::id::{ChannelId, UserId};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// Time from the start of a tournament by which its final is decided
+pub const TOURNAMENT_LENGTH: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Error in a command on a tournament.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum TournamentError {
+    #[error("You are already registered")]
+    AlreadyRegistered,
+    #[error("The tournament has already started")]
+    AlreadyStarted,
+    #[error("At least two players have to register")]
+    NotEnoughPlayers,
+    #[error("The tournament has not started yet")]
+    NotStarted,
+    #[error("You have no match to play now")]
+    NoPendingMatch,
+    #[error("The match is already decided or is not being played")]
+    MatchNotPending,
+    #[error("<@{0}> is not a player of the match")]
+    NotInMatch(u64),
+}
+
+/// Position of a match in the bracket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MatchId {
+    /// Round of the match, `0` being the first round
+    pub round: usize,
+    /// Position of the match in its round, from the top of the bracket
+    pub index: usize,
+}
+
+/// A head-to-head regex race: both players get the quiz generated from `seed`,
+/// and the first to guess its answer wins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// The players, one of them missing if the other has a bye
+    pub players: [Option<UserId>; 2],
+    pub winner: Option<UserId>,
+    pub seed: u64,
+    /// Whether the match was decided by the deadline rather than played
+    pub walkover: bool,
+}
+
+impl Match {
+    fn new(players: [Option<UserId>; 2], seed: u64) -> Self {
+        Match {
+            players,
+            winner: None,
+            seed,
+            walkover: false,
+        }
+    }
+
+    pub fn has_player(&self, user: UserId) -> bool {
+        self.players.contains(&Some(user))
+    }
+}
+
+/// A single-elimination tournament of regex races held over [TOURNAMENT_LENGTH],
+/// each round of which has an equal share of the time.
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    /// Channel where the tournament was opened and its results are announced
+    channel: ChannelId,
+    seed: u64,
+    players: IndexSet<UserId>,
+    /// Matches of each round played so far, empty until the tournament starts
+    rounds: Vec<Vec<Match>>,
+    started_at: Option<SystemTime>,
+}
+
+impl Tournament {
+    /// Open a tournament for registration.
+    pub fn new(channel: ChannelId, seed: u64) -> Self {
+        Tournament {
+            channel,
+            seed,
+            players: IndexSet::new(),
+            rounds: vec![],
+            started_at: None,
+        }
+    }
+
+    pub fn channel(&self) -> ChannelId {
+        self.channel
+    }
+
+    pub fn players(&self) -> &IndexSet<UserId> {
+        &self.players
+    }
+
+    pub fn rounds(&self) -> &[Vec<Match>] {
+        &self.rounds
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    pub fn register(&mut self, user: UserId) -> Result<(), TournamentError> {
+        if self.is_started() {
+            return Err(TournamentError::AlreadyStarted);
+        }
+        self.players
+            .insert(user)
+            .then_some(())
+            .ok_or(TournamentError::AlreadyRegistered)
+    }
+
+    /// Draw the bracket of the registered players at random and start the first round.
+    /// Players are paired so that byes, if any, are spread over the bracket.
+    pub fn start(&mut self, now: SystemTime) -> Result<(), TournamentError> {
+        if self.is_started() {
+            return Err(TournamentError::AlreadyStarted);
+        }
+        if self.players.len() < 2 {
+            return Err(TournamentError::NotEnoughPlayers);
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut slots = self.players.iter().copied().map(Some).collect::<Vec<_>>();
+        slots.shuffle(&mut rng);
+        let size = slots.len().next_power_of_two();
+        slots.resize(size, None);
+        // slot `i` meets slot `size - 1 - i`, and more than half of the slots are filled,
+        // so that no match is between two byes
+        let first_round = (0..size / 2)
+            .map(|i| Match::new([slots[i], slots[size - 1 - i]], rng.gen()))
+            .collect();
+
+        self.rounds.push(first_round);
+        self.started_at = Some(now);
+        self.advance();
+        Ok(())
+    }
+
+    /// Number of rounds from the first round to the final.
+    pub fn round_count(&self) -> usize {
+        self.players.len().next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// Time by which the matches of `round` have to be played.
+    pub fn deadline(&self, round: usize) -> Option<SystemTime> {
+        let share = TOURNAMENT_LENGTH / self.round_count().max(1) as u32;
+        self.started_at
+            .map(|started_at| started_at + share * (round + 1) as u32)
+    }
+
+    /// The winner of the final, once it is decided.
+    pub fn champion(&self) -> Option<UserId> {
+        match self.rounds.last() {
+            Some(last) if last.len() == 1 => last[0].winner,
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, id: MatchId) -> Option<&Match> {
+        self.rounds.get(id.round)?.get(id.index)
+    }
+
+    /// The match `user` has to play in the current round.
+    pub fn pending_match(&self, user: UserId) -> Result<(MatchId, &Match), TournamentError> {
+        let round = self
+            .rounds
+            .len()
+            .checked_sub(1)
+            .ok_or(TournamentError::NotStarted)?;
+        self.rounds[round]
+            .iter()
+            .enumerate()
+            .find(|(_, m)| m.winner.is_none() && m.has_player(user))
+            .map(|(index, m)| (MatchId { round, index }, m))
+            .ok_or(TournamentError::NoPendingMatch)
+    }
+
+    /// Record that `winner` won the match `id`, advancing the bracket.
+    pub fn report(&mut self, id: MatchId, winner: UserId) -> Result<(), TournamentError> {
+        if id.round + 1 != self.rounds.len() {
+            return Err(TournamentError::MatchNotPending);
+        }
+        let m = self.rounds[id.round]
+            .get_mut(id.index)
+            .filter(|m| m.winner.is_none())
+            .ok_or(TournamentError::MatchNotPending)?;
+        if !m.has_player(winner) {
+            return Err(TournamentError::NotInMatch(winner.0));
+        }
+        m.winner = Some(winner);
+        self.advance();
+        Ok(())
+    }
+
+    /// Decide the matches whose deadlines have passed by `now` by walkover,
+    /// returning them. The winner of a walkover is chosen by a coin toss seeded by the match.
+    pub fn expire(&mut self, now: SystemTime) -> Vec<MatchId> {
+        let mut expired = vec![];
+        while self.champion().is_none() {
+            let round = match self.rounds.len().checked_sub(1) {
+                Some(round) if self.deadline(round).is_some_and(|deadline| deadline <= now) => {
+                    round
+                }
+                _ => break,
+            };
+            for (index, m) in self.rounds[round].iter_mut().enumerate() {
+                if m.winner.is_none() {
+                    m.winner = m.players[(m.seed % 2) as usize];
+                    m.walkover = true;
+                    expired.push(MatchId { round, index });
+                }
+            }
+            self.advance();
+        }
+        expired
+    }
+
+    /// Give byes to players without opponents and start the next round
+    /// as long as every match of the current round is decided.
+    fn advance(&mut self) {
+        while let Some(round) = self.rounds.last_mut() {
+            for m in round.iter_mut() {
+                if let [Some(player), None] | [None, Some(player)] = m.players {
+                    m.winner = Some(player);
+                }
+            }
+            if round.len() < 2 || round.iter().any(|m| m.winner.is_none()) {
+                return;
+            }
+
+            let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.rounds.len() as u64));
+            let winners = self.rounds[self.rounds.len() - 1]
+                .iter()
+                .map(|m| m.winner)
+                .collect::<Vec<_>>();
+            let next = winners
+                .chunks(2)
+                .map(|pair| Match::new([pair[0], pair[1]], rng.gen()))
+                .collect();
+            self.rounds.push(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MatchId, Tournament, TournamentError, TOURNAMENT_LENGTH};
+    use serenity::model::id::{ChannelId, UserId};
+    use std::time::SystemTime;
+
+    #[test]
+    fn tournament_crowns_a_champion() {
+        let now = SystemTime::now();
+        let mut tournament = Tournament::new(ChannelId(1), 42);
+        tournament.register(UserId(1)).unwrap();
+        assert_eq!(
+            tournament.register(UserId(1)),
+            Err(TournamentError::AlreadyRegistered)
+        );
+        assert_eq!(
+            tournament.start(now),
+            Err(TournamentError::NotEnoughPlayers)
+        );
+        for user in 2..=5 {
+            tournament.register(UserId(user)).unwrap();
+        }
+        tournament.start(now).unwrap();
+        assert_eq!(
+            tournament.register(UserId(6)),
+            Err(TournamentError::AlreadyStarted)
+        );
+
+        // 5 players make a bracket of 8 with 3 byes
+        assert_eq!(tournament.round_count(), 3);
+        assert_eq!(tournament.rounds()[0].len(), 4);
+        assert_eq!(
+            tournament.rounds()[0]
+                .iter()
+                .filter(|m| m.players.contains(&None))
+                .count(),
+            3
+        );
+
+        while tournament.champion().is_none() {
+            let round = tournament.rounds().len() - 1;
+            let pending = tournament.rounds()[round]
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.winner.is_none())
+                .map(|(index, m)| (MatchId { round, index }, m.players))
+                .collect::<Vec<_>>();
+            assert!(!pending.is_empty());
+            for (id, players) in pending {
+                let winner = players[0].unwrap();
+                assert_eq!(tournament.pending_match(winner).unwrap().0, id);
+                assert_eq!(
+                    tournament.report(id, UserId(100)),
+                    Err(TournamentError::NotInMatch(100))
+                );
+                tournament.report(id, winner).unwrap();
+                assert_eq!(
+                    tournament.report(id, winner),
+                    Err(TournamentError::MatchNotPending)
+                );
+            }
+        }
+        assert_eq!(tournament.rounds().len(), 3);
+        assert!(tournament.champion().is_some());
+    }
+
+    #[test]
+    fn tournament_ends_within_its_length() {
+        let now = SystemTime::now();
+        let mut tournament = Tournament::new(ChannelId(1), 7);
+        for user in 1..=8 {
+            tournament.register(UserId(user)).unwrap();
+        }
+        tournament.start(now).unwrap();
+
+        assert!(tournament.expire(now).is_empty());
+        let expired = tournament.expire(tournament.deadline(0).unwrap());
+        assert_eq!(expired.len(), 4);
+        assert_eq!(tournament.rounds().len(), 2);
+        assert!(tournament.champion().is_none());
+
+        let expired = tournament.expire(now + TOURNAMENT_LENGTH);
+        assert_eq!(expired.len(), 3);
+        assert!(tournament.champion().is_some());
+        assert!(tournament.rounds()[2][0].walkover);
+    }
+}
@@ -0,0 +1,101 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use indexmap::IndexSet;
+use serenity::model::id::UserId;
+
+/// State of a vote after a ballot is cast.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoteStatus {
+    /// `votes` of the eligible players agree, and `needed` are required to pass
+    Pending {
+        votes: usize,
+        needed: usize,
+    },
+    Passed,
+}
+
+/// A vote among the players of a quiz, passing once a strict majority of them agree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Vote {
+    ballots: IndexSet<UserId>,
+}
+
+impl Vote {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `user` agrees, returning whether they had not agreed yet.
+    pub fn cast(&mut self, user: UserId) -> bool {
+        self.ballots.insert(user)
+    }
+
+    /// Status of the vote among `eligible` players; ballots of other players are not counted,
+    /// so that the vote reflects players who have left since.
+    pub fn status(&self, eligible: &IndexSet<UserId>) -> VoteStatus {
+        let votes = self
+            .ballots
+            .iter()
+            .filter(|user| eligible.contains(*user))
+            .count();
+        let needed = eligible.len() / 2 + 1;
+        if votes >= needed {
+            VoteStatus::Passed
+        } else {
+            VoteStatus::Pending { votes, needed }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Vote, VoteStatus};
+    use indexmap::IndexSet;
+    use serenity::model::id::UserId;
+
+    #[test]
+    fn vote_passes_by_majority() {
+        let eligible = (1..=4).map(UserId).collect::<IndexSet<_>>();
+        let mut vote = Vote::new();
+        assert!(vote.cast(UserId(1)));
+        assert!(!vote.cast(UserId(1)));
+        assert!(vote.cast(UserId(9)));
+        assert_eq!(
+            vote.status(&eligible),
+            VoteStatus::Pending {
+                votes: 1,
+                needed: 3
+            }
+        );
+        vote.cast(UserId(2));
+        assert_eq!(
+            vote.status(&eligible),
+            VoteStatus::Pending {
+                votes: 2,
+                needed: 3
+            }
+        );
+        vote.cast(UserId(3));
+        assert_eq!(vote.status(&eligible), VoteStatus::Passed);
+
+        let eligible = (1..=1).map(UserId).collect::<IndexSet<_>>();
+        assert_eq!(vote.status(&eligible), VoteStatus::Passed);
+    }
+}
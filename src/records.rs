@@ -0,0 +1,278 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+use crate::{
+    database::{from_sql_id, to_sql_id},
+    puzzle_bank::PlayOutcome,
+    regex::DifficultyTier,
+};
+use anyhow::Context;
+use indoc::{formatdoc, indoc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serenity::model::id::{GuildId, UserId};
+use std::{
+    fmt::{Display, Formatter},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Tiers records are kept for, from the easiest
+pub const TIERS: [DifficultyTier; 4] = [
+    DifficultyTier::Easy,
+    DifficultyTier::Medium,
+    DifficultyTier::Hard,
+    DifficultyTier::Expert,
+];
+
+/// What a record is the best of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordKind {
+    /// Fewest distinct words queried, the faster solve breaking ties
+    FewestQueries,
+    /// Shortest time from the start of the quiz, the fewer queries breaking ties
+    Fastest,
+}
+
+impl RecordKind {
+    /// Order of solves from the best, the earlier solve breaking exact ties.
+    fn order(self) -> &'static str {
+        match self {
+            RecordKind::FewestQueries => "queries, duration_ms, rowid",
+            RecordKind::Fastest => "duration_ms, queries, rowid",
+        }
+    }
+
+    /// Key of a solve of `queries` in `duration`, smaller for better solves.
+    fn key(self, queries: u32, duration: Duration) -> (u128, u128) {
+        match self {
+            RecordKind::FewestQueries => (queries.into(), duration.as_millis()),
+            RecordKind::Fastest => (duration.as_millis(), queries.into()),
+        }
+    }
+}
+
+impl Display for RecordKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RecordKind::FewestQueries => "fewest queries",
+            RecordKind::Fastest => "fastest time",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The best solve of a kind among the quizzes of a tier.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub kind: RecordKind,
+    pub tier: DifficultyTier,
+    pub holder: UserId,
+    /// Guild the record was set in
+    pub guild: GuildId,
+    pub queries: u32,
+    pub duration: Duration,
+    pub set_at: SystemTime,
+}
+
+/// A record beaten by a new solve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrokenRecord {
+    /// Whether the record was held across every guild, rather than in the guild of the solve
+    pub global: bool,
+    pub previous: Record,
+}
+
+/// Solves of every guild by the tier of the quiz, from which the records are drawn,
+/// stored in an SQLite database.
+pub struct Records<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> Records<'a> {
+    /// Create the tables of the records in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS record_solves (
+                    tier INTEGER NOT NULL,
+                    guild_id INTEGER NOT NULL,
+                    user_id INTEGER NOT NULL,
+                    queries INTEGER NOT NULL,
+                    duration_ms INTEGER NOT NULL,
+                    solved_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS record_solves_by_tier
+                    ON record_solves (tier, guild_id);
+            "})
+            .context("failed to initialize the records")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        Records { connection }
+    }
+
+    /// Record that `user` solved a quiz of `tier` in `guild` at `at`,
+    /// returning the records it broke in the guild and across every guild.
+    /// The first solve of a tier sets its records without breaking any.
+    pub fn record_solve(
+        &self,
+        guild: GuildId,
+        tier: DifficultyTier,
+        user: UserId,
+        outcome: &PlayOutcome,
+        at: SystemTime,
+    ) -> anyhow::Result<Vec<BrokenRecord>> {
+        let mut broken = vec![];
+        for kind in [RecordKind::FewestQueries, RecordKind::Fastest] {
+            for global in [false, true] {
+                let scope = if global { None } else { Some(guild) };
+                if let Some(previous) = self.best(kind, tier, scope)? {
+                    if kind.key(outcome.queries, outcome.duration)
+                        < kind.key(previous.queries, previous.duration)
+                    {
+                        broken.push(BrokenRecord { global, previous });
+                    }
+                }
+            }
+        }
+
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO record_solves (tier, guild_id, user_id, queries, duration_ms, solved_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "},
+                params![
+                    tier_index(tier),
+                    to_sql_id(guild.0),
+                    to_sql_id(user.0),
+                    outcome.queries,
+                    outcome.duration.as_millis() as i64,
+                    at.duration_since(UNIX_EPOCH)
+                        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+                ],
+            )
+            .context("failed to record a solve")?;
+        Ok(broken)
+    }
+
+    /// The record of `kind` among quizzes of `tier` solved in `guild`, or in every guild if `None`.
+    pub fn best(
+        &self,
+        kind: RecordKind,
+        tier: DifficultyTier,
+        guild: Option<GuildId>,
+    ) -> anyhow::Result<Option<Record>> {
+        self.connection
+            .query_row(
+                &formatdoc! {"
+                    SELECT guild_id, user_id, queries, duration_ms, solved_at
+                    FROM record_solves
+                    WHERE tier = ?1 AND (?2 IS NULL OR guild_id = ?2)
+                    ORDER BY {order}
+                    LIMIT 1
+                ", order = kind.order()},
+                params![tier_index(tier), guild.map(|guild| to_sql_id(guild.0))],
+                |row| {
+                    Ok(Record {
+                        kind,
+                        tier,
+                        guild: GuildId(from_sql_id(row.get(0)?)),
+                        holder: UserId(from_sql_id(row.get(1)?)),
+                        queries: row.get(2)?,
+                        duration: Duration::from_millis(row.get::<_, i64>(3)? as u64),
+                        set_at: UNIX_EPOCH + Duration::from_secs(row.get::<_, i64>(4)? as u64),
+                    })
+                },
+            )
+            .optional()
+            .context("failed to query a record")
+    }
+
+    /// Every record held in `guild`, or across every guild if `None`, from the easiest tier.
+    pub fn all(&self, guild: Option<GuildId>) -> anyhow::Result<Vec<Record>> {
+        let mut records = vec![];
+        for tier in TIERS {
+            for kind in [RecordKind::FewestQueries, RecordKind::Fastest] {
+                records.extend(self.best(kind, tier, guild)?);
+            }
+        }
+        Ok(records)
+    }
+}
+
+fn tier_index(tier: DifficultyTier) -> i64 {
+    TIERS.iter().position(|t| *t == tier).unwrap_or_default() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordKind;
+    use crate::{database::Database, puzzle_bank::PlayOutcome, regex::DifficultyTier};
+    use serenity::model::id::{GuildId, UserId};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn solve(queries: u32, seconds: u64) -> PlayOutcome {
+        PlayOutcome {
+            queries,
+            duration: Duration::from_secs(seconds),
+            solved: true,
+        }
+    }
+
+    #[test]
+    fn records_are_broken_in_the_guild_and_globally() {
+        let database = Database::open_in_memory().unwrap();
+        let records = database.records();
+        let (home, away) = (GuildId(1), GuildId(2));
+        let hard = DifficultyTier::Hard;
+
+        let first = records
+            .record_solve(home, hard, UserId(1), &solve(8, 120), UNIX_EPOCH)
+            .unwrap();
+        assert!(first.is_empty());
+
+        // fewer queries but slower, in another guild: only the global query record falls
+        let broken = records
+            .record_solve(away, hard, UserId(2), &solve(5, 300), UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(broken.len(), 1);
+        assert!(broken[0].global);
+        assert_eq!(broken[0].previous.kind, RecordKind::FewestQueries);
+        let broken = records
+            .record_solve(away, hard, UserId(3), &solve(4, 200), UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(broken.len(), 3);
+        assert!(broken
+            .iter()
+            .all(|broken| broken.previous.holder == UserId(2)));
+
+        let best = |kind, guild| records.best(kind, hard, guild).unwrap().unwrap();
+        assert_eq!(
+            best(RecordKind::FewestQueries, Some(home)).holder,
+            UserId(1)
+        );
+        assert_eq!(best(RecordKind::FewestQueries, None).holder, UserId(3));
+        assert_eq!(best(RecordKind::Fastest, None).holder, UserId(1));
+        assert_eq!(records.all(Some(home)).unwrap().len(), 2);
+        assert!(records
+            .best(RecordKind::Fastest, DifficultyTier::Easy, None)
+            .unwrap()
+            .is_none());
+    }
+}
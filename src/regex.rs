@@ -17,8 +17,78 @@
  *
  */
 
+mod ambiguity;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+mod automaton;
+mod bisimulation;
+mod budget;
+mod closure;
+mod compiled;
+mod counting;
+pub mod crossword;
+mod dedup;
+mod derivative;
+mod dfa;
+mod difference;
+mod difficulty;
+mod dot;
+pub mod enumerate;
+mod finite;
 mod generate_quiz;
+pub mod generator;
+mod glushkov;
+mod golf;
+mod growth;
+mod homomorphism;
+pub mod learn;
+mod levenshtein;
+mod monoid;
+mod nfa;
+mod parikh;
+mod partial_derivative;
+mod product;
+mod pumping;
+mod query;
+mod railroad;
 mod regex_tree;
+mod sample;
+mod serialization;
+mod shuffle;
+mod similarity;
+pub mod solver;
+mod spectrum;
+mod state_elimination;
+mod stats;
+mod svg;
+mod table;
+mod trace;
+mod words;
 
+pub use bisimulation::Bisimulation;
+#[cfg(test)]
+pub(crate) use budget::tests::blowup;
+pub use budget::BudgetExceeded;
+pub use compiled::*;
+pub use dedup::dedup_equivalent;
+pub use dfa::Dfa;
+pub use difference::DiffExample;
+pub use difficulty::{
+    estimate_difficulty, estimate_difficulty_with_solver, DifficultyEstimate, DifficultyTier,
+};
 pub use generate_quiz::*;
+pub use golf::{golf_score, GolfError};
+pub use growth::GrowthClass;
+pub use monoid::TransitionMonoid;
+pub use nfa::Nfa;
+pub use parikh::{LinearSet, SemilinearSet};
+pub use pumping::PumpingDecomposition;
+pub use query::{best_distinguishing_query, DistinguishingQuery};
 pub use regex_tree::*;
+pub use sample::LengthDistribution;
+pub use serialization::AUTOMATON_FORMAT_VERSION;
+pub use spectrum::LengthSpectrum;
+pub use stats::*;
+pub use svg::svg_to_png;
+pub use table::{TableError, MAX_TABLE_STATES};
+pub use trace::{MatchTrace, TraceDetail};
@@ -0,0 +1,116 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Nfa, RegexAst};
+use std::collections::{HashSet, VecDeque};
+
+impl Nfa {
+    /// Whether some word has two distinct accepting runs.
+    ///
+    /// This is decided on the self-product of the automaton:
+    /// the automaton is ambiguous if and only if a pair of distinct states is reachable
+    /// from a pair of initial states and reaches a pair of accepting states.
+    pub fn is_ambiguous(&self) -> bool {
+        let successors = |(p, q): (usize, usize)| {
+            (0..self.alphabet.len()).flat_map(move |i| {
+                self.transitions[p][i].iter().flat_map(move |p_next| {
+                    self.transitions[q][i]
+                        .iter()
+                        .map(move |q_next| (*p_next, *q_next))
+                })
+            })
+        };
+
+        let mut reachable = HashSet::new();
+        let mut queue = VecDeque::new();
+        for p in &self.initial {
+            for q in &self.initial {
+                reachable.insert((*p, *q));
+                queue.push_back((*p, *q));
+            }
+        }
+        while let Some(pair) = queue.pop_front() {
+            for next in successors(pair) {
+                if reachable.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        // pairs reaching a pair of accepting states, found backwards among reachable pairs
+        let mut productive = reachable
+            .iter()
+            .filter(|(p, q)| self.accepting[*p] && self.accepting[*q])
+            .cloned()
+            .collect::<HashSet<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for pair in &reachable {
+                if !productive.contains(pair)
+                    && successors(*pair).any(|next| productive.contains(&next))
+                {
+                    productive.insert(*pair);
+                    changed = true;
+                }
+            }
+        }
+
+        productive.iter().any(|(p, q)| p != q)
+    }
+}
+
+impl RegexAst {
+    /// Whether some word can be matched in two different ways,
+    /// i.e. by two different sequences of literals of this AST.
+    ///
+    /// This is decided on the Glushkov automaton, whose states correspond to literals.
+    /// Note that ambiguity only through ε (as in `(ε|a)*`) is not taken into account.
+    pub fn is_ambiguous(&self) -> bool {
+        self.to_glushkov_nfa().is_ambiguous()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_ambiguity() {
+        let cases = vec![
+            ("(a|b)*abb", false),
+            ("a*a*", true),
+            ("(a|ab)(c|bc)", true),
+            ("(ab|c)*a", false),
+            ("(a|b)*a(a|b)*", true),
+            ("b*a(a|b)*", false),
+            ("a|a", true),
+        ];
+
+        for (regex_str, expected) in cases {
+            assert_eq!(
+                RegexAst::parse_str(regex_str).unwrap().is_ambiguous(),
+                expected,
+                r#""{}" should{} be ambiguous"#,
+                regex_str,
+                if expected { "" } else { " not" }
+            );
+        }
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, RegexAst};
+use proptest::{
+    arbitrary::Arbitrary,
+    collection::vec,
+    prelude::{BoxedStrategy, Just, Strategy},
+    prop_oneof,
+    sample::select,
+};
+use strum::IntoEnumIterator;
+
+/// Expected number of nodes of ASTs generated by `any::<RegexAst>()`
+const DEFAULT_EXPECTED_SIZE: u32 = 12;
+/// Maximum length of words generated by [`any_word`]
+const DEFAULT_MAX_WORD_LENGTH: usize = 10;
+
+/// A strategy generating ASTs over `alphabet` with about `expected_size` nodes.
+///
+/// Every generated concatenation and alternation has at least two children,
+/// and shrinking only replaces subtrees by their descendants or drops surplus children,
+/// so shrunk ASTs are as well-formed as the ones parsed by [`RegexAst::parse_str`].
+///
+/// Panics if `alphabet` is empty.
+pub fn regex_ast(alphabet: Vec<Alphabet>, expected_size: u32) -> impl Strategy<Value = RegexAst> {
+    let leaf = prop_oneof![
+        Just(RegexAst::Epsilon),
+        select(alphabet).prop_map(RegexAst::Literal),
+    ];
+
+    leaf.prop_recursive(6, expected_size, 3, |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|ast| RegexAst::Star(Box::new(ast))),
+            vec(inner.clone(), 2..=3).prop_map(RegexAst::Concatenation),
+            vec(inner, 2..=3).prop_map(RegexAst::Alternation),
+        ]
+    })
+}
+
+/// A strategy generating words over `alphabet` of length at most `max_length`,
+/// shrinking towards shorter words.
+///
+/// Panics if `alphabet` is empty.
+pub fn word(alphabet: Vec<Alphabet>, max_length: usize) -> impl Strategy<Value = Vec<Alphabet>> {
+    vec(select(alphabet), 0..=max_length)
+}
+
+/// A strategy generating words over all alphabets, to be paired with `any::<RegexAst>()`.
+pub fn any_word() -> impl Strategy<Value = Vec<Alphabet>> {
+    word(Alphabet::iter().collect(), DEFAULT_MAX_WORD_LENGTH)
+}
+
+impl Arbitrary for Alphabet {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Alphabet>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        select(Alphabet::iter().collect::<Vec<_>>()).boxed()
+    }
+}
+
+impl Arbitrary for RegexAst {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<RegexAst>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        regex_ast(Alphabet::iter().collect(), DEFAULT_EXPECTED_SIZE).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{regex_ast, word};
+    use crate::regex::{Alphabet, RegexAst};
+    use proptest::prelude::*;
+
+    fn ab() -> Vec<Alphabet> {
+        vec![Alphabet::A, Alphabet::B]
+    }
+
+    proptest! {
+        #[test]
+        fn flatten_preserves_equivalence(ast in any::<RegexAst>()) {
+            prop_assert!(ast.flatten().equivalent_to(&ast));
+        }
+
+        #[test]
+        fn display_parse_roundtrip(ast in any::<RegexAst>()) {
+            let reparsed = RegexAst::parse_str(&ast.to_string()).unwrap();
+            prop_assert!(reparsed.equivalent_to(&ast), "{} reparsed as {}", ast, reparsed);
+        }
+
+        #[test]
+        fn simplify_preserves_matches(ast in regex_ast(ab(), 10), input in word(ab(), 8)) {
+            prop_assert_eq!(ast.simplify().matches(&input), ast.matches(&input));
+        }
+    }
+}
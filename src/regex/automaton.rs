@@ -0,0 +1,260 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::Dfa;
+use std::collections::VecDeque;
+
+/// A partition of states `0..n` into blocks that supports marking states
+/// and splitting blocks into their marked and unmarked parts,
+/// both in time proportional to the number of marked states.
+struct RefinablePartition {
+    /// States arranged so that each block occupies a contiguous range
+    elements: Vec<usize>,
+    /// Index of each state in `elements`
+    location: Vec<usize>,
+    block_of: Vec<usize>,
+    /// `elements[start[b]..end[b]]` are the states in block `b`
+    start: Vec<usize>,
+    end: Vec<usize>,
+    /// Marked states of block `b` are `elements[start[b]..start[b] + marked[b]]`
+    marked: Vec<usize>,
+}
+
+impl RefinablePartition {
+    fn new(state_count: usize) -> Self {
+        RefinablePartition {
+            elements: (0..state_count).collect(),
+            location: (0..state_count).collect(),
+            block_of: vec![0; state_count],
+            start: vec![0],
+            end: vec![state_count],
+            marked: vec![0],
+        }
+    }
+
+    fn block_count(&self) -> usize {
+        self.start.len()
+    }
+
+    fn block_size(&self, block: usize) -> usize {
+        self.end[block] - self.start[block]
+    }
+
+    fn members(&self, block: usize) -> &[usize] {
+        &self.elements[self.start[block]..self.end[block]]
+    }
+
+    /// Mark `state`, returning `true` if it is the first marked state of its block.
+    fn mark(&mut self, state: usize) -> bool {
+        let block = self.block_of[state];
+        let index = self.location[state];
+        let first_unmarked = self.start[block] + self.marked[block];
+
+        if index < first_unmarked {
+            return false;
+        }
+
+        let swapped = self.elements[first_unmarked];
+        self.elements.swap(index, first_unmarked);
+        self.location[swapped] = index;
+        self.location[state] = first_unmarked;
+        self.marked[block] += 1;
+
+        self.marked[block] == 1
+    }
+
+    /// Split the marked states of `block` off into a new block and clear the marks.
+    ///
+    /// Returns the index of the new block,
+    /// or `None` if all of the states in `block` were marked and nothing was split.
+    fn split(&mut self, block: usize) -> Option<usize> {
+        let marked = std::mem::replace(&mut self.marked[block], 0);
+        if marked == self.block_size(block) {
+            return None;
+        }
+
+        let new_block = self.block_count();
+        let boundary = self.start[block] + marked;
+        self.start.push(self.start[block]);
+        self.end.push(boundary);
+        self.marked.push(0);
+        self.start[block] = boundary;
+
+        for index in self.start[new_block]..self.end[new_block] {
+            self.block_of[self.elements[index]] = new_block;
+        }
+
+        Some(new_block)
+    }
+}
+
+impl Dfa {
+    /// Compute a mapping from states to equivalence classes of indistinguishable states
+    /// using Hopcroft's O(n log n) partition refinement.
+    fn hopcroft_classes(&self) -> Vec<usize> {
+        let letter_count = self.alphabet.len();
+
+        // preimages[q][i] are the states reaching q by reading alphabet[i]
+        let mut preimages = vec![vec![vec![]; letter_count]; self.state_count()];
+        for (q, row) in self.transitions.iter().enumerate() {
+            for (i, target) in row.iter().enumerate() {
+                preimages[*target][i].push(q);
+            }
+        }
+
+        let mut partition = RefinablePartition::new(self.state_count());
+        for q in self.accepting_states() {
+            partition.mark(q);
+        }
+        partition.split(0);
+
+        let mut worklist = vec![];
+        let mut in_worklist = vec![vec![false; letter_count]; partition.block_count()];
+        if partition.block_count() == 2 {
+            let smaller = if partition.block_size(0) <= partition.block_size(1) {
+                0
+            } else {
+                1
+            };
+            for (i, queued) in in_worklist[smaller].iter_mut().enumerate() {
+                worklist.push((smaller, i));
+                *queued = true;
+            }
+        }
+
+        while let Some((splitter, i)) = worklist.pop() {
+            in_worklist[splitter][i] = false;
+
+            let mut touched_blocks = vec![];
+            for q in partition.members(splitter).to_vec() {
+                for p in &preimages[q][i] {
+                    let block = partition.block_of[*p];
+                    if partition.mark(*p) {
+                        touched_blocks.push(block);
+                    }
+                }
+            }
+
+            for block in touched_blocks {
+                if let Some(new_block) = partition.split(block) {
+                    in_worklist.push(vec![false; letter_count]);
+
+                    let queued_for_block = in_worklist[block].clone();
+                    for (j, queued) in queued_for_block.into_iter().enumerate() {
+                        let to_add = if queued
+                            || partition.block_size(new_block) <= partition.block_size(block)
+                        {
+                            new_block
+                        } else {
+                            block
+                        };
+                        if !in_worklist[to_add][j] {
+                            worklist.push((to_add, j));
+                            in_worklist[to_add][j] = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        partition.block_of
+    }
+
+    /// Renumber the states reachable from the initial state in breadth-first order,
+    /// visiting successors in the order of alphabets, and drop unreachable states.
+    ///
+    /// Two DFAs with the same alphabet are isomorphic if and only if
+    /// their canonicalized forms are equal.
+    fn canonicalized(&self) -> Dfa {
+        let mut numbering = vec![None; self.state_count()];
+        let mut order = vec![self.initial];
+        numbering[self.initial] = Some(0);
+
+        let mut queue = VecDeque::from(vec![self.initial]);
+        while let Some(q) = queue.pop_front() {
+            for target in &self.transitions[q] {
+                if numbering[*target].is_none() {
+                    numbering[*target] = Some(order.len());
+                    order.push(*target);
+                    queue.push_back(*target);
+                }
+            }
+        }
+
+        Dfa {
+            alphabet: self.alphabet.clone(),
+            initial: 0,
+            accepting: order.iter().map(|q| self.accepting[*q]).collect(),
+            transitions: order
+                .iter()
+                .map(|q| {
+                    self.transitions[*q]
+                        .iter()
+                        .map(|target| numbering[*target].unwrap())
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Compute the minimal DFA recognizing the same language.
+    ///
+    /// States of the result are numbered in breadth-first order from the initial state,
+    /// so the minimal DFAs of two equivalent automata over the same alphabet are equal.
+    pub fn minimized(&self) -> Dfa {
+        let classes = self.hopcroft_classes();
+        let class_count = classes.iter().max().map_or(0, |max| max + 1);
+
+        let mut accepting = vec![false; class_count];
+        let mut transitions = vec![vec![]; class_count];
+        for (q, class) in classes.iter().enumerate() {
+            accepting[*class] = self.accepting[q];
+            transitions[*class] = self.transitions[q]
+                .iter()
+                .map(|target| classes[*target])
+                .collect();
+        }
+
+        Dfa {
+            alphabet: self.alphabet.clone(),
+            initial: classes[self.initial],
+            accepting,
+            transitions,
+        }
+        .canonicalized()
+    }
+}
+
+#[test]
+fn minimized_dfa_is_canonical() {
+    use crate::regex::{Alphabet, RegexAst};
+
+    let alphabet = Alphabet::vec_from_str("ab").unwrap().into_iter().collect();
+
+    let dfa_1 = RegexAst::parse_str("(a|b)*b(a|b)")
+        .unwrap()
+        .to_min_dfa(&alphabet);
+    let dfa_2 = RegexAst::parse_str("(a|b)*(ba|bb)")
+        .unwrap()
+        .to_min_dfa(&alphabet);
+
+    assert_eq!(dfa_1.state_count(), 4);
+    assert_eq!(dfa_1, dfa_2);
+    assert_eq!(dfa_1.minimized(), dfa_1);
+}
@@ -0,0 +1,135 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, RegexAst};
+use std::collections::{HashSet, VecDeque};
+
+/// Machine-checkable evidence that two DFAs reading the same alphabets accept the same language.
+///
+/// `pairs` relates states of `lhs` to states of `rhs` so that
+/// the initial states are related, related states agree on acceptance,
+/// and successors of related states by each alphabet are related again.
+/// Such a relation exists if and only if the two DFAs are equivalent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bisimulation {
+    pub lhs: Dfa,
+    pub rhs: Dfa,
+    pub pairs: Vec<(usize, usize)>,
+}
+
+impl Bisimulation {
+    /// Check that this is indeed a bisimulation between `lhs` and `rhs`,
+    /// without trusting the procedure that constructed it.
+    pub fn verify(&self) -> bool {
+        let in_range =
+            |(p, q): (usize, usize)| p < self.lhs.state_count() && q < self.rhs.state_count();
+        if self.lhs.alphabet != self.rhs.alphabet || !self.pairs.iter().all(|pair| in_range(*pair))
+        {
+            return false;
+        }
+
+        let related = self.pairs.iter().cloned().collect::<HashSet<_>>();
+        related.contains(&(self.lhs.initial, self.rhs.initial))
+            && related.iter().all(|(p, q)| {
+                self.lhs.accepting[*p] == self.rhs.accepting[*q]
+                    && (0..self.lhs.alphabet.len()).all(|i| {
+                        related
+                            .contains(&(self.lhs.transitions[*p][i], self.rhs.transitions[*q][i]))
+                    })
+            })
+    }
+}
+
+impl Dfa {
+    /// The smallest bisimulation between this automaton and `other`,
+    /// or `None` if they read different alphabets or accept different languages.
+    pub fn bisimulation_with(&self, other: &Dfa) -> Option<Bisimulation> {
+        if self.alphabet != other.alphabet {
+            return None;
+        }
+
+        let initial = (self.initial, other.initial);
+        let mut pairs = vec![initial];
+        let mut visited = vec![initial].into_iter().collect::<HashSet<_>>();
+        let mut queue = VecDeque::from(vec![initial]);
+
+        while let Some((p, q)) = queue.pop_front() {
+            if self.accepting[p] != other.accepting[q] {
+                return None;
+            }
+            for i in 0..self.alphabet.len() {
+                let next = (self.transitions[p][i], other.transitions[q][i]);
+                if visited.insert(next) {
+                    pairs.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Some(Bisimulation {
+            lhs: self.clone(),
+            rhs: other.clone(),
+            pairs,
+        })
+    }
+}
+
+impl RegexAst {
+    /// Evidence that this AST is equivalent to `other`, relating states of their DFAs
+    /// over the alphabets used in either, or `None` if they are not equivalent.
+    pub fn equivalence_evidence(&self, other: &RegexAst) -> Option<Bisimulation> {
+        let alphabet = self
+            .used_alphabets()
+            .union(&other.used_alphabets())
+            .cloned()
+            .collect::<HashSet<Alphabet>>();
+        Dfa::from_ast(self, &alphabet).bisimulation_with(&Dfa::from_ast(other, &alphabet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_equivalence_evidence() {
+        let regex = |s: &str| RegexAst::parse_str(s).unwrap();
+
+        let evidence = regex("(a|b)*")
+            .equivalence_evidence(&regex("(a*b*)*"))
+            .unwrap();
+        assert!(evidence.verify());
+
+        let evidence = regex("a(ba)*")
+            .equivalence_evidence(&regex("(ab)*a"))
+            .unwrap();
+        assert!(evidence.verify());
+
+        // a tampered relation is rejected
+        let mut tampered = evidence.clone();
+        tampered.pairs.pop();
+        assert!(!tampered.verify());
+
+        assert_eq!(
+            regex("(a|b)*a").equivalence_evidence(&regex("(a|b)*ab")),
+            None
+        );
+        assert_eq!(regex("a*").equivalence_evidence(&regex("a*|b")), None);
+    }
+}
@@ -0,0 +1,48 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use thiserror::Error;
+
+/// Error returned when a construction would need more automaton states,
+/// or build larger expressions, than allowed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+#[error("Construction exceeded the budget of {budget}")]
+pub struct BudgetExceeded {
+    pub budget: usize,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::BudgetExceeded;
+    use crate::regex::RegexAst;
+
+    /// `(a|b)*a(a|b)^n`, whose minimal DFA has `2^(n + 1)` states
+    /// to remember the last `n + 1` letters read.
+    pub(crate) fn blowup(n: usize) -> RegexAst {
+        RegexAst::parse_str(&format!("(a|b)*a{}", "(a|b)".repeat(n))).unwrap()
+    }
+
+    #[test]
+    fn budget_exceeded_message() {
+        assert_eq!(
+            BudgetExceeded { budget: 100 }.to_string(),
+            "Construction exceeded the budget of 100"
+        );
+    }
+}
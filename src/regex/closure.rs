@@ -0,0 +1,111 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Dfa, Nfa, RegexAst};
+use std::collections::BTreeSet;
+
+impl Dfa {
+    /// Automaton accepting prefixes of accepted words,
+    /// obtained by making every productive state accepting.
+    pub fn prefix_closure(&self) -> Dfa {
+        Dfa {
+            accepting: self.productive_states(),
+            ..self.clone()
+        }
+    }
+
+    /// Automaton accepting suffixes of accepted words,
+    /// obtained by determinizing the automaton with every reachable state made initial.
+    pub fn suffix_closure(&self) -> Dfa {
+        let reachable = self.reachable_states();
+
+        Nfa {
+            alphabet: self.alphabet.clone(),
+            initial: (0..self.state_count()).filter(|q| reachable[*q]).collect(),
+            accepting: self.accepting.clone(),
+            transitions: self
+                .transitions
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|target| std::iter::once(*target).collect::<BTreeSet<_>>())
+                        .collect()
+                })
+                .collect(),
+        }
+        .to_dfa()
+    }
+}
+
+impl RegexAst {
+    /// An AST matching prefixes of words matched by this AST.
+    pub fn prefix_closure(&self) -> RegexAst {
+        self.to_min_dfa(&self.used_alphabets())
+            .prefix_closure()
+            .minimized()
+            .to_regex()
+            // ε is a prefix of any word, and this AST matches some word
+            .unwrap()
+    }
+
+    /// An AST matching suffixes of words matched by this AST.
+    pub fn suffix_closure(&self) -> RegexAst {
+        self.to_min_dfa(&self.used_alphabets())
+            .suffix_closure()
+            .minimized()
+            .to_regex()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_prefix_and_suffix_closures() {
+        let cases = vec![
+            ("abc", "ε|a|ab|abc", "ε|c|bc|abc"),
+            ("(ab)*", "(ab)*(ε|a)", "(ε|b)(ab)*"),
+            ("a*b", "a*(ε|b)", "a*b|ε"),
+            ("ε", "ε", "ε"),
+        ];
+
+        for (regex_str, prefixes, suffixes) in cases {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+
+            assert!(
+                ast.prefix_closure()
+                    .equivalent_to(&RegexAst::parse_str(prefixes).unwrap()),
+                r#"Prefix closure of "{}" should be "{}", but got "{}""#,
+                regex_str,
+                prefixes,
+                ast.prefix_closure()
+            );
+            assert!(
+                ast.suffix_closure()
+                    .equivalent_to(&RegexAst::parse_str(suffixes).unwrap()),
+                r#"Suffix closure of "{}" should be "{}", but got "{}""#,
+                regex_str,
+                suffixes,
+                ast.suffix_closure()
+            );
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{dfa::Dfa, Alphabet, RegexAst};
+
+/// A [RegexAst] compiled into a deterministic finite automaton.
+///
+/// Compiling once and reusing the result is much cheaper than calling
+/// [RegexAst::matches] repeatedly against the same expression.
+pub struct CompiledRegex {
+    dfa: Dfa,
+}
+
+impl CompiledRegex {
+    pub fn new(ast: &RegexAst) -> Self {
+        CompiledRegex {
+            dfa: Dfa::from_ast(ast, &ast.used_alphabets()),
+        }
+    }
+
+    pub fn matches(&self, input: &[Alphabet]) -> bool {
+        self.dfa.accepts(input)
+    }
+
+    /// Answer membership queries of all `words` with the same automaton.
+    pub fn matches_many(&self, words: &[Vec<Alphabet>]) -> Vec<bool> {
+        words.iter().map(|word| self.dfa.accepts(word)).collect()
+    }
+
+    /// Whether each prefix of `word` is matched, from the empty prefix to `word` itself,
+    /// computed in a single pass.
+    pub fn accepted_prefixes(&self, word: &[Alphabet]) -> Vec<bool> {
+        let mut state = Some(self.dfa.initial_state());
+        let mut accum = vec![self.dfa.is_accepting(self.dfa.initial_state())];
+
+        for a in word {
+            // once an alphabet outside of the automaton is read, no prefix is matched
+            state = state.and_then(|q| self.dfa.next_state(q, *a));
+            accum.push(state.is_some_and(|q| self.dfa.is_accepting(q)));
+        }
+
+        accum
+    }
+
+    /// A matched word closest to `word` in edit distance (Levenshtein distance),
+    /// along with the distance, or `None` if no matched word is within `max_dist` edits.
+    ///
+    /// Of matched words at the same distance, which one is returned is unspecified.
+    pub fn closest_word(
+        &self,
+        word: &[Alphabet],
+        max_dist: usize,
+    ) -> Option<(Vec<Alphabet>, usize)> {
+        self.dfa.closest_word(word, max_dist)
+    }
+
+    /// The minimum number of edits (substitutions, insertions and deletions of a letter)
+    /// turning `word` into a matched word.
+    pub fn distance_to_language(&self, word: &[Alphabet]) -> usize {
+        self.dfa
+            .closest_word(word, usize::MAX)
+            .map(|(_, distance)| distance)
+            .expect("a regular expression always matches some word")
+    }
+}
+
+impl RegexAst {
+    /// Compile this AST so that membership queries can be answered efficiently.
+    pub fn compile(&self) -> CompiledRegex {
+        CompiledRegex::new(self)
+    }
+}
+
+#[test]
+fn compiled_regex_matches() {
+    let compiled = RegexAst::parse_str("(ab|c)*a").unwrap().compile();
+
+    let positives = vec!["a", "aba", "ca", "abcaba"];
+    let negatives = vec!["", "ab", "aa", "da", "abab"];
+
+    for input in positives {
+        assert!(compiled.matches(&Alphabet::vec_from_str(input).unwrap()));
+    }
+    for input in negatives {
+        assert!(!compiled.matches(&Alphabet::vec_from_str(input).unwrap()));
+    }
+}
+
+#[test]
+fn compiled_regex_matches_many() {
+    let compiled = RegexAst::parse_str("(ab|c)*a").unwrap().compile();
+    let words = ["a", "", "aba", "abab", "cca"]
+        .iter()
+        .map(|w| Alphabet::vec_from_str(w).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        compiled.matches_many(&words),
+        vec![true, false, true, false, true]
+    );
+}
+
+#[test]
+fn compiled_regex_accepted_prefixes() {
+    let compiled = RegexAst::parse_str("(ab|c)*").unwrap().compile();
+
+    assert_eq!(
+        compiled.accepted_prefixes(&Alphabet::vec_from_str("abcab").unwrap()),
+        vec![true, false, true, true, false, true]
+    );
+    assert_eq!(
+        compiled.accepted_prefixes(&Alphabet::vec_from_str("cdc").unwrap()),
+        vec![true, true, false, false]
+    );
+}
+
+#[test]
+fn compiled_regex_closest_word() {
+    let compiled = RegexAst::parse_str("(ab)*").unwrap().compile();
+    let word = |w: &str| Alphabet::vec_from_str(w).unwrap();
+
+    assert_eq!(
+        compiled.closest_word(&word("abab"), 0),
+        Some((word("abab"), 0))
+    );
+    assert_eq!(
+        compiled.closest_word(&word("abba"), 2).map(|(_, d)| d),
+        Some(2)
+    );
+    assert_eq!(
+        compiled.closest_word(&word("aab"), 1),
+        Some((word("ab"), 1))
+    );
+    assert_eq!(
+        compiled.closest_word(&word("abcab"), 1),
+        Some((word("abab"), 1))
+    );
+    assert_eq!(compiled.closest_word(&word("bbbb"), 1), None);
+
+    for (input, max_dist) in [("abba", 2), ("bbbb", 4), ("c", 3), ("", 0)] {
+        let (closest, _) = compiled.closest_word(&word(input), max_dist).unwrap();
+        assert!(compiled.matches(&closest));
+    }
+}
+
+#[test]
+fn compiled_regex_distance_to_language() {
+    let compiled = RegexAst::parse_str("a(b|c)*a").unwrap().compile();
+    let word = |w: &str| Alphabet::vec_from_str(w).unwrap();
+
+    let cases = vec![
+        ("aba", 0),
+        ("ab", 1),
+        ("", 2),
+        ("bcb", 2),
+        ("abdca", 1),
+        ("dddd", 4),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            compiled.distance_to_language(&word(input)),
+            expected,
+            "{}",
+            input
+        );
+    }
+}
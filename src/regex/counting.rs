@@ -0,0 +1,125 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Dfa, RegexAst};
+use num_bigint::BigUint;
+
+impl Dfa {
+    /// Numbers of accepted words of each length from `0` to `max_len`.
+    ///
+    /// The number of paths of each length from the initial state to each state is computed
+    /// by repeatedly multiplying the vector of path counts by the transition matrix.
+    pub fn count_words_by_length(&self, max_len: usize) -> Vec<BigUint> {
+        let mut counts = vec![BigUint::default(); self.state_count()];
+        counts[self.initial] = BigUint::from(1u8);
+
+        let mut accum = vec![];
+        for n in 0..=max_len {
+            accum.push(
+                counts
+                    .iter()
+                    .zip(self.accepting.iter())
+                    .filter(|(_, accepting)| **accepting)
+                    .fold(BigUint::default(), |sum, (count, _)| sum + count),
+            );
+            if n == max_len {
+                break;
+            }
+
+            let mut next = vec![BigUint::default(); self.state_count()];
+            for (q, count) in counts.iter().enumerate() {
+                for target in &self.transitions[q] {
+                    next[*target] += count;
+                }
+            }
+            counts = next;
+        }
+
+        accum
+    }
+
+    /// Number of accepted words of length `n`.
+    pub fn count_words_of_length(&self, n: usize) -> BigUint {
+        self.count_words_by_length(n).pop().unwrap()
+    }
+}
+
+impl RegexAst {
+    /// Number of words of length `n` matched by this AST.
+    pub fn count_words_of_length(&self, n: usize) -> BigUint {
+        self.to_min_dfa(&self.used_alphabets())
+            .count_words_of_length(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, Dfa, RegexAst};
+    use num_bigint::BigUint;
+
+    #[test]
+    fn dfa_count_words_by_length_agrees_with_enumeration() {
+        let max_len = 6;
+
+        for regex_str in ["(a|b)*abb", "a*b*", "(ab|c)*", "ab|ba|ε"] {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            let mut expected = vec![BigUint::default(); max_len + 1];
+            for word in ast.words().take_while(|word| word.len() <= max_len) {
+                expected[word.len()] += 1u8;
+            }
+
+            assert_eq!(
+                ast.to_min_dfa(&ast.used_alphabets())
+                    .count_words_by_length(max_len),
+                expected,
+                r#"Numbers of words matched by "{}" are wrong"#,
+                regex_str
+            );
+        }
+
+        let empty = Dfa::parse_table("0 0 0", &Alphabet::vec_from_str("ab").unwrap()).unwrap();
+        assert_eq!(empty.count_words_by_length(3), vec![BigUint::default(); 4]);
+    }
+
+    #[test]
+    fn regex_ast_count_words_of_length() {
+        let cases = vec![
+            ("(a|b)*a", 5, BigUint::from(16u8)),
+            ("ab|ba|aa", 2, BigUint::from(3u8)),
+            ("ab|ba|aa", 3, BigUint::from(0u8)),
+            ("ε", 0, BigUint::from(1u8)),
+            // words without two consecutive "a"s are counted by Fibonacci numbers
+            ("(b|ab)*(ε|a)", 10, BigUint::from(144u8)),
+            // does not fit in u64
+            ("(a|b)*", 100, BigUint::from(2u8).pow(100)),
+        ];
+
+        for (regex_str, n, expected) in cases {
+            assert_eq!(
+                RegexAst::parse_str(regex_str)
+                    .unwrap()
+                    .count_words_of_length(n),
+                expected,
+                r#"Number of words of length {} matched by "{}" is wrong"#,
+                n,
+                regex_str
+            );
+        }
+    }
+}
@@ -0,0 +1,294 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, RegexAst};
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashSet;
+
+/// Probability that a letter of the solution is generalized into an alternation with another letter
+const ALTERNATION_PROBABILITY: f64 = 0.4;
+/// Probability that a run of letters of the solution is generalized into a star
+const STAR_PROBABILITY: f64 = 0.3;
+
+/// Parameters of generated crosswords.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrosswordConfig {
+    pub rows: usize,
+    pub columns: usize,
+    /// Alphabets filling the grid. Must not be empty.
+    pub alphabet: Vec<Alphabet>,
+    /// Number of grids tried before giving up on finding clues with a unique solution
+    pub max_attempts: usize,
+}
+
+impl Default for CrosswordConfig {
+    fn default() -> Self {
+        CrosswordConfig {
+            rows: 3,
+            columns: 3,
+            alphabet: vec![Alphabet::A, Alphabet::B, Alphabet::C],
+            max_attempts: 100,
+        }
+    }
+}
+
+/// A grid of alphabets whose rows and columns are constrained by regexes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Crossword {
+    /// Clues of the rows from top to bottom, each matching its row read from left to right
+    pub row_clues: Vec<RegexAst>,
+    /// Clues of the columns from left to right, each matching its column read from top to bottom
+    pub column_clues: Vec<RegexAst>,
+    /// The unique grid satisfying all the clues, as a list of rows
+    pub solution: Vec<Vec<Alphabet>>,
+}
+
+/// A clue matching `word`, obtained by generalizing letters into alternations and runs into stars.
+fn clue_for<R: Rng + ?Sized>(word: &[Alphabet], alphabet: &[Alphabet], rng: &mut R) -> RegexAst {
+    let mut factors = vec![];
+    let mut position = 0;
+    while position < word.len() {
+        let letter = word[position];
+        if rng.gen_bool(STAR_PROBABILITY) {
+            // a run of up to two letters, along with another letter
+            let end = (position + rng.gen_range(1..=2)).min(word.len());
+            let mut letters = word[position..end].iter().cloned().collect::<HashSet<_>>();
+            letters.insert(*alphabet.choose(rng).unwrap());
+            let mut letters = letters.into_iter().collect::<Vec<_>>();
+            letters.sort();
+
+            let inner = match letters.as_slice() {
+                [a] => RegexAst::Literal(*a),
+                _ => RegexAst::Alternation(letters.into_iter().map(RegexAst::Literal).collect()),
+            };
+            factors.push(RegexAst::Star(Box::new(inner)));
+            position = end;
+        } else {
+            let other = *alphabet.choose(rng).unwrap();
+            if other != letter && rng.gen_bool(ALTERNATION_PROBABILITY) {
+                factors.push(RegexAst::Alternation(vec![
+                    RegexAst::Literal(letter),
+                    RegexAst::Literal(other),
+                ]));
+            } else {
+                factors.push(RegexAst::Literal(letter));
+            }
+            position += 1;
+        }
+    }
+
+    RegexAst::Concatenation(factors).simplify()
+}
+
+/// A clue compiled for the search of solutions.
+struct Constraint {
+    dfa: Dfa,
+    /// `can_finish[k][q]` tells whether a word of length `k` is accepted from the state `q`
+    can_finish: Vec<Vec<bool>>,
+}
+
+impl Constraint {
+    fn new(clue: &RegexAst, alphabet: &HashSet<Alphabet>, length: usize) -> Self {
+        let dfa = clue.to_min_dfa(alphabet);
+        let mut can_finish = vec![dfa.accepting.clone()];
+        for k in 1..=length {
+            let shorter = &can_finish[k - 1];
+            let current = (0..dfa.state_count())
+                .map(|q| dfa.transitions[q].iter().any(|next| shorter[*next]))
+                .collect();
+            can_finish.push(current);
+        }
+        Constraint { dfa, can_finish }
+    }
+}
+
+/// Up to `limit` grids of `row_clues.len()` rows and `column_clues.len()` columns over `alphabet`
+/// such that each row and column is matched by its clue.
+pub fn solutions(
+    row_clues: &[RegexAst],
+    column_clues: &[RegexAst],
+    alphabet: &[Alphabet],
+    limit: usize,
+) -> Vec<Vec<Vec<Alphabet>>> {
+    let (rows, columns) = (row_clues.len(), column_clues.len());
+    let alphabet_set = alphabet.iter().cloned().collect::<HashSet<_>>();
+    let row_constraints = row_clues
+        .iter()
+        .map(|clue| Constraint::new(clue, &alphabet_set, columns))
+        .collect::<Vec<_>>();
+    let column_constraints = column_clues
+        .iter()
+        .map(|clue| Constraint::new(clue, &alphabet_set, rows))
+        .collect::<Vec<_>>();
+
+    struct Search<'a> {
+        rows: &'a [Constraint],
+        columns: &'a [Constraint],
+        grid: Vec<Vec<Alphabet>>,
+        found: Vec<Vec<Vec<Alphabet>>>,
+        limit: usize,
+    }
+
+    impl Search<'_> {
+        /// Fill the grid in row-major order, keeping the states of the rows and the columns
+        fn fill(&mut self, cell: usize, row_states: &mut [usize], column_states: &mut [usize]) {
+            let (row_count, column_count) = (self.rows.len(), self.columns.len());
+            if self.found.len() >= self.limit {
+                return;
+            }
+            if cell == row_count * column_count {
+                self.found.push(self.grid.clone());
+                return;
+            }
+
+            let (i, j) = (cell / column_count, cell % column_count);
+            let alphabet = self.rows[i].dfa.alphabet.clone();
+            for (index, a) in alphabet.iter().enumerate() {
+                let row_next = self.rows[i].dfa.transitions[row_states[i]][index];
+                let column_next = self.columns[j].dfa.transitions[column_states[j]][index];
+                if !self.rows[i].can_finish[column_count - j - 1][row_next]
+                    || !self.columns[j].can_finish[row_count - i - 1][column_next]
+                {
+                    continue;
+                }
+
+                let (row_previous, column_previous) = (row_states[i], column_states[j]);
+                row_states[i] = row_next;
+                column_states[j] = column_next;
+                self.grid[i][j] = *a;
+
+                self.fill(cell + 1, row_states, column_states);
+
+                row_states[i] = row_previous;
+                column_states[j] = column_previous;
+            }
+        }
+    }
+
+    if rows == 0 || columns == 0 || alphabet.is_empty() {
+        return vec![];
+    }
+
+    let mut row_states = row_constraints
+        .iter()
+        .map(|c| c.dfa.initial)
+        .collect::<Vec<_>>();
+    let mut column_states = column_constraints
+        .iter()
+        .map(|c| c.dfa.initial)
+        .collect::<Vec<_>>();
+    let mut search = Search {
+        rows: &row_constraints,
+        columns: &column_constraints,
+        grid: vec![vec![alphabet[0]; columns]; rows],
+        found: vec![],
+        limit,
+    };
+    search.fill(0, &mut row_states, &mut column_states);
+    search.found
+}
+
+/// Generate a crossword whose clues have exactly one solution,
+/// or `None` if no such crossword is found within `config.max_attempts` grids.
+pub fn generate<R: Rng + ?Sized>(config: &CrosswordConfig, rng: &mut R) -> Option<Crossword> {
+    for _ in 0..config.max_attempts {
+        let solution = (0..config.rows)
+            .map(|_| {
+                (0..config.columns)
+                    .map(|_| *config.alphabet.choose(rng).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let row_clues = solution
+            .iter()
+            .map(|row| clue_for(row, &config.alphabet, rng))
+            .collect::<Vec<_>>();
+        let column_clues = (0..config.columns)
+            .map(|j| {
+                let column = solution.iter().map(|row| row[j]).collect::<Vec<_>>();
+                clue_for(&column, &config.alphabet, rng)
+            })
+            .collect::<Vec<_>>();
+
+        if solutions(&row_clues, &column_clues, &config.alphabet, 2).len() == 1 {
+            return Some(Crossword {
+                row_clues,
+                column_clues,
+                solution,
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, solutions, CrosswordConfig};
+    use crate::regex::{Alphabet, RegexAst};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn crossword_solutions() {
+        let regex = |s: &str| RegexAst::parse_str(s).unwrap();
+        let word = |w: &str| Alphabet::vec_from_str(w).unwrap();
+        let alphabet = word("ab");
+
+        // the grid
+        //   a b
+        //   b a
+        let rows = vec![regex("a(a|b)"), regex("b*a")];
+        let columns = vec![regex("(a|b)*"), regex("ba")];
+        assert_eq!(
+            solutions(&rows, &columns, &alphabet, 2),
+            vec![vec![word("ab"), word("ba")]]
+        );
+
+        let loose = vec![regex("(a|b)*"), regex("(a|b)*")];
+        assert_eq!(solutions(&loose, &loose, &alphabet, 10).len(), 10);
+
+        let contradictory = vec![regex("aa"), regex("aa")];
+        assert!(solutions(&contradictory, &[regex("ab"), regex("ab")], &alphabet, 2).is_empty());
+    }
+
+    #[test]
+    fn crossword_generate_has_unique_solution() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let config = CrosswordConfig::default();
+
+        for _ in 0..5 {
+            let crossword = generate(&config, &mut rng).unwrap();
+            assert_eq!(crossword.row_clues.len(), config.rows);
+            assert_eq!(crossword.column_clues.len(), config.columns);
+            for (row, clue) in crossword.solution.iter().zip(crossword.row_clues.iter()) {
+                assert!(clue.matches(row));
+            }
+            assert_eq!(
+                solutions(
+                    &crossword.row_clues,
+                    &crossword.column_clues,
+                    &config.alphabet,
+                    2
+                ),
+                vec![crossword.solution.clone()]
+            );
+        }
+    }
+}
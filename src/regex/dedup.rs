@@ -0,0 +1,62 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::RegexAst;
+use std::collections::HashSet;
+
+/// Remove expressions equivalent to an earlier one in `asts`,
+/// keeping the first spelling of each language.
+///
+/// Minimal DFAs are numbered canonically, so the expressions are grouped
+/// by hashing their minimal DFAs over the alphabets used anywhere in `asts`.
+pub fn dedup_equivalent(asts: Vec<RegexAst>) -> Vec<RegexAst> {
+    let alphabet = asts
+        .iter()
+        .flat_map(|ast| ast.used_alphabets())
+        .collect::<HashSet<_>>();
+    let mut seen = HashSet::new();
+
+    asts.into_iter()
+        .filter(|ast| seen.insert(ast.to_min_dfa(&alphabet)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dedup_equivalent;
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn dedup_equivalent_keeps_first_spelling() {
+        let asts = [
+            "(a|b)*", "a*", "(a*b*)*", "a*(ba*)*", "b", "ε|aa*", "(b|a)*",
+        ]
+        .iter()
+        .map(|s| RegexAst::parse_str(s).unwrap())
+        .collect();
+
+        assert_eq!(
+            dedup_equivalent(asts),
+            ["(a|b)*", "a*", "b"]
+                .iter()
+                .map(|s| RegexAst::parse_str(s).unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, RegexAst};
+
+impl RegexAst {
+    /// Whether the language contains the empty word.
+    ///
+    /// This is decided by the structure of the AST alone, without building any automaton,
+    /// so it is cheap enough to answer queries of the empty word.
+    pub fn nullable(&self) -> bool {
+        match self {
+            RegexAst::Epsilon | RegexAst::Star(_) => true,
+            RegexAst::Literal(_) => false,
+            RegexAst::Concatenation(asts) => asts.iter().all(|ast| ast.nullable()),
+            RegexAst::Alternation(asts) => asts.iter().any(|ast| ast.nullable()),
+        }
+    }
+
+    /// The Brzozowski derivative of this expression with respect to `a`,
+    /// that is, an expression matching `w` exactly when this expression matches `aw`.
+    ///
+    /// Since [RegexAst] cannot express the empty language,
+    /// `None` is returned when no word matching this expression starts with `a`.
+    /// The result is simplified with [RegexAst::simplify] to keep iterated derivatives small.
+    pub fn derivative(&self, a: Alphabet) -> Option<RegexAst> {
+        self.derivative_unsimplified(a).map(|ast| ast.simplify())
+    }
+
+    fn derivative_unsimplified(&self, a: Alphabet) -> Option<RegexAst> {
+        match self {
+            RegexAst::Epsilon => None,
+            RegexAst::Literal(b) => (*b == a).then_some(RegexAst::Epsilon),
+            // ∂(r*) = ∂(r) r*
+            RegexAst::Star(ast) => ast
+                .derivative_unsimplified(a)
+                .map(|derivative| RegexAst::Concatenation(vec![derivative, self.clone()])),
+            // ∂(r₁r₂…rₙ) = ∂(r₁)r₂…rₙ | ∂(r₂)r₃…rₙ (if r₁ is nullable) | …
+            RegexAst::Concatenation(asts) => {
+                let mut branches = vec![];
+
+                for (i, ast) in asts.iter().enumerate() {
+                    if let Some(derivative) = ast.derivative_unsimplified(a) {
+                        let mut factors = vec![derivative];
+                        factors.extend(asts[i + 1..].iter().cloned());
+                        branches.push(RegexAst::concatenation_of(factors));
+                    }
+                    if !ast.nullable() {
+                        break;
+                    }
+                }
+
+                (!branches.is_empty()).then(|| RegexAst::alternation_of(branches))
+            }
+            // ∂(r₁|r₂|…|rₙ) = ∂(r₁)|∂(r₂)|…|∂(rₙ)
+            RegexAst::Alternation(asts) => {
+                let branches = asts
+                    .iter()
+                    .filter_map(|ast| ast.derivative_unsimplified(a))
+                    .collect::<Vec<_>>();
+
+                (!branches.is_empty()).then(|| RegexAst::alternation_of(branches))
+            }
+        }
+    }
+
+    /// The derivative of this expression with respect to a whole word,
+    /// obtained by taking derivatives letter by letter.
+    pub fn word_derivative(&self, word: &[Alphabet]) -> Option<RegexAst> {
+        word.iter()
+            .try_fold(self.simplify(), |ast, a| ast.derivative(*a))
+    }
+
+    /// Decide whether this expression matches `input` using derivatives,
+    /// without compiling the expression into an automaton.
+    pub fn matches_by_derivatives(&self, input: &[Alphabet]) -> bool {
+        self.word_derivative(input)
+            .is_some_and(|derivative| derivative.nullable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn regex_ast_nullable() {
+        let nullables = vec!["ε", "a*", "a*b*", "a|ε", "(ab)*|c"];
+        let non_nullables = vec!["a", "a*b", "ab|c", "(a|ε)b"];
+
+        for regex_str in nullables {
+            assert!(RegexAst::parse_str(regex_str).unwrap().nullable());
+        }
+        for regex_str in non_nullables {
+            assert!(!RegexAst::parse_str(regex_str).unwrap().nullable());
+        }
+    }
+
+    #[test]
+    fn regex_ast_derivative() {
+        let cases = vec![
+            ("abc", Alphabet::A, Some("bc")),
+            ("abc", Alphabet::B, None),
+            ("a*b", Alphabet::A, Some("a*b")),
+            ("a*b", Alphabet::B, Some("ε")),
+            ("(ab)*", Alphabet::A, Some("b(ab)*")),
+            ("ab|ac", Alphabet::A, Some("b|c")),
+            ("ε", Alphabet::A, None),
+        ];
+
+        for (regex_str, a, expected) in cases {
+            let derivative = RegexAst::parse_str(regex_str).unwrap().derivative(a);
+
+            assert_eq!(
+                derivative,
+                expected.map(|e| RegexAst::parse_str(e).unwrap()),
+                r#"Derivative of "{}" with respect to {} is wrong"#,
+                regex_str,
+                a
+            );
+        }
+    }
+
+    #[test]
+    fn regex_ast_matches_by_derivatives() {
+        let regex_strs = vec!["(a|b)*abb", "a*bεcc*", "(ab|c)*|ε", "ε|aaa*"];
+        let inputs = vec!["", "a", "abb", "babb", "bc", "aabccc", "abcab", "aaa"];
+
+        for regex_str in regex_strs {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+
+            for input in &inputs {
+                let input = Alphabet::vec_from_str(input).unwrap();
+                assert_eq!(ast.matches_by_derivatives(&input), ast.matches(&input));
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, BudgetExceeded, RegexAst};
+use std::collections::HashSet;
+
+/// A complete deterministic finite automaton over a fixed set of alphabets.
+///
+/// States are numbered from `0` to `state_count() - 1`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Dfa {
+    /// Alphabets the automaton reads, sorted in ascending order
+    pub(super) alphabet: Vec<Alphabet>,
+    pub(super) initial: usize,
+    pub(super) accepting: Vec<bool>,
+    /// `transitions[q][i]` is the state reached from `q` by reading `alphabet[i]`
+    pub(super) transitions: Vec<Vec<usize>>,
+}
+
+impl Dfa {
+    /// Compile `ast` into a DFA reading `alphabet`
+    /// by determinizing the Glushkov automaton of `ast`.
+    pub fn from_ast(ast: &RegexAst, alphabet: &HashSet<Alphabet>) -> Self {
+        ast.glushkov_nfa_over(alphabet).to_dfa()
+    }
+
+    pub(super) fn letter_index(&self, a: &Alphabet) -> Option<usize> {
+        self.alphabet.binary_search(a).ok()
+    }
+
+    /// Alphabets read by the automaton, in ascending order.
+    pub fn alphabet(&self) -> &[Alphabet] {
+        &self.alphabet
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.accepting.len()
+    }
+
+    pub fn initial_state(&self) -> usize {
+        self.initial
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    pub fn accepting_states(&self) -> Vec<usize> {
+        (0..self.state_count())
+            .filter(|q| self.accepting[*q])
+            .collect()
+    }
+
+    /// The state reached from `state` by reading `a`,
+    /// or `None` if the automaton does not read `a`.
+    pub fn next_state(&self, state: usize, a: Alphabet) -> Option<usize> {
+        self.letter_index(&a).map(|i| self.transitions[state][i])
+    }
+
+    /// All transitions of the automaton as triples of (source, alphabet, target).
+    pub fn transitions(&self) -> impl Iterator<Item = (usize, Alphabet, usize)> + '_ {
+        self.transitions
+            .iter()
+            .enumerate()
+            .flat_map(move |(q, row)| {
+                row.iter()
+                    .zip(self.alphabet.iter())
+                    .map(move |(target, a)| (q, *a, *target))
+            })
+    }
+
+    /// Whether the automaton accepts `input`.
+    /// Words containing alphabets the automaton does not read are never accepted.
+    pub fn accepts(&self, input: &[Alphabet]) -> bool {
+        let mut state = self.initial;
+
+        for a in input {
+            match self.letter_index(a) {
+                Some(i) => state = self.transitions[state][i],
+                None => return false,
+            }
+        }
+
+        self.accepting[state]
+    }
+}
+
+impl RegexAst {
+    /// Compile this AST into the minimal complete DFA reading `alphabet`.
+    ///
+    /// Words containing alphabets outside of `alphabet` are not taken into account,
+    /// so the resulting automaton recognizes the intersection of the language and `alphabet`*.
+    ///
+    /// States of the result are numbered canonically (see [Dfa::minimized]),
+    /// so two ASTs denote the same language over `alphabet`
+    /// if and only if their minimal DFAs are equal.
+    pub fn to_min_dfa(&self, alphabet: &HashSet<Alphabet>) -> Dfa {
+        Dfa::from_ast(self, alphabet).minimized()
+    }
+
+    /// Compile this AST into the minimal complete DFA reading `alphabet` like [RegexAst::to_min_dfa],
+    /// but give up if determinizing it needs more than `budget` states.
+    pub fn to_min_dfa_with_budget(
+        &self,
+        alphabet: &HashSet<Alphabet>,
+        budget: usize,
+    ) -> Result<Dfa, BudgetExceeded> {
+        self.glushkov_nfa_over(alphabet)
+            .to_dfa_with_budget(budget)
+            .map(|dfa| dfa.minimized())
+    }
+
+    /// Number of states of the minimal complete DFA over the alphabets used in this AST,
+    /// which equals the number of Myhill–Nerode equivalence classes of the language.
+    pub fn state_complexity(&self) -> usize {
+        self.to_min_dfa(&self.used_alphabets()).state_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{blowup, Alphabet, BudgetExceeded, RegexAst};
+    use std::collections::HashSet;
+
+    fn alphabet_set(alphabets: &str) -> HashSet<Alphabet> {
+        Alphabet::vec_from_str(alphabets)
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn min_dfa_state_count() {
+        let cases = vec![
+            ("(a|b)*a", "ab", 2),
+            ("a*", "ab", 2),
+            ("a*", "a", 1),
+            ("ab|ac", "abc", 4),
+            ("(aa)*", "a", 2),
+            ("(a|b)*abb", "ab", 4),
+            ("ε", "ab", 2),
+        ];
+
+        for (regex_str, alphabets, expected) in cases {
+            let dfa = RegexAst::parse_str(regex_str)
+                .unwrap()
+                .to_min_dfa(&alphabet_set(alphabets));
+
+            assert_eq!(
+                dfa.state_count(),
+                expected,
+                r#"The minimal DFA of "{}" over "{}" should have {} states"#,
+                regex_str,
+                alphabets,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn min_dfa_preserves_language() {
+        let ast = RegexAst::parse_str("(a|b)*abb|ε").unwrap();
+        let dfa = ast.to_min_dfa(&alphabet_set("ab"));
+
+        for input in ["", "abb", "aabb", "babb", "ab", "abba", "c"] {
+            let input = Alphabet::vec_from_str(input).unwrap();
+            assert_eq!(dfa.accepts(&input), ast.matches(&input));
+        }
+
+        assert_eq!(dfa.transitions().count(), dfa.state_count() * 2);
+    }
+
+    #[test]
+    fn min_dfa_with_budget() {
+        let ast = RegexAst::parse_str("(a|b)*abb").unwrap();
+        assert_eq!(
+            ast.to_min_dfa_with_budget(&alphabet_set("ab"), 10),
+            Ok(ast.to_min_dfa(&alphabet_set("ab")))
+        );
+
+        // determinizing this needs 2^21 states without a budget
+        assert_eq!(
+            blowup(20).to_min_dfa_with_budget(&alphabet_set("ab"), 100),
+            Err(BudgetExceeded { budget: 100 })
+        );
+    }
+
+    #[test]
+    fn regex_ast_state_complexity() {
+        let cases = vec![("a*", 1), ("(a|b)*abb", 4), ("ab|ba", 5), ("(aaa)*", 3)];
+
+        for (regex_str, expected) in cases {
+            assert_eq!(
+                RegexAst::parse_str(regex_str).unwrap().state_complexity(),
+                expected,
+                r#"State complexity of "{}" is wrong"#,
+                regex_str
+            );
+        }
+    }
+}
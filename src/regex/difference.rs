@@ -0,0 +1,167 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, BudgetExceeded, Dfa, RegexAst};
+use std::collections::HashSet;
+
+/// A word on which two languages disagree, labeled by the language containing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffExample {
+    /// The word is matched by the left-hand side (`self`) only.
+    OnlyLeft(Vec<Alphabet>),
+    /// The word is matched by the right-hand side (`other`) only.
+    OnlyRight(Vec<Alphabet>),
+}
+
+impl DiffExample {
+    pub fn word(&self) -> &[Alphabet] {
+        match self {
+            DiffExample::OnlyLeft(word) | DiffExample::OnlyRight(word) => word,
+        }
+    }
+}
+
+impl RegexAst {
+    fn union_of_used_alphabets(&self, other: &RegexAst) -> HashSet<Alphabet> {
+        self.used_alphabets()
+            .union(&other.used_alphabets())
+            .cloned()
+            .collect()
+    }
+
+    /// Up to `k` words in the symmetric difference of the languages, in shortlex order.
+    ///
+    /// For `k > 0`, the result is empty if and only if the two ASTs are equivalent.
+    pub fn diff_examples(&self, other: &RegexAst, k: usize) -> Vec<DiffExample> {
+        let alphabet = self.union_of_used_alphabets(other);
+        Dfa::from_ast(self, &alphabet).diff_examples(&Dfa::from_ast(other, &alphabet), k)
+    }
+
+    /// The first word in shortlex order on which the two ASTs disagree,
+    /// or `None` if they are equivalent.
+    ///
+    /// Like [RegexAst::equivalent_to_with_budget], this gives up if determinizing either AST
+    /// needs more than `budget` states.
+    pub fn counterexample_with_budget(
+        &self,
+        other: &RegexAst,
+        budget: usize,
+    ) -> Result<Option<DiffExample>, BudgetExceeded> {
+        let alphabet = self.union_of_used_alphabets(other);
+        let lhs = self.to_min_dfa_with_budget(&alphabet, budget)?;
+        let rhs = other.to_min_dfa_with_budget(&alphabet, budget)?;
+
+        Ok(lhs.counterexample(&rhs))
+    }
+}
+
+impl Dfa {
+    /// Up to `k` words in the symmetric difference of the languages, in shortlex order.
+    ///
+    /// # Panics
+    /// Panics if the automata do not read the same alphabets.
+    pub fn diff_examples(&self, other: &Dfa, k: usize) -> Vec<DiffExample> {
+        self.product(other, |p, q| p != q)
+            .words()
+            .take(k)
+            .map(|word| {
+                if self.accepts(&word) {
+                    DiffExample::OnlyLeft(word)
+                } else {
+                    DiffExample::OnlyRight(word)
+                }
+            })
+            .collect()
+    }
+
+    /// The first word in shortlex order on which the two automata disagree,
+    /// or `None` if they are equivalent.
+    ///
+    /// # Panics
+    /// Panics if the automata do not read the same alphabets.
+    pub fn counterexample(&self, other: &Dfa) -> Option<DiffExample> {
+        self.product(other, |p, q| p != q)
+            .shortest_word()
+            .map(|word| {
+                if self.accepts(&word) {
+                    DiffExample::OnlyLeft(word)
+                } else {
+                    DiffExample::OnlyRight(word)
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiffExample;
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn regex_ast_diff_examples() {
+        let word = |w: &str| Alphabet::vec_from_str(w).unwrap();
+
+        let lhs = RegexAst::parse_str("a*").unwrap();
+        let rhs = RegexAst::parse_str("aa*|b").unwrap();
+        assert_eq!(
+            lhs.diff_examples(&rhs, 3),
+            vec![
+                DiffExample::OnlyLeft(word("")),
+                DiffExample::OnlyRight(word("b"))
+            ]
+        );
+
+        let lhs = RegexAst::parse_str("(a|b)*a").unwrap();
+        let rhs = RegexAst::parse_str("(a|b)*ab").unwrap();
+        assert_eq!(
+            lhs.diff_examples(&rhs, 3),
+            vec![
+                DiffExample::OnlyLeft(word("a")),
+                DiffExample::OnlyLeft(word("aa")),
+                DiffExample::OnlyRight(word("ab")),
+            ]
+        );
+
+        let lhs = RegexAst::parse_str("(a|b)*").unwrap();
+        let rhs = RegexAst::parse_str("(a*b*)*").unwrap();
+        assert!(lhs.diff_examples(&rhs, 3).is_empty());
+    }
+
+    #[test]
+    fn regex_ast_counterexample_with_budget() {
+        let word = |w: &str| Alphabet::vec_from_str(w).unwrap();
+        let regex = |s: &str| RegexAst::parse_str(s).unwrap();
+
+        assert_eq!(
+            regex("(a|b)*a").counterexample_with_budget(&regex("(a|b)*ab"), 100),
+            Ok(Some(DiffExample::OnlyLeft(word("a"))))
+        );
+        assert_eq!(
+            regex("ab*").counterexample_with_budget(&regex("ab*|c"), 100),
+            Ok(Some(DiffExample::OnlyRight(word("c"))))
+        );
+        assert_eq!(
+            regex("(a|b)*").counterexample_with_budget(&regex("(a*b*)*"), 100),
+            Ok(None)
+        );
+        assert!(regex("(a|b)*a(a|b)(a|b)(a|b)")
+            .counterexample_with_budget(&regex("a"), 4)
+            .is_err());
+    }
+}
@@ -0,0 +1,192 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{
+    solver::{AnswerOracle, Solver},
+    LengthDistribution, RegexAst,
+};
+use num_traits::ToPrimitive;
+use std::fmt::{Display, Formatter};
+
+/// Length up to which the density of a language is measured
+const DENSITY_WORD_LENGTH: usize = 8;
+/// Length up to which the solver tests its hypotheses when estimating difficulty
+const SOLVER_TEST_LENGTH: usize = 6;
+/// Lowest scores of [DifficultyTier::Medium], [DifficultyTier::Hard] and [DifficultyTier::Expert]
+const TIER_THRESHOLDS: [f64; 3] = [7.0, 10.0, 14.0];
+
+/// Coarse difficulty of a puzzle, as shown to players.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DifficultyTier {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl DifficultyTier {
+    pub fn of_score(score: f64) -> Self {
+        match TIER_THRESHOLDS.iter().filter(|t| score >= **t).count() {
+            0 => DifficultyTier::Easy,
+            1 => DifficultyTier::Medium,
+            2 => DifficultyTier::Hard,
+            _ => DifficultyTier::Expert,
+        }
+    }
+
+    /// Lengths of example words shown when a quiz starts:
+    /// short words for easy quizzes and longer ones for hard quizzes.
+    pub fn example_lengths(&self) -> LengthDistribution {
+        match self {
+            DifficultyTier::Easy => LengthDistribution::geometric(0.5, 6),
+            DifficultyTier::Medium => LengthDistribution::geometric(0.3, 8),
+            DifficultyTier::Hard => LengthDistribution::geometric(0.2, 10),
+            DifficultyTier::Expert => LengthDistribution::geometric(0.1, 12),
+        }
+    }
+}
+
+impl Display for DifficultyTier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DifficultyTier::Easy => "Easy",
+            DifficultyTier::Medium => "Medium",
+            DifficultyTier::Hard => "Hard",
+            DifficultyTier::Expert => "Expert",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Estimated difficulty of guessing a regex, together with the features it is computed from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifficultyEstimate {
+    /// Weighted sum of the features; higher is harder
+    pub score: f64,
+    pub tier: DifficultyTier,
+    /// Number of states of the minimal DFA over the used alphabets
+    pub state_complexity: usize,
+    pub star_height: usize,
+    /// Number of distinct alphabets used
+    pub alphabet_size: usize,
+    /// Fraction of the words of length at most `DENSITY_WORD_LENGTH` matched
+    pub density: f64,
+    /// Number of membership queries [Solver] needed, if it was consulted
+    pub solver_queries: Option<usize>,
+}
+
+impl DifficultyEstimate {
+    fn from_features(
+        state_complexity: usize,
+        star_height: usize,
+        alphabet_size: usize,
+        density: f64,
+        solver_queries: Option<usize>,
+    ) -> Self {
+        // languages matching almost nothing or almost everything are found out quickly,
+        // so `balance` is highest for languages matching half of the words
+        let balance = 1.0 - (2.0 * density - 1.0).abs();
+        let score = state_complexity as f64
+            + star_height as f64
+            + alphabet_size as f64
+            + 3.0 * balance
+            + solver_queries.map_or(0.0, |queries| (queries as f64 + 1.0).log2() / 2.0);
+
+        DifficultyEstimate {
+            score,
+            tier: DifficultyTier::of_score(score),
+            state_complexity,
+            star_height,
+            alphabet_size,
+            density,
+            solver_queries,
+        }
+    }
+}
+
+fn density(ast: &RegexAst) -> f64 {
+    let alphabet = ast.used_alphabets();
+    let matched = ast
+        .to_min_dfa(&alphabet)
+        .count_words_by_length(DENSITY_WORD_LENGTH)
+        .iter()
+        .map(|count| count.to_f64().unwrap())
+        .sum::<f64>();
+    let total = (0..=DENSITY_WORD_LENGTH)
+        .map(|length| (alphabet.len() as f64).powi(length as i32))
+        .sum::<f64>();
+
+    matched / total
+}
+
+fn estimate(ast: &RegexAst, solver_queries: Option<usize>) -> DifficultyEstimate {
+    DifficultyEstimate::from_features(
+        ast.state_complexity(),
+        ast.star_height(),
+        ast.used_alphabets().len(),
+        density(ast),
+        solver_queries,
+    )
+}
+
+/// Estimate how hard it is to guess `ast` from its structure and language.
+pub fn estimate_difficulty(ast: &RegexAst) -> DifficultyEstimate {
+    estimate(ast, None)
+}
+
+/// Estimate the difficulty like [estimate_difficulty],
+/// additionally taking into account how many queries [Solver] needs to find `ast`.
+pub fn estimate_difficulty_with_solver(ast: &RegexAst) -> DifficultyEstimate {
+    let alphabet = ast.used_alphabets().into_iter().collect::<Vec<_>>();
+    let outcome =
+        Solver::new(&alphabet, SOLVER_TEST_LENGTH).solve(&mut AnswerOracle::new(ast.clone()));
+
+    estimate(ast, Some(outcome.membership_queries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate_difficulty, estimate_difficulty_with_solver, DifficultyTier};
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn difficulty_tiers_are_ordered() {
+        let estimate = |regex: &str| estimate_difficulty(&RegexAst::parse_str(regex).unwrap());
+
+        let easy = estimate("a*");
+        assert_eq!(easy.tier, DifficultyTier::Easy);
+        assert_eq!(easy.state_complexity, 1);
+        assert_eq!(easy.density, 1.0);
+
+        let hard = estimate("(a(b|c)*d|ba*)*c");
+        assert!(hard.score > easy.score);
+        assert!(hard.tier >= DifficultyTier::Hard, "{:?}", hard);
+
+        assert!(estimate("(a|b)*a(a|b)(a|b)").tier > estimate("ab").tier);
+    }
+
+    #[test]
+    fn solver_queries_raise_difficulty() {
+        let ast = RegexAst::parse_str("(ab|ba)*").unwrap();
+        let with_solver = estimate_difficulty_with_solver(&ast);
+
+        assert!(with_solver.solver_queries.unwrap() > 0);
+        assert!(with_solver.score > estimate_difficulty(&ast).score);
+    }
+}
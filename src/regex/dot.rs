@@ -0,0 +1,139 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, Nfa};
+use itertools::Itertools;
+use std::collections::BTreeMap;
+
+/// DOT text of an automaton given its states, with transitions sharing source and target merged.
+fn automaton_to_dot(
+    state_count: usize,
+    initial: impl Iterator<Item = usize>,
+    is_accepting: impl Fn(usize) -> bool,
+    transitions: impl Iterator<Item = (usize, Alphabet, usize)>,
+) -> String {
+    let mut lines = vec![
+        "digraph {".to_string(),
+        "    rankdir=LR;".to_string(),
+        "    node [shape=circle];".to_string(),
+    ];
+
+    for q in 0..state_count {
+        let shape = if is_accepting(q) {
+            "doublecircle"
+        } else {
+            "circle"
+        };
+        lines.push(format!("    {} [label=\"{}\", shape={}];", q, q, shape));
+    }
+
+    for (i, q) in initial.enumerate() {
+        lines.push(format!("    start{} [shape=point];", i));
+        lines.push(format!("    start{} -> {};", i, q));
+    }
+
+    let mut labels = BTreeMap::new();
+    for (source, a, target) in transitions {
+        labels
+            .entry((source, target))
+            .or_insert_with(Vec::new)
+            .push(a);
+    }
+    for ((source, target), alphabets) in labels {
+        lines.push(format!(
+            "    {} -> {} [label=\"{}\"];",
+            source,
+            target,
+            alphabets.iter().join(",")
+        ));
+    }
+
+    lines.push("}".to_string());
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+impl Dfa {
+    /// Graphviz DOT representation of the automaton.
+    ///
+    /// Accepting states are drawn as double circles,
+    /// and transitions between the same pair of states are merged into one edge.
+    pub fn to_dot(&self) -> String {
+        automaton_to_dot(
+            self.state_count(),
+            std::iter::once(self.initial),
+            |q| self.accepting[q],
+            self.transitions(),
+        )
+    }
+}
+
+impl Nfa {
+    /// Graphviz DOT representation of the automaton.
+    ///
+    /// Accepting states are drawn as double circles,
+    /// and transitions between the same pair of states are merged into one edge.
+    pub fn to_dot(&self) -> String {
+        automaton_to_dot(
+            self.state_count(),
+            self.initial.iter().cloned(),
+            |q| self.accepting[q],
+            self.transitions(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+    use indoc::indoc;
+
+    #[test]
+    fn dfa_to_dot() {
+        let ast = RegexAst::parse_str("(a|b)*a").unwrap();
+
+        assert_eq!(
+            ast.to_min_dfa(&ast.used_alphabets()).to_dot(),
+            indoc! {r#"
+                digraph {
+                    rankdir=LR;
+                    node [shape=circle];
+                    0 [label="0", shape=circle];
+                    1 [label="1", shape=doublecircle];
+                    start0 [shape=point];
+                    start0 -> 0;
+                    0 -> 0 [label="b"];
+                    0 -> 1 [label="a"];
+                    1 -> 0 [label="b"];
+                    1 -> 1 [label="a"];
+                }
+            "#}
+        );
+    }
+
+    #[test]
+    fn nfa_to_dot() {
+        let nfa = RegexAst::parse_str("a|ab").unwrap().to_glushkov_nfa();
+        let dot = nfa.to_dot();
+
+        assert!(dot.contains("start0 -> 0;"));
+        assert!(dot.contains(r#"0 -> 1 [label="a"];"#));
+        assert!(dot.contains(r#"0 -> 2 [label="a"];"#));
+        assert!(dot.contains(r#"2 -> 3 [label="b"];"#));
+    }
+}
@@ -0,0 +1,249 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, RegexAst};
+use std::collections::HashSet;
+
+/// All ways of writing `total` as an ordered sum of at least two positive integers.
+fn compositions(total: usize) -> Vec<Vec<usize>> {
+    fn go(remaining: usize, prefix: &mut Vec<usize>, accum: &mut Vec<Vec<usize>>) {
+        if remaining == 0 {
+            if prefix.len() >= 2 {
+                accum.push(prefix.clone());
+            }
+            return;
+        }
+        for part in 1..=remaining {
+            prefix.push(part);
+            go(remaining - part, prefix, accum);
+            prefix.pop();
+        }
+    }
+
+    let mut accum = vec![];
+    go(total, &mut vec![], &mut accum);
+    accum
+}
+
+/// Enumerates ASTs by their number of nodes (see [crate::regex::RegexStats]),
+/// memoizing the ASTs of each size.
+///
+/// Only ASTs without obvious redundancy are produced:
+/// stars are never applied to stars or ε, concatenations contain neither ε nor
+/// concatenations, and branches of an alternation are non-alternations in strictly
+/// ascending order. Every AST is equivalent to one of the enumerated ASTs of at most the same size.
+pub(crate) struct Enumerator {
+    alphabets: Vec<Alphabet>,
+    by_size: Vec<Vec<RegexAst>>,
+}
+
+impl Enumerator {
+    pub(crate) fn new(alphabets: &[Alphabet]) -> Self {
+        let mut alphabets = alphabets.to_vec();
+        alphabets.sort();
+        alphabets.dedup();
+
+        Enumerator {
+            alphabets,
+            // there are no ASTs of size 0
+            by_size: vec![vec![]],
+        }
+    }
+
+    /// ASTs consisting of exactly `size` nodes.
+    pub(crate) fn of_size(&mut self, size: usize) -> &[RegexAst] {
+        while self.by_size.len() <= size {
+            let next = self.generate(self.by_size.len());
+            self.by_size.push(next);
+        }
+
+        &self.by_size[size]
+    }
+
+    /// Every choice of one AST per part of `sizes`, satisfying `allowed` for each chosen AST.
+    fn products(
+        &mut self,
+        sizes: &[usize],
+        allowed: &dyn Fn(&RegexAst) -> bool,
+    ) -> Vec<Vec<RegexAst>> {
+        let mut accum = vec![vec![]];
+
+        for size in sizes {
+            let choices = self
+                .of_size(*size)
+                .iter()
+                .filter(|ast| allowed(ast))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            accum = accum
+                .into_iter()
+                .flat_map(|prefix| {
+                    choices.iter().map(move |choice| {
+                        let mut extended = prefix.clone();
+                        extended.push(choice.clone());
+                        extended
+                    })
+                })
+                .collect();
+        }
+
+        accum
+    }
+
+    fn generate(&mut self, size: usize) -> Vec<RegexAst> {
+        if size == 1 {
+            return std::iter::once(RegexAst::Epsilon)
+                .chain(self.alphabets.iter().map(|a| RegexAst::Literal(*a)))
+                .collect();
+        }
+
+        let mut accum = self
+            .of_size(size - 1)
+            .iter()
+            .filter(|ast| !matches!(ast, RegexAst::Star(_) | RegexAst::Epsilon))
+            .map(|ast| RegexAst::Star(Box::new(ast.clone())))
+            .collect::<Vec<_>>();
+
+        for sizes in compositions(size - 1) {
+            let factors = self.products(&sizes, &|ast| {
+                !matches!(ast, RegexAst::Concatenation(_) | RegexAst::Epsilon)
+            });
+            accum.extend(factors.into_iter().map(RegexAst::Concatenation));
+
+            let branches = self.products(&sizes, &|ast| !matches!(ast, RegexAst::Alternation(_)));
+            accum.extend(
+                branches
+                    .into_iter()
+                    .filter(|branches| branches.windows(2).all(|w| w[0] < w[1]))
+                    .map(RegexAst::Alternation),
+            );
+        }
+
+        accum
+    }
+}
+
+/// Every AST over `alphabet` with at most `size` nodes, up to canonical form.
+///
+/// Each enumerated AST is replaced by its canonical form ([RegexAst::simplify]),
+/// and every canonical form is yielded only once, in ascending order of the size
+/// at which it is first reached. Every AST with at most `size` nodes is
+/// equivalent to one of the yielded ASTs.
+pub fn all_up_to(size: usize, alphabet: &[Alphabet]) -> impl Iterator<Item = RegexAst> {
+    let mut enumerator = Enumerator::new(alphabet);
+    let mut seen = HashSet::new();
+
+    (1..=size)
+        .flat_map(move |size| enumerator.of_size(size).to_vec())
+        .map(|ast| ast.simplify())
+        .filter(move |canonical| seen.insert(canonical.clone()))
+}
+
+impl RegexAst {
+    /// Search for a smallest AST equivalent to this one,
+    /// where the size of an AST is its number of nodes.
+    ///
+    /// Candidates are enumerated exhaustively in the order of their sizes up to `max_size`.
+    /// If no equivalent AST smaller than the simplified form of this AST ([RegexAst::simplify])
+    /// is found within the bound, the simplified form is returned.
+    pub fn minimize_syntax(&self, max_size: usize) -> RegexAst {
+        let simplified = self.simplify();
+
+        let used_alphabets = self.used_alphabets();
+        let target = self.to_min_dfa(&used_alphabets);
+        let bound = max_size.min(simplified.stats().node_count - 1);
+
+        let mut enumerator = Enumerator::new(&used_alphabets.iter().cloned().collect::<Vec<_>>());
+        for size in 1..=bound {
+            // Equivalent ASTs must use exactly the same alphabets (see [RegexAst::equivalent_to]).
+            let found = enumerator.of_size(size).iter().find(|candidate| {
+                candidate.used_alphabets() == used_alphabets
+                    && candidate.to_min_dfa(&used_alphabets) == target
+            });
+
+            if let Some(found) = found {
+                return found.clone();
+            }
+        }
+
+        simplified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{all_up_to, Enumerator};
+    use crate::regex::{Alphabet, RegexAst};
+    use std::collections::HashSet;
+
+    #[test]
+    fn enumerator_counts() {
+        let mut enumerator = Enumerator::new(&[Alphabet::A, Alphabet::B]);
+
+        // ε, a, b
+        assert_eq!(enumerator.of_size(1).len(), 3);
+        // a*, b*
+        assert_eq!(enumerator.of_size(2).len(), 2);
+        // a**, ... are excluded; aa, ab, ba, bb, ε|a, ε|b, a|b (ε sorts first)
+        assert_eq!(enumerator.of_size(3).len(), 7);
+    }
+
+    #[test]
+    fn all_up_to_is_deduplicated() {
+        let asts = all_up_to(3, &[Alphabet::A, Alphabet::B]).collect::<Vec<_>>();
+
+        // the 12 ASTs counted in `enumerator_counts` are already in canonical form
+        assert_eq!(asts.len(), 12);
+        assert!(asts.iter().all(|ast| ast.simplify() == *ast));
+
+        let distinct = asts.iter().collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), asts.len());
+
+        // ASTs with 4 nodes such as `ε|a*` simplify to ones already yielded
+        let larger = all_up_to(4, &[Alphabet::A, Alphabet::B]).collect::<Vec<_>>();
+        assert_eq!(&larger[..asts.len()], &asts[..]);
+        assert!(!larger.contains(&RegexAst::parse_str("ε|a*").unwrap()));
+        assert_eq!(larger.iter().collect::<HashSet<_>>().len(), larger.len());
+    }
+
+    #[test]
+    fn regex_ast_minimize_syntax() {
+        let cases = vec![
+            ("(a|b)*(a|b)*", "(a|b)*"),
+            ("a*(ba*)*", "(a|b)*"),
+            ("ε|aa*", "a*"),
+            ("(a|ε)(a|ε)*", "a*"),
+            ("abab", "abab"),
+        ];
+
+        for (regex_str, expected) in cases {
+            let minimized = RegexAst::parse_str(regex_str).unwrap().minimize_syntax(6);
+
+            assert_eq!(
+                minimized.stats().node_count,
+                RegexAst::parse_str(expected).unwrap().stats().node_count,
+                r#""{}" should be minimized into an AST as small as "{}", but got "{}""#,
+                regex_str,
+                expected,
+                minimized
+            );
+        }
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, RegexAst};
+
+impl Dfa {
+    /// States reachable from the initial state.
+    pub(super) fn reachable_states(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.state_count()];
+        let mut stack = vec![self.initial];
+        reachable[self.initial] = true;
+
+        while let Some(q) = stack.pop() {
+            for next in &self.transitions[q] {
+                if !reachable[*next] {
+                    reachable[*next] = true;
+                    stack.push(*next);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// States that are both reachable and productive,
+    /// i.e. states of the trimmed automaton.
+    pub(super) fn live_states(&self) -> Vec<bool> {
+        self.reachable_states()
+            .into_iter()
+            .zip(self.productive_states())
+            .map(|(reachable, productive)| reachable && productive)
+            .collect()
+    }
+
+    /// Whether the automaton accepts finitely many words,
+    /// i.e. the trimmed automaton has no cycle.
+    pub fn is_finite(&self) -> bool {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Unvisited,
+            OnStack,
+            Done,
+        }
+
+        let live = self.live_states();
+        let mut marks = vec![Mark::Unvisited; self.state_count()];
+
+        // iterative depth-first search, remembering the next letter to try for each state on the stack
+        for root in (0..self.state_count()).filter(|q| live[*q]) {
+            if marks[root] != Mark::Unvisited {
+                continue;
+            }
+
+            marks[root] = Mark::OnStack;
+            let mut stack = vec![(root, 0)];
+            while let Some((q, i)) = stack.pop() {
+                if i == self.alphabet.len() {
+                    marks[q] = Mark::Done;
+                    continue;
+                }
+                stack.push((q, i + 1));
+
+                let next = self.transitions[q][i];
+                if !live[next] {
+                    continue;
+                }
+                match marks[next] {
+                    Mark::OnStack => return false,
+                    Mark::Unvisited => {
+                        marks[next] = Mark::OnStack;
+                        stack.push((next, 0));
+                    }
+                    Mark::Done => {}
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Every word accepted by the automaton in shortlex order,
+    /// or `None` if there are infinitely many of them.
+    pub fn enumerate_all(&self) -> Option<Vec<Vec<Alphabet>>> {
+        self.is_finite().then(|| self.words().collect())
+    }
+}
+
+impl RegexAst {
+    /// Whether this AST matches finitely many words.
+    pub fn is_finite(&self) -> bool {
+        self.to_min_dfa(&self.used_alphabets()).is_finite()
+    }
+
+    /// Every word matched by this AST in shortlex order,
+    /// or `None` if there are infinitely many of them.
+    pub fn enumerate_all(&self) -> Option<Vec<Vec<Alphabet>>> {
+        self.to_min_dfa(&self.used_alphabets()).enumerate_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn regex_ast_finiteness() {
+        let cases = vec![
+            ("a*", false),
+            ("ab|ba", true),
+            ("ε", true),
+            ("(a|b)(a|b)", true),
+            ("ab*a", false),
+            ("(ε|a)(ε|b)", true),
+            ("(a|b)*abb", false),
+        ];
+
+        for (regex_str, expected) in cases {
+            assert_eq!(
+                RegexAst::parse_str(regex_str).unwrap().is_finite(),
+                expected,
+                r#""{}" should{} be finite"#,
+                regex_str,
+                if expected { "" } else { " not" }
+            );
+        }
+    }
+
+    #[test]
+    fn regex_ast_enumerate_all() {
+        let cases = vec![
+            ("ab|ba", Some(vec!["ab", "ba"])),
+            ("ε", Some(vec![""])),
+            ("(ε|a)(ε|b)", Some(vec!["", "a", "b", "ab"])),
+            ("(a|b)(a|b)", Some(vec!["aa", "ab", "ba", "bb"])),
+            ("ab*", None),
+        ];
+
+        for (regex_str, expected) in cases {
+            let expected = expected.map(|words| {
+                words
+                    .into_iter()
+                    .map(|w| Alphabet::vec_from_str(w).unwrap())
+                    .collect::<Vec<_>>()
+            });
+
+            assert_eq!(
+                RegexAst::parse_str(regex_str).unwrap().enumerate_all(),
+                expected,
+                r#"Words matched by "{}" are not enumerated properly"#,
+                regex_str
+            );
+        }
+    }
+}
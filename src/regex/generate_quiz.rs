@@ -17,17 +17,108 @@
  *
  */
 
-use super::RegexAst;
+use super::{
+    generator::{generate_with_state_count, Constraints, GeneratorConfig},
+    RegexAst,
+};
 use crate::regex::Alphabet;
+use anyhow::anyhow;
 use itertools::Itertools;
 use rand::{distributions::Slice, Rng};
 use rand_distr::{Binomial, Distribution, Uniform, WeightedIndex};
-use std::num::NonZeroU8;
+use std::{fmt, num::NonZeroU8, ops::RangeInclusive, str::FromStr};
 use strum::IntoEnumIterator;
+use thiserror::Error;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Difficulty(pub NonZeroU8);
 
+/// Difficulty chosen when starting a quiz, selecting the distribution answers are drawn from.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DifficultyPreset {
+    Easy,
+    Normal,
+    Hard,
+    Lunatic,
+}
+
+impl DifficultyPreset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DifficultyPreset::Easy => "easy",
+            DifficultyPreset::Normal => "normal",
+            DifficultyPreset::Hard => "hard",
+            DifficultyPreset::Lunatic => "lunatic",
+        }
+    }
+
+    /// Number of alphabets in the domain unless chosen otherwise.
+    pub fn alphabet_size(&self) -> NonZeroU8 {
+        let size = match self {
+            DifficultyPreset::Easy => 2,
+            DifficultyPreset::Normal | DifficultyPreset::Hard => 3,
+            DifficultyPreset::Lunatic => 4,
+        };
+        NonZeroU8::new(size).unwrap()
+    }
+
+    /// Range of the number of states of the minimal DFA of an answer over the domain.
+    pub fn state_count(&self) -> RangeInclusive<usize> {
+        match self {
+            DifficultyPreset::Easy => 3..=4,
+            DifficultyPreset::Normal => 4..=6,
+            DifficultyPreset::Hard => 6..=8,
+            DifficultyPreset::Lunatic => 8..=12,
+        }
+    }
+
+    /// Shape of expressions generated over `alphabet`:
+    /// easier presets forbid nested stars, and harder ones require a star and allow larger trees.
+    pub fn generator_config(&self, alphabet: Vec<Alphabet>) -> GeneratorConfig {
+        let (max_depth, star_probability, max_alternation_width, max_size) = match self {
+            DifficultyPreset::Easy => (4, 0.2, 2, 8),
+            DifficultyPreset::Normal => (5, 0.3, 3, 12),
+            DifficultyPreset::Hard => (6, 0.35, 3, 16),
+            DifficultyPreset::Lunatic => (7, 0.4, 4, 20),
+        };
+        let easier = *self <= DifficultyPreset::Normal;
+
+        GeneratorConfig {
+            alphabet,
+            max_depth,
+            star_probability,
+            max_alternation_width,
+            max_size,
+            constraints: Constraints {
+                require_star: !easier,
+                forbid_nested_stars: easier,
+                ..Constraints::default()
+            },
+            reject_trivial: true,
+        }
+    }
+}
+
+impl fmt::Display for DifficultyPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DifficultyPreset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(DifficultyPreset::Easy),
+            "normal" => Ok(DifficultyPreset::Normal),
+            "hard" => Ok(DifficultyPreset::Hard),
+            "lunatic" => Ok(DifficultyPreset::Lunatic),
+            _ => Err(anyhow!("unknown difficulty: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct AlphabetSet(pub Vec<Alphabet>);
 
@@ -35,6 +126,41 @@ pub struct AlphabetSet(pub Vec<Alphabet>);
 const MAX_QUIZ_TREE_SIZE: u8 = 12;
 const MINIMUM_ALLOWED_ACCEPTANCE_RATE: f64 = 0.25;
 const MAXIMUM_ALLOWED_ACCEPTANCE_RATE: f64 = 0.8;
+const MINIMUM_ALLOWED_FINITE_LANGUAGE_SIZE: usize = 8;
+const MINIMUM_ALLOWED_STATE_COMPLEXITY: usize = 3;
+const MAX_RETRIES_FOR_UNAMBIGUOUS_QUIZ: usize = 10;
+const MAX_ATTEMPTS_FOR_PRESET: usize = 2000;
+
+// constants related to quizzes submitted by players
+const MAX_SUBMITTED_TREE_SIZE: usize = 40;
+const MAX_SUBMITTED_STATE_COMPLEXITY: usize = 12;
+const SUBMITTED_DETERMINIZATION_BUDGET: usize = 256;
+
+/// Reason why an expression is not fit to be a quiz.
+#[derive(Debug, Error, PartialEq)]
+pub enum UnsuitableQuiz {
+    #[error("The language has only {0} words, which can be found out by brute force")]
+    TooFewWords(usize),
+    #[error(
+        "The minimal DFA has only {0} states, fewer than {}",
+        MINIMUM_ALLOWED_STATE_COMPLEXITY
+    )]
+    TooFewStates(usize),
+    #[error(
+        "{:.0}% of random words match, outside of {:.0}%–{:.0}%",
+        .0 * 100.0,
+        MINIMUM_ALLOWED_ACCEPTANCE_RATE * 100.0,
+        MAXIMUM_ALLOWED_ACCEPTANCE_RATE * 100.0
+    )]
+    Acceptance(f64),
+    #[error("The expression has {0} nodes, more than {}", MAX_SUBMITTED_TREE_SIZE)]
+    TooLarge(usize),
+    #[error(
+        "The minimal DFA has more than {} states",
+        MAX_SUBMITTED_STATE_COMPLEXITY
+    )]
+    TooComplex,
+}
 
 struct WordDistribution<L, A>(L, A);
 impl<'a, L: Distribution<usize>, A: Distribution<&'a Alphabet>> Distribution<Vec<Alphabet>>
@@ -59,26 +185,81 @@ fn word_distribution(alphabets: &AlphabetSet) -> impl Distribution<Vec<Alphabet>
     WordDistribution(length_distribution, alphabet_distribution(alphabets))
 }
 
-fn estimate_acceptance_probability(alphabets: &AlphabetSet, regex_ast: &RegexAst) -> f64 {
-    let compiled_ast = regex_ast.compile_to_string_regex();
-
-    let thread_rng = rand::thread_rng();
+fn estimate_acceptance_probability<R: Rng + ?Sized>(
+    alphabets: &AlphabetSet,
+    regex_ast: &RegexAst,
+    rng: &mut R,
+) -> f64 {
+    let compiled_ast = regex_ast.compile();
 
     let sample_size = 1000;
-    let matched = word_distribution(alphabets)
-        .sample_iter(thread_rng)
+    let distribution = word_distribution(alphabets);
+    let words = std::iter::repeat_with(|| distribution.sample(rng))
         .take(sample_size)
-        .filter(|w| compiled_ast.is_match(Alphabet::slice_to_plain_string(w).as_str()))
+        .collect::<Vec<_>>();
+    let matched = compiled_ast
+        .matches_many(&words)
+        .into_iter()
+        .filter(|matches| *matches)
         .count();
 
     (matched as f64) / (sample_size as f64)
 }
 
-fn good_as_a_quiz_problem(alphabets: &AlphabetSet, ast: &RegexAst) -> bool {
-    let estimated_acceptance = estimate_acceptance_probability(alphabets, ast);
+fn good_as_a_quiz_problem<R: Rng + ?Sized>(
+    alphabets: &AlphabetSet,
+    ast: &RegexAst,
+    rng: &mut R,
+) -> bool {
+    check_quiz_problem(alphabets, ast, rng).is_ok()
+}
 
-    MINIMUM_ALLOWED_ACCEPTANCE_RATE < estimated_acceptance
-        && estimated_acceptance < MAXIMUM_ALLOWED_ACCEPTANCE_RATE
+fn check_quiz_problem<R: Rng + ?Sized>(
+    alphabets: &AlphabetSet,
+    ast: &RegexAst,
+    rng: &mut R,
+) -> Result<(), UnsuitableQuiz> {
+    // finite languages with a handful of words can be found out by brute force
+    if let Some(words) = ast.enumerate_all() {
+        if words.len() < MINIMUM_ALLOWED_FINITE_LANGUAGE_SIZE {
+            return Err(UnsuitableQuiz::TooFewWords(words.len()));
+        }
+    }
+    let state_complexity = ast.state_complexity();
+    if state_complexity < MINIMUM_ALLOWED_STATE_COMPLEXITY {
+        return Err(UnsuitableQuiz::TooFewStates(state_complexity));
+    }
+
+    let estimated_acceptance = estimate_acceptance_probability(alphabets, ast, rng);
+
+    (MINIMUM_ALLOWED_ACCEPTANCE_RATE < estimated_acceptance
+        && estimated_acceptance < MAXIMUM_ALLOWED_ACCEPTANCE_RATE)
+        .then_some(())
+        .ok_or(UnsuitableQuiz::Acceptance(estimated_acceptance))
+}
+
+/// Check an expression submitted by a player as a quiz over `size` alphabets:
+/// it has to pass the filters generated quizzes pass, and to stay small enough to play.
+pub fn check_submitted_quiz<R: Rng + ?Sized>(
+    ast: &RegexAst,
+    size: NonZeroU8,
+    rng: &mut R,
+) -> Result<(), UnsuitableQuiz> {
+    let node_count = ast.stats().node_count;
+    if node_count > MAX_SUBMITTED_TREE_SIZE {
+        return Err(UnsuitableQuiz::TooLarge(node_count));
+    }
+    let alphabets = alphabets_used_with(&Difficulty(size));
+    let dfa = ast
+        .to_min_dfa_with_budget(
+            &alphabets.0.iter().cloned().collect(),
+            SUBMITTED_DETERMINIZATION_BUDGET,
+        )
+        .map_err(|_| UnsuitableQuiz::TooComplex)?;
+    if dfa.state_count() > MAX_SUBMITTED_STATE_COMPLEXITY {
+        return Err(UnsuitableQuiz::TooComplex);
+    }
+    check_quiz_problem(&alphabets, ast, rng)
 }
 
 fn alphabets_used_with(diff: &Difficulty) -> AlphabetSet {
@@ -207,28 +388,63 @@ impl<'a> Distribution<RegexAst> for BoundedRegexAstDistribution<'a> {
     }
 }
 
-fn generate_ast_smaller_than(alphabets: &AlphabetSet, tree_size: u8) -> RegexAst {
-    let mut rng = rand::thread_rng();
-
+fn generate_ast_smaller_than<R: Rng + ?Sized>(
+    alphabets: &AlphabetSet,
+    tree_size: u8,
+    rng: &mut R,
+) -> RegexAst {
     BoundedRegexAstDistribution {
         alphabet_set: alphabets,
         max_tree_size: tree_size,
     }
-    .sample(&mut rng)
+    .sample(rng)
 }
 
-pub fn randomly_generate(diff: &Difficulty) -> RegexAst {
-    let alphabets = alphabets_used_with(diff);
-
+fn generate_good_problem<R: Rng + ?Sized>(alphabets: &AlphabetSet, rng: &mut R) -> RegexAst {
     loop {
-        let ast = generate_ast_smaller_than(&alphabets, MAX_QUIZ_TREE_SIZE);
+        let ast = generate_ast_smaller_than(alphabets, MAX_QUIZ_TREE_SIZE, rng);
 
-        if good_as_a_quiz_problem(&alphabets, &ast) {
-            return ast.flatten();
+        if good_as_a_quiz_problem(alphabets, &ast, rng) {
+            return ast.simplify();
         }
     }
 }
 
+/// Generate a quiz, preferring unambiguous expressions whose match traces are easier to follow.
+///
+/// All randomness is drawn from `rng`, so a seeded `rng` reproduces the same quiz.
+pub fn randomly_generate<R: Rng + ?Sized>(diff: &Difficulty, rng: &mut R) -> RegexAst {
+    let alphabets = alphabets_used_with(diff);
+
+    let first = generate_good_problem(&alphabets, rng);
+    if !first.is_ambiguous() {
+        return first;
+    }
+
+    std::iter::repeat_with(|| generate_good_problem(&alphabets, rng))
+        .take(MAX_RETRIES_FOR_UNAMBIGUOUS_QUIZ)
+        .find(|ast| !ast.is_ambiguous())
+        .unwrap_or(first)
+}
+
+/// Generate a quiz over `size` alphabets from the distribution of `preset`.
+///
+/// Falls back to [randomly_generate] if no expression with a minimal DFA of the size of `preset`
+/// is found, which may happen when the domain is too small for it.
+/// All randomness is drawn from `rng`, so a seeded `rng` reproduces the same quiz.
+pub fn generate_with_preset<R: Rng + ?Sized>(
+    preset: DifficultyPreset,
+    size: NonZeroU8,
+    rng: &mut R,
+) -> RegexAst {
+    let alphabets = alphabets_used_with(&Difficulty(size));
+    let config = preset.generator_config(alphabets.0);
+
+    generate_with_state_count(&config, preset.state_count(), MAX_ATTEMPTS_FOR_PRESET, rng)
+        .map(|ast| ast.simplify())
+        .unwrap_or_else(|| randomly_generate(&Difficulty(size), rng))
+}
+
 #[test]
 fn difficulty_affects_alphabet_set() {
     assert_eq!(
@@ -253,6 +469,39 @@ fn difficulty_affects_alphabet_set() {
     );
 }
 
+#[test]
+fn submitted_quizzes_are_filtered() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let size = NonZeroU8::new(2).unwrap();
+    let mut rng = StdRng::seed_from_u64(0);
+    let check = |regex: &str, rng: &mut StdRng| {
+        check_submitted_quiz(&RegexAst::parse_str(regex).unwrap(), size, rng)
+    };
+
+    assert_eq!(
+        check("ab|ba", &mut rng),
+        Err(UnsuitableQuiz::TooFewWords(2))
+    );
+    assert_eq!(
+        check("(a|b)*", &mut rng),
+        Err(UnsuitableQuiz::TooFewStates(1))
+    );
+    assert!(matches!(
+        check("aaa(a|b)*", &mut rng),
+        Err(UnsuitableQuiz::Acceptance(_))
+    ));
+    assert_eq!(
+        check(&"a".repeat(50), &mut rng),
+        Err(UnsuitableQuiz::TooLarge(51))
+    );
+    assert_eq!(
+        check("(a|b)*a(a|b)(a|b)(a|b)(a|b)(a|b)", &mut rng),
+        Err(UnsuitableQuiz::TooComplex)
+    );
+    assert_eq!(check("(b|ab*ab*a)*", &mut rng), Ok(()));
+}
+
 #[test]
 fn randomly_generate_returns() {
     use std::convert::TryInto;
@@ -260,13 +509,81 @@ fn randomly_generate_returns() {
     let diff = Difficulty(3u8.try_into().unwrap());
 
     let alphabets = alphabets_used_with(&diff);
-    let asts = std::iter::repeat_with(|| randomly_generate(&diff)).take(3);
+    let mut rng = rand::thread_rng();
+    let asts = std::iter::repeat_with(|| randomly_generate(&diff, &mut rng))
+        .take(3)
+        .collect::<Vec<_>>();
 
     for ast in asts {
         println!(
             "Generated AST\n\t{:?}\nwith estimated acceptance rate of {}",
             ast,
-            estimate_acceptance_probability(&alphabets, &ast)
+            estimate_acceptance_probability(&alphabets, &ast, &mut rng)
+        );
+    }
+}
+
+#[test]
+fn randomly_generate_is_reproducible_from_seed() {
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::convert::TryInto;
+
+    let diff = Difficulty(3u8.try_into().unwrap());
+
+    for seed in 0..3 {
+        assert_eq!(
+            randomly_generate(&diff, &mut StdRng::seed_from_u64(seed)),
+            randomly_generate(&diff, &mut StdRng::seed_from_u64(seed))
+        );
+    }
+}
+
+#[test]
+fn presets_select_state_count() {
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::HashSet;
+
+    for preset in [
+        DifficultyPreset::Easy,
+        DifficultyPreset::Normal,
+        DifficultyPreset::Hard,
+        DifficultyPreset::Lunatic,
+    ] {
+        assert_eq!(preset.name().parse::<DifficultyPreset>().unwrap(), preset);
+
+        let size = preset.alphabet_size();
+        let domain = Alphabet::iter()
+            .take(size.get().into())
+            .collect::<HashSet<_>>();
+        let ast = generate_with_preset(preset, size, &mut StdRng::seed_from_u64(0));
+        assert!(
+            preset
+                .state_count()
+                .contains(&ast.to_min_dfa(&domain).state_count()),
+            "{:?}: {}",
+            preset,
+            ast
+        );
+        assert_eq!(
+            generate_with_preset(preset, size, &mut StdRng::seed_from_u64(0)),
+            ast
+        );
+    }
+}
+
+#[test]
+fn presets_honor_the_number_of_letters() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let letters = NonZeroU8::new(2).unwrap();
+    let domain = alphabets_used_with(&Difficulty(letters)).0;
+
+    for seed in 0..3 {
+        let ast = generate_with_preset(
+            DifficultyPreset::Lunatic,
+            letters,
+            &mut StdRng::seed_from_u64(seed),
         );
+        assert!(ast.used_alphabets().iter().all(|a| domain.contains(a)));
     }
 }
@@ -0,0 +1,409 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, RegexAst};
+use rand::{distributions::Slice, seq::index, Rng};
+use rand_distr::Distribution;
+use std::{collections::HashSet, ops::RangeInclusive};
+
+/// Parameters controlling the shape of generated expressions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratorConfig {
+    /// Alphabets literals are drawn from. Must not be empty.
+    pub alphabet: Vec<Alphabet>,
+    /// Maximum length of a path from the root to a leaf, counted in nodes
+    pub max_depth: usize,
+    /// Probability of choosing a `Star` whenever a node with a child can be placed
+    pub star_probability: f64,
+    /// Maximum number of branches of a single `Alternation`
+    pub max_alternation_width: usize,
+    /// Maximum number of nodes in the whole tree
+    pub max_size: usize,
+    /// Requirements on the generated expression, enforced by resampling
+    pub constraints: Constraints,
+    /// Whether resampling rejects languages that are trivial over `alphabet`
+    /// (see [`is_trivial_language`])
+    pub reject_trivial: bool,
+}
+
+/// Structural requirements a generated expression must meet,
+/// checked by [`generate_satisfying`] and [`generate_with_state_count`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    /// The expression contains at least one `Star`
+    pub require_star: bool,
+    /// No `Star` occurs inside another `Star`
+    pub forbid_nested_stars: bool,
+    /// The expression uses exactly this many distinct alphabets
+    pub distinct_letters: Option<usize>,
+    /// The expression does not match the empty word
+    pub forbid_nullable: bool,
+}
+
+impl Constraints {
+    pub fn are_satisfied_by(&self, ast: &RegexAst) -> bool {
+        let stats = ast.stats();
+
+        (!self.require_star || stats.star_count > 0)
+            && (!self.forbid_nested_stars || stats.star_height <= 1)
+            && self
+                .distinct_letters
+                .iter()
+                .all(|count| ast.used_alphabets().len() == *count)
+            && (!self.forbid_nullable || !ast.nullable())
+    }
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            alphabet: vec![Alphabet::A, Alphabet::B, Alphabet::C],
+            max_depth: 5,
+            star_probability: 0.3,
+            max_alternation_width: 3,
+            max_size: 12,
+            constraints: Constraints::default(),
+            reject_trivial: true,
+        }
+    }
+}
+
+/// A distribution of subtrees that fit in the given number of nodes and levels.
+struct SubtreeDistribution<'a> {
+    config: &'a GeneratorConfig,
+    max_size: usize,
+    max_depth: usize,
+}
+
+impl SubtreeDistribution<'_> {
+    fn child(&self, max_size: usize) -> Self {
+        SubtreeDistribution {
+            config: self.config,
+            max_size,
+            max_depth: self.max_depth - 1,
+        }
+    }
+
+    /// Split `total` nodes into `count` subtrees of at least one node each.
+    fn partition<R: Rng + ?Sized>(total: usize, count: usize, rng: &mut R) -> Vec<usize> {
+        let mut cuts = index::sample(rng, total - 1, count - 1)
+            .into_iter()
+            .map(|i| i + 1)
+            .collect::<Vec<_>>();
+        cuts.sort_unstable();
+
+        std::iter::once(0)
+            .chain(cuts)
+            .chain(std::iter::once(total))
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .collect()
+    }
+}
+
+impl Distribution<RegexAst> for SubtreeDistribution<'_> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> RegexAst {
+        let literal = |rng: &mut R| {
+            RegexAst::Literal(*Slice::new(&self.config.alphabet).unwrap().sample(rng))
+        };
+
+        if self.max_size < 2 || self.max_depth < 2 {
+            return literal(rng);
+        }
+        if rng.gen_bool(self.config.star_probability) {
+            return RegexAst::Star(Box::new(self.child(self.max_size - 1).sample(rng)));
+        }
+        if self.max_size < 3 {
+            return literal(rng);
+        }
+
+        // the operator node itself takes one of the nodes
+        let children_size = self.max_size - 1;
+        let alternation_width = self.config.max_alternation_width.min(children_size);
+        let is_alternation = alternation_width >= 2 && rng.gen_bool(0.5);
+        let count = rng.gen_range(
+            2..=if is_alternation {
+                alternation_width
+            } else {
+                children_size
+            },
+        );
+
+        let children = Self::partition(children_size, count, rng)
+            .into_iter()
+            .map(|size| self.child(size).sample(rng))
+            .collect();
+
+        if is_alternation {
+            RegexAst::Alternation(children)
+        } else {
+            RegexAst::Concatenation(children)
+        }
+    }
+}
+
+/// Generate a random expression whose shape is bounded by `config`.
+///
+/// The result is not simplified, so that its statistics (see [`RegexAst::stats`])
+/// stay within the bounds of `config`. `config.constraints` is not taken into account;
+/// use [`generate_satisfying`] to enforce it.
+///
+/// Panics if `config.alphabet` is empty or `config.star_probability` is not within `[0, 1]`.
+pub fn generate<R: Rng + ?Sized>(config: &GeneratorConfig, rng: &mut R) -> RegexAst {
+    SubtreeDistribution {
+        config,
+        max_size: config.max_size,
+        max_depth: config.max_depth,
+    }
+    .sample(rng)
+}
+
+/// Whether the language of `ast` over `alphabet` is too degenerate to make a puzzle:
+/// its minimal DFA has at most two states (which includes Σ* and the empty word alone),
+/// or it consists of a single word (which includes a single literal).
+pub fn is_trivial_language(ast: &RegexAst, alphabet: &HashSet<Alphabet>) -> bool {
+    ast.to_min_dfa(alphabet).state_count() <= 2
+        || ast.enumerate_all().is_some_and(|words| words.len() == 1)
+}
+
+/// Generate expressions by [`generate`] until one satisfies both `config.constraints` and `accept`,
+/// giving up after `max_attempts` samples.
+/// Trivial languages are also rejected if `config.reject_trivial` is set.
+fn resample<R: Rng + ?Sized>(
+    config: &GeneratorConfig,
+    max_attempts: usize,
+    rng: &mut R,
+    accept: impl Fn(&RegexAst) -> bool,
+) -> Option<RegexAst> {
+    let alphabet = config.alphabet.iter().cloned().collect::<HashSet<_>>();
+
+    (0..max_attempts)
+        .map(|_| generate(config, rng))
+        .find(|ast| {
+            config.constraints.are_satisfied_by(ast)
+                && !(config.reject_trivial && is_trivial_language(ast, &alphabet))
+                && accept(ast)
+        })
+}
+
+/// Generate an expression satisfying `config.constraints`,
+/// and whose language is not trivial if `config.reject_trivial` is set.
+///
+/// Returns `None` if no such expression is found in `max_attempts` samples.
+pub fn generate_satisfying<R: Rng + ?Sized>(
+    config: &GeneratorConfig,
+    max_attempts: usize,
+    rng: &mut R,
+) -> Option<RegexAst> {
+    resample(config, max_attempts, rng, |_| true)
+}
+
+/// Generate an expression whose minimal DFA over `config.alphabet` has a number of states
+/// within `state_count`, so that the difficulty of a puzzle depends on its language
+/// rather than on how large the expression happens to be.
+///
+/// Returns `None` if no such expression is found in `max_attempts` samples.
+pub fn generate_with_state_count<R: Rng + ?Sized>(
+    config: &GeneratorConfig,
+    state_count: RangeInclusive<usize>,
+    max_attempts: usize,
+    rng: &mut R,
+) -> Option<RegexAst> {
+    let alphabet = config.alphabet.iter().cloned().collect::<HashSet<_>>();
+
+    resample(config, max_attempts, rng, |ast| {
+        state_count.contains(&ast.to_min_dfa(&alphabet).state_count())
+    })
+}
+
+/// Generate a complete DFA over `alphabet` by drawing transitions and accepting states uniformly,
+/// until its minimal DFA has exactly `state_count` states and accepts at least two words.
+/// Unlike generated expressions, such automata often have no short regular expression.
+///
+/// Returns the minimal DFA, or `None` if none is found in `max_attempts` samples.
+pub fn generate_dfa<R: Rng + ?Sized>(
+    alphabet: &[Alphabet],
+    state_count: usize,
+    max_attempts: usize,
+    rng: &mut R,
+) -> Option<Dfa> {
+    let mut alphabet = alphabet.to_vec();
+    alphabet.sort();
+    alphabet.dedup();
+
+    (0..max_attempts)
+        .map(|_| {
+            Dfa {
+                alphabet: alphabet.clone(),
+                initial: 0,
+                accepting: (0..state_count).map(|_| rng.gen_bool(0.5)).collect(),
+                transitions: (0..state_count)
+                    .map(|_| {
+                        (0..alphabet.len())
+                            .map(|_| rng.gen_range(0..state_count))
+                            .collect()
+                    })
+                    .collect(),
+            }
+            .minimized()
+        })
+        .find(|dfa| dfa.state_count() == state_count && dfa.words().take(2).count() == 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        generate, generate_dfa, generate_satisfying, generate_with_state_count,
+        is_trivial_language, Constraints, GeneratorConfig,
+    };
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn generated_asts_respect_config() {
+        let config = GeneratorConfig {
+            alphabet: vec![Alphabet::B, Alphabet::D],
+            max_depth: 4,
+            star_probability: 0.2,
+            max_alternation_width: 2,
+            max_size: 10,
+            constraints: Constraints::default(),
+            reject_trivial: false,
+        };
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..200 {
+            let ast = generate(&config, &mut rng);
+            let stats = ast.stats();
+
+            assert!(stats.node_count <= config.max_size, "{:?}", ast);
+            assert!(stats.nesting_depth <= config.max_depth, "{:?}", ast);
+            assert!(
+                stats.alternation_width <= config.max_alternation_width,
+                "{:?}",
+                ast
+            );
+            assert!(ast
+                .used_alphabets()
+                .iter()
+                .all(|a| config.alphabet.contains(a)));
+        }
+    }
+
+    #[test]
+    fn generator_without_stars() {
+        let config = GeneratorConfig {
+            star_probability: 0.0,
+            ..GeneratorConfig::default()
+        };
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let ast = generate(&config, &mut rng);
+            assert_eq!(ast.stats().star_count, 0);
+            assert!(ast.enumerate_all().is_some());
+        }
+    }
+
+    #[test]
+    fn generator_targets_state_count() {
+        let config = GeneratorConfig::default();
+        let alphabet = config.alphabet.iter().cloned().collect();
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let ast = generate_with_state_count(&config, 4..=6, 1000, &mut rng).unwrap();
+            assert!((4..=6).contains(&ast.to_min_dfa(&alphabet).state_count()));
+        }
+
+        // a tree of three nodes cannot need that many states
+        let tiny = GeneratorConfig {
+            max_size: 3,
+            ..config
+        };
+        assert_eq!(
+            generate_with_state_count(&tiny, 8..=12, 100, &mut rng),
+            None
+        );
+    }
+
+    #[test]
+    fn generator_of_automata() {
+        let alphabet = Alphabet::vec_from_str("ab").unwrap();
+        let mut rng = rand::thread_rng();
+
+        for state_count in 3..=5 {
+            let dfa = generate_dfa(&alphabet, state_count, 1000, &mut rng).unwrap();
+            assert_eq!(dfa.state_count(), state_count);
+            assert_eq!(dfa.alphabet(), alphabet.as_slice());
+            assert_eq!(dfa.minimized(), dfa);
+            assert!(dfa.to_regex().is_some());
+        }
+    }
+
+    #[test]
+    fn generator_satisfies_constraints() {
+        let config = GeneratorConfig {
+            constraints: Constraints {
+                require_star: true,
+                forbid_nested_stars: true,
+                distinct_letters: Some(2),
+                forbid_nullable: true,
+            },
+            ..GeneratorConfig::default()
+        };
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let ast = generate_satisfying(&config, 1000, &mut rng).unwrap();
+            let stats = ast.stats();
+
+            assert!(stats.star_count > 0 && stats.star_height <= 1, "{:?}", ast);
+            assert_eq!(ast.used_alphabets().len(), 2);
+            assert!(!ast.nullable());
+        }
+
+        let impossible = GeneratorConfig {
+            constraints: Constraints {
+                distinct_letters: Some(4),
+                ..Constraints::default()
+            },
+            ..GeneratorConfig::default()
+        };
+        assert_eq!(generate_satisfying(&impossible, 100, &mut rng), None);
+    }
+
+    #[test]
+    fn trivial_languages() {
+        let alphabet = Alphabet::vec_from_str("ab").unwrap().into_iter().collect();
+        let is_trivial =
+            |regex: &str| is_trivial_language(&RegexAst::parse_str(regex).unwrap(), &alphabet);
+
+        assert!(is_trivial("(a|b)*"));
+        assert!(is_trivial("(a*b*)*"));
+        assert!(is_trivial("a"));
+        assert!(is_trivial("ε"));
+        assert!(is_trivial("abba"));
+        assert!(is_trivial("(a|b)(a|b)*"));
+        assert!(is_trivial("a*"));
+        assert!(!is_trivial("a*b"));
+        assert!(!is_trivial("ab|ba"));
+        assert!(!is_trivial("(a|b)*ab"));
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Nfa, RegexAst};
+use std::collections::{BTreeSet, HashSet};
+
+/// Positions of literals in a [RegexAst], together with the sets of positions
+/// that can start, end and follow each other in a matching word.
+///
+/// Position `p` refers to the `p`-th literal in the AST, counted from the left.
+struct Positions {
+    letters: Vec<Alphabet>,
+    follow: Vec<BTreeSet<usize>>,
+    first: BTreeSet<usize>,
+    last: BTreeSet<usize>,
+    nullable: bool,
+}
+
+impl Positions {
+    fn of(ast: &RegexAst) -> Self {
+        let mut letters = vec![];
+        let mut follow = vec![];
+        let (nullable, first, last) = Self::linearize(ast, &mut letters, &mut follow);
+
+        Positions {
+            letters,
+            follow,
+            first,
+            last,
+            nullable,
+        }
+    }
+
+    /// Assign positions to literals in `ast` while recording follow sets,
+    /// and return the nullability, the first set and the last set of `ast`.
+    fn linearize(
+        ast: &RegexAst,
+        letters: &mut Vec<Alphabet>,
+        follow: &mut Vec<BTreeSet<usize>>,
+    ) -> (bool, BTreeSet<usize>, BTreeSet<usize>) {
+        match ast {
+            RegexAst::Epsilon => (true, BTreeSet::new(), BTreeSet::new()),
+            RegexAst::Literal(a) => {
+                let position = letters.len();
+                letters.push(*a);
+                follow.push(BTreeSet::new());
+                let singleton = std::iter::once(position).collect::<BTreeSet<_>>();
+                (false, singleton.clone(), singleton)
+            }
+            RegexAst::Star(ast) => {
+                let (_, first, last) = Self::linearize(ast, letters, follow);
+                for p in &last {
+                    follow[*p].extend(&first);
+                }
+                (true, first, last)
+            }
+            RegexAst::Concatenation(asts) => {
+                let mut nullable = true;
+                let mut first = BTreeSet::new();
+                let mut last = BTreeSet::<usize>::new();
+
+                for ast in asts {
+                    let (n, f, l) = Self::linearize(ast, letters, follow);
+                    for p in &last {
+                        follow[*p].extend(&f);
+                    }
+                    if nullable {
+                        first.extend(&f);
+                    }
+                    if n {
+                        last.extend(l);
+                    } else {
+                        last = l;
+                    }
+                    nullable &= n;
+                }
+
+                (nullable, first, last)
+            }
+            RegexAst::Alternation(asts) => {
+                let mut nullable = false;
+                let mut first = BTreeSet::new();
+                let mut last = BTreeSet::new();
+
+                for ast in asts {
+                    let (n, f, l) = Self::linearize(ast, letters, follow);
+                    nullable |= n;
+                    first.extend(f);
+                    last.extend(l);
+                }
+
+                (nullable, first, last)
+            }
+        }
+    }
+}
+
+impl RegexAst {
+    /// Construct the Glushkov (position) automaton of this expression,
+    /// reading the alphabets used in the expression.
+    ///
+    /// The automaton has no ε-transitions and `n + 1` states, where `n` is the number of literals.
+    /// State `0` is the initial state, and state `p + 1` corresponds to the `p`-th literal
+    /// of the expression counted from the left; every transition into state `p + 1`
+    /// reads the alphabet of that literal.
+    pub fn to_glushkov_nfa(&self) -> Nfa {
+        self.glushkov_nfa_over(&self.used_alphabets())
+    }
+
+    pub(crate) fn glushkov_nfa_over(&self, alphabet: &HashSet<Alphabet>) -> Nfa {
+        let positions = Positions::of(self);
+
+        let mut alphabet = alphabet.iter().cloned().collect::<Vec<_>>();
+        alphabet.sort();
+
+        let transitions_from = |successors: &BTreeSet<usize>| {
+            alphabet
+                .iter()
+                .map(|a| {
+                    successors
+                        .iter()
+                        .filter(|q| positions.letters[**q] == *a)
+                        .map(|q| q + 1)
+                        .collect::<BTreeSet<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let transitions = std::iter::once(&positions.first)
+            .chain(positions.follow.iter())
+            .map(transitions_from)
+            .collect();
+
+        let accepting = std::iter::once(positions.nullable)
+            .chain((0..positions.letters.len()).map(|p| positions.last.contains(&p)))
+            .collect();
+
+        Nfa {
+            alphabet,
+            initial: std::iter::once(0).collect(),
+            accepting,
+            transitions,
+        }
+    }
+}
+
+#[test]
+fn glushkov_nfa_states_correspond_to_positions() {
+    let ast = RegexAst::parse_str("(a|b)*ab").unwrap();
+    let nfa = ast.to_glushkov_nfa();
+
+    assert_eq!(nfa.state_count(), 5);
+
+    let literals = [Alphabet::A, Alphabet::B, Alphabet::A, Alphabet::B];
+    for (source, a, target) in nfa.transitions() {
+        assert_ne!(target, 0, "no transition enters the initial state");
+        assert_eq!(
+            a,
+            literals[target - 1],
+            "transition {} -> {} should read the literal at the position of its target",
+            source,
+            target
+        );
+    }
+
+    for input in ["", "ab", "bab", "abab", "ba", "aab"] {
+        let input = Alphabet::vec_from_str(input).unwrap();
+        assert_eq!(nfa.accepts(&input), ast.matches(&input));
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, RegexAst};
+use thiserror::Error;
+
+fn show_word(word: &[Alphabet]) -> String {
+    if word.is_empty() {
+        "ε".to_string()
+    } else {
+        Alphabet::slice_to_plain_string(word)
+    }
+}
+
+/// Reason a regex is disqualified in regex golf.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum GolfError {
+    #[error("`{}` must be matched", show_word(.0))]
+    MissedPositive(Vec<Alphabet>),
+    #[error("`{}` must not be matched", show_word(.0))]
+    MatchedNegative(Vec<Alphabet>),
+}
+
+/// Score `regex` as an entry of regex golf: it must match every word of `positives`
+/// and none of `negatives`, and its score is its length, so lower scores are better.
+///
+/// The length is the number of characters of the displayed form of `regex`,
+/// so redundant parentheses in the submitted text are not counted.
+pub fn golf_score(
+    regex: &RegexAst,
+    positives: &[Vec<Alphabet>],
+    negatives: &[Vec<Alphabet>],
+) -> Result<usize, GolfError> {
+    let compiled = regex.compile();
+
+    if let Some(missed) = positives.iter().find(|word| !compiled.matches(word)) {
+        return Err(GolfError::MissedPositive(missed.clone()));
+    }
+    if let Some(matched) = negatives.iter().find(|word| compiled.matches(word)) {
+        return Err(GolfError::MatchedNegative(matched.clone()));
+    }
+
+    Ok(format!("{}", regex).chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{golf_score, GolfError};
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn golf_score_checks_and_measures() {
+        let words = |words: &[&str]| {
+            words
+                .iter()
+                .map(|w| Alphabet::vec_from_str(w).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let positives = words(&["ab", "aab", "b"]);
+        let negatives = words(&["", "ba", "a"]);
+        let score =
+            |regex: &str| golf_score(&RegexAst::parse_str(regex).unwrap(), &positives, &negatives);
+
+        assert_eq!(score("a*b"), Ok(3));
+        assert_eq!(score("((a)*)b"), Ok(3));
+        assert_eq!(score("ab|aab|b"), Ok(8));
+        assert_eq!(
+            score("ab|b"),
+            Err(GolfError::MissedPositive(
+                Alphabet::vec_from_str("aab").unwrap()
+            ))
+        );
+        assert_eq!(score("(a|b)*"), Err(GolfError::MatchedNegative(vec![])));
+    }
+}
@@ -0,0 +1,176 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Dfa, RegexAst};
+
+/// How fast the number of words of length `n` in a language grows as `n` increases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrowthClass {
+    /// There are finitely many words.
+    Finite,
+    /// There are Θ(n^d) words of length n for infinitely many n, where d is the degree.
+    Polynomial(usize),
+    /// There are 2^Θ(n) words of length n for infinitely many n.
+    Exponential,
+}
+
+impl Dfa {
+    /// Strongly connected components of the subgraph induced by `included` states.
+    ///
+    /// Returns the component index of each state (`None` for states not included).
+    /// Components are numbered in a topological order,
+    /// so any transition goes from a component to one with the same or a larger index.
+    pub(super) fn strongly_connected_components(
+        &self,
+        included: &[bool],
+    ) -> (Vec<Option<usize>>, usize) {
+        let n = self.state_count();
+        let mut predecessors = vec![vec![]; n];
+        for (source, _, target) in self.transitions() {
+            if included[source] && included[target] {
+                predecessors[target].push(source);
+            }
+        }
+
+        // Kosaraju's algorithm: first order states by the time their depth-first search finishes
+        let mut visited = vec![false; n];
+        let mut finished = vec![];
+        for root in (0..n).filter(|q| included[*q]) {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![(root, 0)];
+            while let Some((q, i)) = stack.pop() {
+                if i == self.alphabet.len() {
+                    finished.push(q);
+                    continue;
+                }
+                stack.push((q, i + 1));
+
+                let next = self.transitions[q][i];
+                if included[next] && !visited[next] {
+                    visited[next] = true;
+                    stack.push((next, 0));
+                }
+            }
+        }
+
+        // then collect states reachable backwards, in the reverse order of finishing
+        let mut component = vec![None; n];
+        let mut component_count = 0;
+        for root in finished.into_iter().rev() {
+            if component[root].is_some() {
+                continue;
+            }
+            component[root] = Some(component_count);
+            let mut stack = vec![root];
+            while let Some(q) = stack.pop() {
+                for p in &predecessors[q] {
+                    if component[*p].is_none() {
+                        component[*p] = Some(component_count);
+                        stack.push(*p);
+                    }
+                }
+            }
+            component_count += 1;
+        }
+
+        (component, component_count)
+    }
+
+    /// Growth of the number of accepted words of each length.
+    ///
+    /// In the trimmed automaton, the growth is exponential if and only if
+    /// some strongly connected component contains two distinct cycles.
+    /// Otherwise every component is a single cycle or a single state without a loop,
+    /// and the degree of polynomial growth is one less than
+    /// the largest number of cycles visited by a path.
+    pub fn growth_class(&self) -> GrowthClass {
+        let live = self.live_states();
+        let (component, component_count) = self.strongly_connected_components(&live);
+
+        let mut sizes = vec![0; component_count];
+        let mut inner_transitions = vec![0; component_count];
+        let mut successors = vec![vec![]; component_count];
+        for q in (0..self.state_count()).filter(|q| live[*q]) {
+            sizes[component[q].unwrap()] += 1;
+        }
+        for (source, _, target) in self.transitions() {
+            match (component[source], component[target]) {
+                (Some(c), Some(d)) if c == d => inner_transitions[c] += 1,
+                (Some(c), Some(d)) => successors[c].push(d),
+                _ => {}
+            }
+        }
+
+        if (0..component_count).any(|c| inner_transitions[c] > sizes[c]) {
+            return GrowthClass::Exponential;
+        }
+
+        // largest number of cycles on a path starting from each component
+        let mut cycles = vec![0; component_count];
+        for c in (0..component_count).rev() {
+            let is_cycle = (inner_transitions[c] == sizes[c]) as usize;
+            cycles[c] = is_cycle + successors[c].iter().map(|d| cycles[*d]).max().unwrap_or(0);
+        }
+
+        match cycles.into_iter().max().unwrap_or(0) {
+            0 => GrowthClass::Finite,
+            k => GrowthClass::Polynomial(k - 1),
+        }
+    }
+}
+
+impl RegexAst {
+    /// Growth of the number of words of each length matched by this AST.
+    pub fn growth_class(&self) -> GrowthClass {
+        self.to_min_dfa(&self.used_alphabets()).growth_class()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GrowthClass;
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_growth_class() {
+        let cases = vec![
+            ("ab|ba", GrowthClass::Finite),
+            ("ε", GrowthClass::Finite),
+            ("a*", GrowthClass::Polynomial(0)),
+            ("(ab)*c", GrowthClass::Polynomial(0)),
+            ("a*b*", GrowthClass::Polynomial(1)),
+            ("a*ba*ba*", GrowthClass::Polynomial(2)),
+            ("(a|b)*", GrowthClass::Exponential),
+            ("(ab|ba)*", GrowthClass::Exponential),
+            ("c(a|b)*c", GrowthClass::Exponential),
+        ];
+
+        for (regex_str, expected) in cases {
+            assert_eq!(
+                RegexAst::parse_str(regex_str).unwrap().growth_class(),
+                expected,
+                r#"Growth of "{}" is classified wrongly"#,
+                regex_str
+            );
+        }
+    }
+}
@@ -0,0 +1,95 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, RegexAst};
+use std::collections::HashMap;
+
+impl RegexAst {
+    /// Replace each literal by the AST `substitution` assigns to it.
+    /// Literals not in `substitution` are left as they are.
+    pub fn substitute(&self, substitution: &HashMap<Alphabet, RegexAst>) -> RegexAst {
+        match self {
+            RegexAst::Epsilon => RegexAst::Epsilon,
+            RegexAst::Literal(a) => substitution
+                .get(a)
+                .cloned()
+                .unwrap_or(RegexAst::Literal(*a)),
+            RegexAst::Star(ast) => RegexAst::Star(Box::new(ast.substitute(substitution))),
+            RegexAst::Concatenation(asts) => RegexAst::Concatenation(
+                asts.iter()
+                    .map(|ast| ast.substitute(substitution))
+                    .collect(),
+            ),
+            RegexAst::Alternation(asts) => RegexAst::Alternation(
+                asts.iter()
+                    .map(|ast| ast.substitute(substitution))
+                    .collect(),
+            ),
+        }
+        .flatten()
+    }
+
+    /// Rename alphabets according to `map`. Alphabets not in `map` are left as they are.
+    ///
+    /// If `map` is injective on the used alphabets, the result is isomorphic to this AST.
+    pub fn rename(&self, map: &HashMap<Alphabet, Alphabet>) -> RegexAst {
+        self.substitute(
+            &map.iter()
+                .map(|(from, to)| (*from, RegexAst::Literal(*to)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, RegexAst};
+    use std::collections::HashMap;
+
+    #[test]
+    fn regex_ast_rename() {
+        let map = vec![(Alphabet::A, Alphabet::B), (Alphabet::B, Alphabet::A)]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        assert_eq!(
+            RegexAst::parse_str("(ab|c)*a").unwrap().rename(&map),
+            RegexAst::parse_str("(ba|c)*b").unwrap()
+        );
+    }
+
+    #[test]
+    fn regex_ast_substitute() {
+        let substitution = vec![
+            (Alphabet::A, RegexAst::parse_str("bc").unwrap()),
+            (Alphabet::B, RegexAst::parse_str("a|c*").unwrap()),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        let substituted = RegexAst::parse_str("a*b")
+            .unwrap()
+            .substitute(&substitution);
+        assert!(
+            substituted.equivalent_to(&RegexAst::parse_str("(bc)*(a|c*)").unwrap()),
+            "Substitution resulted in {}",
+            substituted
+        );
+    }
+}
@@ -0,0 +1,221 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa};
+use std::collections::HashMap;
+
+/// The oracle a learner asks questions about a hidden regular language.
+pub trait Teacher {
+    /// Whether `word` belongs to the hidden language.
+    fn is_member(&mut self, word: &[Alphabet]) -> bool;
+
+    /// A word on which `hypothesis` and the hidden language disagree,
+    /// or `None` if `hypothesis` recognizes exactly the hidden language.
+    fn find_counterexample(&mut self, hypothesis: &Dfa) -> Option<Vec<Alphabet>>;
+}
+
+/// A teacher answering queries about the language of a known DFA.
+///
+/// Counterexamples are the shortest words in shortlex order on which the automata disagree.
+pub struct DfaTeacher {
+    target: Dfa,
+}
+
+impl DfaTeacher {
+    pub fn new(target: Dfa) -> Self {
+        DfaTeacher { target }
+    }
+}
+
+impl Teacher for DfaTeacher {
+    fn is_member(&mut self, word: &[Alphabet]) -> bool {
+        self.target.accepts(word)
+    }
+
+    fn find_counterexample(&mut self, hypothesis: &Dfa) -> Option<Vec<Alphabet>> {
+        self.target
+            .product(hypothesis, |in_target, in_hypothesis| {
+                in_target != in_hypothesis
+            })
+            .words()
+            .next()
+    }
+}
+
+/// Result of [`learn`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LearningOutcome {
+    /// The minimal DFA of the hidden language, numbered canonically (see [`Dfa::minimized`])
+    pub dfa: Dfa,
+    /// Number of distinct words asked to [`Teacher::is_member`]
+    pub membership_queries: usize,
+    /// Number of hypotheses submitted to [`Teacher::find_counterexample`]
+    pub equivalence_queries: usize,
+}
+
+/// An observation table of the L* algorithm.
+///
+/// Rows are indexed by access words and columns by distinguishing suffixes.
+/// Following Maler and Pnueli, every suffix of a counterexample becomes a column,
+/// which keeps the rows of `prefixes` pairwise distinct and the table always consistent.
+struct ObservationTable<'a, T: Teacher + ?Sized> {
+    alphabet: Vec<Alphabet>,
+    teacher: &'a mut T,
+    answers: HashMap<Vec<Alphabet>, bool>,
+    /// Access words of the states of the hypothesis, starting with ε
+    prefixes: Vec<Vec<Alphabet>>,
+    /// Distinguishing suffixes, starting with ε
+    suffixes: Vec<Vec<Alphabet>>,
+}
+
+impl<T: Teacher + ?Sized> ObservationTable<'_, T> {
+    fn is_member(&mut self, word: Vec<Alphabet>) -> bool {
+        if let Some(answer) = self.answers.get(&word) {
+            return *answer;
+        }
+
+        let answer = self.teacher.is_member(&word);
+        self.answers.insert(word, answer);
+        answer
+    }
+
+    fn row(&mut self, prefix: &[Alphabet]) -> Vec<bool> {
+        let suffixes = self.suffixes.clone();
+
+        suffixes
+            .iter()
+            .map(|suffix| self.is_member([prefix, suffix].concat()))
+            .collect()
+    }
+
+    /// Add access words until every one-letter extension of an access word
+    /// has the same row as some access word, and build the hypothesis.
+    fn close(&mut self) -> Dfa {
+        let mut rows = self
+            .prefixes
+            .clone()
+            .iter()
+            .map(|prefix| self.row(prefix))
+            .collect::<Vec<_>>();
+        let mut transitions: Vec<Vec<usize>> = vec![];
+
+        let mut processed = 0;
+        while processed < self.prefixes.len() {
+            let prefix = self.prefixes[processed].clone();
+
+            let mut transition_row = vec![];
+            for a in self.alphabet.clone() {
+                let extended = [prefix.as_slice(), &[a]].concat();
+                let row = self.row(&extended);
+
+                let target = match rows.iter().position(|r| *r == row) {
+                    Some(target) => target,
+                    None => {
+                        self.prefixes.push(extended);
+                        rows.push(row);
+                        rows.len() - 1
+                    }
+                };
+                transition_row.push(target);
+            }
+            transitions.push(transition_row);
+            processed += 1;
+        }
+
+        Dfa {
+            alphabet: self.alphabet.clone(),
+            initial: 0,
+            // the first column is the one of ε
+            accepting: rows.iter().map(|row| row[0]).collect(),
+            transitions,
+        }
+    }
+
+    fn add_counterexample(&mut self, counterexample: &[Alphabet]) {
+        for start in 0..counterexample.len() {
+            let suffix = counterexample[start..].to_vec();
+            if !self.suffixes.contains(&suffix) {
+                self.suffixes.push(suffix);
+            }
+        }
+    }
+}
+
+/// Learn the minimal DFA over `alphabet` of the language hidden by `teacher`
+/// with Angluin's L* algorithm.
+///
+/// The number of equivalence queries is at most the number of states of the result.
+pub fn learn<T: Teacher + ?Sized>(alphabet: &[Alphabet], teacher: &mut T) -> LearningOutcome {
+    let mut alphabet = alphabet.to_vec();
+    alphabet.sort();
+    alphabet.dedup();
+
+    let mut table = ObservationTable {
+        alphabet,
+        teacher,
+        answers: HashMap::new(),
+        prefixes: vec![vec![]],
+        suffixes: vec![vec![]],
+    };
+
+    let mut equivalence_queries = 0;
+    loop {
+        let hypothesis = table.close();
+        equivalence_queries += 1;
+
+        match table.teacher.find_counterexample(&hypothesis) {
+            Some(counterexample) => table.add_counterexample(&counterexample),
+            None => {
+                return LearningOutcome {
+                    dfa: hypothesis.minimized(),
+                    membership_queries: table.answers.len(),
+                    equivalence_queries,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{learn, DfaTeacher};
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn learn_minimal_dfas() {
+        let alphabet = Alphabet::vec_from_str("ab").unwrap();
+        let domain = alphabet.iter().cloned().collect();
+
+        for regex in [
+            "a",
+            "(a|b)*",
+            "(a|b)*b(a|b)",
+            "(ab|ba)*",
+            "a*(ba*ba*)*",
+            "ab(a|b)*ba",
+        ] {
+            let target = RegexAst::parse_str(regex).unwrap().to_min_dfa(&domain);
+            let outcome = learn(&alphabet, &mut DfaTeacher::new(target.clone()));
+
+            assert_eq!(outcome.dfa, target, "failed to learn {}", regex);
+            assert!(outcome.equivalence_queries <= target.state_count());
+            assert!(outcome.membership_queries > 0);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa};
+use std::collections::VecDeque;
+
+/// How a state of the search was reached from its predecessor
+#[derive(Clone, Copy)]
+enum Edit {
+    /// The letter of the input is kept or substituted with the alphabet
+    Keep(Alphabet),
+    /// The letter of the input is deleted
+    Delete,
+    /// The alphabet is inserted
+    Insert(Alphabet),
+}
+
+impl Dfa {
+    /// An accepted word with the minimum edit distance (Levenshtein distance) from `word`,
+    /// along with the distance, or `None` if every accepted word is farther than `max_dist`.
+    ///
+    /// This is a shortest path search on the product of the automaton and the
+    /// Levenshtein automaton of `word`, whose states are pairs of a position in `word` and a state.
+    pub(super) fn closest_word(
+        &self,
+        word: &[Alphabet],
+        max_dist: usize,
+    ) -> Option<(Vec<Alphabet>, usize)> {
+        let index = |position: usize, q: usize| position * self.state_count() + q;
+        let size = (word.len() + 1) * self.state_count();
+        let mut distance = vec![usize::MAX; size];
+        let mut parent: Vec<Option<(usize, Edit)>> = vec![None; size];
+
+        // 0-1 BFS, since each edit costs one and keeping a letter costs nothing
+        let mut deque = VecDeque::new();
+        distance[index(0, self.initial)] = 0;
+        deque.push_back((0, self.initial));
+
+        while let Some((position, q)) = deque.pop_front() {
+            let d = distance[index(position, q)];
+            if position == word.len() && self.accepting[q] {
+                let mut edits = vec![];
+                let mut current = index(position, q);
+                while let Some((previous, edit)) = parent[current] {
+                    edits.push(edit);
+                    current = previous;
+                }
+                let closest = edits
+                    .into_iter()
+                    .rev()
+                    .filter_map(|edit| match edit {
+                        Edit::Keep(a) | Edit::Insert(a) => Some(a),
+                        Edit::Delete => None,
+                    })
+                    .collect();
+                return Some((closest, d));
+            }
+
+            let mut successors = vec![];
+            for (i, a) in self.alphabet.iter().enumerate() {
+                let next = self.transitions[q][i];
+                if let Some(b) = word.get(position) {
+                    successors.push((position + 1, next, (a != b) as usize, Edit::Keep(*a)));
+                }
+                successors.push((position, next, 1, Edit::Insert(*a)));
+            }
+            if position < word.len() {
+                successors.push((position + 1, q, 1, Edit::Delete));
+            }
+
+            for (next_position, next, cost, edit) in successors {
+                let next_distance = d + cost;
+                let target = index(next_position, next);
+                if next_distance > max_dist || next_distance >= distance[target] {
+                    continue;
+                }
+
+                distance[target] = next_distance;
+                parent[target] = Some((index(position, q), edit));
+                if cost == 0 {
+                    deque.push_front((next_position, next));
+                } else {
+                    deque.push_back((next_position, next));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, Dfa, RegexAst};
+
+    fn word(w: &str) -> Vec<Alphabet> {
+        Alphabet::vec_from_str(w).unwrap()
+    }
+
+    fn min_dfa(regex_str: &str, alphabets: &str) -> Dfa {
+        RegexAst::parse_str(regex_str)
+            .unwrap()
+            .to_min_dfa(&word(alphabets).into_iter().collect())
+    }
+
+    /// Edit distance between two words by the textbook dynamic programming.
+    fn edit_distance(lhs: &[Alphabet], rhs: &[Alphabet]) -> usize {
+        let mut row = (0..=rhs.len()).collect::<Vec<_>>();
+        for (i, a) in lhs.iter().enumerate() {
+            let mut next = vec![i + 1];
+            for (j, b) in rhs.iter().enumerate() {
+                next.push(
+                    (row[j] + (a != b) as usize)
+                        .min(row[j + 1] + 1)
+                        .min(next[j] + 1),
+                );
+            }
+            row = next;
+        }
+        row[rhs.len()]
+    }
+
+    #[test]
+    fn dfa_closest_word_in_empty_language() {
+        let empty = Dfa::parse_table("0 0 0", &word("ab")).unwrap();
+
+        assert_eq!(empty.closest_word(&word(""), usize::MAX), None);
+        assert_eq!(empty.closest_word(&word("abba"), usize::MAX), None);
+    }
+
+    #[test]
+    fn dfa_closest_word_of_accepted_word() {
+        let dfa = min_dfa("(ab)*c", "abc");
+
+        for input in ["c", "abc", "ababc"] {
+            assert_eq!(dfa.closest_word(&word(input), 0), Some((word(input), 0)));
+            assert_eq!(
+                dfa.closest_word(&word(input), usize::MAX),
+                Some((word(input), 0))
+            );
+        }
+    }
+
+    #[test]
+    fn dfa_closest_word_with_tied_edits() {
+        // `ab` is one deletion from `a`, one insertion from `abb` and one substitution from `aa`
+        let dfa = min_dfa("a|abb|aa", "ab");
+
+        let (closest, distance) = dfa.closest_word(&word("ab"), usize::MAX).unwrap();
+        assert_eq!(distance, 1);
+        assert!(dfa.accepts(&closest));
+        assert_eq!(edit_distance(&closest, &word("ab")), 1);
+
+        // each kind of edit alone also finds a word at distance one
+        for (regex_str, expected) in [("a", "a"), ("abb", "abb"), ("aa", "aa")] {
+            assert_eq!(
+                min_dfa(regex_str, "ab").closest_word(&word("ab"), 1),
+                Some((word(expected), 1))
+            );
+        }
+    }
+
+    #[test]
+    fn dfa_closest_word_within_max_dist() {
+        let dfa = min_dfa("ccc", "abc");
+
+        assert_eq!(dfa.closest_word(&word("ab"), 2), None);
+        assert_eq!(dfa.closest_word(&word("ab"), 3), Some((word("ccc"), 3)));
+    }
+}
@@ -0,0 +1,149 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Dfa, RegexAst};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A transformation of states, mapping each state `q` to `self[q]`.
+type Transformation = Vec<usize>;
+
+/// The monoid of transformations of states induced by words, under composition.
+///
+/// For a minimal DFA, this is isomorphic to the syntactic monoid of the language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransitionMonoid {
+    /// Transformations induced by words, the first one being the identity (induced by ε)
+    pub elements: Vec<Transformation>,
+}
+
+/// The transformation applying `first` and then `second`.
+fn compose(first: &[usize], second: &[usize]) -> Transformation {
+    first.iter().map(|q| second[*q]).collect()
+}
+
+impl TransitionMonoid {
+    pub fn size(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Whether every element `m` satisfies `m^n = m^(n+1)` for some `n`,
+    /// i.e. the monoid contains no nontrivial group.
+    pub fn is_aperiodic(&self) -> bool {
+        self.elements.iter().all(|m| {
+            // find the first repetition in m, m^2, m^3, ...
+            let mut exponents = HashMap::new();
+            let mut power = m.clone();
+            for exponent in 1.. {
+                if let Some(previous) = exponents.insert(power.clone(), exponent) {
+                    return exponent - previous == 1;
+                }
+                power = compose(&power, m);
+            }
+            unreachable!()
+        })
+    }
+}
+
+impl Dfa {
+    /// Transformations of states induced by words.
+    pub fn transition_monoid(&self) -> TransitionMonoid {
+        let identity = (0..self.state_count()).collect::<Transformation>();
+        let generators = (0..self.alphabet.len())
+            .map(|i| {
+                (0..self.state_count())
+                    .map(|q| self.transitions[q][i])
+                    .collect::<Transformation>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut elements = vec![identity.clone()];
+        let mut known = HashSet::new();
+        known.insert(identity.clone());
+        let mut queue = VecDeque::from(vec![identity]);
+        while let Some(m) = queue.pop_front() {
+            for generator in &generators {
+                let next = compose(&m, generator);
+                if known.insert(next.clone()) {
+                    elements.push(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        TransitionMonoid { elements }
+    }
+}
+
+impl RegexAst {
+    /// Whether the language can be expressed without stars
+    /// using complement, union and concatenation.
+    ///
+    /// By Schützenberger's theorem, this is the case if and only if
+    /// the syntactic monoid (the transition monoid of the minimal DFA) is aperiodic.
+    pub fn is_star_free(&self) -> bool {
+        self.to_min_dfa(&self.used_alphabets())
+            .transition_monoid()
+            .is_aperiodic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_transition_monoid_size() {
+        let cases = vec![("a*", 1), ("(aa)*", 2), ("(aaa)*", 3), ("(a|b)*a", 3)];
+
+        for (regex_str, expected) in cases {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            let monoid = ast.to_min_dfa(&ast.used_alphabets()).transition_monoid();
+
+            assert_eq!(
+                monoid.size(),
+                expected,
+                r#"Transition monoid of "{}" has wrong size"#,
+                regex_str
+            );
+        }
+    }
+
+    #[test]
+    fn regex_ast_star_freeness() {
+        let cases = vec![
+            ("a*", true),
+            ("(a|b)*abb", true),
+            ("(ab)*", true),
+            ("a*b*", true),
+            ("(aa)*", false),
+            ("(a|b)*a(a|b)*a(a|b)*|b*", true),
+            ("(b*ab*a)*b*", false),
+        ];
+
+        for (regex_str, expected) in cases {
+            assert_eq!(
+                RegexAst::parse_str(regex_str).unwrap().is_star_free(),
+                expected,
+                r#""{}" should{} be star-free"#,
+                regex_str,
+                if expected { "" } else { " not" }
+            );
+        }
+    }
+}
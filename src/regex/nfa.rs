@@ -0,0 +1,186 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, BudgetExceeded, Dfa};
+use std::collections::{BTreeSet, HashMap};
+
+/// A nondeterministic finite automaton without ε-transitions over a fixed set of alphabets.
+///
+/// States are numbered from `0` to `state_count() - 1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Nfa {
+    /// Alphabets the automaton reads, sorted in ascending order
+    pub(super) alphabet: Vec<Alphabet>,
+    pub(super) initial: BTreeSet<usize>,
+    pub(super) accepting: Vec<bool>,
+    /// `transitions[q][i]` are the states reached from `q` by reading `alphabet[i]`
+    pub(super) transitions: Vec<Vec<BTreeSet<usize>>>,
+}
+
+impl Nfa {
+    fn letter_index(&self, a: &Alphabet) -> Option<usize> {
+        self.alphabet.binary_search(a).ok()
+    }
+
+    /// Alphabets read by the automaton, in ascending order.
+    pub fn alphabet(&self) -> &[Alphabet] {
+        &self.alphabet
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.accepting.len()
+    }
+
+    pub fn initial_states(&self) -> &BTreeSet<usize> {
+        &self.initial
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    /// The states reached from `state` by reading `a`.
+    pub fn next_states(&self, state: usize, a: Alphabet) -> BTreeSet<usize> {
+        self.letter_index(&a)
+            .map(|i| self.transitions[state][i].clone())
+            .unwrap_or_default()
+    }
+
+    /// All transitions of the automaton as triples of (source, alphabet, target).
+    pub fn transitions(&self) -> impl Iterator<Item = (usize, Alphabet, usize)> + '_ {
+        self.transitions
+            .iter()
+            .enumerate()
+            .flat_map(move |(q, row)| {
+                row.iter()
+                    .zip(self.alphabet.iter())
+                    .flat_map(move |(targets, a)| {
+                        targets.iter().map(move |target| (q, *a, *target))
+                    })
+            })
+    }
+
+    fn step(&self, states: &BTreeSet<usize>, letter_index: usize) -> BTreeSet<usize> {
+        states
+            .iter()
+            .flat_map(|q| self.transitions[*q][letter_index].iter().cloned())
+            .collect()
+    }
+
+    /// Whether the automaton accepts `input`.
+    /// Words containing alphabets the automaton does not read are never accepted.
+    pub fn accepts(&self, input: &[Alphabet]) -> bool {
+        let mut states = self.initial.clone();
+
+        for a in input {
+            match self.letter_index(a) {
+                Some(i) => states = self.step(&states, i),
+                None => return false,
+            }
+        }
+
+        states.iter().any(|q| self.accepting[*q])
+    }
+
+    /// Determinize the automaton by the subset construction.
+    ///
+    /// Only subsets reachable from the initial subset become states of the result.
+    pub fn to_dfa(&self) -> Dfa {
+        self.to_dfa_with_budget(usize::MAX)
+            .expect("number of subsets fits in usize")
+    }
+
+    /// Determinize the automaton like [`Nfa::to_dfa`],
+    /// but fail as soon as more than `budget` subsets are reached.
+    pub fn to_dfa_with_budget(&self, budget: usize) -> Result<Dfa, BudgetExceeded> {
+        let mut subsets = vec![self.initial.clone()];
+        let mut indices = HashMap::new();
+        indices.insert(self.initial.clone(), 0);
+
+        let mut accepting = vec![];
+        let mut transitions = vec![];
+
+        let mut processed = 0;
+        while processed < subsets.len() {
+            if subsets.len() > budget {
+                return Err(BudgetExceeded { budget });
+            }
+
+            let subset = subsets[processed].clone();
+            accepting.push(subset.iter().any(|q| self.accepting[*q]));
+
+            let row = (0..self.alphabet.len())
+                .map(|i| {
+                    let next = self.step(&subset, i);
+                    *indices.entry(next.clone()).or_insert_with(|| {
+                        subsets.push(next);
+                        subsets.len() - 1
+                    })
+                })
+                .collect();
+
+            transitions.push(row);
+            processed += 1;
+        }
+
+        Ok(Dfa {
+            alphabet: self.alphabet.clone(),
+            initial: 0,
+            accepting,
+            transitions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{blowup, Alphabet, BudgetExceeded, RegexAst};
+
+    fn word(w: &str) -> Vec<Alphabet> {
+        Alphabet::vec_from_str(w).unwrap()
+    }
+
+    #[test]
+    fn nfa_to_dfa_preserves_language() {
+        let ast = RegexAst::parse_str("(a|b)*abb|c").unwrap();
+        let nfa = ast.glushkov_nfa_over(&word("abc").into_iter().collect());
+        let dfa = nfa.to_dfa();
+
+        for input in ["", "abb", "babb", "c", "cc", "ab", "abbc", "d"] {
+            assert_eq!(dfa.accepts(&word(input)), nfa.accepts(&word(input)));
+            assert_eq!(dfa.accepts(&word(input)), ast.matches(&word(input)));
+        }
+    }
+
+    #[test]
+    fn nfa_to_dfa_with_budget() {
+        let alphabet = word("ab").into_iter().collect();
+        let small = RegexAst::parse_str("(a|b)*abb")
+            .unwrap()
+            .glushkov_nfa_over(&alphabet);
+        assert_eq!(small.to_dfa_with_budget(10), Ok(small.to_dfa()));
+
+        // the subset construction reaches 2^21 subsets without a budget
+        let large = blowup(20).glushkov_nfa_over(&alphabet);
+        assert_eq!(
+            large.to_dfa_with_budget(100),
+            Err(BudgetExceeded { budget: 100 })
+        );
+    }
+}
@@ -0,0 +1,252 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, RegexAst};
+use itertools::Itertools;
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+};
+
+/// The set of vectors `base + n_1 p_1 + ... + n_k p_k` for natural numbers `n_i`,
+/// where `p_i` are the periods.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LinearSet {
+    pub base: Vec<usize>,
+    pub periods: Vec<Vec<usize>>,
+}
+
+/// A finite union of linear sets of letter-count vectors.
+///
+/// The `i`-th component of each vector counts occurrences of `alphabet[i]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemilinearSet {
+    pub alphabet: Vec<Alphabet>,
+    pub linear_sets: Vec<LinearSet>,
+}
+
+fn vector_sum(v: &[usize], w: &[usize]) -> Vec<usize> {
+    v.iter().zip(w.iter()).map(|(x, y)| x + y).collect()
+}
+
+impl LinearSet {
+    /// Sort and deduplicate periods, dropping the zero vector.
+    fn normalized(mut self) -> Self {
+        self.periods.retain(|p| p.iter().any(|x| *x > 0));
+        self.periods.sort();
+        self.periods.dedup();
+        self
+    }
+
+    /// Whether `counts` belongs to this set.
+    pub fn contains(&self, counts: &[usize]) -> bool {
+        if self.base.iter().zip(counts.iter()).any(|(b, c)| b > c) {
+            return false;
+        }
+
+        // search vectors of the form `base + (combination of periods)` not exceeding `counts`
+        let mut visited = HashSet::new();
+        let mut stack = vec![self.base.clone()];
+        while let Some(v) = stack.pop() {
+            if v == counts {
+                return true;
+            }
+            for p in &self.periods {
+                let next = vector_sum(&v, p);
+                if next.iter().zip(counts.iter()).all(|(x, c)| x <= c)
+                    && visited.insert(next.clone())
+                {
+                    stack.push(next);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// Parikh image of `ast` as linear sets over `alphabet`.
+fn linear_sets_of(ast: &RegexAst, alphabet: &[Alphabet]) -> Vec<LinearSet> {
+    let zero = vec![0; alphabet.len()];
+    let point = |base: Vec<usize>| LinearSet {
+        base,
+        periods: vec![],
+    };
+
+    let sets = match ast {
+        RegexAst::Epsilon => vec![point(zero)],
+        RegexAst::Literal(a) => {
+            let mut base = zero;
+            base[alphabet.binary_search(a).unwrap()] = 1;
+            vec![point(base)]
+        }
+        RegexAst::Alternation(asts) => asts
+            .iter()
+            .flat_map(|ast| linear_sets_of(ast, alphabet))
+            .collect(),
+        RegexAst::Concatenation(asts) => asts.iter().fold(vec![point(zero)], |accum, ast| {
+            let factor = linear_sets_of(ast, alphabet);
+            accum
+                .iter()
+                .cartesian_product(factor.iter())
+                .map(|(l1, l2)| LinearSet {
+                    base: vector_sum(&l1.base, &l2.base),
+                    periods: l1
+                        .periods
+                        .iter()
+                        .chain(l2.periods.iter())
+                        .cloned()
+                        .collect(),
+                })
+                .collect()
+        }),
+        // (L_1 ∪ ... ∪ L_k)* is the union, for each subset I of linear sets with periods,
+        // of words using every L_i (i ∈ I) at least once.
+        // Linear sets without periods (single vectors) may be used any number of times.
+        RegexAst::Star(ast) => {
+            let (points, others): (Vec<_>, Vec<_>) = linear_sets_of(ast, alphabet)
+                .into_iter()
+                .partition(|l| l.periods.is_empty());
+            let points = points.into_iter().map(|l| l.base).collect::<Vec<_>>();
+
+            (0..=others.len())
+                .flat_map(|k| {
+                    others.iter().combinations(k).map(|chosen| LinearSet {
+                        base: chosen
+                            .iter()
+                            .fold(zero.clone(), |accum, l| vector_sum(&accum, &l.base)),
+                        periods: chosen
+                            .iter()
+                            .flat_map(|l| l.periods.iter().chain(std::iter::once(&l.base)))
+                            .chain(points.iter())
+                            .cloned()
+                            .collect(),
+                    })
+                })
+                .collect()
+        }
+    };
+
+    sets.into_iter()
+        .map(LinearSet::normalized)
+        .unique()
+        .collect()
+}
+
+impl SemilinearSet {
+    /// Whether `counts` belongs to this set.
+    pub fn contains(&self, counts: &[usize]) -> bool {
+        self.linear_sets.iter().any(|l| l.contains(counts))
+    }
+
+    /// Letter-count vector of `word` over the alphabet of this set,
+    /// or `None` if `word` contains other alphabets.
+    pub fn counts_of(&self, word: &[Alphabet]) -> Option<Vec<usize>> {
+        let mut counts = vec![0; self.alphabet.len()];
+        for a in word {
+            counts[self.alphabet.binary_search(a).ok()?] += 1;
+        }
+        Some(counts)
+    }
+}
+
+impl Display for SemilinearSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |v: &[usize]| format!("({})", v.iter().join(", "));
+
+        let linear_sets = self.linear_sets.iter().map(|l| {
+            std::iter::once(show(&l.base))
+                .chain(l.periods.iter().map(|p| format!("ℕ{}", show(p))))
+                .join(" + ")
+        });
+
+        write!(
+            f,
+            "[{}]: {}",
+            self.alphabet.iter().join(", "),
+            linear_sets.format(" ∪ ")
+        )
+    }
+}
+
+impl RegexAst {
+    /// Parikh image of the language, i.e. the set of vectors counting occurrences of each alphabet
+    /// in matched words, as a semilinear set over the alphabets used in this AST.
+    ///
+    /// The image is computed structurally; the number of linear sets grows exponentially
+    /// with the number of branches under a star, so this is meant for small ASTs.
+    pub fn parikh_image(&self) -> SemilinearSet {
+        let alphabet = self
+            .used_alphabets()
+            .into_iter()
+            .sorted()
+            .collect::<Vec<_>>();
+        let linear_sets = linear_sets_of(self, &alphabet);
+
+        SemilinearSet {
+            alphabet,
+            linear_sets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, RegexAst};
+    use itertools::Itertools;
+
+    #[test]
+    fn regex_ast_parikh_image() {
+        for regex_str in ["(ab|ba)*", "a(bb)*|ba*", "(a|bc)*c", "((ab)*c)*", "ε|aab"] {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            let image = ast.parikh_image();
+
+            // a vector belongs to the image if and only if some matched word has the counts
+            let counts_of_matched = ast
+                .words()
+                .take_while(|w| w.len() <= 6)
+                .map(|w| image.counts_of(&w).unwrap())
+                .unique()
+                .collect::<Vec<_>>();
+
+            for counts in (0..image.alphabet.len())
+                .map(|_| 0..=6)
+                .multi_cartesian_product()
+                .filter(|counts| counts.iter().sum::<usize>() <= 6)
+            {
+                assert_eq!(
+                    image.contains(&counts),
+                    counts_of_matched.contains(&counts),
+                    r#"Parikh image {} of "{}" is wrong at {:?}"#,
+                    image,
+                    regex_str,
+                    counts
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn semilinear_set_display() {
+        let image = RegexAst::parse_str("a(bb)*").unwrap().parikh_image();
+
+        assert_eq!(image.alphabet, Alphabet::vec_from_str("ab").unwrap());
+        assert_eq!(format!("{}", image), "[a, b]: (1, 0) + ℕ(0, 2)");
+    }
+}
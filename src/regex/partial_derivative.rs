@@ -0,0 +1,186 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Nfa, RegexAst};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Concatenate `head` with `tail`, flattening nested concatenations and dropping ε.
+///
+/// Keeping terms in this normal form guarantees that
+/// iterated partial derivatives only produce finitely many distinct terms.
+fn concatenate(head: RegexAst, tail: &[RegexAst]) -> RegexAst {
+    let head_factors = match head {
+        RegexAst::Concatenation(asts) => asts,
+        _ => vec![head],
+    };
+
+    RegexAst::concatenation_of(
+        head_factors
+            .into_iter()
+            .chain(tail.iter().cloned())
+            .filter(|ast| *ast != RegexAst::Epsilon)
+            .collect(),
+    )
+}
+
+impl RegexAst {
+    /// The Antimirov partial derivatives of this expression with respect to `a`.
+    ///
+    /// The alternation of the returned expressions is equivalent to the
+    /// Brzozowski derivative ([RegexAst::derivative]), and an empty vector
+    /// corresponds to the empty language.
+    pub fn partial_derivatives(&self, a: Alphabet) -> Vec<RegexAst> {
+        let mut accum = vec![];
+        self.collect_partial_derivatives(a, &mut accum);
+        accum
+    }
+
+    fn collect_partial_derivatives(&self, a: Alphabet, accum: &mut Vec<RegexAst>) {
+        let mut push = |ast: RegexAst| {
+            if !accum.contains(&ast) {
+                accum.push(ast)
+            }
+        };
+
+        match self {
+            RegexAst::Epsilon => {}
+            RegexAst::Literal(b) => {
+                if *b == a {
+                    push(RegexAst::Epsilon)
+                }
+            }
+            RegexAst::Star(ast) => {
+                for term in ast.partial_derivatives(a) {
+                    push(concatenate(term, std::slice::from_ref(self)))
+                }
+            }
+            RegexAst::Concatenation(asts) => {
+                for (i, ast) in asts.iter().enumerate() {
+                    for term in ast.partial_derivatives(a) {
+                        push(concatenate(term, &asts[i + 1..]))
+                    }
+                    if !ast.nullable() {
+                        break;
+                    }
+                }
+            }
+            RegexAst::Alternation(asts) => {
+                for ast in asts {
+                    for term in ast.partial_derivatives(a) {
+                        push(term)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Construct the partial derivative automaton (Antimirov automaton) reading `alphabet`.
+    ///
+    /// States are the distinct partial derivatives of this expression
+    /// (with the flattened expression itself as the initial state),
+    /// so the automaton has at most one more state than the number of literals.
+    pub fn to_antimirov_nfa(&self, alphabet: &HashSet<Alphabet>) -> Nfa {
+        let mut alphabet = alphabet.iter().cloned().collect::<Vec<_>>();
+        alphabet.sort();
+
+        let initial_term = self.flatten();
+        let mut terms = vec![initial_term.clone()];
+        let mut indices = HashMap::new();
+        indices.insert(initial_term, 0);
+
+        let mut transitions = vec![];
+        let mut processed = 0;
+        while processed < terms.len() {
+            let term = terms[processed].clone();
+
+            let row = alphabet
+                .iter()
+                .map(|a| {
+                    term.partial_derivatives(*a)
+                        .into_iter()
+                        .map(|derivative| {
+                            *indices.entry(derivative.clone()).or_insert_with(|| {
+                                terms.push(derivative);
+                                terms.len() - 1
+                            })
+                        })
+                        .collect::<BTreeSet<_>>()
+                })
+                .collect();
+
+            transitions.push(row);
+            processed += 1;
+        }
+
+        Nfa {
+            alphabet,
+            initial: std::iter::once(0).collect(),
+            accepting: terms.iter().map(|term| term.nullable()).collect(),
+            transitions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn regex_ast_partial_derivatives() {
+        let cases = vec![
+            ("ab|ac", Alphabet::A, vec!["b", "c"]),
+            ("a*ab", Alphabet::A, vec!["a*ab", "b"]),
+            ("(ab)*", Alphabet::A, vec!["b(ab)*"]),
+            ("ab", Alphabet::B, vec![]),
+        ];
+
+        for (regex_str, a, expected) in cases {
+            let expected = expected
+                .into_iter()
+                .map(|e| RegexAst::parse_str(e).unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                RegexAst::parse_str(regex_str)
+                    .unwrap()
+                    .partial_derivatives(a),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn antimirov_nfa_matches() {
+        let regex_strs = vec!["(a|b)*abb", "a*bεcc*", "(ab|c)*|ε", "((a|b)*c)*"];
+        let inputs = vec!["", "a", "abb", "babb", "bc", "aabccc", "abcab", "acbc"];
+
+        for regex_str in regex_strs {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            let nfa = ast.to_antimirov_nfa(&ast.used_alphabets());
+
+            assert!(nfa.state_count() <= ast.stats().literal_count + 1);
+
+            for input in &inputs {
+                let input = Alphabet::vec_from_str(input).unwrap();
+                assert_eq!(nfa.accepts(&input), ast.matches(&input));
+                assert_eq!(nfa.to_dfa().accepts(&input), ast.matches(&input));
+            }
+        }
+    }
+}
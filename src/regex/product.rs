@@ -0,0 +1,138 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Dfa, RegexAst};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+impl Dfa {
+    /// Product automaton of `self` and `other` running in parallel,
+    /// whose state is accepting if `accept` holds for the acceptance of the component states.
+    ///
+    /// Only pairs reachable from the pair of initial states are constructed.
+    ///
+    /// # Panics
+    /// Panics if the automata do not read the same alphabets.
+    pub fn product(&self, other: &Dfa, accept: impl Fn(bool, bool) -> bool) -> Dfa {
+        assert_eq!(
+            self.alphabet, other.alphabet,
+            "product of automata over different alphabets"
+        );
+
+        let initial_pair = (self.initial, other.initial);
+        let mut index = HashMap::new();
+        let mut pairs = vec![initial_pair];
+        let mut queue = VecDeque::new();
+        index.insert(initial_pair, 0);
+        queue.push_back(initial_pair);
+
+        let mut transitions = vec![];
+        while let Some((p, q)) = queue.pop_front() {
+            let row = (0..self.alphabet.len())
+                .map(|i| {
+                    let next = (self.transitions[p][i], other.transitions[q][i]);
+                    *index.entry(next).or_insert_with(|| {
+                        pairs.push(next);
+                        queue.push_back(next);
+                        pairs.len() - 1
+                    })
+                })
+                .collect();
+            transitions.push(row);
+        }
+
+        Dfa {
+            alphabet: self.alphabet.clone(),
+            initial: 0,
+            accepting: pairs
+                .iter()
+                .map(|(p, q)| accept(self.accepting[*p], other.accepting[*q]))
+                .collect(),
+            transitions,
+        }
+    }
+
+    /// Whether the automaton accepts no word at all.
+    pub fn is_empty(&self) -> bool {
+        let mut visited = vec![false; self.state_count()];
+        let mut stack = vec![self.initial];
+        visited[self.initial] = true;
+
+        while let Some(q) = stack.pop() {
+            if self.accepting[q] {
+                return false;
+            }
+            for next in &self.transitions[q] {
+                if !visited[*next] {
+                    visited[*next] = true;
+                    stack.push(*next);
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl RegexAst {
+    /// Whether no word is matched by both `self` and `other`.
+    pub fn disjoint_with(&self, other: &RegexAst) -> bool {
+        let alphabet = self
+            .used_alphabets()
+            .union(&other.used_alphabets())
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        Dfa::from_ast(self, &alphabet)
+            .product(&Dfa::from_ast(other, &alphabet), |p, q| p && q)
+            .is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_disjointness() {
+        let cases = vec![
+            ("a*", "b*", false),
+            ("aa*", "bb*", true),
+            ("(aa)*a", "(aa)*", true),
+            ("(aa)*a", "a(aa)*", false),
+            ("(a|b)*abb", "(a|b)*ba", true),
+            ("ab|ba", "c", true),
+            ("(ab)*", "(ba)*", false),
+        ];
+
+        for (lhs, rhs, expected) in cases {
+            let lhs = RegexAst::parse_str(lhs).unwrap();
+            let rhs = RegexAst::parse_str(rhs).unwrap();
+
+            assert_eq!(
+                lhs.disjoint_with(&rhs),
+                expected,
+                r#""{}" and "{}" should{} be disjoint"#,
+                lhs,
+                rhs,
+                if expected { "" } else { " not" }
+            );
+            assert_eq!(rhs.disjoint_with(&lhs), expected);
+        }
+    }
+}
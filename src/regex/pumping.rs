@@ -0,0 +1,154 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, RegexAst};
+use std::fmt::{Display, Formatter};
+
+/// A decomposition `xyz` of an accepted word,
+/// such that `xy^kz` is accepted for every `k >= 0` and `y` is not empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PumpingDecomposition {
+    pub x: Vec<Alphabet>,
+    pub y: Vec<Alphabet>,
+    pub z: Vec<Alphabet>,
+}
+
+impl PumpingDecomposition {
+    /// The word `xy^kz`.
+    pub fn pumped(&self, k: usize) -> Vec<Alphabet> {
+        let mut word = self.x.clone();
+        for _ in 0..k {
+            word.extend(self.y.iter());
+        }
+        word.extend(self.z.iter());
+        word
+    }
+}
+
+impl Display for PumpingDecomposition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |w: &[Alphabet]| match w {
+            [] => "ε".to_string(),
+            w => Alphabet::slice_to_plain_string(w),
+        };
+
+        write!(
+            f,
+            "x = {}, y = {}, z = {}",
+            show(&self.x),
+            show(&self.y),
+            show(&self.z)
+        )
+    }
+}
+
+impl Dfa {
+    /// A pumping length of the accepted language,
+    /// i.e. every accepted word of at least this length can be pumped.
+    pub fn pumping_length(&self) -> usize {
+        self.state_count()
+    }
+
+    /// Decompose an accepted `word` of length at least [Dfa::pumping_length]
+    /// with `|xy|` no more than the pumping length,
+    /// by finding the first state visited twice while reading `word`.
+    ///
+    /// Returns `None` if `word` is rejected or too short to be pumped.
+    pub fn pumping_decomposition(&self, word: &[Alphabet]) -> Option<PumpingDecomposition> {
+        if word.len() < self.pumping_length() || !self.accepts(word) {
+            return None;
+        }
+
+        // first_visit[q] is the number of letters read when q is visited first
+        let mut first_visit = vec![None; self.state_count()];
+        let mut state = self.initial;
+        for (read, a) in word.iter().enumerate() {
+            first_visit[state] = Some(read);
+            state = self.next_state(state, *a).unwrap();
+
+            if let Some(start) = first_visit[state] {
+                let end = read + 1;
+                return Some(PumpingDecomposition {
+                    x: word[..start].to_vec(),
+                    y: word[start..end].to_vec(),
+                    z: word[end..].to_vec(),
+                });
+            }
+        }
+
+        // by the pigeonhole principle, some state is visited twice within the first pumping_length letters
+        unreachable!()
+    }
+}
+
+impl RegexAst {
+    /// A pumping length of the language, namely the number of states of the minimal DFA.
+    pub fn pumping_length(&self) -> usize {
+        self.state_complexity()
+    }
+
+    /// Decomposition of the shortest matched word of length at least [RegexAst::pumping_length],
+    /// or `None` if the language is finite and no word is long enough to be pumped.
+    pub fn pumping_decomposition(&self) -> Option<PumpingDecomposition> {
+        let dfa = self.to_min_dfa(&self.used_alphabets());
+        if dfa.is_finite() {
+            return None;
+        }
+
+        let word = dfa
+            .words()
+            .find(|word| word.len() >= dfa.pumping_length())
+            .unwrap();
+        dfa.pumping_decomposition(&word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn regex_ast_pumping_decomposition() {
+        for regex_str in ["(a|b)*abb", "a(bc)*", "(aaa)*b", "ab*a|ba*b", "a*b*"] {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            let decomposition = ast.pumping_decomposition().unwrap();
+
+            assert!(!decomposition.y.is_empty());
+            assert!(decomposition.x.len() + decomposition.y.len() <= ast.pumping_length());
+            for k in 0..4 {
+                let pumped = decomposition.pumped(k);
+                assert!(
+                    ast.matches(&pumped),
+                    r#""{}" ({}, k = {}) should be matched by "{}""#,
+                    Alphabet::slice_to_plain_string(&pumped),
+                    decomposition,
+                    k,
+                    regex_str
+                );
+            }
+        }
+
+        assert_eq!(
+            RegexAst::parse_str("ab|ba")
+                .unwrap()
+                .pumping_decomposition(),
+            None
+        );
+    }
+}
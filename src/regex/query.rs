@@ -0,0 +1,114 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, CompiledRegex, RegexAst};
+
+/// A membership query together with how it splits a set of candidate answers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistinguishingQuery {
+    pub word: Vec<Alphabet>,
+    /// Number of candidates matching the word
+    pub matching: usize,
+    /// Number of candidates not matching the word
+    pub not_matching: usize,
+}
+
+/// Every word over `alphabet` of length at most `max_len`, in shortlex order.
+fn words_up_to(alphabet: &[Alphabet], max_len: usize) -> Vec<Vec<Alphabet>> {
+    let mut alphabet = alphabet.to_vec();
+    alphabet.sort();
+    alphabet.dedup();
+
+    let mut words = vec![vec![]];
+    let mut last_length = vec![vec![]];
+    for _ in 0..max_len {
+        last_length = last_length
+            .iter()
+            .flat_map(|word: &Vec<Alphabet>| {
+                alphabet.iter().map(move |a| {
+                    let mut extended = word.clone();
+                    extended.push(*a);
+                    extended
+                })
+            })
+            .collect();
+        words.extend(last_length.iter().cloned());
+    }
+
+    words
+}
+
+/// The word over `alphabet` of length at most `max_len`
+/// that splits `candidates` most evenly into those matching it and those not,
+/// so that its answer gives the most information about which candidate is the answer.
+/// Shorter words are preferred among equally good ones.
+///
+/// Returns `None` if no such word distinguishes any pair of candidates.
+pub fn best_distinguishing_query(
+    candidates: &[RegexAst],
+    alphabet: &[Alphabet],
+    max_len: usize,
+) -> Option<DistinguishingQuery> {
+    let compiled = candidates
+        .iter()
+        .map(|ast| ast.compile())
+        .collect::<Vec<CompiledRegex>>();
+
+    words_up_to(alphabet, max_len)
+        .into_iter()
+        .map(|word| {
+            let matching = compiled.iter().filter(|c| c.matches(&word)).count();
+            DistinguishingQuery {
+                word,
+                matching,
+                not_matching: candidates.len() - matching,
+            }
+        })
+        .filter(|query| query.matching > 0 && query.not_matching > 0)
+        // `max_by_key` returns the last maximum, so search from the longest word
+        .rev()
+        .max_by_key(|query| query.matching.min(query.not_matching))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::best_distinguishing_query;
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn best_distinguishing_query_splits_evenly() {
+        let candidates = ["a*", "(a|b)*", "b*", "ab*", "(ab)*", "a|b"]
+            .iter()
+            .map(|s| RegexAst::parse_str(s).unwrap())
+            .collect::<Vec<_>>();
+        let alphabet = Alphabet::vec_from_str("ab").unwrap();
+
+        let query = best_distinguishing_query(&candidates, &alphabet, 3).unwrap();
+        assert_eq!(query.matching + query.not_matching, candidates.len());
+        assert_eq!(query.matching.min(query.not_matching), 3);
+        // ε and "a" are matched by four candidates, while "b" is matched by (a|b)*, b* and a|b
+        assert_eq!(query.word, Alphabet::vec_from_str("b").unwrap());
+
+        let equivalent = ["a*", "(a*)*"]
+            .iter()
+            .map(|s| RegexAst::parse_str(s).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(best_distinguishing_query(&equivalent, &alphabet, 3), None);
+    }
+}
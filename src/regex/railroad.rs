@@ -0,0 +1,286 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::RegexAst;
+
+// constants related to the layout of railroad diagrams, in pixels
+const ARC_RADIUS: f64 = 10.0;
+const BOX_HEIGHT: f64 = 24.0;
+const CHARACTER_WIDTH: f64 = 10.0;
+const HORIZONTAL_GAP: f64 = 10.0;
+const VERTICAL_GAP: f64 = 10.0;
+const MARGIN: f64 = 20.0;
+
+/// A part of a railroad diagram, whose track enters at `(0, 0)` and leaves at `(width, 0)`.
+struct Block {
+    width: f64,
+    /// Extent above the track
+    up: f64,
+    /// Extent below the track
+    down: f64,
+    /// SVG elements drawn relative to the entrance
+    body: String,
+}
+
+fn translated(body: &str, x: f64, y: f64) -> String {
+    format!(
+        r#"<g transform="translate({:.1} {:.1})">{}</g>"#,
+        x, y, body
+    )
+}
+
+fn track(d: String) -> String {
+    format!(
+        r#"<path d="{}" fill="none" stroke="black" stroke-width="2"/>"#,
+        d
+    )
+}
+
+fn terminal(text: &str) -> Block {
+    let width = CHARACTER_WIDTH * text.chars().count() as f64 + 2.0 * HORIZONTAL_GAP;
+    let body = format!(
+        concat!(
+            r#"<rect x="0" y="{:.1}" width="{:.1}" height="{:.1}" rx="{:.1}" "#,
+            r#"fill="white" stroke="black" stroke-width="2"/>"#,
+            r#"<text x="{:.1}" y="0" text-anchor="middle" dominant-baseline="middle">{}</text>"#
+        ),
+        -BOX_HEIGHT / 2.0,
+        width,
+        BOX_HEIGHT,
+        BOX_HEIGHT / 2.0,
+        width / 2.0,
+        text
+    );
+
+    Block {
+        width,
+        up: BOX_HEIGHT / 2.0,
+        down: BOX_HEIGHT / 2.0,
+        body,
+    }
+}
+
+fn sequence(blocks: Vec<Block>) -> Block {
+    let mut x = 0.0;
+    let mut body = String::new();
+    let (mut up, mut down) = (0.0f64, 0.0f64);
+
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 {
+            body.push_str(&track(format!("M {:.1} 0 h {:.1}", x, HORIZONTAL_GAP)));
+            x += HORIZONTAL_GAP;
+        }
+        body.push_str(&translated(&block.body, x, 0.0));
+        x += block.width;
+        up = up.max(block.up);
+        down = down.max(block.down);
+    }
+
+    Block {
+        width: x,
+        up,
+        down,
+        body,
+    }
+}
+
+fn choice(blocks: Vec<Block>) -> Block {
+    let r = ARC_RADIUS;
+    let inner_width = blocks.iter().map(|b| b.width).fold(0.0, f64::max);
+    let width = inner_width + 4.0 * r;
+
+    let mut body = String::new();
+    let mut y = 0.0;
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 {
+            y += blocks[i - 1].down + VERTICAL_GAP + block.up;
+            // branch off the main track on the left, and join it again on the right
+            body.push_str(&track(format!(
+                "M 0 0 Q {r:.1} 0 {r:.1} {r:.1} V {:.1} Q {r:.1} {y:.1} {:.1} {y:.1}",
+                y - r,
+                2.0 * r,
+                r = r,
+                y = y
+            )));
+            body.push_str(&track(format!(
+                "M {:.1} {y:.1} Q {:.1} {y:.1} {:.1} {:.1} V {r:.1} Q {:.1} 0 {:.1} 0",
+                width - 2.0 * r,
+                width - r,
+                width - r,
+                y - r,
+                width - r,
+                width,
+                r = r,
+                y = y
+            )));
+        } else {
+            body.push_str(&track(format!("M 0 0 h {:.1}", 2.0 * r)));
+            body.push_str(&track(format!("M {:.1} 0 H {:.1}", width - 2.0 * r, width)));
+        }
+
+        body.push_str(&translated(&block.body, 2.0 * r, y));
+        body.push_str(&track(format!(
+            "M {:.1} {:.1} H {:.1}",
+            2.0 * r + block.width,
+            y,
+            width - 2.0 * r
+        )));
+    }
+
+    Block {
+        width,
+        up: blocks.first().map_or(0.0, |b| b.up),
+        down: y + blocks.last().map_or(0.0, |b| b.down),
+        body,
+    }
+}
+
+/// Zero or more repetitions: a track skipping the block above it and a track looping back below it.
+fn repetition(block: Block) -> Block {
+    let r = ARC_RADIUS;
+    let width = block.width + 4.0 * r;
+    let skip_y = -(block.up + VERTICAL_GAP);
+    let loop_y = block.down + VERTICAL_GAP;
+
+    let mut body = String::new();
+    body.push_str(&track(format!("M 0 0 h {:.1}", 2.0 * r)));
+    body.push_str(&track(format!(
+        "M {:.1} 0 H {:.1}",
+        2.0 * r + block.width,
+        width
+    )));
+    body.push_str(&track(format!(
+        "M 0 0 Q {r:.1} 0 {r:.1} {:.1} V {:.1} Q {r:.1} {s:.1} {:.1} {s:.1} H {:.1} Q {:.1} \
+         {s:.1} {:.1} {:.1} V {:.1} Q {:.1} 0 {:.1} 0",
+        -r,
+        skip_y + r,
+        2.0 * r,
+        width - 2.0 * r,
+        width - r,
+        width - r,
+        skip_y + r,
+        -r,
+        width - r,
+        width,
+        r = r,
+        s = skip_y
+    )));
+    body.push_str(&track(format!(
+        "M {:.1} 0 Q {:.1} 0 {:.1} {:.1} V {:.1} Q {:.1} {l:.1} {:.1} {l:.1} H {:.1} Q {r:.1} \
+         {l:.1} {r:.1} {:.1} V {r:.1} Q {r:.1} 0 {:.1} 0",
+        2.0 * r + block.width,
+        3.0 * r + block.width,
+        3.0 * r + block.width,
+        r,
+        loop_y - r,
+        3.0 * r + block.width,
+        2.0 * r + block.width,
+        2.0 * r,
+        loop_y - r,
+        2.0 * r,
+        r = r,
+        l = loop_y
+    )));
+    body.push_str(&translated(&block.body, 2.0 * r, 0.0));
+
+    Block {
+        width,
+        up: -skip_y,
+        down: loop_y,
+        body,
+    }
+}
+
+fn block_of(ast: &RegexAst) -> Block {
+    match ast {
+        RegexAst::Epsilon => Block {
+            width: 2.0 * HORIZONTAL_GAP,
+            up: 0.0,
+            down: 0.0,
+            body: track(format!("M 0 0 h {:.1}", 2.0 * HORIZONTAL_GAP)),
+        },
+        RegexAst::Literal(a) => terminal(&format!("{}", a)),
+        RegexAst::Concatenation(asts) => sequence(asts.iter().map(block_of).collect()),
+        RegexAst::Alternation(asts) => choice(asts.iter().map(block_of).collect()),
+        RegexAst::Star(ast) => repetition(block_of(ast)),
+    }
+}
+
+impl RegexAst {
+    /// An SVG image of the railroad (syntax) diagram of this AST.
+    ///
+    /// A word is matched if and only if it is spelled by the boxes along
+    /// some path following the tracks from the left end to the right end.
+    pub fn to_railroad_svg(&self) -> String {
+        let block = block_of(&self.flatten());
+        let end_width = 2.0 * HORIZONTAL_GAP;
+        let width = block.width + 2.0 * end_width + 2.0 * MARGIN;
+        let height = block.up + block.down + 2.0 * MARGIN;
+
+        // short bars marking both ends of the track
+        let ends = track(format!(
+            "M 0 {:.1} V {:.1} M 0 0 h {:.1} M {:.1} 0 h {:.1} M {:.1} {:.1} V {:.1}",
+            -BOX_HEIGHT / 2.0,
+            BOX_HEIGHT / 2.0,
+            end_width,
+            end_width + block.width,
+            end_width,
+            2.0 * end_width + block.width,
+            -BOX_HEIGHT / 2.0,
+            BOX_HEIGHT / 2.0
+        ));
+
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" "#,
+                r#"font-family="monospace" font-size="16">"#,
+                "\n",
+                r#"<rect width="100%" height="100%" fill="white"/>"#,
+                "\n{}\n</svg>\n"
+            ),
+            width,
+            height,
+            translated(
+                &format!("{}{}", ends, translated(&block.body, end_width, 0.0)),
+                MARGIN,
+                MARGIN + block.up
+            )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_to_railroad_svg() {
+        let svg = RegexAst::parse_str("(ab|c)*a(ε|b)")
+            .unwrap()
+            .to_railroad_svg();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        // one box for each literal
+        assert_eq!(svg.matches("<rect x=").count(), 5);
+        assert_eq!(svg.matches(">a</text>").count(), 2);
+        assert_eq!(svg.matches(">b</text>").count(), 2);
+        assert_eq!(svg.matches(">c</text>").count(), 1);
+    }
+}
@@ -17,11 +17,11 @@
  *
  */
 
+use super::BudgetExceeded;
 use anyhow::anyhow;
 use combine::{choice, parser, unexpected_any, value, ParseError, Parser, Stream};
 use itertools::Itertools;
 use parser::char::{char, letter};
-use rustomaton::{automaton::Buildable, nfa::NFA};
 use std::{
     collections::HashSet,
     fmt::{Display, Formatter},
@@ -108,7 +108,7 @@ impl Display for Alphabet {
 /// in a descending order.
 ///
 /// For example, `ab*|cd` should be equivalent to `(a((b)*))|(cd)`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RegexAst {
     /// The expression that matches the empty string
     Epsilon,
@@ -190,50 +190,13 @@ impl RegexAst {
         }
     }
 
-    /// Compile the current AST to a regular expression that does not use a ε.
-    fn compile_to_epsilonless_regex(&self) -> String {
-        fn join_with_separator(sep: &str, asts: &[RegexAst]) -> String {
-            asts.iter()
-                .map(|ast| ast.compile_to_epsilonless_regex())
-                .join(sep)
-        }
-
-        match self {
-            RegexAst::Epsilon => "(.{0})".to_owned(),
-            RegexAst::Literal(a) => format!("{}", a),
-            RegexAst::Star(ast) => format!("({})*", (*ast).compile_to_epsilonless_regex()),
-            RegexAst::Concatenation(asts) => format!("({})", join_with_separator("", asts)),
-            RegexAst::Alternation(asts) => format!("({})", join_with_separator("|", asts)),
-        }
-    }
-
-    pub fn compile_to_string_regex(&self) -> regex::Regex {
-        let regex = format!("^({})$", self.compile_to_epsilonless_regex());
-
-        regex::Regex::new(&regex).unwrap()
-    }
-
+    /// Whether `input` is matched by this AST.
+    ///
+    /// This builds an automaton on every call, so it is only meant for one-off checks.
+    /// Use [`RegexAst::compile`] to answer several queries with one
+    /// [`CompiledRegex`](super::CompiledRegex).
     pub fn matches(&self, input: &[Alphabet]) -> bool {
-        self.compile_to_string_regex()
-            .is_match(&Alphabet::slice_to_plain_string(input))
-    }
-
-    fn compile_to_nfa(&self, alphabets: HashSet<Alphabet>) -> NFA<Alphabet> {
-        match self {
-            RegexAst::Epsilon => NFA::new_length(alphabets, 0),
-            RegexAst::Literal(a) => NFA::new_matching(alphabets, &[*a]),
-            RegexAst::Star(ast) => ast.compile_to_nfa(alphabets).kleene(),
-            RegexAst::Concatenation(asts) => asts
-                .iter()
-                .map(|ast| ast.compile_to_nfa(alphabets.clone()))
-                .fold1(|nfa1, nfa2| nfa1.concatenate(nfa2))
-                .unwrap(),
-            RegexAst::Alternation(asts) => asts
-                .iter()
-                .map(|ast| ast.compile_to_nfa(alphabets.clone()))
-                .fold1(|nfa1, nfa2| nfa1.unite(nfa2))
-                .unwrap(),
-        }
+        self.compile().matches(input)
     }
 
     /// Set of alphabets used within this AST.
@@ -274,10 +237,29 @@ impl RegexAst {
             return false;
         }
 
-        let nfa_1 = self.compile_to_nfa(used_alphabets.clone());
-        let nfa_2 = another.compile_to_nfa(used_alphabets);
+        // Minimal DFAs are numbered canonically, so equivalent expressions yield equal automata.
+        self.to_min_dfa(&used_alphabets) == another.to_min_dfa(&used_alphabets)
+    }
 
-        nfa_1.eq(&nfa_2)
+    /// Same as [`RegexAst::equivalent_to`], but gives up if determinizing either expression
+    /// needs more than `budget` states, so that adversarially large inputs cannot stall the caller.
+    pub fn equivalent_to_with_budget(
+        &self,
+        another: &RegexAst,
+        budget: usize,
+    ) -> Result<bool, BudgetExceeded> {
+        let used_alphabets = self.used_alphabets();
+        if used_alphabets != another.used_alphabets() {
+            // see the comment in `equivalent_to`
+            return Ok(false);
+        }
+
+        let min_dfa_of = |ast: &RegexAst| {
+            ast.glushkov_nfa_over(&used_alphabets)
+                .to_dfa_with_budget(budget)
+                .map(|dfa| dfa.minimized())
+        };
+        Ok(min_dfa_of(self)? == min_dfa_of(another)?)
     }
 
     //region flattening oeprations
@@ -344,7 +326,7 @@ impl RegexAst {
             RegexAst::Star(ast) => {
                 let flattened_child = ast.flatten_consecutive_stars();
                 match flattened_child {
-                    RegexAst::Star(grand_child) => *grand_child,
+                    RegexAst::Star(_) => flattened_child,
                     _ => RegexAst::Star(Box::new(flattened_child)),
                 }
             }
@@ -378,6 +360,138 @@ impl RegexAst {
     }
 
     //endregion
+
+    //region simplification operations
+
+    /// Build a concatenation of `asts`, collapsing it when there are less than two factors.
+    pub(crate) fn concatenation_of(mut asts: Vec<RegexAst>) -> Self {
+        match asts.len() {
+            0 => RegexAst::Epsilon,
+            1 => asts.pop().unwrap(),
+            _ => RegexAst::Concatenation(asts),
+        }
+    }
+
+    /// Build an alternation of `asts`, collapsing it when there are less than two branches.
+    pub(crate) fn alternation_of(mut asts: Vec<RegexAst>) -> Self {
+        match asts.len() {
+            0 => RegexAst::Epsilon,
+            1 => asts.pop().unwrap(),
+            _ => RegexAst::Alternation(asts),
+        }
+    }
+
+    fn simplify_once(&self) -> Self {
+        match self {
+            RegexAst::Epsilon | RegexAst::Literal(_) => self.clone(),
+            RegexAst::Star(ast) => match ast.simplify_once() {
+                // ε* → ε
+                RegexAst::Epsilon => RegexAst::Epsilon,
+                // (x*)* → x*
+                RegexAst::Star(inner) => RegexAst::Star(inner),
+                // (ε|x|y*)* → (x|y)*
+                RegexAst::Alternation(asts) => {
+                    let branches = asts
+                        .into_iter()
+                        .filter(|ast| *ast != RegexAst::Epsilon)
+                        .map(|ast| match ast {
+                            RegexAst::Star(inner) => *inner,
+                            _ => ast,
+                        })
+                        .collect::<Vec<_>>();
+
+                    if branches.is_empty() {
+                        RegexAst::Epsilon
+                    } else {
+                        RegexAst::Star(Box::new(RegexAst::alternation_of(branches)))
+                    }
+                }
+                simplified => RegexAst::Star(Box::new(simplified)),
+            },
+            RegexAst::Concatenation(asts) => {
+                let mut factors: Vec<RegexAst> = vec![];
+                let simplified_factors = asts.iter().flat_map(|ast| match ast.simplify_once() {
+                    RegexAst::Concatenation(inner) => inner,
+                    simplified => vec![simplified],
+                });
+
+                for factor in simplified_factors {
+                    match factor {
+                        // εx → x
+                        RegexAst::Epsilon => {}
+                        // x*x* → x*
+                        RegexAst::Star(_) if factors.last() == Some(&factor) => {}
+                        _ => factors.push(factor),
+                    }
+                }
+
+                RegexAst::concatenation_of(factors)
+            }
+            RegexAst::Alternation(asts) => {
+                let mut branches: Vec<RegexAst> = vec![];
+                let simplified_branches = asts.iter().flat_map(|ast| match ast.simplify_once() {
+                    RegexAst::Alternation(inner) => inner,
+                    simplified => vec![simplified],
+                });
+
+                // x|x → x
+                for branch in simplified_branches {
+                    if !branches.contains(&branch) {
+                        branches.push(branch);
+                    }
+                }
+
+                // x|x* → x*
+                let starred = branches
+                    .iter()
+                    .filter_map(|branch| match branch {
+                        RegexAst::Star(inner) => Some((**inner).clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                branches.retain(|branch| !starred.contains(branch));
+
+                // ε|x* → x*, or more generally, ε is absorbed by any other branch accepting ε
+                let epsilon_absorbed = branches
+                    .iter()
+                    .any(|branch| *branch != RegexAst::Epsilon && branch.nullable());
+                if epsilon_absorbed {
+                    branches.retain(|branch| *branch != RegexAst::Epsilon);
+                }
+
+                RegexAst::alternation_of(branches)
+            }
+        }
+    }
+
+    /// Simplifies the AST.
+    ///
+    /// In addition to what [flatten] does, this operation repeatedly applies
+    /// the following rewrites until the AST no longer changes:
+    ///
+    ///  * `εx → x` and `xε → x`
+    ///  * `x*x* → x*`
+    ///  * `x|x → x`
+    ///  * `x|x* → x*`
+    ///  * `ε* → ε` and `(x*)* → x*`
+    ///  * `(ε|x|y*)* → (x|y)*`
+    ///  * ε is dropped from an alternation whenever another branch accepts ε.
+    ///    For example, `ε|a*b*` will be simplified into `a*b*`.
+    ///
+    /// Like [flatten], this operation preserves the regular expression up to equivalence.
+    pub fn simplify(&self) -> Self {
+        let mut current = self.flatten();
+
+        loop {
+            let next = current.simplify_once();
+            if next == current {
+                return current;
+            }
+            current = next;
+        }
+    }
+
+    //endregion
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -437,7 +551,7 @@ impl Display for RegexAst {
 
 #[cfg(test)]
 mod tests {
-    use crate::regex::{Alphabet, RegexAst};
+    use crate::regex::{Alphabet, BudgetExceeded, RegexAst};
 
     #[test]
     fn str_to_alphabets() {
@@ -679,6 +793,11 @@ mod tests {
             RegexAst::parse_str("a*").unwrap()
         );
 
+        assert_eq!(
+            RegexAst::parse_str("((a)*)*").unwrap().flatten(),
+            RegexAst::parse_str("a*").unwrap()
+        );
+
         assert_eq!(
             RegexAst::Alternation(vec![RegexAst::parse_str("(((a)*)*)*").unwrap()]).flatten(),
             RegexAst::parse_str("a*").unwrap()
@@ -689,4 +808,73 @@ mod tests {
             RegexAst::parse_str("a*").unwrap()
         );
     }
+
+    #[test]
+    fn regex_ast_flattening_nested_stars() {
+        // `a**` is read as `a*` by the parser, so the nested stars are built by hand
+        let star = |ast| RegexAst::Star(Box::new(ast));
+        let a = RegexAst::parse_str("a").unwrap();
+
+        assert_eq!(
+            star(star(a.clone())).flatten_consecutive_stars(),
+            RegexAst::parse_str("a*").unwrap()
+        );
+        assert_eq!(
+            star(star(star(a))).flatten_consecutive_stars(),
+            RegexAst::parse_str("a*").unwrap()
+        );
+        assert_eq!(
+            star(star(RegexAst::parse_str("ab").unwrap())).flatten_consecutive_stars(),
+            RegexAst::parse_str("(ab)*").unwrap()
+        );
+    }
+
+    #[test]
+    fn regex_ast_simplification() {
+        let pairs = vec![
+            ("εaε", "a"),
+            ("a|a", "a"),
+            ("(a*)*", "a*"),
+            ("ε|a*", "a*"),
+            ("a*a*b", "a*b"),
+            ("ε|a*b*|c", "a*b*|c"),
+            ("b|b*", "b*"),
+            ("(ε|a|b*)*", "(a|b)*"),
+            ("(ε|ε)*c", "c"),
+            ("a(b(ε|c|c))", "ab(ε|c)"),
+        ];
+
+        for (regex_str, simplified_str) in pairs {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            let simplified = ast.simplify();
+
+            assert_eq!(
+                simplified,
+                RegexAst::parse_str(simplified_str).unwrap(),
+                r#""{}" should be simplified into "{}""#,
+                regex_str,
+                simplified_str
+            );
+            assert!(
+                simplified.equivalent_to(&ast),
+                r#"Simplifying "{}" should preserve the language"#,
+                regex_str
+            );
+        }
+    }
+
+    #[test]
+    fn regex_ast_equivalent_to_with_budget() {
+        let ast = RegexAst::parse_str("(a|b)*b(a|b)").unwrap();
+        let another = RegexAst::parse_str("(a|b)*(ba|bb)").unwrap();
+        assert_eq!(ast.equivalent_to_with_budget(&another, 10), Ok(true));
+
+        // The minimal DFA of `(a|b)*a(a|b)(a|b)(a|b)(a|b)(a|b)` has 2^6 states.
+        let blowup = RegexAst::parse_str("(a|b)*a(a|b)(a|b)(a|b)(a|b)(a|b)").unwrap();
+        assert_eq!(
+            blowup.equivalent_to_with_budget(&ast, 50),
+            Err(BudgetExceeded { budget: 50 })
+        );
+        assert_eq!(blowup.equivalent_to_with_budget(&ast, 100), Ok(false));
+    }
 }
@@ -0,0 +1,374 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, RegexAst};
+use rand::{seq::SliceRandom, Rng};
+use rand_distr::{Distribution, WeightedIndex};
+use std::{collections::HashSet, ops::RangeInclusive};
+
+// constants related to sampling of words
+const STOP_PROBABILITY: f64 = 0.3;
+const MAX_SAMPLED_WORD_LENGTH: usize = 12;
+const MAX_ATTEMPTS_PER_WORD: usize = 20;
+
+impl Dfa {
+    /// A random accepted word obtained by a random walk on the trimmed automaton,
+    /// which stops at accepting states with a fixed probability.
+    ///
+    /// Returns `None` if the walk fails to reach an accepting state in a reasonable number of steps.
+    fn random_accepted_word<R: Rng + ?Sized>(
+        &self,
+        live: &[bool],
+        rng: &mut R,
+    ) -> Option<Vec<Alphabet>> {
+        let mut word = vec![];
+        let mut state = self.initial;
+        if !live[state] {
+            return None;
+        }
+
+        loop {
+            let successors = self
+                .alphabet
+                .iter()
+                .zip(self.transitions[state].iter())
+                .filter(|(_, next)| live[**next])
+                .collect::<Vec<_>>();
+
+            if self.accepting[state]
+                && (successors.is_empty()
+                    || word.len() >= MAX_SAMPLED_WORD_LENGTH
+                    || rng.gen_bool(STOP_PROBABILITY))
+            {
+                return Some(word);
+            }
+            if word.len() >= 2 * MAX_SAMPLED_WORD_LENGTH {
+                return None;
+            }
+
+            // a live state that is not accepting always has a live successor
+            let (a, next) = successors.choose(rng).unwrap();
+            word.push(**a);
+            state = **next;
+        }
+    }
+}
+
+/// Distribution of the lengths of sampled words,
+/// given by relative weights of the lengths `0, 1, ..., max_len`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LengthDistribution {
+    weights: Vec<f64>,
+}
+
+impl LengthDistribution {
+    /// `weights[n]` is the relative weight of the length `n`.
+    pub fn from_weights(weights: Vec<f64>) -> Self {
+        Self { weights }
+    }
+
+    /// Every length in `lengths` equally likely.
+    pub fn uniform(lengths: RangeInclusive<usize>) -> Self {
+        Self::from_weights(
+            (0..=*lengths.end())
+                .map(|n| if lengths.contains(&n) { 1.0 } else { 0.0 })
+                .collect(),
+        )
+    }
+
+    /// Geometric distribution truncated at `max_len`, where each length is longer
+    /// than the previous one with probability `1 - stop_probability`.
+    /// Shorter words are more likely, the more so the larger `stop_probability` is.
+    pub fn geometric(stop_probability: f64, max_len: usize) -> Self {
+        Self::from_weights(
+            (0..=max_len)
+                .map(|n| (1.0 - stop_probability).powi(n as i32) * stop_probability)
+                .collect(),
+        )
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.weights.len().saturating_sub(1)
+    }
+}
+
+impl Dfa {
+    /// `counts[n][q]` is the number of words of length `n` accepted from the state `q`,
+    /// for `n` up to `max_len`, approximated by floating point numbers.
+    fn count_accepted_from_each_state(&self, max_len: usize) -> Vec<Vec<f64>> {
+        let mut counts = vec![self
+            .accepting
+            .iter()
+            .map(|accepting| if *accepting { 1.0 } else { 0.0 })
+            .collect::<Vec<_>>()];
+
+        for n in 1..=max_len {
+            let shorter = &counts[n - 1];
+            let current = (0..self.state_count())
+                .map(|q| self.transitions[q].iter().map(|next| shorter[*next]).sum())
+                .collect();
+            counts.push(current);
+        }
+
+        counts
+    }
+
+    /// A random accepted word whose length follows `lengths` restricted to the lengths of accepted words,
+    /// uniformly chosen among accepted words of that length.
+    ///
+    /// Returns `None` if no accepted word has a length of positive weight.
+    fn sample_by_length<R: Rng + ?Sized>(
+        &self,
+        lengths: &LengthDistribution,
+        counts: &[Vec<f64>],
+        rng: &mut R,
+    ) -> Option<Vec<Alphabet>> {
+        let weights = lengths
+            .weights
+            .iter()
+            .zip(counts.iter())
+            .map(|(weight, count)| {
+                if count[self.initial] > 0.0 {
+                    *weight
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+        let length = WeightedIndex::new(weights).ok()?.sample(rng);
+
+        // each letter is chosen proportionally to the number of ways to complete the word
+        let mut word = vec![];
+        let mut state = self.initial;
+        for remaining in (0..length).rev() {
+            let choices = self.transitions[state]
+                .iter()
+                .map(|next| counts[remaining][*next])
+                .collect::<Vec<_>>();
+            let i = WeightedIndex::new(choices).ok()?.sample(rng);
+            word.push(self.alphabet[i]);
+            state = self.transitions[state][i];
+        }
+
+        Some(word)
+    }
+}
+
+/// `word` with a random letter substituted, inserted or deleted, using letters of `alphabet`.
+fn randomly_edited<R: Rng + ?Sized>(
+    word: &[Alphabet],
+    alphabet: &[Alphabet],
+    rng: &mut R,
+) -> Vec<Alphabet> {
+    let mut edited = word.to_vec();
+    let letter = *alphabet.choose(rng).unwrap();
+
+    match (word.is_empty(), rng.gen_range(0..3)) {
+        (true, _) | (false, 0) => edited.insert(rng.gen_range(0..=word.len()), letter),
+        (false, 1) => edited[rng.gen_range(0..word.len())] = letter,
+        (false, _) => {
+            edited.remove(rng.gen_range(0..word.len()));
+        }
+    }
+
+    edited
+}
+
+impl RegexAst {
+    /// Sample `count` words matched by this AST (possibly with repetition)
+    /// whose lengths follow `lengths`, restricted to the lengths of matched words.
+    /// Words of the same length are equally likely.
+    ///
+    /// The result is empty if no matched word has a length of positive weight.
+    pub fn sample_with_lengths<R: Rng + ?Sized>(
+        &self,
+        lengths: &LengthDistribution,
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<Vec<Alphabet>> {
+        let dfa = self.to_min_dfa(&self.used_alphabets());
+        let counts = dfa.count_accepted_from_each_state(lengths.max_len());
+
+        (0..count)
+            .map_while(|_| dfa.sample_by_length(lengths, &counts, rng))
+            .collect()
+    }
+
+    /// Sample up to `count` distinct words matched by this AST, by random walks on its minimal DFA.
+    ///
+    /// Fewer words are returned if the AST matches fewer words or such words are hard to find.
+    pub fn sample_matches<R: Rng + ?Sized>(&self, count: usize, rng: &mut R) -> Vec<Vec<Alphabet>> {
+        let dfa = self.to_min_dfa(&self.used_alphabets());
+        let live = dfa.live_states();
+
+        let mut seen = HashSet::new();
+        let mut matches = vec![];
+        for _ in 0..count * MAX_ATTEMPTS_PER_WORD {
+            if matches.len() == count {
+                break;
+            }
+
+            if let Some(word) = dfa.random_accepted_word(&live, rng) {
+                if seen.insert(word.clone()) {
+                    matches.push(word);
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Sample up to `count` distinct words over `alphabet` NOT matched by this AST
+    /// that are one edit (substitution, insertion or deletion of a letter) away from matched words.
+    ///
+    /// Such words look plausible as members of the language, which makes them good negative examples.
+    /// Fewer words are returned if such words are hard to find, e.g. when almost all words are matched.
+    pub fn sample_near_misses<R: Rng + ?Sized>(
+        &self,
+        alphabet: &[Alphabet],
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<Vec<Alphabet>> {
+        if alphabet.is_empty() {
+            return vec![];
+        }
+
+        let dfa = self.to_min_dfa(&self.used_alphabets());
+        let live = dfa.live_states();
+
+        let mut seen = HashSet::new();
+        let mut near_misses = vec![];
+        for _ in 0..count * MAX_ATTEMPTS_PER_WORD {
+            if near_misses.len() == count {
+                break;
+            }
+
+            let candidate = match dfa.random_accepted_word(&live, rng) {
+                Some(word) => randomly_edited(&word, alphabet, rng),
+                None => continue,
+            };
+            if !dfa.accepts(&candidate) && seen.insert(candidate.clone()) {
+                near_misses.push(candidate);
+            }
+        }
+
+        near_misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, LengthDistribution, RegexAst};
+    use itertools::Itertools;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Words obtained by one substitution, insertion or deletion of a letter
+    fn neighbours(word: &[Alphabet], alphabet: &[Alphabet]) -> Vec<Vec<Alphabet>> {
+        let mut accum = vec![];
+        for i in 0..=word.len() {
+            for a in alphabet {
+                let mut inserted = word.to_vec();
+                inserted.insert(i, *a);
+                accum.push(inserted);
+
+                if i < word.len() {
+                    let mut substituted = word.to_vec();
+                    substituted[i] = *a;
+                    accum.push(substituted);
+                }
+            }
+            if i < word.len() {
+                let mut deleted = word.to_vec();
+                deleted.remove(i);
+                accum.push(deleted);
+            }
+        }
+        accum
+    }
+
+    #[test]
+    fn regex_ast_sample_with_lengths() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let ast = RegexAst::parse_str("(a|b)*c").unwrap();
+
+        let words = ast.sample_with_lengths(&LengthDistribution::uniform(4..=6), 50, &mut rng);
+        assert_eq!(words.len(), 50);
+        assert!(words
+            .iter()
+            .all(|word| ast.matches(word) && (4..=6).contains(&word.len())));
+        // all three lengths and various words appear
+        assert_eq!(words.iter().map(|word| word.len()).unique().count(), 3);
+        assert!(words.iter().unique().count() > 10);
+
+        let short = ast.sample_with_lengths(&LengthDistribution::geometric(0.7, 10), 50, &mut rng);
+        let long = ast.sample_with_lengths(&LengthDistribution::geometric(0.1, 10), 50, &mut rng);
+        let total_length = |words: &[Vec<Alphabet>]| words.iter().map(|w| w.len()).sum::<usize>();
+        assert!(total_length(&short) < total_length(&long));
+
+        // only words of length 3 are matched
+        let fixed = RegexAst::parse_str("abc").unwrap();
+        assert!(fixed
+            .sample_with_lengths(&LengthDistribution::uniform(0..=2), 5, &mut rng)
+            .is_empty());
+    }
+
+    #[test]
+    fn regex_ast_sample_matches() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let ast = RegexAst::parse_str("a(b|c)*").unwrap();
+        let matches = ast.sample_matches(5, &mut rng);
+        assert_eq!(matches.len(), 5);
+        assert!(matches.iter().all(|word| ast.matches(word)));
+
+        // only two words are matched
+        let finite = RegexAst::parse_str("ab|c").unwrap();
+        assert_eq!(finite.sample_matches(5, &mut rng).len(), 2);
+    }
+
+    #[test]
+    fn regex_ast_sample_near_misses() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let alphabet = Alphabet::vec_from_str("abc").unwrap();
+
+        for regex_str in ["(ab)*", "a*ba*", "(a|b)*c", "abc"] {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            let near_misses = ast.sample_near_misses(&alphabet, 5, &mut rng);
+
+            assert_eq!(near_misses.len(), 5);
+            for word in near_misses {
+                assert!(!ast.matches(&word));
+                assert!(
+                    neighbours(&word, &alphabet)
+                        .iter()
+                        .any(|neighbour| ast.matches(neighbour)),
+                    r#""{}" is not close to words matched by "{}""#,
+                    Alphabet::slice_to_plain_string(&word),
+                    regex_str
+                );
+            }
+        }
+
+        // every word over "a" is matched
+        let everything = RegexAst::parse_str("a*").unwrap();
+        assert!(everything
+            .sample_near_misses(&alphabet[..1], 5, &mut rng)
+            .is_empty());
+    }
+}
@@ -0,0 +1,275 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Bisimulation, Dfa, Nfa};
+use anyhow::{anyhow, ensure, Context};
+use serde::{Deserialize, Serialize};
+
+/// Version of the serialized format of automata.
+/// Bump this whenever the layout of `SerializedDfa` or `SerializedNfa` changes,
+/// so that automata stored by an older version are rejected instead of misread.
+pub const AUTOMATON_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SerializedDfa {
+    version: u32,
+    /// Alphabets the automaton reads, as a string of letters in ascending order
+    alphabet: String,
+    initial: usize,
+    accepting: Vec<bool>,
+    transitions: Vec<Vec<usize>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedNfa {
+    version: u32,
+    /// Alphabets the automaton reads, as a string of letters in ascending order
+    alphabet: String,
+    initial: Vec<usize>,
+    accepting: Vec<bool>,
+    transitions: Vec<Vec<Vec<usize>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedBisimulation {
+    version: u32,
+    lhs: SerializedDfa,
+    rhs: SerializedDfa,
+    pairs: Vec<(usize, usize)>,
+}
+
+impl From<&Dfa> for SerializedDfa {
+    fn from(dfa: &Dfa) -> Self {
+        SerializedDfa {
+            version: AUTOMATON_FORMAT_VERSION,
+            alphabet: Alphabet::slice_to_plain_string(&dfa.alphabet),
+            initial: dfa.initial,
+            accepting: dfa.accepting.clone(),
+            transitions: dfa.transitions.clone(),
+        }
+    }
+}
+
+fn check_version(version: u32) -> anyhow::Result<()> {
+    ensure!(
+        version == AUTOMATON_FORMAT_VERSION,
+        "Unsupported automaton format version {} (expected {})",
+        version,
+        AUTOMATON_FORMAT_VERSION
+    );
+    Ok(())
+}
+
+fn parse_alphabet(alphabet: &str) -> anyhow::Result<Vec<Alphabet>> {
+    let alphabet = Alphabet::vec_from_str(alphabet)?;
+    ensure!(
+        alphabet.windows(2).all(|pair| pair[0] < pair[1]),
+        "Alphabets of an automaton must be in strictly ascending order"
+    );
+    Ok(alphabet)
+}
+
+fn check_state(state: usize, state_count: usize) -> anyhow::Result<usize> {
+    (state < state_count)
+        .then_some(state)
+        .ok_or_else(|| anyhow!("State {} is out of range (0..{})", state, state_count))
+}
+
+impl Dfa {
+    /// Serialize the automaton into a versioned JSON document,
+    /// which can be restored by [`Dfa::from_json`].
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&SerializedDfa::from(self)).expect("valid json")
+    }
+
+    /// Restore an automaton serialized by [`Dfa::to_json`].
+    ///
+    /// Fails if the document was written in another format version
+    /// or does not describe a complete DFA.
+    pub fn from_json(json: &str) -> anyhow::Result<Dfa> {
+        let serialized: SerializedDfa =
+            serde_json::from_str(json).context("Malformed serialized DFA")?;
+        check_version(serialized.version)?;
+
+        let alphabet = parse_alphabet(&serialized.alphabet)?;
+        let state_count = serialized.accepting.len();
+        ensure!(
+            serialized.transitions.len() == state_count,
+            "Expected transitions of {} states, found {}",
+            state_count,
+            serialized.transitions.len()
+        );
+        check_state(serialized.initial, state_count)?;
+        for row in serialized.transitions.iter() {
+            ensure!(
+                row.len() == alphabet.len(),
+                "Expected a transition for each of {} alphabets, found {}",
+                alphabet.len(),
+                row.len()
+            );
+            for target in row {
+                check_state(*target, state_count)?;
+            }
+        }
+
+        Ok(Dfa {
+            alphabet,
+            initial: serialized.initial,
+            accepting: serialized.accepting,
+            transitions: serialized.transitions,
+        })
+    }
+}
+
+impl Nfa {
+    /// Serialize the automaton into a versioned JSON document,
+    /// which can be restored by [`Nfa::from_json`].
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&SerializedNfa {
+            version: AUTOMATON_FORMAT_VERSION,
+            alphabet: Alphabet::slice_to_plain_string(&self.alphabet),
+            initial: self.initial.iter().cloned().collect(),
+            accepting: self.accepting.clone(),
+            transitions: self
+                .transitions
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|targets| targets.iter().cloned().collect())
+                        .collect()
+                })
+                .collect(),
+        })
+        .expect("valid json")
+    }
+
+    /// Restore an automaton serialized by [`Nfa::to_json`].
+    ///
+    /// Fails if the document was written in another format version
+    /// or does not describe a well-formed NFA.
+    pub fn from_json(json: &str) -> anyhow::Result<Nfa> {
+        let serialized: SerializedNfa =
+            serde_json::from_str(json).context("Malformed serialized NFA")?;
+        check_version(serialized.version)?;
+
+        let alphabet = parse_alphabet(&serialized.alphabet)?;
+        let state_count = serialized.accepting.len();
+        ensure!(
+            serialized.transitions.len() == state_count,
+            "Expected transitions of {} states, found {}",
+            state_count,
+            serialized.transitions.len()
+        );
+
+        let initial = serialized
+            .initial
+            .iter()
+            .map(|q| check_state(*q, state_count))
+            .collect::<anyhow::Result<_>>()?;
+        let transitions = serialized
+            .transitions
+            .iter()
+            .map(|row| {
+                ensure!(
+                    row.len() == alphabet.len(),
+                    "Expected transitions for each of {} alphabets, found {}",
+                    alphabet.len(),
+                    row.len()
+                );
+                row.iter()
+                    .map(|targets| {
+                        targets
+                            .iter()
+                            .map(|q| check_state(*q, state_count))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Nfa {
+            alphabet,
+            initial,
+            accepting: serialized.accepting,
+            transitions,
+        })
+    }
+}
+
+impl Bisimulation {
+    /// Serialize the evidence, including both automata, into a versioned JSON document,
+    /// so that it can be checked independently of this crate.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&SerializedBisimulation {
+            version: AUTOMATON_FORMAT_VERSION,
+            lhs: SerializedDfa::from(&self.lhs),
+            rhs: SerializedDfa::from(&self.rhs),
+            pairs: self.pairs.clone(),
+        })
+        .expect("valid json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AUTOMATON_FORMAT_VERSION;
+    use crate::regex::{Dfa, Nfa, RegexAst};
+
+    #[test]
+    fn automaton_json_roundtrip() {
+        let ast = RegexAst::parse_str("(a|bc)*c").unwrap();
+        let dfa = ast.to_min_dfa(&ast.used_alphabets());
+        let nfa = ast.to_glushkov_nfa();
+
+        assert_eq!(Dfa::from_json(&dfa.to_json()).unwrap(), dfa);
+        assert_eq!(Nfa::from_json(&nfa.to_json()).unwrap(), nfa);
+    }
+
+    #[test]
+    fn automaton_json_rejects_malformed_documents() {
+        let json = |version: u32, alphabet: &str, transitions: &str| {
+            format!(
+                r#"{{"version":{},"alphabet":"{}","initial":0,"accepting":[false,true],"transitions":{}}}"#,
+                version, alphabet, transitions
+            )
+        };
+
+        assert!(Dfa::from_json(&json(AUTOMATON_FORMAT_VERSION, "ab", "[[1,0],[1,1]]")).is_ok());
+        assert!(
+            Dfa::from_json(&json(AUTOMATON_FORMAT_VERSION + 1, "ab", "[[1,0],[1,1]]")).is_err()
+        );
+        assert!(Dfa::from_json(&json(AUTOMATON_FORMAT_VERSION, "ba", "[[1,0],[1,1]]")).is_err());
+        assert!(Dfa::from_json(&json(AUTOMATON_FORMAT_VERSION, "ab", "[[1,2],[1,1]]")).is_err());
+        assert!(Dfa::from_json(&json(AUTOMATON_FORMAT_VERSION, "ab", "[[1],[1,1]]")).is_err());
+        assert!(Dfa::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn bisimulation_json() {
+        let lhs = RegexAst::parse_str("a*").unwrap();
+        let rhs = RegexAst::parse_str("a*a*").unwrap();
+        let evidence = lhs.equivalence_evidence(&rhs).unwrap();
+        let json = evidence.to_json();
+
+        assert!(json.starts_with(&format!(r#"{{"version":{}"#, AUTOMATON_FORMAT_VERSION)));
+        assert!(json.contains(&format!(r#""lhs":{}"#, evidence.lhs.to_json())));
+        assert!(json.contains(&format!(r#""rhs":{}"#, evidence.rhs.to_json())));
+        assert!(json.contains(r#""pairs":[[0,0]"#));
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{BudgetExceeded, Nfa, RegexAst};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+impl Nfa {
+    /// Automaton accepting interleavings of a word accepted by `self` and a word accepted by `other`.
+    ///
+    /// States are pairs of states of the two automata, where each letter advances exactly one of them.
+    /// Only pairs reachable from initial pairs are constructed,
+    /// and the construction fails if there are more than `budget` of them.
+    ///
+    /// # Panics
+    /// Panics if the automata do not read the same alphabets.
+    pub fn shuffle(&self, other: &Nfa, budget: usize) -> Result<Nfa, BudgetExceeded> {
+        assert_eq!(
+            self.alphabet, other.alphabet,
+            "shuffle of automata over different alphabets"
+        );
+
+        let mut index = HashMap::new();
+        let mut pairs = vec![];
+        let mut queue = VecDeque::new();
+        let mut visit = |pair: (usize, usize),
+                         pairs: &mut Vec<(usize, usize)>,
+                         queue: &mut VecDeque<(usize, usize)>|
+         -> Result<usize, BudgetExceeded> {
+            if let Some(i) = index.get(&pair) {
+                return Ok(*i);
+            }
+            if pairs.len() == budget {
+                return Err(BudgetExceeded { budget });
+            }
+            index.insert(pair, pairs.len());
+            pairs.push(pair);
+            queue.push_back(pair);
+            Ok(pairs.len() - 1)
+        };
+
+        let mut initial = BTreeSet::new();
+        for p in &self.initial {
+            for q in &other.initial {
+                initial.insert(visit((*p, *q), &mut pairs, &mut queue)?);
+            }
+        }
+
+        let mut transitions = vec![];
+        while let Some((p, q)) = queue.pop_front() {
+            let mut row = vec![];
+            for i in 0..self.alphabet.len() {
+                let mut targets = BTreeSet::new();
+                for next in &self.transitions[p][i] {
+                    targets.insert(visit((*next, q), &mut pairs, &mut queue)?);
+                }
+                for next in &other.transitions[q][i] {
+                    targets.insert(visit((p, *next), &mut pairs, &mut queue)?);
+                }
+                row.push(targets);
+            }
+            transitions.push(row);
+        }
+
+        Ok(Nfa {
+            alphabet: self.alphabet.clone(),
+            initial,
+            accepting: pairs
+                .iter()
+                .map(|(p, q)| self.accepting[*p] && other.accepting[*q])
+                .collect(),
+            transitions,
+        })
+    }
+}
+
+impl RegexAst {
+    /// An AST matching interleavings of a word matched by `self` and a word matched by `other`.
+    ///
+    /// Fails if the shuffle of the Glushkov automata or its determinization has more than
+    /// `budget` states, or if converting the minimal DFA back into an expression
+    /// builds expressions of more than `budget` nodes in total.
+    pub fn shuffle(&self, other: &RegexAst, budget: usize) -> Result<RegexAst, BudgetExceeded> {
+        let alphabet = self
+            .used_alphabets()
+            .union(&other.used_alphabets())
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        let shuffled = self
+            .glushkov_nfa_over(&alphabet)
+            .shuffle(&other.glushkov_nfa_over(&alphabet), budget)?;
+
+        // the shuffle of nonempty languages is nonempty
+        Ok(shuffled
+            .to_dfa_with_budget(budget)?
+            .minimized()
+            .to_regex_with_budget(budget)?
+            .unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{blowup, Alphabet, BudgetExceeded, RegexAst};
+
+    #[test]
+    fn regex_ast_shuffle() {
+        let cases = vec![
+            ("ab", "c", "cab|acb|abc"),
+            ("a*", "b", "a*ba*"),
+            ("ab", "ab", "abab|aabb"),
+            ("ε", "(ab)*", "(ab)*"),
+        ];
+
+        for (lhs, rhs, expected) in cases {
+            let shuffled = RegexAst::parse_str(lhs)
+                .unwrap()
+                .shuffle(&RegexAst::parse_str(rhs).unwrap(), 100)
+                .unwrap();
+
+            assert!(
+                shuffled.equivalent_to(&RegexAst::parse_str(expected).unwrap()),
+                r#"Shuffle of "{}" and "{}" should be "{}", but got "{}""#,
+                lhs,
+                rhs,
+                expected,
+                shuffled
+            );
+        }
+
+        assert_eq!(
+            RegexAst::parse_str("abcde")
+                .unwrap()
+                .shuffle(&RegexAst::parse_str("abcde").unwrap(), 10),
+            Err(BudgetExceeded { budget: 10 })
+        );
+    }
+
+    #[test]
+    fn regex_ast_shuffle_determinization_budget() {
+        let lhs = blowup(3);
+        let rhs = RegexAst::parse_str("c").unwrap();
+        let alphabet = Alphabet::vec_from_str("abc").unwrap().into_iter().collect();
+
+        // the shuffle automaton fits in the budget, but its determinization does not
+        let nfa = lhs
+            .glushkov_nfa_over(&alphabet)
+            .shuffle(&rhs.glushkov_nfa_over(&alphabet), 20)
+            .unwrap();
+        assert!(nfa.state_count() <= 20);
+        assert!(nfa.to_dfa().state_count() > 20);
+
+        assert_eq!(lhs.shuffle(&rhs, 20), Err(BudgetExceeded { budget: 20 }));
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Dfa, RegexAst};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use std::collections::HashSet;
+
+impl RegexAst {
+    /// Jaccard similarity of the languages restricted to words of length at most `max_len`,
+    /// i.e. the number of words matched by both divided by the number of words matched by either.
+    ///
+    /// The similarity is `1.0` for languages equal up to the bound and `0.0` for disjoint ones.
+    pub fn similarity(&self, other: &RegexAst, max_len: usize) -> f64 {
+        let alphabet = self
+            .used_alphabets()
+            .union(&other.used_alphabets())
+            .cloned()
+            .collect::<HashSet<_>>();
+        let lhs = Dfa::from_ast(self, &alphabet);
+        let rhs = Dfa::from_ast(other, &alphabet);
+
+        let count = |dfa: Dfa| {
+            dfa.count_words_by_length(max_len)
+                .into_iter()
+                .fold(BigUint::default(), |sum, count| sum + count)
+        };
+        let intersection = count(lhs.product(&rhs, |p, q| p && q));
+        let union = count(lhs.product(&rhs, |p, q| p || q));
+
+        if union == BigUint::default() {
+            // both languages have no word within the bound
+            return 1.0;
+        }
+        intersection.to_f64().unwrap() / union.to_f64().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_similarity() {
+        let cases = vec![
+            ("a*", "a*", 5, 1.0),
+            ("(a|b)*", "(ab|a|b)*", 5, 1.0),
+            ("a*", "b*", 3, 1.0 / 7.0),
+            ("aa*", "bb*", 3, 0.0),
+            // words of length at most 2 over a, b are ε, a, b, aa, ab, ba, bb
+            ("(a|b)*", "(a|b)*a", 2, 3.0 / 7.0),
+            ("aaaaa", "bbbbb", 3, 1.0),
+        ];
+
+        for (lhs, rhs, max_len, expected) in cases {
+            let similarity = RegexAst::parse_str(lhs)
+                .unwrap()
+                .similarity(&RegexAst::parse_str(rhs).unwrap(), max_len);
+
+            assert!(
+                (similarity - expected).abs() < 1e-9,
+                r#"Similarity of "{}" and "{}" up to length {} should be {}, but got {}"#,
+                lhs,
+                rhs,
+                max_len,
+                expected,
+                similarity
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{
+    learn::{learn, Teacher},
+    Alphabet, CompiledRegex, Dfa, RegexAst,
+};
+use std::collections::{HashMap, HashSet};
+
+/// The interface of the game as seen by a player.
+pub trait Oracle {
+    /// Whether `word` is matched by the answer, as asked by `/query`.
+    fn is_member(&mut self, word: &[Alphabet]) -> bool;
+
+    /// Whether `guess` is equivalent to the answer, as asked by `/guess`.
+    fn guess(&mut self, guess: &RegexAst) -> bool;
+}
+
+/// An oracle hiding a known answer.
+pub struct AnswerOracle {
+    answer: RegexAst,
+    compiled: CompiledRegex,
+}
+
+impl AnswerOracle {
+    pub fn new(answer: RegexAst) -> Self {
+        AnswerOracle {
+            compiled: answer.compile(),
+            answer,
+        }
+    }
+}
+
+impl Oracle for AnswerOracle {
+    fn is_member(&mut self, word: &[Alphabet]) -> bool {
+        if word.is_empty() {
+            self.answer.nullable()
+        } else {
+            self.compiled.matches(word)
+        }
+    }
+
+    fn guess(&mut self, guess: &RegexAst) -> bool {
+        self.answer.equivalent_to(guess)
+    }
+}
+
+/// Result of [`Solver::solve`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolverOutcome {
+    /// The accepted guess, or `None` if the solver gave up
+    pub answer: Option<RegexAst>,
+    /// Number of distinct words queried
+    pub membership_queries: usize,
+    /// Number of guesses made
+    pub guesses: usize,
+}
+
+/// A player of the game, which learns the answer with [`learn`]
+/// and submits each hypothesis of the learner as a guess.
+///
+/// Since a rejected guess comes without a counterexample, the solver looks for one itself:
+/// first among the words already queried, and then by querying untested words
+/// in shortlex order up to a length bound.
+pub struct Solver {
+    alphabet: Vec<Alphabet>,
+    max_test_length: usize,
+}
+
+impl Solver {
+    /// A solver for answers over `alphabet`, which gives up when a hypothesis is rejected
+    /// but agrees with the answer on every word of length at most `max_test_length`.
+    pub fn new(alphabet: &[Alphabet], max_test_length: usize) -> Self {
+        let mut alphabet = alphabet.to_vec();
+        alphabet.sort();
+        alphabet.dedup();
+
+        Solver {
+            alphabet,
+            max_test_length,
+        }
+    }
+
+    pub fn solve<O: Oracle + ?Sized>(&self, oracle: &mut O) -> SolverOutcome {
+        let mut player = Player {
+            solver: self,
+            oracle,
+            answers: HashMap::new(),
+            answer: None,
+            guesses: 0,
+        };
+        learn(&self.alphabet, &mut player);
+
+        SolverOutcome {
+            answer: player.answer,
+            membership_queries: player.answers.len(),
+            guesses: player.guesses,
+        }
+    }
+}
+
+/// A teacher answering the learner by playing the game through an oracle.
+struct Player<'a, O: Oracle + ?Sized> {
+    solver: &'a Solver,
+    oracle: &'a mut O,
+    answers: HashMap<Vec<Alphabet>, bool>,
+    answer: Option<RegexAst>,
+    guesses: usize,
+}
+
+impl<O: Oracle + ?Sized> Player<'_, O> {
+    /// A word on which `hypothesis` disagrees with the answer, preferring words already queried.
+    fn search_counterexample(&mut self, hypothesis: &Dfa) -> Option<Vec<Alphabet>> {
+        let known = self
+            .answers
+            .iter()
+            .filter(|(word, answer)| hypothesis.accepts(word) != **answer)
+            .map(|(word, _)| word.clone())
+            .min_by_key(|word| word.len());
+        if known.is_some() {
+            return known;
+        }
+
+        let tested = self.answers.keys().cloned().collect::<HashSet<_>>();
+        let mut words = vec![vec![]];
+        for length in 0..=self.solver.max_test_length {
+            for word in words.iter() {
+                if !tested.contains(word) && self.is_member(word) != hypothesis.accepts(word) {
+                    return Some(word.clone());
+                }
+            }
+            if length < self.solver.max_test_length {
+                words = words
+                    .iter()
+                    .flat_map(|word| {
+                        self.solver.alphabet.iter().map(move |a| {
+                            let mut extended = word.clone();
+                            extended.push(*a);
+                            extended
+                        })
+                    })
+                    .collect();
+            }
+        }
+
+        None
+    }
+}
+
+impl<O: Oracle + ?Sized> Teacher for Player<'_, O> {
+    fn is_member(&mut self, word: &[Alphabet]) -> bool {
+        if let Some(answer) = self.answers.get(word) {
+            return *answer;
+        }
+
+        let answer = self.oracle.is_member(word);
+        self.answers.insert(word.to_vec(), answer);
+        answer
+    }
+
+    fn find_counterexample(&mut self, hypothesis: &Dfa) -> Option<Vec<Alphabet>> {
+        // the empty language cannot be guessed, so it is only refuted by a counterexample
+        if let Some(guess) = hypothesis.to_regex() {
+            self.guesses += 1;
+            if self.oracle.guess(&guess) {
+                self.answer = Some(guess);
+                return None;
+            }
+        }
+
+        self.search_counterexample(hypothesis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnswerOracle, Solver};
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn solver_finds_answers() {
+        let solver = Solver::new(&Alphabet::vec_from_str("ab").unwrap(), 6);
+
+        for answer in ["a*b", "(a|b)*b(a|b)", "(ab|ba)*", "a*(ba*ba*)*", "ε"] {
+            let answer = RegexAst::parse_str(answer).unwrap();
+            let outcome = solver.solve(&mut AnswerOracle::new(answer.clone()));
+
+            let found = outcome.answer.expect("solver should find the answer");
+            assert!(found.equivalent_to(&answer), "{} is not {}", found, answer);
+            assert!(
+                outcome.guesses <= answer.to_min_dfa(&answer.used_alphabets()).state_count() + 1
+            );
+            assert!(outcome.membership_queries > 0);
+        }
+    }
+
+    #[test]
+    fn solver_gives_up_beyond_test_length() {
+        // differs from the first hypothesis `∅` only on a word of length 4
+        let answer = RegexAst::parse_str("abab").unwrap();
+        let outcome = Solver::new(&Alphabet::vec_from_str("ab").unwrap(), 3)
+            .solve(&mut AnswerOracle::new(answer));
+
+        assert_eq!(outcome.answer, None);
+        assert_eq!(outcome.guesses, 0);
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Dfa, RegexAst};
+use std::collections::VecDeque;
+
+/// Lengths of words in a language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LengthSpectrum {
+    /// `has_length[n]` is whether the language contains a word of length `n`,
+    /// for every `n` up to the bound the spectrum was computed with
+    pub has_length: Vec<bool>,
+    /// Length of the shortest word, or `None` if the language is empty
+    pub min_length: Option<usize>,
+    /// Whether the language contains arbitrarily long words
+    pub unbounded: bool,
+}
+
+impl LengthSpectrum {
+    /// Lengths up to the bound for which the language contains a word.
+    pub fn lengths(&self) -> Vec<usize> {
+        (0..self.has_length.len())
+            .filter(|n| self.has_length[*n])
+            .collect()
+    }
+}
+
+impl Dfa {
+    /// Length of the shortest accepted word, or `None` if no word is accepted.
+    fn shortest_word_length(&self) -> Option<usize> {
+        let mut distance = vec![None; self.state_count()];
+        let mut queue = VecDeque::new();
+        distance[self.initial] = Some(0);
+        queue.push_back(self.initial);
+
+        while let Some(q) = queue.pop_front() {
+            let d = distance[q].unwrap();
+            if self.accepting[q] {
+                return Some(d);
+            }
+            for next in &self.transitions[q] {
+                if distance[*next].is_none() {
+                    distance[*next] = Some(d + 1);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Lengths of accepted words, listing those of at most `max_len` explicitly.
+    pub fn length_spectrum(&self, max_len: usize) -> LengthSpectrum {
+        // states reached by some word of the current length
+        let mut current = vec![false; self.state_count()];
+        current[self.initial] = true;
+
+        let mut has_length = vec![];
+        for _ in 0..=max_len {
+            has_length.push((0..self.state_count()).any(|q| current[q] && self.accepting[q]));
+
+            let mut next = vec![false; self.state_count()];
+            for q in (0..self.state_count()).filter(|q| current[*q]) {
+                for target in &self.transitions[q] {
+                    next[*target] = true;
+                }
+            }
+            current = next;
+        }
+
+        LengthSpectrum {
+            has_length,
+            min_length: self.shortest_word_length(),
+            unbounded: !self.is_finite(),
+        }
+    }
+}
+
+impl RegexAst {
+    /// Lengths of matched words, listing those of at most `max_len` explicitly.
+    pub fn length_spectrum(&self, max_len: usize) -> LengthSpectrum {
+        self.to_min_dfa(&self.used_alphabets())
+            .length_spectrum(max_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn regex_ast_length_spectrum() {
+        let cases = vec![
+            ("(aa)*", vec![0, 2, 4, 6], Some(0), true),
+            ("ab(aaa)*", vec![2, 5], Some(2), true),
+            ("ab|bab", vec![2, 3], Some(2), false),
+            ("(a|b)(a|b)(a|b)*", vec![2, 3, 4, 5, 6], Some(2), true),
+            ("aaaaaaaaa", vec![], Some(9), false),
+        ];
+
+        for (regex_str, lengths, min_length, unbounded) in cases {
+            let spectrum = RegexAst::parse_str(regex_str).unwrap().length_spectrum(6);
+
+            assert_eq!(spectrum.has_length.len(), 7);
+            assert_eq!(
+                spectrum.lengths(),
+                lengths,
+                r#"Lengths of words matched by "{}" are wrong"#,
+                regex_str
+            );
+            assert_eq!(spectrum.min_length, min_length);
+            assert_eq!(spectrum.unbounded, unbounded);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{BudgetExceeded, Dfa, RegexAst};
+use std::collections::VecDeque;
+
+/// Union of two edge labels, where `None` stands for the empty language.
+fn union(r1: Option<RegexAst>, r2: Option<RegexAst>) -> Option<RegexAst> {
+    match (r1, r2) {
+        (Some(r1), Some(r2)) => Some(RegexAst::Alternation(vec![r1, r2]).simplify()),
+        (r1, r2) => r1.or(r2),
+    }
+}
+
+/// Number of nodes of an edge label, where `None` stands for the empty language.
+fn label_size(label: &Option<RegexAst>) -> usize {
+    label.as_ref().map_or(0, |ast| ast.stats().node_count)
+}
+
+/// Labels of a generalized automaton, along with their total number of nodes.
+struct Labels {
+    edges: Vec<Vec<Option<RegexAst>>>,
+    size: usize,
+    budget: usize,
+}
+
+impl Labels {
+    /// Add `ast` to the label of the edge from `i` to `j`,
+    /// failing if the labels get more than `budget` nodes in total.
+    fn add(&mut self, i: usize, j: usize, ast: RegexAst) -> Result<(), BudgetExceeded> {
+        let edge = self.edges[i][j].take();
+        self.size -= label_size(&edge);
+        self.edges[i][j] = union(edge, Some(ast));
+        self.size += label_size(&self.edges[i][j]);
+
+        if self.size > self.budget {
+            return Err(BudgetExceeded {
+                budget: self.budget,
+            });
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, i: usize, j: usize) -> Option<RegexAst> {
+        let edge = self.edges[i][j].take();
+        self.size -= label_size(&edge);
+        edge
+    }
+}
+
+impl Dfa {
+    /// States from which some accepting state is reachable.
+    pub(super) fn productive_states(&self) -> Vec<bool> {
+        let mut predecessors = vec![vec![]; self.state_count()];
+        for (source, _, target) in self.transitions() {
+            predecessors[target].push(source);
+        }
+
+        let mut productive = self.accepting.clone();
+        let mut queue = self.accepting_states().into_iter().collect::<VecDeque<_>>();
+        while let Some(q) = queue.pop_front() {
+            for p in &predecessors[q] {
+                if !productive[*p] {
+                    productive[*p] = true;
+                    queue.push_back(*p);
+                }
+            }
+        }
+
+        productive
+    }
+
+    /// Convert the automaton into an equivalent regular expression by state elimination.
+    ///
+    /// Returns `None` if the automaton accepts no word,
+    /// since [RegexAst] cannot express the empty language.
+    pub fn to_regex(&self) -> Option<RegexAst> {
+        self.to_regex_with_budget(usize::MAX)
+            .expect("number of nodes fits in usize")
+    }
+
+    /// Convert the automaton into a regular expression like [`Dfa::to_regex`],
+    /// but fail as soon as the intermediate expressions have more than `budget` nodes in total.
+    ///
+    /// State elimination may build expressions exponentially larger than the automaton,
+    /// so this should be used on automata not known to be small.
+    pub fn to_regex_with_budget(&self, budget: usize) -> Result<Option<RegexAst>, BudgetExceeded> {
+        let productive = self.productive_states();
+        if !productive[self.initial] {
+            return Ok(None);
+        }
+
+        // Generalized automaton over the productive states plus a fresh start state `n`
+        // and a fresh final state `n + 1`, whose edges are labelled by regular expressions.
+        let n = self.state_count();
+        let mut labels = Labels {
+            edges: vec![vec![None; n + 2]; n + 2],
+            size: 0,
+            budget,
+        };
+
+        labels.add(n, self.initial, RegexAst::Epsilon)?;
+        for q in self.accepting_states() {
+            labels.add(q, n + 1, RegexAst::Epsilon)?;
+        }
+        for (source, a, target) in self.transitions() {
+            if productive[source] && productive[target] {
+                labels.add(source, target, RegexAst::Literal(a))?;
+            }
+        }
+
+        let mut remaining = (0..n).filter(|q| productive[*q]).collect::<Vec<_>>();
+
+        while !remaining.is_empty() {
+            let edges = &labels.edges;
+            // Eliminating a state with few incoming and outgoing edges first keeps the result small.
+            let (index, &k) = remaining
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, &k)| {
+                    let incoming = (0..n + 2).filter(|i| *i != k && edges[*i][k].is_some());
+                    let outgoing = (0..n + 2).filter(|j| *j != k && edges[k][*j].is_some());
+                    incoming.count() * outgoing.count()
+                })
+                .unwrap();
+            remaining.swap_remove(index);
+
+            let loop_part = labels
+                .take(k, k)
+                .map(|ast| RegexAst::Star(Box::new(ast)))
+                .unwrap_or(RegexAst::Epsilon);
+
+            for i in (0..n + 2).filter(|i| *i != k) {
+                let into_k = match &labels.edges[i][k] {
+                    Some(ast) => ast.clone(),
+                    None => continue,
+                };
+
+                for j in (0..n + 2).filter(|j| *j != k) {
+                    if let Some(out_of_k) = labels.edges[k][j].clone() {
+                        let bypass = RegexAst::Concatenation(vec![
+                            into_k.clone(),
+                            loop_part.clone(),
+                            out_of_k,
+                        ])
+                        .simplify();
+                        labels.add(i, j, bypass)?;
+                    }
+                }
+            }
+
+            for q in 0..n + 2 {
+                labels.take(q, k);
+                labels.take(k, q);
+            }
+        }
+
+        Ok(labels.take(n, n + 1).map(|ast| ast.simplify()))
+    }
+}
+
+#[test]
+fn dfa_to_regex_preserves_language() {
+    use crate::regex::Alphabet;
+
+    let regex_strs = vec!["(a|b)*abb", "a*bεcc*", "(ab|c)*|ε", "((a|b)*c)*", "ε", "ab"];
+
+    for regex_str in regex_strs {
+        let ast = RegexAst::parse_str(regex_str).unwrap();
+        let alphabet = Alphabet::vec_from_str("abc").unwrap().into_iter().collect();
+        let dfa = ast.to_min_dfa(&alphabet);
+
+        let converted = dfa.to_regex().unwrap();
+        assert!(
+            converted.equivalent_to(&ast),
+            r#""{}" was converted back into an inequivalent expression "{}""#,
+            ast,
+            converted
+        );
+    }
+}
+
+#[test]
+fn dfa_to_regex_with_budget() {
+    use crate::regex::{blowup, Alphabet, BudgetExceeded};
+
+    let alphabet = Alphabet::vec_from_str("ab").unwrap().into_iter().collect();
+
+    let small = RegexAst::parse_str("(a|b)*abb")
+        .unwrap()
+        .to_min_dfa(&alphabet);
+    let converted = small.to_regex_with_budget(100).unwrap().unwrap();
+    assert!(converted.equivalent_to(&RegexAst::parse_str("(a|b)*abb").unwrap()));
+
+    // State elimination on the 32 states of this minimal DFA builds huge expressions.
+    let large = blowup(4).to_min_dfa(&alphabet);
+    assert_eq!(
+        large.to_regex_with_budget(1000),
+        Err(BudgetExceeded { budget: 1000 })
+    );
+}
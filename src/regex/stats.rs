@@ -0,0 +1,160 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::RegexAst;
+
+/// Structural statistics of a [RegexAst].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RegexStats {
+    /// Number of nodes in the tree
+    pub node_count: usize,
+    /// Number of `Literal` nodes
+    pub literal_count: usize,
+    /// Number of `Star` nodes
+    pub star_count: usize,
+    /// Largest number of branches of a single `Alternation`, or 0 if there is none
+    pub alternation_width: usize,
+    /// Length of the longest path from the root to a leaf, counted in nodes
+    pub nesting_depth: usize,
+    /// Maximum number of `Star` nodes nested within each other
+    pub star_height: usize,
+}
+
+impl RegexStats {
+    /// Statistics of a tree whose root has the given children.
+    fn of_children<'a>(children: impl Iterator<Item = &'a RegexAst>) -> Self {
+        children
+            .map(|ast| ast.stats())
+            .fold(RegexStats::default(), |acc, stats| RegexStats {
+                node_count: acc.node_count + stats.node_count,
+                literal_count: acc.literal_count + stats.literal_count,
+                star_count: acc.star_count + stats.star_count,
+                alternation_width: acc.alternation_width.max(stats.alternation_width),
+                nesting_depth: acc.nesting_depth.max(stats.nesting_depth),
+                star_height: acc.star_height.max(stats.star_height),
+            })
+    }
+}
+
+impl RegexAst {
+    /// Compute structural statistics of this AST.
+    pub fn stats(&self) -> RegexStats {
+        let leaf = RegexStats {
+            node_count: 1,
+            nesting_depth: 1,
+            ..RegexStats::default()
+        };
+
+        match self {
+            RegexAst::Epsilon => leaf,
+            RegexAst::Literal(_) => RegexStats {
+                literal_count: 1,
+                ..leaf
+            },
+            RegexAst::Star(ast) => {
+                let inner = ast.stats();
+                RegexStats {
+                    node_count: inner.node_count + 1,
+                    star_count: inner.star_count + 1,
+                    nesting_depth: inner.nesting_depth + 1,
+                    star_height: inner.star_height + 1,
+                    ..inner
+                }
+            }
+            RegexAst::Concatenation(asts) => {
+                let inner = RegexStats::of_children(asts.iter());
+                RegexStats {
+                    node_count: inner.node_count + 1,
+                    nesting_depth: inner.nesting_depth + 1,
+                    ..inner
+                }
+            }
+            RegexAst::Alternation(asts) => {
+                let inner = RegexStats::of_children(asts.iter());
+                RegexStats {
+                    node_count: inner.node_count + 1,
+                    nesting_depth: inner.nesting_depth + 1,
+                    alternation_width: inner.alternation_width.max(asts.len()),
+                    ..inner
+                }
+            }
+        }
+    }
+}
+
+impl RegexAst {
+    /// Maximum number of `Star` nodes nested within each other in this AST.
+    pub fn star_height(&self) -> usize {
+        self.stats().star_height
+    }
+}
+
+#[test]
+fn regex_ast_stats() {
+    assert_eq!(
+        RegexAst::parse_str("ab*|(c|d|ε)*").unwrap().stats(),
+        RegexStats {
+            node_count: 10,
+            literal_count: 4,
+            star_count: 2,
+            alternation_width: 3,
+            nesting_depth: 4,
+            star_height: 1,
+        }
+    );
+
+    assert_eq!(
+        RegexAst::parse_str("((ab)*c)*").unwrap().stats(),
+        RegexStats {
+            node_count: 7,
+            literal_count: 3,
+            star_count: 2,
+            alternation_width: 0,
+            nesting_depth: 5,
+            star_height: 2,
+        }
+    );
+
+    assert_eq!(
+        RegexAst::Epsilon.stats(),
+        RegexStats {
+            node_count: 1,
+            nesting_depth: 1,
+            ..RegexStats::default()
+        }
+    );
+}
+
+#[test]
+fn regex_ast_star_height() {
+    let cases = vec![
+        ("ab|ba", 0),
+        ("a*b", 1),
+        ("((ab)*c)*", 2),
+        ("(a*)*", 2),
+        ("(ε|a)(ε|b)", 0),
+    ];
+
+    for (regex_str, height) in cases {
+        assert_eq!(
+            RegexAst::parse_str(regex_str).unwrap().star_height(),
+            height
+        );
+    }
+}
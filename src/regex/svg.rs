@@ -0,0 +1,318 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use resvg::{
+    tiny_skia,
+    usvg::{
+        self,
+        fontdb::{Database, Family, Query, Stretch, Style, Weight},
+    },
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
+
+// constants related to the layout of automaton diagrams, in pixels
+const STATE_RADIUS: f64 = 20.0;
+const LAYER_GAP: f64 = 110.0;
+const ROW_GAP: f64 = 90.0;
+const MARGIN: f64 = 80.0;
+/// Distance of the control point of an edge from the straight line between its ends
+const EDGE_BEND: f64 = 18.0;
+
+/// Fonts installed on the system, loaded once for every rasterized image
+static FONTS: Lazy<Arc<Database>> = Lazy::new(|| {
+    let mut fonts = Database::new();
+    fonts.load_system_fonts();
+    if let Some(family) = fallback_family(&fonts, Family::SansSerif, false) {
+        fonts.set_sans_serif_family(family);
+    }
+    if let Some(family) = fallback_family(&fonts, Family::Monospace, true) {
+        fonts.set_monospace_family(family);
+    }
+    Arc::new(fonts)
+});
+
+/// An installed family to draw `generic` with, if the font it names by default is missing.
+///
+/// The defaults are fonts of Windows, such as Arial for `sans-serif`, which other systems may lack.
+fn fallback_family(fonts: &Database, generic: Family, monospaced: bool) -> Option<String> {
+    let query = Query {
+        families: &[generic],
+        weight: Weight::NORMAL,
+        stretch: Stretch::Normal,
+        style: Style::Normal,
+    };
+    if fonts.query(&query).is_some() {
+        return None;
+    }
+    // prefer families named like "DejaVu Sans" or "Noto Sans Mono" to serif ones
+    fonts
+        .faces()
+        .filter(|face| {
+            face.monospaced == monospaced
+                && face.weight == Weight::NORMAL
+                && face.style == Style::Normal
+        })
+        .filter_map(|face| face.families.first())
+        .map(|(family, _)| family)
+        .max_by_key(|family| family.contains("Sans"))
+        .cloned()
+}
+
+/// Rasterize `svg` into a PNG image of the same size, since chat clients preview PNG but not SVG.
+///
+/// Text is drawn with the fonts installed on the system.
+/// Returns `None` if `svg` cannot be parsed or has no area.
+pub fn svg_to_png(svg: &str) -> Option<Vec<u8>> {
+    let options = usvg::Options {
+        fontdb: FONTS.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options).ok()?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().ok()
+}
+
+/// A point or a vector on the canvas.
+#[derive(Clone, Copy, Debug)]
+struct Point(f64, f64);
+
+impl Point {
+    fn add(self, other: Point) -> Point {
+        Point(self.0 + other.0, self.1 + other.1)
+    }
+
+    fn scale(self, k: f64) -> Point {
+        Point(self.0 * k, self.1 * k)
+    }
+
+    fn unit_towards(self, other: Point) -> Point {
+        let (dx, dy) = (other.0 - self.0, other.1 - self.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        Point(dx / length, dy / length)
+    }
+}
+
+impl Dfa {
+    /// Assign each drawn state a layer (its distance from the initial state)
+    /// and a row (its order of discovery within the layer).
+    ///
+    /// States from which no accepting state is reachable are not drawn,
+    /// except for the initial state.
+    fn layered_layout(&self) -> BTreeMap<usize, (usize, usize)> {
+        let mut drawn = self.live_states();
+        drawn[self.initial] = true;
+
+        let mut layout = BTreeMap::new();
+        let mut rows_used = vec![];
+        let mut queue = VecDeque::new();
+        layout.insert(self.initial, (0, 0));
+        rows_used.push(1);
+        queue.push_back(self.initial);
+
+        while let Some(q) = queue.pop_front() {
+            let layer = layout[&q].0 + 1;
+            for next in &self.transitions[q] {
+                if drawn[*next] && !layout.contains_key(next) {
+                    if rows_used.len() == layer {
+                        rows_used.push(0);
+                    }
+                    layout.insert(*next, (layer, rows_used[layer]));
+                    rows_used[layer] += 1;
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        layout
+    }
+
+    /// An SVG image of the automaton drawn with a layered layout.
+    ///
+    /// States are arranged in columns by their distance from the initial state,
+    /// accepting states are drawn as double circles,
+    /// and states from which no word is accepted (e.g. the sink) are omitted.
+    pub fn to_svg(&self) -> String {
+        let layout = self.layered_layout();
+        let position = |q: usize| {
+            let (layer, row) = layout[&q];
+            Point(
+                MARGIN + layer as f64 * LAYER_GAP,
+                MARGIN + row as f64 * ROW_GAP,
+            )
+        };
+
+        let layers = layout
+            .values()
+            .map(|(layer, _)| layer + 1)
+            .max()
+            .unwrap_or(1);
+        let rows = layout.values().map(|(_, row)| row + 1).max().unwrap_or(1);
+        let width = 2.0 * MARGIN + (layers - 1) as f64 * LAYER_GAP;
+        let height = 2.0 * MARGIN + (rows - 1) as f64 * ROW_GAP;
+
+        let mut elements = vec![];
+
+        // arrow pointing at the initial state
+        let initial = position(self.initial);
+        elements.push(format!(
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="black" marker-end="url(#arrow)"/>"#,
+            initial.0 - 2.0 * STATE_RADIUS - 10.0,
+            initial.1,
+            initial.0 - STATE_RADIUS,
+            initial.1
+        ));
+
+        let mut labels: BTreeMap<(usize, usize), Vec<Alphabet>> = BTreeMap::new();
+        for (source, a, target) in self.transitions() {
+            if layout.contains_key(&source) && layout.contains_key(&target) {
+                labels.entry((source, target)).or_default().push(a);
+            }
+        }
+
+        for ((source, target), alphabets) in labels {
+            let label = alphabets.iter().join(",");
+            let (from, to) = (position(source), position(target));
+
+            let (path, label_at) = if source == target {
+                let top = from.add(Point(0.0, -STATE_RADIUS));
+                (
+                    format!(
+                        "M {:.1} {:.1} C {:.1} {:.1} {:.1} {:.1} {:.1} {:.1}",
+                        top.0 - 8.0,
+                        top.1,
+                        top.0 - 30.0,
+                        top.1 - 45.0,
+                        top.0 + 30.0,
+                        top.1 - 45.0,
+                        top.0 + 8.0,
+                        top.1
+                    ),
+                    top.add(Point(0.0, -42.0)),
+                )
+            } else {
+                // bend every edge to its left, so that edges in opposite directions do not overlap
+                let direction = from.unit_towards(to);
+                let normal = Point(direction.1, -direction.0);
+                let middle = from.add(to).scale(0.5);
+                let control = middle.add(normal.scale(2.0 * EDGE_BEND));
+                let start = from.add(from.unit_towards(control).scale(STATE_RADIUS));
+                let end = to.add(to.unit_towards(control).scale(STATE_RADIUS));
+                (
+                    format!(
+                        "M {:.1} {:.1} Q {:.1} {:.1} {:.1} {:.1}",
+                        start.0, start.1, control.0, control.1, end.0, end.1
+                    ),
+                    middle.add(normal.scale(EDGE_BEND + 8.0)),
+                )
+            };
+
+            elements.push(format!(
+                r#"<path d="{}" fill="none" stroke="black" marker-end="url(#arrow)"/>"#,
+                path
+            ));
+            elements.push(format!(
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
+                label_at.0, label_at.1, label
+            ));
+        }
+
+        for q in layout.keys() {
+            let center = position(*q);
+            elements.push(format!(
+                r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="white" stroke="black"/>"#,
+                center.0, center.1, STATE_RADIUS
+            ));
+            if self.accepting[*q] {
+                elements.push(format!(
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="none" stroke="black"/>"#,
+                    center.0,
+                    center.1,
+                    STATE_RADIUS - 4.0
+                ));
+            }
+            elements.push(format!(
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
+                center.0, center.1, q
+            ));
+        }
+
+        let defs = concat!(
+            r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" "#,
+            r#"markerWidth="8" markerHeight="8" orient="auto-start-reverse">"#,
+            r#"<path d="M 0 0 L 10 5 L 0 10 z"/></marker></defs>"#
+        );
+
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" "#,
+                r#"font-family="sans-serif" font-size="14">"#,
+                "\n{}\n",
+                r#"<rect width="100%" height="100%" fill="white"/>"#,
+                "\n{}\n</svg>\n"
+            ),
+            width,
+            height,
+            defs,
+            elements.join("\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::RegexAst;
+
+    #[test]
+    fn dfa_to_svg() {
+        // the minimal DFA has a sink state besides the three states of the trimmed automaton
+        let ast = RegexAst::parse_str("ab(c|ab)*").unwrap();
+        let dfa = ast.to_min_dfa(&ast.used_alphabets());
+        assert_eq!(dfa.state_count(), 4);
+
+        let svg = dfa.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        // one circle for each drawn state and an extra one for the accepting state
+        assert_eq!(svg.matches("<circle").count(), 3 + 1);
+        // one path for each pair of states connected by transitions
+        assert_eq!(
+            svg.matches(r#"fill="none" stroke="black" marker-end"#)
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn svg_to_png() {
+        let ast = RegexAst::parse_str("ab*").unwrap();
+        let svg = ast.to_min_dfa(&ast.used_alphabets()).to_svg();
+        let png = super::svg_to_png(&svg).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        assert_eq!(super::svg_to_png("<svg"), None);
+    }
+}
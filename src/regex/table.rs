@@ -0,0 +1,277 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa};
+use thiserror::Error;
+
+/// Maximum number of states of a transition table given to [Dfa::parse_table]
+pub const MAX_TABLE_STATES: usize = 32;
+
+/// Error in a transition table given to [Dfa::parse_table].
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum TableError {
+    #[error("The table has no rows")]
+    Empty,
+    #[error(
+        "The table has {0} states, but at most {} are allowed",
+        MAX_TABLE_STATES
+    )]
+    TooManyStates(usize),
+    #[error("`{0}` is not a state label")]
+    InvalidLabel(String),
+    #[error("Row {found} is given where row {expected} is expected")]
+    UnexpectedRow { expected: usize, found: usize },
+    #[error("Row {state} has {found} targets, but there are {expected} alphabets")]
+    WrongRowLength {
+        state: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("`{0}` is not a state")]
+    InvalidTarget(String),
+    #[error("More than one state is marked as initial")]
+    MultipleInitial,
+}
+
+impl Dfa {
+    /// A markdown table of transitions, with a row for each state and a column for each alphabet.
+    ///
+    /// The initial state is marked with `→` and accepting states with `*`.
+    /// Columns are padded to the same width, so that the table is also readable
+    /// when shown as plain text (e.g. in a code block on Discord, which does not render tables).
+    pub fn to_markdown_table(&self) -> String {
+        let state_label = |q: usize| {
+            format!(
+                "{}{}{}",
+                if q == self.initial { "→" } else { "" },
+                q,
+                if self.accepting[q] { "*" } else { "" }
+            )
+        };
+
+        let header = std::iter::once("state".to_string())
+            .chain(self.alphabet.iter().map(|a| format!("{}", a)))
+            .collect::<Vec<_>>();
+        let rows = (0..self.state_count())
+            .map(|q| {
+                std::iter::once(state_label(q))
+                    .chain(
+                        self.transitions[q]
+                            .iter()
+                            .map(|target| format!("{}", target)),
+                    )
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let widths = (0..header.len())
+            .map(|column| {
+                std::iter::once(&header)
+                    .chain(rows.iter())
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let show_row = |row: &[String]| {
+            let cells = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| {
+                    format!("{}{}", cell, " ".repeat(width - cell.chars().count()))
+                })
+                .collect::<Vec<_>>();
+            format!("| {} |\n", cells.join(" | "))
+        };
+        let separator = format!(
+            "|{}|\n",
+            widths
+                .iter()
+                .map(|width| "-".repeat(width + 2))
+                .collect::<Vec<_>>()
+                .join("|")
+        );
+
+        std::iter::once(show_row(&header))
+            .chain(std::iter::once(separator))
+            .chain(rows.iter().map(|row| show_row(row)))
+            .collect()
+    }
+
+    /// Parse a transition table over `alphabet`, the inverse of [Dfa::to_markdown_table].
+    ///
+    /// Rows are separated by newlines or `;`, and the `i`-th row lists the targets of state `i`
+    /// for each alphabet in ascending order, after a label `i`.
+    /// A label is marked with `*` if the state is accepting,
+    /// and with a leading `>` (or `→`) if it is initial, which defaults to state `0`.
+    /// For example, `0 1 0; 1* 1 0` over `{a, b}` accepts words ending with `a`.
+    ///
+    /// Cell borders `|`, the header row and the separator row of a markdown table are ignored,
+    /// so that a table shown by the bot can be pasted back.
+    /// Tables of more than [MAX_TABLE_STATES] states are rejected.
+    pub fn parse_table(input: &str, alphabet: &[Alphabet]) -> Result<Dfa, TableError> {
+        let mut alphabet = alphabet.to_vec();
+        alphabet.sort();
+        alphabet.dedup();
+
+        let rows = input
+            .split(['\n', ';'])
+            .map(|row| row.replace('|', " "))
+            .map(|row| {
+                row.split_whitespace()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|cells| match cells.first() {
+                None => false,
+                Some(head) => head != "state" && !head.chars().all(|c| c == '-'),
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return Err(TableError::Empty);
+        }
+        if rows.len() > MAX_TABLE_STATES {
+            return Err(TableError::TooManyStates(rows.len()));
+        }
+
+        let mut initial = None;
+        let mut accepting = vec![];
+        for (expected, cells) in rows.iter().enumerate() {
+            let label = &cells[0];
+            let unmarked = label.trim_start_matches(['>', '→']);
+            let is_initial = unmarked.len() < label.len();
+            let is_accepting = unmarked.ends_with('*');
+            let found = unmarked
+                .trim_end_matches('*')
+                .parse::<usize>()
+                .map_err(|_| TableError::InvalidLabel(label.clone()))?;
+            if found != expected {
+                return Err(TableError::UnexpectedRow { expected, found });
+            }
+            if is_initial && initial.replace(found).is_some() {
+                return Err(TableError::MultipleInitial);
+            }
+            accepting.push(is_accepting);
+        }
+
+        let transitions = rows
+            .iter()
+            .enumerate()
+            .map(|(state, cells)| {
+                let targets = &cells[1..];
+                if targets.len() != alphabet.len() {
+                    return Err(TableError::WrongRowLength {
+                        state,
+                        expected: alphabet.len(),
+                        found: targets.len(),
+                    });
+                }
+                targets
+                    .iter()
+                    .map(|target| match target.parse::<usize>() {
+                        Ok(target) if target < rows.len() => Ok(target),
+                        _ => Err(TableError::InvalidTarget(target.clone())),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Dfa {
+            alphabet,
+            initial: initial.unwrap_or(0),
+            accepting,
+            transitions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TableError, MAX_TABLE_STATES};
+    use crate::regex::{Alphabet, Dfa, RegexAst};
+    use indoc::indoc;
+
+    #[test]
+    fn dfa_to_markdown_table() {
+        let ast = RegexAst::parse_str("(a|b)*a").unwrap();
+
+        assert_eq!(
+            ast.to_min_dfa(&ast.used_alphabets()).to_markdown_table(),
+            indoc! {"
+                | state | a | b |
+                |-------|---|---|
+                | →0    | 1 | 0 |
+                | 1*    | 1 | 0 |
+            "}
+        );
+    }
+
+    #[test]
+    fn dfa_parse_table() {
+        let ab = Alphabet::vec_from_str("ab").unwrap();
+        let ast = RegexAst::parse_str("(a|b)*a").unwrap();
+        let dfa = ast.to_min_dfa(&ast.used_alphabets());
+
+        assert_eq!(Dfa::parse_table("0 1 0; 1* 1 0", &ab), Ok(dfa.clone()));
+        assert_eq!(Dfa::parse_table(&dfa.to_markdown_table(), &ab), Ok(dfa));
+
+        let shifted = Dfa::parse_table("0* 0 0\n>1 0 1", &ab).unwrap();
+        assert_eq!(shifted.initial_state(), 1);
+        assert!(shifted.accepts(&Alphabet::vec_from_str("bba").unwrap()));
+        assert!(!shifted.accepts(&Alphabet::vec_from_str("bb").unwrap()));
+
+        assert_eq!(Dfa::parse_table(" ; ", &ab), Err(TableError::Empty));
+        let long = (0..=MAX_TABLE_STATES)
+            .map(|q| format!("{} {} 0", q, (q + 1) % (MAX_TABLE_STATES + 1)))
+            .collect::<Vec<_>>()
+            .join("; ");
+        assert_eq!(
+            Dfa::parse_table(&long, &ab),
+            Err(TableError::TooManyStates(MAX_TABLE_STATES + 1))
+        );
+        assert_eq!(
+            Dfa::parse_table("0 1 0; 2 1 0", &ab),
+            Err(TableError::UnexpectedRow {
+                expected: 1,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Dfa::parse_table("0 1; 1 1 0", &ab),
+            Err(TableError::WrongRowLength {
+                state: 0,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Dfa::parse_table("0 1 2; 1 1 0", &ab),
+            Err(TableError::InvalidTarget("2".to_string()))
+        );
+        assert_eq!(
+            Dfa::parse_table(">0 1 0; >1 1 0", &ab),
+            Err(TableError::MultipleInitial)
+        );
+        assert_eq!(
+            Dfa::parse_table("q0 1 0", &ab),
+            Err(TableError::InvalidLabel("q0".to_string()))
+        );
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, RegexAst};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::{Display, Formatter},
+};
+
+/// How the letters consumed by a subexpression are distributed among its children.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceDetail {
+    /// `ε` or a literal
+    Leaf,
+    /// Traces of the factors of a concatenation
+    Factors(Vec<MatchTrace>),
+    /// Traces of the iterations of a star, each consuming at least one letter
+    Iterations(Vec<MatchTrace>),
+    /// Index and trace of the alternation branch taken
+    Branch(usize, Box<MatchTrace>),
+}
+
+/// A witness of a subexpression matching a part of a word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchTrace {
+    pub ast: RegexAst,
+    pub consumed: Vec<Alphabet>,
+    pub detail: TraceDetail,
+}
+
+/// Traces of `ast` matching `word[start..end]`, one for each possible `end`.
+fn traces(ast: &RegexAst, word: &[Alphabet], start: usize) -> Vec<MatchTrace> {
+    let trace = |end: usize, detail: TraceDetail| MatchTrace {
+        ast: ast.clone(),
+        consumed: word[start..end].to_vec(),
+        detail,
+    };
+
+    match ast {
+        RegexAst::Epsilon => vec![trace(start, TraceDetail::Leaf)],
+        RegexAst::Literal(a) => {
+            if word.get(start) == Some(a) {
+                vec![trace(start + 1, TraceDetail::Leaf)]
+            } else {
+                vec![]
+            }
+        }
+        RegexAst::Concatenation(asts) => {
+            // ways to match prefixes of the factors, keyed by the position reached
+            let mut partial = BTreeMap::new();
+            partial.insert(start, vec![]);
+
+            for factor in asts {
+                let mut next = BTreeMap::new();
+                for (position, factors) in partial {
+                    for t in traces(factor, word, position) {
+                        next.entry(position + t.consumed.len()).or_insert_with(|| {
+                            let mut extended: Vec<MatchTrace> = factors.clone();
+                            extended.push(t);
+                            extended
+                        });
+                    }
+                }
+                partial = next;
+            }
+
+            partial
+                .into_iter()
+                .map(|(end, factors)| trace(end, TraceDetail::Factors(factors)))
+                .collect()
+        }
+        RegexAst::Alternation(asts) => {
+            let mut by_end = BTreeMap::new();
+            for (index, branch) in asts.iter().enumerate() {
+                for t in traces(branch, word, start) {
+                    by_end
+                        .entry(start + t.consumed.len())
+                        .or_insert_with(|| TraceDetail::Branch(index, Box::new(t)));
+                }
+            }
+
+            by_end
+                .into_iter()
+                .map(|(end, detail)| trace(end, detail))
+                .collect()
+        }
+        RegexAst::Star(inner) => {
+            // breadth-first search on positions, so that fewer iterations are preferred
+            let mut reached = BTreeMap::new();
+            let mut queue = VecDeque::new();
+            reached.insert(start, vec![]);
+            queue.push_back(start);
+
+            while let Some(position) = queue.pop_front() {
+                let iterations = reached[&position].clone();
+                for t in traces(inner, word, position) {
+                    let end = position + t.consumed.len();
+                    if t.consumed.is_empty() || reached.contains_key(&end) {
+                        continue;
+                    }
+
+                    let mut extended: Vec<MatchTrace> = iterations.clone();
+                    extended.push(t);
+                    reached.insert(end, extended);
+                    queue.push_back(end);
+                }
+            }
+
+            reached
+                .into_iter()
+                .map(|(end, iterations)| trace(end, TraceDetail::Iterations(iterations)))
+                .collect()
+        }
+    }
+}
+
+impl MatchTrace {
+    fn fmt_indented(&self, f: &mut Formatter<'_>, label: &str, depth: usize) -> std::fmt::Result {
+        let consumed = match self.consumed.as_slice() {
+            [] => "ε".to_string(),
+            w => Alphabet::slice_to_plain_string(w),
+        };
+        writeln!(
+            f,
+            "{}{}{} matches {}",
+            "  ".repeat(depth),
+            label,
+            self.ast,
+            consumed
+        )?;
+
+        match &self.detail {
+            TraceDetail::Leaf => Ok(()),
+            TraceDetail::Factors(factors) => factors
+                .iter()
+                .try_for_each(|t| t.fmt_indented(f, "", depth + 1)),
+            TraceDetail::Iterations(iterations) => {
+                iterations.iter().enumerate().try_for_each(|(i, t)| {
+                    t.fmt_indented(f, &format!("iteration {}: ", i + 1), depth + 1)
+                })
+            }
+            TraceDetail::Branch(index, t) => {
+                t.fmt_indented(f, &format!("branch {}: ", index + 1), depth + 1)
+            }
+        }
+    }
+}
+
+impl Display for MatchTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, "", 0)
+    }
+}
+
+impl RegexAst {
+    /// Explain how `word` is matched by this AST,
+    /// showing the letters consumed by each alternation branch taken and each star iteration.
+    ///
+    /// Returns `None` if `word` is not matched.
+    /// If there are multiple ways to match, one with fewer star iterations
+    /// and earlier alternation branches is preferred.
+    pub fn match_trace(&self, word: &[Alphabet]) -> Option<MatchTrace> {
+        traces(self, word, 0)
+            .into_iter()
+            .find(|t| t.consumed.len() == word.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TraceDetail;
+    use crate::regex::{Alphabet, RegexAst};
+    use indoc::indoc;
+
+    #[test]
+    fn regex_ast_match_trace() {
+        let ast = RegexAst::parse_str("(ab|c)*a").unwrap();
+        let trace = ast
+            .match_trace(&Alphabet::vec_from_str("abca").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            format!("{}", trace),
+            indoc! {"
+                (ab|c)*a matches abca
+                  (ab|c)* matches abc
+                    iteration 1: ab|c matches ab
+                      branch 1: ab matches ab
+                        a matches a
+                        b matches b
+                    iteration 2: ab|c matches c
+                      branch 2: c matches c
+                  a matches a
+            "}
+        );
+
+        assert_eq!(
+            ast.match_trace(&Alphabet::vec_from_str("abc").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn regex_ast_match_trace_agrees_with_matches() {
+        for regex_str in ["(a|ab)(c|bcd)(d*)", "(a*)*b", "(ε|a)*", "a*(ba*)*"] {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            for word in ["", "a", "b", "ab", "abcd", "abcdd", "aab", "bab", "abba"] {
+                let word = Alphabet::vec_from_str(word).unwrap();
+                let trace = ast.match_trace(&word);
+
+                assert_eq!(trace.is_some(), ast.matches(&word));
+                if let Some(trace) = trace {
+                    assert_eq!(trace.consumed, word);
+                    assert_ne!(trace.detail, TraceDetail::Leaf);
+                }
+            }
+        }
+    }
+}
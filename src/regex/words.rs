@@ -0,0 +1,178 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use super::{Alphabet, Dfa, RegexAst};
+use std::collections::VecDeque;
+
+/// Breadth-first traversal of the trimmed automaton,
+/// which visits words in shortlex order.
+struct Words {
+    dfa: Dfa,
+    live: Vec<bool>,
+    /// Words leading to live states that have not been visited yet, along with the states
+    queue: VecDeque<(Vec<Alphabet>, usize)>,
+}
+
+impl Iterator for Words {
+    type Item = Vec<Alphabet>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((word, q)) = self.queue.pop_front() {
+            for (i, a) in self.dfa.alphabet.iter().enumerate() {
+                let next = self.dfa.transitions[q][i];
+                if self.live[next] {
+                    let mut extended = word.clone();
+                    extended.push(*a);
+                    self.queue.push_back((extended, next));
+                }
+            }
+
+            if self.dfa.accepting[q] {
+                return Some(word);
+            }
+        }
+
+        None
+    }
+}
+
+impl Dfa {
+    /// Words accepted by the automaton in shortlex order.
+    ///
+    /// The iterator is infinite if and only if the automaton accepts infinitely many words.
+    pub fn words(&self) -> impl Iterator<Item = Vec<Alphabet>> {
+        let live = self.live_states();
+        let queue = if live[self.initial] {
+            VecDeque::from(vec![(vec![], self.initial)])
+        } else {
+            VecDeque::new()
+        };
+
+        Words {
+            dfa: self.clone(),
+            live,
+            queue,
+        }
+    }
+}
+
+impl Dfa {
+    /// The first accepted word in shortlex order, or `None` if no word is accepted.
+    ///
+    /// Unlike taking the first of [Dfa::words], this visits each state at most once.
+    pub fn shortest_word(&self) -> Option<Vec<Alphabet>> {
+        // the state and the alphabet each state is first reached from
+        let mut parent: Vec<Option<(usize, Alphabet)>> = vec![None; self.state_count()];
+        let mut visited = vec![false; self.state_count()];
+        let mut queue = VecDeque::from(vec![self.initial]);
+        visited[self.initial] = true;
+
+        while let Some(q) = queue.pop_front() {
+            if self.accepting[q] {
+                let mut word = vec![];
+                let mut state = q;
+                while let Some((previous, a)) = parent[state] {
+                    word.push(a);
+                    state = previous;
+                }
+                word.reverse();
+                return Some(word);
+            }
+
+            for (i, a) in self.alphabet.iter().enumerate() {
+                let next = self.transitions[q][i];
+                if !visited[next] {
+                    visited[next] = true;
+                    parent[next] = Some((q, *a));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl RegexAst {
+    /// The first word matched by this AST in shortlex order.
+    pub fn shortest_word(&self) -> Option<Vec<Alphabet>> {
+        self.to_min_dfa(&self.used_alphabets()).shortest_word()
+    }
+
+    /// Words matched by this AST in shortlex order,
+    /// i.e. shorter words first and words of the same length in lexicographic order.
+    pub fn words(&self) -> impl Iterator<Item = Vec<Alphabet>> {
+        self.to_min_dfa(&self.used_alphabets()).words()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::regex::{Alphabet, RegexAst};
+
+    #[test]
+    fn regex_ast_words() {
+        let cases = vec![
+            ("(a|b)*a", vec!["a", "aa", "ba", "aaa", "aba", "baa", "bba"]),
+            ("a*b", vec!["b", "ab", "aab", "aaab"]),
+            ("ε|ba", vec!["", "ba"]),
+            ("(ab)*", vec!["", "ab", "abab", "ababab"]),
+        ];
+
+        for (regex_str, expected) in cases {
+            let expected = expected
+                .into_iter()
+                .map(|w| Alphabet::vec_from_str(w).unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                RegexAst::parse_str(regex_str)
+                    .unwrap()
+                    .words()
+                    .take(expected.len())
+                    .collect::<Vec<_>>(),
+                expected,
+                r#"Words matched by "{}" are not listed in shortlex order"#,
+                regex_str
+            );
+        }
+
+        assert_eq!(RegexAst::parse_str("ε|ba").unwrap().words().count(), 2);
+    }
+
+    #[test]
+    fn regex_ast_shortest_word() {
+        let cases = vec![
+            ("(a|b)*a", "a"),
+            ("c(b|a)(a|b)", "caa"),
+            ("ba*|ab", "b"),
+            ("(ab)*", ""),
+        ];
+        for (regex_str, expected) in cases {
+            let ast = RegexAst::parse_str(regex_str).unwrap();
+            assert_eq!(
+                ast.shortest_word(),
+                Some(Alphabet::vec_from_str(expected).unwrap()),
+                "{}",
+                regex_str
+            );
+            assert_eq!(ast.shortest_word(), ast.words().next());
+        }
+    }
+}
@@ -0,0 +1,137 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    bot::QuizId,
+    database::{from_sql_id, to_sql_id},
+    quiz::saved::SavedQuiz,
+};
+use anyhow::Context;
+use indoc::indoc;
+use rusqlite::{params, Connection};
+use serenity::model::id::ChannelId;
+
+/// A quiz read back from the database, or why it could not be.
+pub struct StoredGame {
+    pub channel: ChannelId,
+    pub id: QuizId,
+    pub state: anyhow::Result<SavedQuiz>,
+}
+
+/// Quizzes going on in each channel stored in an SQLite database,
+/// so that they are restored when the bot restarts.
+pub struct SavedGames<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> SavedGames<'a> {
+    /// Create the tables of the saved games in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS saved_games (
+                    channel_id INTEGER NOT NULL,
+                    quiz_id INTEGER NOT NULL,
+                    state TEXT NOT NULL,
+                    PRIMARY KEY (channel_id, quiz_id)
+                );
+            "})
+            .context("failed to initialize the saved games")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        SavedGames { connection }
+    }
+
+    /// Keep `state` as the latest state of the quiz `id` in `channel`.
+    pub fn save(&self, channel: ChannelId, id: QuizId, state: &SavedQuiz) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO saved_games (channel_id, quiz_id, state) VALUES (?1, ?2, ?3)
+                    ON CONFLICT (channel_id, quiz_id) DO UPDATE SET state = excluded.state
+                "},
+                params![to_sql_id(channel.0), to_sql_id(id.0), state.to_json()],
+            )
+            .context("failed to save a game")?;
+        Ok(())
+    }
+
+    /// Forget the quiz `id` in `channel`, which has ended.
+    pub fn remove(&self, channel: ChannelId, id: QuizId) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                "DELETE FROM saved_games WHERE channel_id = ?1 AND quiz_id = ?2",
+                params![to_sql_id(channel.0), to_sql_id(id.0)],
+            )
+            .context("failed to remove a saved game")?;
+        Ok(())
+    }
+
+    /// Every quiz saved, in the order they were started in each channel.
+    pub fn games(&self) -> anyhow::Result<Vec<StoredGame>> {
+        let mut statement = self.connection.prepare(
+            "SELECT channel_id, quiz_id, state FROM saved_games ORDER BY channel_id, quiz_id",
+        )?;
+        let games = statement
+            .query_map(params![], |row| {
+                Ok(StoredGame {
+                    channel: ChannelId(from_sql_id(row.get(0)?)),
+                    id: QuizId(from_sql_id(row.get(1)?)),
+                    state: SavedQuiz::from_json(&row.get::<_, String>(2)?),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query the saved games")?;
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bot::{Quiz, QuizId, QuizOptions},
+        database::Database,
+    };
+    use serenity::model::id::{ChannelId, UserId};
+
+    #[test]
+    fn saved_games_keep_the_latest_state_until_removed() {
+        let database = Database::open_in_memory().unwrap();
+        let games = database.saved_games();
+        let channel = ChannelId(1);
+        let mut quiz = Quiz::new_with_options(&QuizOptions::default());
+        games.save(channel, QuizId(3), &quiz.saved()).unwrap();
+        quiz.register(UserId(2)).unwrap();
+        games.save(channel, QuizId(3), &quiz.saved()).unwrap();
+        games.save(channel, QuizId(4), &quiz.saved()).unwrap();
+
+        let stored = games.games().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].id, QuizId(3));
+        let state = stored[0].state.as_ref().unwrap();
+        assert_eq!(state.moves, quiz.saved().moves);
+
+        games.remove(channel, QuizId(3)).unwrap();
+        let stored = games.games().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, QuizId(4));
+    }
+}
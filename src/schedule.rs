@@ -0,0 +1,637 @@
+/*
+ * ISC License
+ *
+ * Copyright (c) 2021 Mitama Lab
+ *
+ * Permission to use, copy, modify, and/or distribute this software for any
+ * purpose with or without fee is hereby granted, provided that the above
+ * copyright notice and this permission notice appear in all copies.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+ * WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+ * MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ * ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+ * WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ * ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+ * OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+ *
+ */
+
+use crate::{
+    bot::QuizId,
+    database::{from_sql_id, to_sql_id},
+    regex::DifficultyPreset,
+};
+use anyhow::{anyhow, Context};
+use indoc::indoc;
+use rusqlite::{params, Connection, Row};
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use std::{
+    fmt,
+    num::NonZeroU8,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SECONDS_PER_MINUTE: u64 = 60;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Days searched for the next run of a schedule: 28 years, after which the calendar repeats
+const SEARCHED_DAYS: u64 = 28 * 366;
+
+/// Times of day and days of the calendar in UTC, written as the five fields of a cron table:
+/// minute, hour, day of month, month and day of week (0 or 7 for Sunday).
+///
+/// Each field is `*`, a number, a range `a-b`, a step `*/n` or `a-b/n`, or a comma-separated list of them.
+/// `@hourly`, `@daily`, `@weekly` and `@monthly` stand for the usual tables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cron {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of month or the day of week is restricted,
+    /// in which case a day matching either of them is chosen as in cron
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+/// Set of the values `field` lists between `min` and `max`, as bits.
+fn parse_field(field: &str, min: u64, max: u64) -> anyhow::Result<u64> {
+    let invalid = || anyhow!("`{}` is not a field from {} to {}", field, min, max);
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u64>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (
+                    start.parse().map_err(|_| invalid())?,
+                    end.parse().map_err(|_| invalid())?,
+                ),
+                // `a/n` runs from `a` to the end, as in cron
+                None if part.contains('/') => (range.parse().map_err(|_| invalid())?, max),
+                None => {
+                    let value = range.parse().map_err(|_| invalid())?;
+                    (value, value)
+                }
+            },
+        };
+        if step == 0 || start < min || start > end || end > max {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// Year, month and day of the month of `days` since the Unix epoch,
+/// by the algorithm of Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    (year, month, day)
+}
+
+impl Cron {
+    fn contains(bits: u64, value: u64) -> bool {
+        bits & (1 << value) != 0
+    }
+
+    /// Whether the schedule runs on `days` since the Unix epoch.
+    fn runs_on(&self, days: u64) -> bool {
+        let (_, month, day) = civil_from_days(days);
+        // the epoch was a Thursday
+        let weekday = (days + 4) % 7;
+        let by_day = Self::contains(self.days, day);
+        let by_weekday = Self::contains(self.weekdays, weekday);
+        Self::contains(self.months, month)
+            && match (self.days_restricted, self.weekdays_restricted) {
+                (true, true) => by_day || by_weekday,
+                _ => by_day && by_weekday,
+            }
+    }
+
+    /// The first time the schedule runs strictly after `time`, if it ever does.
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        let elapsed = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let start = (elapsed / SECONDS_PER_MINUTE + 1) * SECONDS_PER_MINUTE;
+        let first_day = start / SECONDS_PER_DAY;
+        (first_day..first_day + SEARCHED_DAYS)
+            .filter(|&days| self.runs_on(days))
+            .find_map(|days| {
+                (0..24)
+                    .filter(|&hour| Self::contains(self.hours, hour))
+                    .flat_map(|hour| {
+                        (0..60)
+                            .filter(|&minute| Self::contains(self.minutes, minute))
+                            .map(move |minute| {
+                                days * SECONDS_PER_DAY + (hour * 60 + minute) * SECONDS_PER_MINUTE
+                            })
+                    })
+                    .find(|&seconds| seconds >= start)
+            })
+            .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+}
+
+impl FromStr for Cron {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let source = s.trim();
+        let table = match source {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            table => table,
+        };
+        let fields = table.split_whitespace().collect::<Vec<_>>();
+        let (minute, hour, day, month, weekday) = match fields.as_slice() {
+            &[minute, hour, day, month, weekday] => (minute, hour, day, month, weekday),
+            _ => {
+                return Err(anyhow!(
+                    "`{}` is not a schedule of five fields such as `0 18 * * 5`",
+                    source
+                ))
+            }
+        };
+        let weekdays = parse_field(weekday, 0, 7)?;
+        Ok(Cron {
+            source: source.to_string(),
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            // 7 is another name of Sunday
+            weekdays: (weekdays | weekdays >> 7) & 0x7f,
+            days_restricted: day != "*",
+            weekdays_restricted: weekday != "*",
+        })
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// The puzzle a schedule posts each time it runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PuzzleSource {
+    /// A fresh quiz generated at the difficulty
+    Generated(DifficultyPreset),
+    /// An expression curated by the moderator who set up the schedule
+    Curated {
+        regex: String,
+        letters: Option<NonZeroU8>,
+    },
+}
+
+/// The puzzle a schedule has posted and not yet closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Posted {
+    pub quiz: QuizId,
+    /// The pinned announcement of the quiz
+    pub message: MessageId,
+    pub closes_at: SystemTime,
+    pub reminded: bool,
+}
+
+/// Something a schedule has to do, in the order it has to be done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Due {
+    /// Reveal the answer of the posted puzzle and unpin it
+    Close,
+    /// Remind the channel that the posted puzzle is about to close
+    Remind,
+    /// Post the next puzzle
+    Post,
+}
+
+/// A puzzle posted to a channel on a schedule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    pub id: i64,
+    pub guild: GuildId,
+    pub channel: ChannelId,
+    pub cron: Cron,
+    pub source: PuzzleSource,
+    /// How long each puzzle stays open, until the next one is posted if not chosen
+    pub open_for: Option<Duration>,
+    /// How long before a puzzle closes the reminder is posted
+    pub remind_before: Duration,
+    pub author: UserId,
+    /// When the schedule last ran, or was set up if it has never run
+    pub last_run: SystemTime,
+    pub posted: Option<Posted>,
+}
+
+impl Schedule {
+    /// When the next puzzle is posted.
+    pub fn next_run(&self) -> Option<SystemTime> {
+        self.cron.next_after(self.last_run)
+    }
+
+    /// When a puzzle posted at `posted_at` closes.
+    pub fn closing_time(&self, posted_at: SystemTime) -> SystemTime {
+        match self.open_for {
+            Some(open_for) => posted_at + open_for,
+            None => self
+                .cron
+                .next_after(posted_at)
+                .unwrap_or(posted_at + Duration::from_secs(7 * SECONDS_PER_DAY)),
+        }
+    }
+
+    /// What the schedule has to do at `now`.
+    /// A puzzle still open when the next one is due is closed first.
+    pub fn due(&self, now: SystemTime) -> Vec<Due> {
+        let post = self.next_run().is_some_and(|next| next <= now);
+        let mut due = vec![];
+        if let Some(posted) = &self.posted {
+            if post || posted.closes_at <= now {
+                due.push(Due::Close);
+            } else if !posted.reminded
+                && posted
+                    .closes_at
+                    .checked_sub(self.remind_before)
+                    .is_some_and(|remind_at| remind_at <= now)
+            {
+                due.push(Due::Remind);
+            }
+        }
+        if post {
+            due.push(Due::Post);
+        }
+        due
+    }
+}
+
+/// A schedule about to be set up.
+pub struct NewSchedule {
+    pub guild: GuildId,
+    pub channel: ChannelId,
+    pub cron: Cron,
+    pub source: PuzzleSource,
+    pub open_for: Option<Duration>,
+    pub remind_before: Duration,
+    pub author: UserId,
+}
+
+fn to_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+fn from_seconds(seconds: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds as u64)
+}
+
+/// Puzzles posted on a schedule in every guild, stored in an SQLite database.
+pub struct Schedules<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> Schedules<'a> {
+    /// Create the tables of the schedules in `connection` unless they exist.
+    pub(crate) fn create_tables(connection: &Connection) -> anyhow::Result<()> {
+        connection
+            .execute_batch(indoc! {"
+                CREATE TABLE IF NOT EXISTS schedules (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id INTEGER NOT NULL,
+                    channel_id INTEGER NOT NULL,
+                    cron TEXT NOT NULL,
+                    difficulty TEXT,
+                    regex TEXT,
+                    letters INTEGER,
+                    open_seconds INTEGER,
+                    remind_seconds INTEGER NOT NULL,
+                    author_id INTEGER NOT NULL,
+                    last_run INTEGER NOT NULL,
+                    quiz_id INTEGER,
+                    message_id INTEGER,
+                    closes_at INTEGER,
+                    reminded INTEGER NOT NULL DEFAULT 0
+                );
+            "})
+            .context("failed to initialize the schedules")?;
+        Ok(())
+    }
+
+    pub(crate) fn new(connection: &'a Connection) -> Self {
+        Schedules { connection }
+    }
+
+    fn schedule(row: &Row) -> anyhow::Result<Schedule> {
+        let source = match (
+            row.get::<_, Option<String>>("difficulty")?,
+            row.get::<_, Option<String>>("regex")?,
+        ) {
+            (Some(difficulty), None) => PuzzleSource::Generated(difficulty.parse()?),
+            (None, Some(regex)) => PuzzleSource::Curated {
+                regex,
+                letters: row
+                    .get::<_, Option<u8>>("letters")?
+                    .and_then(NonZeroU8::new),
+            },
+            _ => return Err(anyhow!("the puzzle of a schedule is broken")),
+        };
+        let posted = match (
+            row.get::<_, Option<i64>>("quiz_id")?,
+            row.get::<_, Option<i64>>("message_id")?,
+            row.get::<_, Option<i64>>("closes_at")?,
+        ) {
+            (Some(quiz), Some(message), Some(closes_at)) => Some(Posted {
+                quiz: QuizId(from_sql_id(quiz)),
+                message: MessageId(from_sql_id(message)),
+                closes_at: from_seconds(closes_at),
+                reminded: row.get("reminded")?,
+            }),
+            _ => None,
+        };
+        Ok(Schedule {
+            id: row.get("id")?,
+            guild: GuildId(from_sql_id(row.get("guild_id")?)),
+            channel: ChannelId(from_sql_id(row.get("channel_id")?)),
+            cron: row.get::<_, String>("cron")?.parse()?,
+            source,
+            open_for: row
+                .get::<_, Option<i64>>("open_seconds")?
+                .map(|seconds| Duration::from_secs(seconds as u64)),
+            remind_before: Duration::from_secs(row.get::<_, i64>("remind_seconds")? as u64),
+            author: UserId(from_sql_id(row.get("author_id")?)),
+            last_run: from_seconds(row.get("last_run")?),
+            posted,
+        })
+    }
+
+    fn query(
+        &self,
+        condition: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> anyhow::Result<Vec<Schedule>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT * FROM schedules {} ORDER BY id",
+            condition
+        ))?;
+        let mut rows = statement.query(params)?;
+        let mut schedules = vec![];
+        while let Some(row) = rows.next()? {
+            schedules.push(Self::schedule(row).context("failed to read a schedule")?);
+        }
+        Ok(schedules)
+    }
+
+    /// Set up `schedule` as of `now`, returning its id.
+    pub fn add(&self, schedule: &NewSchedule, now: SystemTime) -> anyhow::Result<i64> {
+        let (difficulty, regex, letters) = match &schedule.source {
+            PuzzleSource::Generated(preset) => (Some(preset.name()), None, None),
+            PuzzleSource::Curated { regex, letters } => {
+                (None, Some(regex.as_str()), letters.map(NonZeroU8::get))
+            }
+        };
+        self.connection
+            .execute(
+                indoc! {"
+                    INSERT INTO schedules (
+                        guild_id, channel_id, cron, difficulty, regex, letters,
+                        open_seconds, remind_seconds, author_id, last_run
+                    )
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                "},
+                params![
+                    to_sql_id(schedule.guild.0),
+                    to_sql_id(schedule.channel.0),
+                    schedule.cron.to_string(),
+                    difficulty,
+                    regex,
+                    letters,
+                    schedule.open_for.map(|open_for| open_for.as_secs() as i64),
+                    schedule.remind_before.as_secs() as i64,
+                    to_sql_id(schedule.author.0),
+                    to_seconds(now)
+                ],
+            )
+            .context("failed to add a schedule")?;
+        Ok(self.connection.last_insert_rowid())
+    }
+
+    /// Stop the schedule `id` of `guild`, returning it if there was one.
+    pub fn remove(&self, guild: GuildId, id: i64) -> anyhow::Result<Option<Schedule>> {
+        let removed = self.query(
+            "WHERE id = ?1 AND guild_id = ?2",
+            params![id, to_sql_id(guild.0)],
+        )?;
+        self.connection
+            .execute(
+                "DELETE FROM schedules WHERE id = ?1 AND guild_id = ?2",
+                params![id, to_sql_id(guild.0)],
+            )
+            .context("failed to remove a schedule")?;
+        Ok(removed.into_iter().next())
+    }
+
+    /// Schedules of `guild`, oldest first.
+    pub fn list(&self, guild: GuildId) -> anyhow::Result<Vec<Schedule>> {
+        self.query("WHERE guild_id = ?1", params![to_sql_id(guild.0)])
+    }
+
+    /// Schedules of every guild.
+    pub fn all(&self) -> anyhow::Result<Vec<Schedule>> {
+        self.query("", params![])
+    }
+
+    /// Record that the schedule `id` posted `posted` at `now`.
+    pub fn posted(&self, id: i64, posted: &Posted, now: SystemTime) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    UPDATE schedules
+                    SET last_run = ?2, quiz_id = ?3, message_id = ?4, closes_at = ?5, reminded = ?6
+                    WHERE id = ?1
+                "},
+                params![
+                    id,
+                    to_seconds(now),
+                    to_sql_id(posted.quiz.0),
+                    to_sql_id(posted.message.0),
+                    to_seconds(posted.closes_at),
+                    posted.reminded
+                ],
+            )
+            .context("failed to update a schedule")?;
+        Ok(())
+    }
+
+    /// Record that the schedule `id` ran at `now` without posting a puzzle, e.g. as posting failed.
+    pub fn skipped(&self, id: i64, now: SystemTime) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                "UPDATE schedules SET last_run = ?2 WHERE id = ?1",
+                params![id, to_seconds(now)],
+            )
+            .context("failed to update a schedule")?;
+        Ok(())
+    }
+
+    /// Record that the reminder of the puzzle posted by the schedule `id` is posted.
+    pub fn reminded(&self, id: i64) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                "UPDATE schedules SET reminded = 1 WHERE id = ?1",
+                params![id],
+            )
+            .context("failed to update a schedule")?;
+        Ok(())
+    }
+
+    /// Record that the puzzle posted by the schedule `id` is closed.
+    pub fn closed(&self, id: i64) -> anyhow::Result<()> {
+        self.connection
+            .execute(
+                indoc! {"
+                    UPDATE schedules
+                    SET quiz_id = NULL, message_id = NULL, closes_at = NULL, reminded = 0
+                    WHERE id = ?1
+                "},
+                params![id],
+            )
+            .context("failed to update a schedule")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{civil_from_days, Cron, Due, NewSchedule, Posted, PuzzleSource};
+    use crate::{bot::QuizId, database::Database, regex::DifficultyPreset};
+    use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+
+    #[test]
+    fn cron_tables_find_the_next_run() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+
+        // Thursday 1970-01-01 00:00
+        let epoch = UNIX_EPOCH;
+        let next = |cron: &str, after| {
+            cron.parse::<Cron>()
+                .unwrap()
+                .next_after(after)
+                .map(|time| time.duration_since(UNIX_EPOCH).unwrap().as_secs())
+        };
+        // Fridays at 18:00
+        assert_eq!(next("0 18 * * 5", epoch), Some(DAY + 18 * HOUR));
+        // strictly after
+        let friday = UNIX_EPOCH + Duration::from_secs(DAY + 18 * HOUR);
+        assert_eq!(next("0 18 * * 5", friday), Some(8 * DAY + 18 * HOUR));
+        assert_eq!(next("@weekly", epoch), Some(3 * DAY));
+        assert_eq!(next("0 0 * * 7", epoch), Some(3 * DAY));
+        assert_eq!(next("*/15 * * * *", epoch), Some(15 * 60));
+        assert_eq!(next("@monthly", epoch), Some(31 * DAY));
+        // the day of month or the day of week
+        assert_eq!(next("0 0 15 * 6", epoch), Some(2 * DAY));
+        assert_eq!(next("0 0 31 2 *", epoch), None);
+
+        for invalid in [
+            "",
+            "0 18 * *",
+            "60 * * * *",
+            "0 0 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(invalid.parse::<Cron>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn schedules_post_remind_and_close_in_turn() {
+        let database = Database::open_in_memory().unwrap();
+        let schedules = database.schedules();
+        let at = |seconds| UNIX_EPOCH + Duration::from_secs(seconds);
+        let guild = GuildId(1);
+        let new = |source| NewSchedule {
+            guild,
+            channel: ChannelId(2),
+            cron: "0 18 * * 5".parse().unwrap(),
+            source,
+            open_for: None,
+            remind_before: Duration::from_secs(DAY),
+            author: UserId(3),
+        };
+        let id = schedules
+            .add(&new(PuzzleSource::Generated(DifficultyPreset::Hard)), at(0))
+            .unwrap();
+        let curated = PuzzleSource::Curated {
+            regex: "(ab)*".to_string(),
+            letters: None,
+        };
+        let other = schedules.add(&new(curated.clone()), at(0)).unwrap();
+        assert_eq!(schedules.list(GuildId(4)).unwrap(), vec![]);
+
+        let schedule = schedules.list(guild).unwrap().remove(0);
+        assert_eq!(schedule.id, id);
+        assert!(schedule.due(at(DAY)).is_empty());
+        let friday = at(DAY + 18 * HOUR);
+        assert_eq!(schedule.due(friday), vec![Due::Post]);
+        // open until the next Friday
+        assert_eq!(schedule.closing_time(friday), at(8 * DAY + 18 * HOUR));
+
+        let posted = Posted {
+            quiz: QuizId(5),
+            message: MessageId(6),
+            closes_at: schedule.closing_time(friday),
+            reminded: false,
+        };
+        schedules.posted(id, &posted, friday).unwrap();
+        let schedule = schedules.list(guild).unwrap().remove(0);
+        assert_eq!(schedule.posted, Some(posted));
+        assert!(schedule.due(at(6 * DAY)).is_empty());
+        assert_eq!(schedule.due(at(7 * DAY + 18 * HOUR)), vec![Due::Remind]);
+
+        schedules.reminded(id).unwrap();
+        let schedule = schedules.list(guild).unwrap().remove(0);
+        assert!(schedule.due(at(7 * DAY + 18 * HOUR)).is_empty());
+        assert_eq!(
+            schedule.due(at(8 * DAY + 18 * HOUR)),
+            vec![Due::Close, Due::Post]
+        );
+
+        schedules.closed(id).unwrap();
+        assert_eq!(schedules.list(guild).unwrap()[0].posted, None);
+        let removed = schedules.remove(guild, other).unwrap().unwrap();
+        assert_eq!(removed.source, curated);
+        assert!(schedules.remove(guild, other).unwrap().is_none());
+        assert_eq!(schedules.all().unwrap().len(), 1);
+    }
+}